@@ -0,0 +1,5 @@
+DROP INDEX idx_audit_events_saga;
+DROP INDEX idx_audit_events_object;
+DROP INDEX idx_audit_events_company_created;
+
+DROP TABLE audit_events;
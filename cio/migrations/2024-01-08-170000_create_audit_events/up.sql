@@ -0,0 +1,17 @@
+CREATE TABLE audit_events (
+    id SERIAL PRIMARY KEY,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id),
+    actor VARCHAR NOT NULL,
+    service VARCHAR NOT NULL,
+    action VARCHAR NOT NULL,
+    object_id VARCHAR NOT NULL DEFAULT '',
+    request_id VARCHAR NOT NULL DEFAULT '',
+    saga_id VARCHAR NOT NULL DEFAULT '',
+    outcome VARCHAR NOT NULL,
+    error VARCHAR NOT NULL DEFAULT '',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_events_company_created ON audit_events(cio_company_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_audit_events_object ON audit_events(service, object_id);
+CREATE INDEX IF NOT EXISTS idx_audit_events_saga ON audit_events(saga_id);
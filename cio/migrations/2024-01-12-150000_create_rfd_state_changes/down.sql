@@ -0,0 +1,3 @@
+DROP INDEX idx_rfd_state_changes_rfd;
+
+DROP TABLE rfd_state_changes;
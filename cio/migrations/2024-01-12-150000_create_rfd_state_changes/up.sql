@@ -0,0 +1,13 @@
+CREATE TABLE rfd_state_changes (
+    id SERIAL PRIMARY KEY,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id),
+    rfd_number INTEGER NOT NULL,
+    from_state VARCHAR,
+    to_state VARCHAR NOT NULL,
+    branch VARCHAR NOT NULL DEFAULT '',
+    commit_sha VARCHAR NOT NULL DEFAULT '',
+    actor VARCHAR NOT NULL DEFAULT '',
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rfd_state_changes_rfd ON rfd_state_changes(cio_company_id, rfd_number, changed_at);
@@ -0,0 +1 @@
+ALTER TABLE rfds DROP COLUMN pdf_links;
@@ -0,0 +1,6 @@
+ALTER TABLE rfds ADD COLUMN pdf_links JSONB NOT NULL DEFAULT '{}'::jsonb;
+
+UPDATE rfds SET pdf_links = jsonb_strip_nulls(jsonb_build_object(
+    'github', NULLIF(pdf_link_github, ''),
+    'google_drive', NULLIF(pdf_link_google_drive, '')
+));
@@ -0,0 +1,3 @@
+DROP INDEX idx_rfd_references_target;
+
+DROP TABLE rfd_references;
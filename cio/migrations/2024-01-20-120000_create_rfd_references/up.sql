@@ -0,0 +1,10 @@
+CREATE TABLE rfd_references (
+    id SERIAL PRIMARY KEY,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id),
+    source_rfd_number INTEGER NOT NULL,
+    target_rfd_number INTEGER NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (cio_company_id, source_rfd_number, target_rfd_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rfd_references_target ON rfd_references(cio_company_id, target_rfd_number);
@@ -0,0 +1 @@
+ALTER TABLE groups ADD COLUMN slack_channels TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[];
@@ -0,0 +1,4 @@
+DROP INDEX idx_applicant_stage_changes_role;
+DROP INDEX idx_applicant_stage_changes_applicant;
+
+DROP TABLE applicant_stage_changes;
@@ -0,0 +1,13 @@
+CREATE TABLE applicant_stage_changes (
+    id SERIAL PRIMARY KEY,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id),
+    applicant_id INTEGER NOT NULL,
+    role VARCHAR NOT NULL DEFAULT '',
+    from_stage VARCHAR,
+    to_stage VARCHAR NOT NULL,
+    actor VARCHAR NOT NULL DEFAULT '',
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_applicant_stage_changes_applicant ON applicant_stage_changes(cio_company_id, applicant_id, changed_at);
+CREATE INDEX IF NOT EXISTS idx_applicant_stage_changes_role ON applicant_stage_changes(cio_company_id, role, changed_at);
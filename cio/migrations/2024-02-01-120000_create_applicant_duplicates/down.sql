@@ -0,0 +1,3 @@
+DROP INDEX idx_applicant_duplicates_duplicate_email;
+
+DROP TABLE applicant_duplicates;
@@ -0,0 +1,21 @@
+CREATE TABLE applicant_duplicates (
+    id SERIAL PRIMARY KEY,
+    primary_applicant_id INTEGER NOT NULL,
+    duplicate_applicant_id INTEGER NOT NULL,
+    primary_name VARCHAR NOT NULL DEFAULT '',
+    primary_email VARCHAR NOT NULL DEFAULT '',
+    primary_role VARCHAR NOT NULL DEFAULT '',
+    duplicate_name VARCHAR NOT NULL DEFAULT '',
+    duplicate_email VARCHAR NOT NULL DEFAULT '',
+    duplicate_role VARCHAR NOT NULL DEFAULT '',
+    score REAL NOT NULL DEFAULT 0,
+    signals TEXT[] NOT NULL DEFAULT ARRAY[]::TEXT[],
+    status VARCHAR NOT NULL DEFAULT '',
+    resolved_by VARCHAR NOT NULL DEFAULT '',
+    resolved_at TIMESTAMPTZ,
+    cio_company_id INTEGER NOT NULL REFERENCES companys(id),
+    airtable_record_id VARCHAR NOT NULL DEFAULT '',
+    UNIQUE (primary_applicant_id, duplicate_applicant_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_applicant_duplicates_duplicate_email ON applicant_duplicates(duplicate_email, status);
@@ -0,0 +1 @@
+ALTER TABLE audit_events ADD COLUMN claimed_by VARCHAR NOT NULL DEFAULT '';
@@ -20,6 +20,7 @@ pub static AIRTABLE_RECORDED_MEETINGS_TABLE: &str = "Recorded Meetings";
 pub static AIRTABLE_RFD_TABLE: &str = "RFDs";
 
 pub static AIRTABLE_APPLICATIONS_TABLE: &str = "Applicants";
+pub static AIRTABLE_APPLICANT_DUPLICATES_TABLE: &str = "Duplicates";
 pub static AIRTABLE_INTERVIEWS_TABLE: &str = "Interviews";
 pub static AIRTABLE_REVIEWER_LEADERBOARD_TABLE: &str = "Reviewer Leaderboard";
 pub static AIRTABLE_REVIEWS_TABLE: &str = "Reviews";
@@ -1,16 +1,28 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
 use chrono::{DateTime, Duration, Utc};
+use docusign::DocuSign;
+use google_drive::Client as GoogleDrive;
+use gusto_api::Client as Gusto;
+use log::{info, warn};
 use macros::db;
+use mailchimp_api::MailChimp;
+use quickbooks::QuickBooks;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use slack_chat_api::{blocks::SectionBlock, FormattedMessage, Slack};
+use token_refresh::{RefreshError, RefreshedToken, TokenRefresh};
+use zoho_api::Zoho;
+use zoom_api::Client as Zoom;
 
 use crate::{
     airtable::AIRTABLE_API_TOKENS_TABLE,
+    audit::{audit, AuditOutcome, AuditQuery},
     companies::Company,
     core::UpdateAirtableRecord,
     db::Database,
+    encryption::{decrypt_token, token_cipher},
     schema::{api_tokens as a_p_i_tokens, api_tokens},
 };
 
@@ -69,6 +81,32 @@ pub struct NewAPIToken {
     pub auth_company_id: i32,
 }
 
+/// How long before `expires_date` the refresh job refreshes a token. This has to be longer than the
+/// interval the job is scheduled at.
+const REFRESH_AHEAD_MINUTES: i64 = 30;
+
+/// How many days before `refresh_token_expires_date` we start warning that a token has to be
+/// authorized again.
+const REFRESH_TOKEN_WARNING_DAYS: i64 = 7;
+
+/// How long to wait before warning about the same expiring refresh token again.
+const EXPIRATION_ALERT_INTERVAL_HOURS: i64 = 24;
+
+/// The products whose tokens we know how to refresh, see [`APIToken::refresher`].
+const REFRESHABLE_PRODUCTS: &[&str] = &[
+    "docusign",
+    "google",
+    "gusto",
+    "mailchimp",
+    "quickbooks",
+    "slack",
+    "zoho",
+    "zoom",
+];
+
+/// The value mirrored to Airtable in place of a secret.
+const REDACTED: &str = "[redacted]";
+
 /// Implement updating the Airtable record for a APIToken.
 #[async_trait]
 impl UpdateAirtableRecord<APIToken> for APIToken {
@@ -78,11 +116,34 @@ impl UpdateAirtableRecord<APIToken> for APIToken {
         let company = Company::get_by_id(&db, self.auth_company_id).await?;
         self.company = vec![company.airtable_record_id];
 
+        // Never mirror the secrets. We overwrite them rather than leaving them empty so that any
+        // values written to Airtable before tokens were encrypted get cleared.
+        if !self.access_token.is_empty() {
+            self.access_token = REDACTED.to_string();
+        }
+        if !self.refresh_token.is_empty() {
+            self.refresh_token = REDACTED.to_string();
+        }
+
         Ok(())
     }
 }
 
 impl NewAPIToken {
+    /// Encrypt the secrets of the token.
+    pub async fn encrypted(mut self, company: &Company) -> Result<Self> {
+        let cipher = token_cipher(company).await?;
+        self.access_token = cipher.encrypt(&self.access_token).await?;
+        self.refresh_token = cipher.encrypt(&self.refresh_token).await?;
+
+        Ok(self)
+    }
+
+    /// Encrypt the secrets of the token and create or update it in the database and Airtable.
+    pub async fn upsert_encrypted(&self, db: &Database, company: &Company) -> Result<APIToken> {
+        self.clone().encrypted(company).await?.upsert(db).await
+    }
+
     pub fn expand(&mut self) {
         if self.expires_in > 0 {
             // Set the time the tokens expire.
@@ -104,6 +165,35 @@ impl NewAPIToken {
 }
 
 impl APIToken {
+    /// Get the token for a product with its secrets decrypted.
+    pub async fn get_decrypted(db: &Database, company: &Company, product: &str) -> Result<Option<Self>> {
+        match APIToken::get_from_db(db, company.id, product.to_string()).await {
+            Some(token) => Ok(Some(token.decrypted(company).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Decrypt the secrets of the token. Tokens that have not been encrypted yet are returned
+    /// unchanged.
+    pub async fn decrypted(mut self, company: &Company) -> Result<Self> {
+        self.access_token = decrypt_token(company, &self.access_token).await?;
+        self.refresh_token = decrypt_token(company, &self.refresh_token).await?;
+
+        Ok(self)
+    }
+
+    /// Encrypt the secrets of the token and update it in the database and Airtable.
+    pub async fn update_encrypted(&self, db: &Database, company: &Company) -> Result<()> {
+        let cipher = token_cipher(company).await?;
+
+        let mut token = self.clone();
+        token.access_token = cipher.encrypt(&token.access_token).await?;
+        token.refresh_token = cipher.encrypt(&token.refresh_token).await?;
+        token.update(db).await?;
+
+        Ok(())
+    }
+
     pub fn expand(&mut self) {
         if self.expires_in > 0 {
             // Set the time the tokens expire.
@@ -123,6 +213,160 @@ impl APIToken {
         }
     }
 
+    /// Update the token with the result of a refresh.
+    pub fn apply_refresh(&mut self, refreshed: RefreshedToken) {
+        if !refreshed.access_token.is_empty() {
+            self.access_token = refreshed.access_token;
+        }
+        if refreshed.expires_in > 0 {
+            self.expires_in = refreshed.expires_in as i32;
+        }
+        if !refreshed.refresh_token.is_empty() {
+            self.refresh_token = refreshed.refresh_token;
+        }
+        if refreshed.refresh_token_expires_in > 0 {
+            self.refresh_token_expires_in = refreshed.refresh_token_expires_in as i32;
+        }
+        self.last_updated_at = Utc::now();
+        self.expand();
+    }
+
+    /// Returns if we know how to refresh tokens of this product.
+    pub fn is_refreshable(&self) -> bool {
+        REFRESHABLE_PRODUCTS.contains(&self.product.as_str())
+    }
+
+    /// A client for the product of this token that is able to refresh it. The token has to be
+    /// decrypted.
+    async fn refresher(&self, db: &Database, company: &Company) -> Result<Box<dyn TokenRefresh>> {
+        let refresher: Box<dyn TokenRefresh> = match self.product.as_str() {
+            "docusign" => Box::new(DocuSign::new_from_env(
+                &self.access_token,
+                &self.refresh_token,
+                &self.company_id,
+                &self.endpoint,
+            )),
+            "google" => Box::new(GoogleRefresh {
+                access_token: self.access_token.to_string(),
+                refresh_token: self.refresh_token.to_string(),
+            }),
+            "gusto" => Box::new(GustoRefresh(Gusto::new_from_env(
+                &self.access_token,
+                &self.refresh_token,
+                gusto_api::RootProductionServer {},
+            ))),
+            // MailChimp access tokens do not expire, so they are stored without an
+            // `expires_date` and `refresh_expiring_api_tokens` never picks them up. Tokens that
+            // do carry an expiry are refreshed like those of any other product.
+            "mailchimp" => Box::new(MailChimp::new_from_env(
+                &self.access_token,
+                &self.refresh_token,
+                &self.endpoint,
+            )),
+            "quickbooks" => Box::new(QuickBooks::new_from_env(
+                &self.company_id,
+                &self.access_token,
+                &self.refresh_token,
+            )),
+            "slack" => {
+                // The user token is stored next to the bot token, but only the bot token is rotated.
+                let user_token = match api_tokens::dsl::api_tokens
+                    .filter(
+                        api_tokens::dsl::auth_company_id
+                            .eq(self.auth_company_id)
+                            .and(api_tokens::dsl::product.eq("slack".to_string()))
+                            .and(api_tokens::dsl::token_type.eq("user".to_string())),
+                    )
+                    .first_async::<APIToken>(db.pool())
+                    .await
+                {
+                    Ok(user_token) => user_token.decrypted(company).await?.access_token,
+                    Err(_) => String::new(),
+                };
+
+                Box::new(
+                    Slack::new_from_env(&self.company_id, &self.access_token, user_token)
+                        .with_refresh_token(&self.refresh_token),
+                )
+            }
+            "zoho" => Box::new(Zoho::new_with_keys_from_env(
+                &self.access_token,
+                Some(&self.refresh_token),
+            )),
+            "zoom" => Box::new(ZoomRefresh(Zoom::new_from_env(&self.access_token, &self.refresh_token))),
+            _ => bail!("refreshing {} tokens is not supported", self.product),
+        };
+
+        Ok(refresher)
+    }
+
+    /// Refresh the token with its provider and save the new token.
+    pub async fn refresh(&self, db: &Database, company: &Company) -> Result<()> {
+        self.clone()
+            .decrypted(company)
+            .await?
+            .refresh_decrypted(db, company)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Refresh the decrypted token with its provider, save the new token, and return it
+    /// decrypted.
+    pub async fn refresh_decrypted(mut self, db: &Database, company: &Company) -> Result<Self> {
+        let mut refresher = self.refresher(db, company).await?;
+
+        let refreshed = refresher.refresh().await.map_err(anyhow::Error::from);
+        let refreshed = audit(
+            db,
+            company,
+            &self.product,
+            "refresh_token",
+            &self.id.to_string(),
+            refreshed,
+        )
+        .await?;
+
+        self.apply_refresh(refreshed);
+        self.update_encrypted(db, company).await?;
+
+        Ok(self)
+    }
+
+    /// Returns when we last warned that the refresh token of this token expires.
+    async fn last_expiration_alert(&self, db: &Database, company: &Company) -> Result<Option<DateTime<Utc>>> {
+        let events = AuditQuery {
+            service: Some("slack".to_string()),
+            action: Some("api_token_expiration_alert".to_string()),
+            object_id: Some(self.id.to_string()),
+            outcome: Some(AuditOutcome::Success),
+            limit: Some(1),
+            ..Default::default()
+        }
+        .run(db, company.id)
+        .await?;
+
+        Ok(events.first().map(|e| e.created_at))
+    }
+
+    /// Returns if the last attempt to refresh this token failed.
+    async fn last_refresh_failed(&self, db: &Database, company: &Company) -> Result<bool> {
+        let events = AuditQuery {
+            service: Some(self.product.to_string()),
+            action: Some("refresh_token".to_string()),
+            object_id: Some(self.id.to_string()),
+            limit: Some(1),
+            ..Default::default()
+        }
+        .run(db, company.id)
+        .await?;
+
+        Ok(events
+            .first()
+            .map(|e| e.outcome == AuditOutcome::Failure)
+            .unwrap_or(false))
+    }
+
     /// Returns if the token is expired.
     pub fn is_expired(&self) -> bool {
         //if let Some(d) = self.expires_date {
@@ -137,7 +381,251 @@ impl APIToken {
     }
 }
 
+/// Refreshes Google tokens. The token is shared by all of the Google clients, so any of them can
+/// refresh it.
+struct GoogleRefresh {
+    access_token: String,
+    refresh_token: String,
+}
+
+#[async_trait]
+impl TokenRefresh for GoogleRefresh {
+    async fn refresh(&mut self) -> std::result::Result<RefreshedToken, RefreshError> {
+        let drive = GoogleDrive::new_from_env(&self.access_token, &self.refresh_token).await;
+        let t = drive
+            .refresh_access_token()
+            .await
+            .map_err(RefreshError::from_client_error)?;
+
+        self.access_token = t.access_token.to_string();
+        if !t.refresh_token.is_empty() {
+            self.refresh_token = t.refresh_token.to_string();
+        }
+
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in,
+            refresh_token: t.refresh_token,
+            refresh_token_expires_in: t.refresh_token_expires_in,
+        })
+    }
+}
+
+/// Refreshes Gusto tokens, the Gusto client lives outside of this workspace.
+struct GustoRefresh(Gusto);
+
+#[async_trait]
+impl TokenRefresh for GustoRefresh {
+    async fn refresh(&mut self) -> std::result::Result<RefreshedToken, RefreshError> {
+        let t = self
+            .0
+            .refresh_access_token()
+            .await
+            .map_err(RefreshError::from_client_error)?;
+
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in,
+            refresh_token: t.refresh_token,
+            refresh_token_expires_in: t.refresh_token_expires_in,
+        })
+    }
+}
+
+/// Refreshes Zoom tokens, the Zoom client lives outside of this workspace.
+struct ZoomRefresh(Zoom);
+
+#[async_trait]
+impl TokenRefresh for ZoomRefresh {
+    async fn refresh(&mut self) -> std::result::Result<RefreshedToken, RefreshError> {
+        let t = self
+            .0
+            .refresh_access_token()
+            .await
+            .map_err(RefreshError::from_client_error)?;
+
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in,
+            refresh_token: t.refresh_token,
+            refresh_token_expires_in: t.refresh_token_expires_in,
+        })
+    }
+}
+
+/// Encrypt any tokens that are still stored in plaintext, and re-encrypt tokens that were
+/// encrypted with a key other than the current one.
+pub async fn reencrypt_api_tokens(db: &Database, company: &Company) -> Result<()> {
+    let cipher = token_cipher(company).await?;
+
+    for mut token in APITokens::get_from_db(db, company.id).await?.0 {
+        let mut changed = false;
+
+        if let Some(access_token) = cipher.reencrypt(&token.access_token).await? {
+            token.access_token = access_token;
+            changed = true;
+        }
+
+        if let Some(refresh_token) = cipher.reencrypt(&token.refresh_token).await? {
+            token.refresh_token = refresh_token;
+            changed = true;
+        }
+
+        if changed {
+            token.update_in_db(db).await?;
+            info!("Re-encrypted {} API token {}", token.product, token.id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Refresh every token that expires soon, so that failures are noticed before the syncs that
+/// depend on the tokens run.
+pub async fn refresh_expiring_api_tokens(db: &Database, company: &Company) -> Result<()> {
+    let cutoff = Utc::now() + Duration::minutes(REFRESH_AHEAD_MINUTES);
+
+    for token in APITokens::get_from_db(db, company.id).await?.0 {
+        match token.expires_date {
+            Some(expires_date) if expires_date <= cutoff => (),
+            _ => continue,
+        }
+
+        if !token.is_refreshable() {
+            continue;
+        }
+
+        // Keep going so one broken token does not stop the others from being refreshed.
+        if let Err(err) = refresh_expiring_api_token(db, company, &token).await {
+            warn!(
+                "Failed to handle the refresh of {} API token {}: {}",
+                token.product, token.id, err
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn refresh_expiring_api_token(db: &Database, company: &Company, token: &APIToken) -> Result<()> {
+    let auth_company = Company::get_by_id(db, token.auth_company_id).await?;
+
+    // Only alert on the first failure, the refresh will keep failing until someone
+    // authorizes the integration again.
+    let previously_failed = token.last_refresh_failed(db, &auth_company).await?;
+
+    match token.refresh(db, &auth_company).await {
+        Ok(()) => info!("Refreshed {} API token {}", token.product, token.id),
+        Err(err) => {
+            warn!("Failed to refresh {} API token {}: {}", token.product, token.id, err);
+
+            let revoked = err
+                .downcast_ref::<RefreshError>()
+                .map(|e| e.is_revoked())
+                .unwrap_or(false);
+
+            if revoked && !previously_failed {
+                send_api_token_alert(
+                    db,
+                    company,
+                    format!(
+                        "The {} token for {} has been revoked. Authorize the integration again to restore it.",
+                        token.product, auth_company.name
+                    ),
+                )
+                .await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Warn about refresh tokens that expire soon. Once a refresh token expires the integration has
+/// to be authorized again by hand. Every token is warned about at most once a day, however often
+/// this runs.
+pub async fn check_api_token_expirations(db: &Database, company: &Company) -> Result<()> {
+    let now = Utc::now();
+    let cutoff = now + Duration::days(REFRESH_TOKEN_WARNING_DAYS);
+
+    for token in APITokens::get_from_db(db, company.id).await?.0 {
+        let expires_date = match token.refresh_token_expires_date {
+            Some(expires_date) if expires_date <= cutoff => expires_date,
+            _ => continue,
+        };
+
+        if let Err(err) = check_api_token_expiration(db, company, &token, expires_date, now).await {
+            warn!(
+                "Failed to warn about the expiration of {} API token {}: {}",
+                token.product, token.id, err
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn check_api_token_expiration(
+    db: &Database,
+    company: &Company,
+    token: &APIToken,
+    expires_date: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let last_alert = token.last_expiration_alert(db, company).await?;
+    if !expiration_alert_due(last_alert, now) {
+        return Ok(());
+    }
+
+    let auth_company = Company::get_by_id(db, token.auth_company_id).await?;
+
+    let when = if expires_date <= now {
+        "has expired".to_string()
+    } else {
+        format!("expires in {} days", (expires_date - now).num_days())
+    };
+
+    let sent = send_api_token_alert(
+        db,
+        company,
+        format!(
+            "The {} refresh token for {} {}. Authorize the integration again to renew it.",
+            token.product, auth_company.name, when
+        ),
+    )
+    .await;
+
+    // The audit log is what later runs check to not send the same alert again.
+    audit(
+        db,
+        company,
+        "slack",
+        "api_token_expiration_alert",
+        &token.id.to_string(),
+        sent,
+    )
+    .await
+}
+
+/// Returns if enough time has passed since the last warning about an expiring refresh token to
+/// warn about it again.
+fn expiration_alert_due(last_alert: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    match last_alert {
+        Some(last_alert) => now - last_alert >= Duration::hours(EXPIRATION_ALERT_INTERVAL_HOURS),
+        None => true,
+    }
+}
+
+async fn send_api_token_alert(db: &Database, company: &Company, text: String) -> Result<()> {
+    let mut msg = FormattedMessage::new().block(SectionBlock::markdown(text));
+    msg.channel = company.slack_channel_debug.to_string();
+
+    company.post_to_slack_channel(db, &msg).await
+}
+
 pub async fn refresh_api_tokens(db: &Database, company: &Company) -> Result<()> {
+    check_api_token_expirations(db, company).await?;
+
     APITokens::get_from_db(db, company.id)
         .await?
         .update_airtable(db)
@@ -145,3 +633,20 @@ pub async fn refresh_api_tokens(db: &Database, company: &Company) -> Result<()>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+
+    use super::expiration_alert_due;
+
+    #[test]
+    fn test_expiration_alert_due() {
+        let now = Utc::now();
+
+        assert!(expiration_alert_due(None, now));
+        assert!(!expiration_alert_due(Some(now - Duration::minutes(15)), now));
+        assert!(!expiration_alert_due(Some(now - Duration::hours(23)), now));
+        assert!(expiration_alert_due(Some(now - Duration::hours(24)), now));
+    }
+}
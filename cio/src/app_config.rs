@@ -2,7 +2,10 @@ use docusign::Envelope;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::{applicants::Applicant, companies::Company, configs::User};
+use crate::{
+    applicant_pipeline::Pipeline, applicant_retention::RetentionPolicy, applicant_rubric::Rubric,
+    applicants::Applicant, companies::Company, configs::User, rfd::PDFStorageTarget,
+};
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct DocuSignConfig {
@@ -157,6 +160,187 @@ pub struct GitHubConfig {
     pub ignored_repos: Vec<String>,
 }
 
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RFDConfig {
+    #[serde(default)]
+    pub pdf_storage: Vec<PDFStorageTarget>,
+    /// The actions to run, in order, when an RFD is updated. The built in list of actions is used
+    /// when this is empty.
+    #[serde(default)]
+    pub actions: Vec<RFDActionConfig>,
+    /// The base URL of the site that RFDs are rendered on, for instance `https://rfd.example.com`.
+    /// Links to an RFD fall back to its stored rendered link when this is not set.
+    #[serde(default)]
+    pub site_url: Option<String>,
+    #[serde(default)]
+    pub changelog: RFDChangelogConfig,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RFDChangelogConfig {
+    /// The recipients of the weekly changelog email. The changelog is sent to the `all@` list of
+    /// the company when this is empty.
+    #[serde(default)]
+    pub subscribers: Vec<RFDChangelogSubscriber>,
+    /// A Slack channel to post the full changelog to.
+    #[serde(default)]
+    pub slack_channel: Option<String>,
+}
+
+impl RFDChangelogConfig {
+    pub fn subscribers(&self, company: &Company) -> Vec<RFDChangelogSubscriber> {
+        if self.subscribers.is_empty() {
+            vec![RFDChangelogSubscriber {
+                email: format!("all@{}", company.gsuite_domain),
+                labels: vec![],
+                authors: vec![],
+            }]
+        } else {
+            self.subscribers.clone()
+        }
+    }
+}
+
+/// A recipient of the changelog email and the RFDs they want to hear about. An RFD is included if
+/// it matches any of the labels and any of the authors. An empty list matches every RFD.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RFDChangelogSubscriber {
+    pub email: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+}
+
+impl RFDChangelogSubscriber {
+    /// Check if an RFD with the given labels and authors is of interest to this subscriber. Authors
+    /// are matched case insensitively against the free form authors line of the RFD.
+    pub fn wants(&self, labels: &[String], authors: &str) -> bool {
+        let authors = authors.to_lowercase();
+
+        (self.labels.is_empty() || self.labels.iter().any(|label| labels.contains(label)))
+            && (self.authors.is_empty()
+                || self
+                    .authors
+                    .iter()
+                    .any(|author| authors.contains(&author.to_lowercase())))
+    }
+}
+
+/// An action to run when an RFD is updated, along with how a failure of the action is handled.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RFDActionConfig {
+    #[serde(flatten)]
+    pub action: RFDActionKind,
+    /// Whether a failure of this action stops the remaining actions from running. Each action has
+    /// its own default when this is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop_on_error: Option<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "name")]
+pub enum RFDActionKind {
+    #[serde(rename = "enforce_rfd_state_transition")]
+    EnforceRFDStateTransition,
+    #[serde(rename = "copy_images_to_gcp")]
+    CopyImagesToGCP,
+    #[serde(rename = "update_search")]
+    UpdateSearch,
+    #[serde(rename = "update_pdfs")]
+    UpdatePDFs,
+    #[serde(rename = "generate_short_urls")]
+    GenerateShortUrls,
+    #[serde(rename = "create_pull_request")]
+    CreatePullRequest,
+    #[serde(rename = "update_pull_request")]
+    UpdatePullRequest,
+    #[serde(rename = "parse_rfd_labels")]
+    ParseRFDLabels,
+    /// Requires `parse_rfd_labels` to run first.
+    #[serde(rename = "apply_rfd_labels")]
+    ApplyRFDLabels,
+    #[serde(rename = "update_references")]
+    UpdateReferences,
+    #[serde(rename = "update_discussion_url")]
+    UpdateDiscussionUrl,
+    #[serde(rename = "ensure_rfd_with_pull_request_is_in_valid_state")]
+    EnsureRFDWithPullRequestIsInValidState,
+    #[serde(rename = "ensure_rfd_on_default_is_in_valid_state")]
+    EnsureRFDOnDefaultIsInValidState,
+    #[serde(rename = "notify_slack")]
+    NotifySlack { channel: String },
+}
+
+impl RFDConfig {
+    /// The locations to store RFD PDFs in. Falls back to the locations enabled by feature flags when
+    /// none are configured.
+    pub fn pdf_storage_targets(&self) -> Vec<PDFStorageTarget> {
+        if self.pdf_storage.is_empty() {
+            PDFStorageTarget::from_features()
+        } else {
+            self.pdf_storage.clone()
+        }
+    }
+
+    /// The link to the rendered version of an RFD on the configured site.
+    pub fn rendered_url(&self, number_string: &str) -> Option<String> {
+        self.site_url
+            .as_ref()
+            .map(|site_url| format!("{}/rfd/{}", site_url.trim_end_matches('/'), number_string))
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct SlackConfig {
+    /// Named groups of Slack users, by user id (e.g. `U012AB3CD`), that restricted slash commands can
+    /// be limited to, e.g. `hiring` for the commands that show applicants. A command that requires a
+    /// group that is not configured is open to no one, unless no groups are configured at all, in
+    /// which case restricted commands are open to everyone like they were before groups existed.
+    #[serde(default)]
+    pub groups: HashMap<String, Vec<String>>,
+}
+
+impl SlackConfig {
+    /// Check if a Slack user is allowed to use commands restricted to a group. Users are only matched
+    /// on their id, as anyone can change their user name.
+    pub fn allows(&self, group: &str, user_id: &str) -> bool {
+        if self.groups.is_empty() {
+            return true;
+        }
+
+        match self.groups.get(group) {
+            Some(members) => members.iter().any(|member| member == user_id),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct HiringConfig {
+    /// The pipelines of the roles we hire for, by role. Roles without a pipeline use the default
+    /// pipeline.
+    #[serde(default)]
+    pub pipelines: HashMap<String, Pipeline>,
+    /// How long we keep the personal information of applicants.
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// The rubric applicant reviews are scored with.
+    #[serde(default)]
+    pub rubric: Rubric,
+}
+
+impl HiringConfig {
+    /// The pipeline for a role. Roles are matched case insensitively.
+    pub fn pipeline(&self, role: &str) -> Pipeline {
+        self.pipelines
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(role.trim()))
+            .map(|(_, pipeline)| pipeline.clone())
+            .unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct AppConfig {
     pub envelopes: DocuSignConfig,
@@ -165,11 +349,20 @@ pub struct AppConfig {
     pub finance: FinanceConfig,
     #[serde(default)]
     pub github: GitHubConfig,
+    #[serde(default)]
+    pub rfd: RFDConfig,
+    #[serde(default)]
+    pub slack: SlackConfig,
+    #[serde(default)]
+    pub hiring: HiringConfig,
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{ApplyConfig, DocuSignConfig, GitHubConfig, OnboardingConfig};
+    use super::{
+        ApplyConfig, DocuSignConfig, GitHubConfig, HiringConfig, OnboardingConfig, RFDActionConfig, RFDActionKind,
+        RFDConfig, SlackConfig,
+    };
     use crate::{applicants::tests::mock_applicant, companies::tests::mock_company, configs::tests::mock_user};
 
     fn mock_docusign_toml(label: &str) -> String {
@@ -369,4 +562,190 @@ ignored_repos = [
         .unwrap();
         assert_eq!(vec!["12345".to_string(), "67890".to_string(),], config.ignored_repos);
     }
+
+    #[test]
+    fn test_missing_rfd_actions_config() {
+        let config: RFDConfig = toml::from_str("").unwrap();
+        assert!(config.actions.is_empty());
+    }
+
+    #[test]
+    fn test_rfd_actions_config() {
+        let config: RFDConfig = toml::from_str(
+            r##"
+[[actions]]
+name = "parse_rfd_labels"
+
+[[actions]]
+name = "apply_rfd_labels"
+
+[[actions]]
+name = "notify_slack"
+channel = "#rfd"
+stop_on_error = false
+
+[[actions]]
+name = "update_discussion_url"
+stop_on_error = true
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                RFDActionConfig {
+                    action: RFDActionKind::ParseRFDLabels,
+                    stop_on_error: None,
+                },
+                RFDActionConfig {
+                    action: RFDActionKind::ApplyRFDLabels,
+                    stop_on_error: None,
+                },
+                RFDActionConfig {
+                    action: RFDActionKind::NotifySlack {
+                        channel: "#rfd".to_string()
+                    },
+                    stop_on_error: Some(false),
+                },
+                RFDActionConfig {
+                    action: RFDActionKind::UpdateDiscussionUrl,
+                    stop_on_error: Some(true),
+                },
+            ],
+            config.actions
+        );
+    }
+
+    #[test]
+    fn test_unknown_rfd_action() {
+        let config = toml::from_str::<RFDConfig>(
+            r#"
+[[actions]]
+name = "launch_rockets"
+"#,
+        );
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn test_rfd_changelog_config() {
+        let config: RFDConfig = toml::from_str(
+            r##"
+site_url = "https://rfd.example.com/"
+
+[changelog]
+slack_channel = "#rfd-changelog"
+
+[[changelog.subscribers]]
+email = "storage@example.com"
+labels = ["storage"]
+
+[[changelog.subscribers]]
+email = "everyone@example.com"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("https://rfd.example.com/rfd/0042".to_string()),
+            config.rendered_url("0042")
+        );
+        assert_eq!(Some("#rfd-changelog".to_string()), config.changelog.slack_channel);
+
+        let company = mock_company();
+        let subscribers = config.changelog.subscribers(&company);
+        assert_eq!(2, subscribers.len());
+        assert!(subscribers[1].labels.is_empty());
+
+        assert_eq!(
+            vec![RFDChangelogSubscriber {
+                email: format!("all@{}", company.gsuite_domain),
+                labels: vec![],
+                authors: vec![],
+            }],
+            RFDConfig::default().changelog.subscribers(&company)
+        );
+        assert_eq!(None, RFDConfig::default().rendered_url("0042"));
+    }
+
+    #[test]
+    fn test_rfd_changelog_subscriber_preferences() {
+        let subscriber = RFDChangelogSubscriber {
+            email: "storage@example.com".to_string(),
+            labels: vec!["storage".to_string(), "networking".to_string()],
+            authors: vec!["Jane Doe".to_string()],
+        };
+
+        let labels = vec!["storage".to_string()];
+        assert!(subscriber.wants(&labels, "jane doe <jane@example.com>, John Doe <john@example.com>"));
+        assert!(!subscriber.wants(&labels, "John Doe <john@example.com>"));
+        assert!(!subscriber.wants(&[], "Jane Doe <jane@example.com>"));
+
+        let everything = RFDChangelogSubscriber {
+            email: "all@example.com".to_string(),
+            labels: vec![],
+            authors: vec![],
+        };
+        assert!(everything.wants(&[], ""));
+    }
+
+    #[test]
+    fn test_slack_config() {
+        let config: SlackConfig = toml::from_str(
+            r#"
+[groups]
+hiring = ["U012AB3CD", "U045EF6GH"]
+"#,
+        )
+        .unwrap();
+
+        assert!(config.allows("hiring", "U012AB3CD"));
+        assert!(config.allows("hiring", "U045EF6GH"));
+        assert!(!config.allows("hiring", "U999"));
+        // Groups that are not configured are closed.
+        assert!(!config.allows("finance", "U012AB3CD"));
+        // Unless there are no groups at all.
+        assert!(SlackConfig::default().allows("hiring", "U999"));
+    }
+
+    #[test]
+    fn test_hiring_config() {
+        let config: HiringConfig = toml::from_str(
+            r#"
+[[pipelines.Hardware.stages]]
+name = "Applied"
+
+[[pipelines.Hardware.stages]]
+name = "Bench test"
+required_artifacts = ["materials"]
+advanced_by = ["lead@example.com"]
+
+[[rubric.evaluations]]
+evaluation = "yes"
+score = 1
+
+[[rubric.evaluations]]
+evaluation = "no"
+score = -1
+requires_rationale = true
+"#,
+        )
+        .unwrap();
+
+        let hardware = config.pipeline("hardware");
+        assert_eq!(hardware.stages.len(), 2);
+        assert_eq!(hardware.stages[1].advanced_by, vec!["lead@example.com".to_string()]);
+
+        // Roles without a pipeline use the default one.
+        assert_eq!(config.pipeline("Engineering"), Default::default());
+
+        // Without any retention rules we keep applicants forever.
+        assert!(config.retention.rules.is_empty());
+
+        // Reviews can only give the evaluations of the rubric, with any value.
+        assert_eq!(config.rubric.evaluations.len(), 2);
+        assert!(config.rubric.evaluations[1].requires_rationale);
+        assert!(config.rubric.evaluation("Emphatic Yes: hire them.").is_none());
+        assert!(config.rubric.values.is_empty());
+    }
 }
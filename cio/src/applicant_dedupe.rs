@@ -0,0 +1,836 @@
+//! Finding and merging applicants that are the same person.
+//!
+//! Applicants are matched on their email and sheet, so someone who applies twice, or with a
+//! second email address, ends up as more than one [`Applicant`] with their reviews and interviews
+//! split between them. We compare what identifies a person (their email, name, GitHub, LinkedIn and
+//! phone number) across all the applicants of a company and record every pair that looks like the
+//! same person as an [`ApplicantDuplicate`]. Those are posted to Slack and synced to Airtable,
+//! where someone confirms or dismisses the merge.
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+    ops::DerefMut,
+};
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use macros::db;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use slack_chat_api::{
+    blocks::{ActionsBlock, ButtonElement, ButtonStyle, ContextBlock, SectionBlock},
+    FormattedMessage, MessageAttachment,
+};
+
+use crate::{
+    airtable::AIRTABLE_APPLICANT_DUPLICATES_TABLE,
+    applicant_reviews::ApplicantReview,
+    applicants::{Applicant, Applicants},
+    audit::AuditScope,
+    companies::Company,
+    core::UpdateAirtableRecord,
+    db::Database,
+    interviews::ApplicantInterview,
+    schema::{applicant_duplicates, applicant_interviews, applicant_reviews, applicant_stage_changes, applicants},
+};
+
+/// How alike two applicants need to be before we ask someone whether they are the same person.
+pub const DUPLICATE_THRESHOLD: f32 = 0.75;
+
+/// The Slack action id of the button that merges a duplicate.
+pub const MERGE_DUPLICATE_ACTION: &str = "applicant_duplicate_merge";
+/// The Slack action id of the button that dismisses a duplicate.
+pub const DISMISS_DUPLICATE_ACTION: &str = "applicant_duplicate_dismiss";
+
+/// Something that identifies an applicant and that two applicants were found to share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateSignal {
+    Email,
+    GitHub,
+    LinkedIn,
+    Phone,
+    Name,
+}
+
+impl DuplicateSignal {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateSignal::Email => "email",
+            DuplicateSignal::GitHub => "github",
+            DuplicateSignal::LinkedIn => "linkedin",
+            DuplicateSignal::Phone => "phone",
+            DuplicateSignal::Name => "name",
+        }
+    }
+
+    /// How likely two applicants are to be the same person when they only share this. A name on
+    /// its own is never enough to flag a pair.
+    pub fn weight(&self) -> f32 {
+        match self {
+            DuplicateSignal::Email => 0.95,
+            DuplicateSignal::GitHub => 0.9,
+            DuplicateSignal::LinkedIn => 0.9,
+            DuplicateSignal::Phone => 0.8,
+            DuplicateSignal::Name => 0.5,
+        }
+    }
+}
+
+impl fmt::Display for DuplicateSignal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The normalized values we compare applicants on. Empty values never match.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Fingerprint {
+    email: String,
+    github: String,
+    linkedin: String,
+    phone: String,
+    name: String,
+}
+
+impl Fingerprint {
+    fn of(applicant: &Applicant) -> Self {
+        Fingerprint {
+            email: normalize_email(&applicant.email),
+            github: normalize_github(&applicant.github),
+            linkedin: normalize_linkedin(applicant),
+            phone: normalize_phone(&applicant.phone),
+            name: normalize_name(&applicant.name),
+        }
+    }
+
+    fn signals(&self) -> Vec<(DuplicateSignal, &str)> {
+        vec![
+            (DuplicateSignal::Email, self.email.as_str()),
+            (DuplicateSignal::GitHub, self.github.as_str()),
+            (DuplicateSignal::LinkedIn, self.linkedin.as_str()),
+            (DuplicateSignal::Phone, self.phone.as_str()),
+            (DuplicateSignal::Name, self.name.as_str()),
+        ]
+        .into_iter()
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+    }
+
+    fn shared(&self, other: &Fingerprint) -> Vec<DuplicateSignal> {
+        let theirs = other.signals();
+
+        self.signals()
+            .into_iter()
+            .filter(|signal| theirs.contains(signal))
+            .map(|(signal, _)| signal)
+            .collect()
+    }
+}
+
+/// Lowercase the email and drop anything that still delivers to the same inbox: a `+tag` and,
+/// for Gmail, the dots in the local part.
+fn normalize_email(email: &str) -> String {
+    let email = email.trim().to_lowercase();
+    let (local, domain) = match email.rsplit_once('@') {
+        Some(parts) => parts,
+        None => return email,
+    };
+
+    let mut local = local.split('+').next().unwrap_or_default().to_string();
+    let mut domain = domain.to_string();
+    if domain == "gmail.com" || domain == "googlemail.com" {
+        local = local.replace('.', "");
+        domain = "gmail.com".to_string();
+    }
+
+    if local.is_empty() {
+        return String::new();
+    }
+
+    format!("{}@{}", local, domain)
+}
+
+/// Reduce whatever someone typed in the GitHub field to their login.
+fn normalize_github(github: &str) -> String {
+    let github = github.trim().to_lowercase();
+    let github = github
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_start_matches("github.com/")
+        .trim_start_matches('@');
+
+    github.split('/').next().unwrap_or_default().to_string()
+}
+
+/// The LinkedIn profile as cleaned up for the applicant, without any query or trailing slash.
+fn normalize_linkedin(applicant: &Applicant) -> String {
+    let mut cleaned = applicant.clone();
+    cleaned.cleanup_linkedin();
+
+    let linkedin = cleaned.linkedin.to_lowercase();
+    let linkedin = linkedin.split('?').next().unwrap_or_default().trim_end_matches('/');
+    if linkedin == "https://linkedin.com" {
+        return String::new();
+    }
+
+    linkedin.to_string()
+}
+
+/// The last ten digits of the phone number, so the same number with and without a country code
+/// matches. Anything too short to be a phone number is ignored.
+fn normalize_phone(phone: &str) -> String {
+    let digits = phone.chars().filter(|c| c.is_ascii_digit()).collect::<String>();
+    if digits.len() < 7 {
+        return String::new();
+    }
+
+    digits[digits.len().saturating_sub(10)..].to_string()
+}
+
+/// The name in lowercase without punctuation. We only compare names of at least two words, a
+/// single name matches far too many people.
+fn normalize_name(name: &str) -> String {
+    let name = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>();
+    let words = name.split_whitespace().collect::<Vec<_>>();
+    if words.len() < 2 {
+        return String::new();
+    }
+
+    words.join(" ")
+}
+
+/// Combine the weights of the shared signals, treating each one as independent evidence.
+fn score(signals: &[DuplicateSignal]) -> f32 {
+    1.0 - signals.iter().map(|signal| 1.0 - signal.weight()).product::<f32>()
+}
+
+/// Two applicants that look like the same person.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateCandidate<'a> {
+    /// The applicant we keep, this is the one that applied first.
+    pub primary: &'a Applicant,
+    /// The applicant that is merged into the primary.
+    pub duplicate: &'a Applicant,
+    pub score: f32,
+    pub signals: Vec<DuplicateSignal>,
+}
+
+/// Compare two applicants, returning the signals they share and how alike that makes them.
+pub fn compare(a: &Applicant, b: &Applicant) -> (f32, Vec<DuplicateSignal>) {
+    let signals = Fingerprint::of(a).shared(&Fingerprint::of(b));
+    (score(&signals), signals)
+}
+
+/// Find the pairs of applicants that score at least `threshold`. We only compare applicants
+/// that share at least one signal, so this does not look at every possible pair.
+pub fn find_duplicates(applicants: &[Applicant], threshold: f32) -> Vec<DuplicateCandidate<'_>> {
+    let fingerprints = applicants.iter().map(Fingerprint::of).collect::<Vec<_>>();
+
+    let mut buckets: HashMap<(DuplicateSignal, &str), Vec<usize>> = HashMap::new();
+    for (i, fingerprint) in fingerprints.iter().enumerate() {
+        for key in fingerprint.signals() {
+            buckets.entry(key).or_default().push(i);
+        }
+    }
+
+    let mut pairs = BTreeSet::new();
+    for indexes in buckets.values() {
+        for (n, a) in indexes.iter().enumerate() {
+            for b in &indexes[n + 1..] {
+                pairs.insert((*a, *b));
+            }
+        }
+    }
+
+    pairs
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let signals = fingerprints[a].shared(&fingerprints[b]);
+            let score = score(&signals);
+            if score < threshold {
+                return None;
+            }
+
+            let (a, b) = (&applicants[a], &applicants[b]);
+            let (primary, duplicate) = if (b.submitted_time, b.id) < (a.submitted_time, a.id) {
+                (b, a)
+            } else {
+                (a, b)
+            };
+
+            Some(DuplicateCandidate {
+                primary,
+                duplicate,
+                score,
+                signals,
+            })
+        })
+        .collect()
+}
+
+/// Fold what the duplicate has into the primary applicant: anything the primary is missing, the
+/// links to reviews and interviews, and the role the duplicate applied for.
+pub fn absorb(primary: &mut Applicant, duplicate: &Applicant) {
+    fn fill(field: &mut String, from: &str) {
+        if field.trim().is_empty() && !from.trim().is_empty() {
+            *field = from.to_string();
+        }
+    }
+
+    fn union(field: &mut Vec<String>, from: &[String]) {
+        for value in from {
+            if !field.contains(value) {
+                field.push(value.to_string());
+            }
+        }
+    }
+
+    fill(&mut primary.phone, &duplicate.phone);
+    fill(&mut primary.location, &duplicate.location);
+    fill(&mut primary.github, &duplicate.github);
+    fill(&mut primary.gitlab, &duplicate.gitlab);
+    fill(&mut primary.linkedin, &duplicate.linkedin);
+    fill(&mut primary.portfolio, &duplicate.portfolio);
+    fill(&mut primary.portfolio_pdf, &duplicate.portfolio_pdf);
+    fill(&mut primary.website, &duplicate.website);
+    fill(&mut primary.resume, &duplicate.resume);
+    fill(&mut primary.resume_contents, &duplicate.resume_contents);
+    fill(&mut primary.materials, &duplicate.materials);
+    fill(&mut primary.materials_contents, &duplicate.materials_contents);
+
+    union(&mut primary.link_to_reviews, &duplicate.link_to_reviews);
+    union(&mut primary.interviews, &duplicate.interviews);
+    union(&mut primary.scorers, &duplicate.scorers);
+
+    let mut roles = duplicate.interested_in.clone();
+    roles.push(duplicate.role.to_string());
+    roles.retain(|role| !role.is_empty() && *role != primary.role);
+    union(&mut primary.interested_in, &roles);
+}
+
+/// What has been decided about a possible duplicate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub enum DuplicateStatus {
+    Pending,
+    Merged,
+    Dismissed,
+}
+
+impl DuplicateStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateStatus::Pending => "Pending",
+            DuplicateStatus::Merged => "Merged",
+            DuplicateStatus::Dismissed => "Dismissed",
+        }
+    }
+}
+
+impl fmt::Display for DuplicateStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A pair of applicants that look like the same person.
+#[db {
+    new_struct_name = "ApplicantDuplicate",
+    airtable_base = "hiring",
+    airtable_table = "AIRTABLE_APPLICANT_DUPLICATES_TABLE",
+    match_on = {
+        "primary_applicant_id" = "i32",
+        "duplicate_applicant_id" = "i32",
+    },
+}]
+#[derive(Debug, Insertable, AsChangeset, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = applicant_duplicates)]
+pub struct NewApplicantDuplicate {
+    pub primary_applicant_id: i32,
+    pub duplicate_applicant_id: i32,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub primary_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub primary_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub primary_role: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub duplicate_name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub duplicate_email: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub duplicate_role: String,
+    #[serde(default)]
+    pub score: f32,
+    /// The signals the two applicants share.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub signals: Vec<String>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub status: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub resolved_by: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at: Option<DateTime<Utc>>,
+
+    /// The CIO company ID.
+    #[serde(default)]
+    pub cio_company_id: i32,
+}
+
+/// Implement updating the Airtable record for an ApplicantDuplicate.
+#[async_trait]
+impl UpdateAirtableRecord<ApplicantDuplicate> for ApplicantDuplicate {
+    async fn update_airtable_record(&mut self, _record: ApplicantDuplicate) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl From<&DuplicateCandidate<'_>> for NewApplicantDuplicate {
+    fn from(candidate: &DuplicateCandidate<'_>) -> Self {
+        NewApplicantDuplicate {
+            primary_applicant_id: candidate.primary.id,
+            duplicate_applicant_id: candidate.duplicate.id,
+            primary_name: candidate.primary.name.to_string(),
+            primary_email: candidate.primary.email.to_string(),
+            primary_role: candidate.primary.role.to_string(),
+            duplicate_name: candidate.duplicate.name.to_string(),
+            duplicate_email: candidate.duplicate.email.to_string(),
+            duplicate_role: candidate.duplicate.role.to_string(),
+            score: candidate.score,
+            signals: candidate.signals.iter().map(|s| s.to_string()).collect(),
+            status: DuplicateStatus::Pending.to_string(),
+            resolved_by: Default::default(),
+            resolved_at: None,
+            cio_company_id: candidate.primary.cio_company_id,
+        }
+    }
+}
+
+/// Convert the duplicate into a Slack message asking whether to merge it. The buttons carry the id
+/// of the pair, as an applicant can be the duplicate in more than one pair.
+impl From<ApplicantDuplicate> for FormattedMessage {
+    fn from(item: ApplicantDuplicate) -> Self {
+        let text = format!(
+            "*Possible duplicate applicant* ({:.0}% match on {})",
+            item.score * 100.0,
+            item.signals.join(", ")
+        );
+        let keep = format!(
+            "keep: *{}* <mailto:{}|{}> | {}",
+            item.primary_name, item.primary_email, item.primary_email, item.primary_role
+        );
+        let merge = format!(
+            "merge: *{}* <mailto:{}|{}> | {}",
+            item.duplicate_name, item.duplicate_email, item.duplicate_email, item.duplicate_role
+        );
+
+        FormattedMessage::new().attachment(
+            MessageAttachment::colored(crate::colors::Colors::Yellow.to_string())
+                .block(SectionBlock::markdown(text))
+                .block(ContextBlock::new().markdown(keep))
+                .block(ContextBlock::new().markdown(merge))
+                .block(
+                    ActionsBlock::new()
+                        .element(
+                            ButtonElement::new("Merge", MERGE_DUPLICATE_ACTION)
+                                .value(item.id)
+                                .style(ButtonStyle::Primary),
+                        )
+                        .element(ButtonElement::new("Not the same person", DISMISS_DUPLICATE_ACTION).value(item.id)),
+                ),
+        )
+    }
+}
+
+impl ApplicantDuplicate {
+    /// The message asking about the pair in the applicants channel of the company.
+    pub fn slack_message(&self, company: &Company) -> FormattedMessage {
+        let mut msg: FormattedMessage = self.clone().into();
+        msg.channel = company.slack_channel_applicants.to_string();
+
+        msg
+    }
+
+    /// The pending pair of applicants with the given id.
+    pub async fn pending(db: &Database, cio_company_id: i32, id: i32) -> Result<Self> {
+        Ok(applicant_duplicates::dsl::applicant_duplicates
+            .filter(applicant_duplicates::dsl::cio_company_id.eq(cio_company_id))
+            .filter(applicant_duplicates::dsl::id.eq(id))
+            .filter(applicant_duplicates::dsl::status.eq(DuplicateStatus::Pending.to_string()))
+            .first_async::<ApplicantDuplicate>(db.pool())
+            .await?)
+    }
+
+    /// Merge the duplicate applicant into the primary one, moving over their reviews, interviews
+    /// and stage history, and delete the duplicate. The links to the resume, materials and other
+    /// uploads of the duplicate are kept on the primary, but the files themselves stay where they
+    /// are in Drive. Upload tokens are left alone, as they belong to the email they were sent to.
+    pub async fn merge(&mut self, db: &Database) -> Result<Applicant> {
+        if self.status != DuplicateStatus::Pending.to_string() {
+            bail!(
+                "duplicate applicant {} is already {}",
+                self.duplicate_applicant_id,
+                self.status
+            );
+        }
+
+        let mut primary = Applicant::get_by_id(db, self.primary_applicant_id).await?;
+        let duplicate = Applicant::get_by_id(db, self.duplicate_applicant_id).await?;
+
+        // Point the reviews and interviews of the duplicate at the primary. Airtable keeps the
+        // links on the applicants themselves in step with these.
+        let mut reviews = applicant_reviews::dsl::applicant_reviews
+            .filter(applicant_reviews::dsl::cio_company_id.eq(self.cio_company_id))
+            .filter(applicant_reviews::dsl::applicant.contains(vec![duplicate.airtable_record_id.to_string()]))
+            .load_async::<ApplicantReview>(db.pool())
+            .await?;
+        for review in reviews.iter_mut() {
+            relink(&mut review.applicant, &duplicate, &primary);
+        }
+
+        let mut interviews = applicant_interviews::dsl::applicant_interviews
+            .filter(applicant_interviews::dsl::cio_company_id.eq(self.cio_company_id))
+            .filter(applicant_interviews::dsl::applicant.contains(vec![duplicate.airtable_record_id.to_string()]))
+            .load_async::<ApplicantInterview>(db.pool())
+            .await?;
+        for interview in interviews.iter_mut() {
+            relink(&mut interview.applicant, &duplicate, &primary);
+            if interview.email == duplicate.email {
+                interview.email = primary.email.to_string();
+            }
+        }
+
+        absorb(&mut primary, &duplicate);
+
+        // Any other pairs the duplicate was part of no longer make sense, they will be found
+        // again against the primary on the next refresh.
+        let stale = applicant_duplicates::dsl::applicant_duplicates
+            .filter(applicant_duplicates::dsl::id.ne(self.id))
+            .filter(applicant_duplicates::dsl::status.eq(DuplicateStatus::Pending.to_string()))
+            .filter(
+                applicant_duplicates::dsl::primary_applicant_id
+                    .eq(duplicate.id)
+                    .or(applicant_duplicates::dsl::duplicate_applicant_id.eq(duplicate.id)),
+            )
+            .load_async::<ApplicantDuplicate>(db.pool())
+            .await?;
+
+        self.status = DuplicateStatus::Merged.to_string();
+        self.resolved_by = AuditScope::current().actor;
+        self.resolved_at = Some(Utc::now());
+
+        // Make every change to the database at once, so a failure part way through can not leave
+        // the applicants half merged. Airtable is only brought in step once they are committed.
+        let (tx_reviews, tx_interviews, tx_primary, tx_pair) =
+            (reviews.clone(), interviews.clone(), primary.clone(), self.clone());
+        let duplicate_id = duplicate.id;
+        let stale_ids = stale.iter().map(|pair| pair.id).collect::<Vec<_>>();
+        db.pool()
+            .transaction(move |mut conn| {
+                for review in tx_reviews {
+                    diesel::update(applicant_reviews::dsl::applicant_reviews)
+                        .filter(applicant_reviews::dsl::id.eq(review.id))
+                        .set(review)
+                        .execute(conn.deref_mut())?;
+                }
+
+                for interview in tx_interviews {
+                    diesel::update(applicant_interviews::dsl::applicant_interviews)
+                        .filter(applicant_interviews::dsl::id.eq(interview.id))
+                        .set(interview)
+                        .execute(conn.deref_mut())?;
+                }
+
+                diesel::update(
+                    applicant_stage_changes::dsl::applicant_stage_changes
+                        .filter(applicant_stage_changes::dsl::applicant_id.eq(duplicate_id)),
+                )
+                .set(applicant_stage_changes::dsl::applicant_id.eq(tx_primary.id))
+                .execute(conn.deref_mut())?;
+
+                diesel::update(applicants::dsl::applicants)
+                    .filter(applicants::dsl::id.eq(tx_primary.id))
+                    .set(tx_primary)
+                    .execute(conn.deref_mut())?;
+
+                diesel::delete(
+                    applicant_duplicates::dsl::applicant_duplicates
+                        .filter(applicant_duplicates::dsl::id.eq_any(stale_ids)),
+                )
+                .execute(conn.deref_mut())?;
+
+                diesel::update(applicant_duplicates::dsl::applicant_duplicates)
+                    .filter(applicant_duplicates::dsl::id.eq(tx_pair.id))
+                    .set(tx_pair)
+                    .execute(conn.deref_mut())?;
+
+                diesel::delete(applicants::dsl::applicants.filter(applicants::dsl::id.eq(duplicate_id)))
+                    .execute(conn.deref_mut())?;
+
+                Ok::<(), anyhow::Error>(())
+            })
+            .await?;
+
+        info!(
+            "merged applicant {} ({}) into {} ({})",
+            duplicate.id, duplicate.email, primary.id, primary.email
+        );
+
+        // The merge is done, so keep syncing the rest of Airtable if one record fails.
+        let mut failures = vec![];
+        for mut review in reviews {
+            if let Err(err) = review.upsert_in_airtable(db).await {
+                failures.push(format!("review {}: {}", review.id, err));
+            }
+        }
+        for mut interview in interviews {
+            if let Err(err) = interview.upsert_in_airtable(db).await {
+                failures.push(format!("interview {}: {}", interview.id, err));
+            }
+        }
+        if let Err(err) = primary.upsert_in_airtable(db).await {
+            failures.push(format!("applicant {}: {}", primary.id, err));
+        }
+        if let Err(err) = duplicate.delete_from_airtable(db).await {
+            failures.push(format!("applicant {}: {}", duplicate.id, err));
+        }
+        for pair in stale {
+            if let Err(err) = pair.delete_from_airtable(db).await {
+                failures.push(format!("duplicate {}: {}", pair.id, err));
+            }
+        }
+        if let Err(err) = self.upsert_in_airtable(db).await {
+            failures.push(format!("duplicate {}: {}", self.id, err));
+        }
+
+        if !failures.is_empty() {
+            bail!(
+                "merged applicant {} into {}, but failed to update Airtable for {}",
+                duplicate.id,
+                primary.id,
+                failures.join(", ")
+            );
+        }
+
+        Ok(primary)
+    }
+
+    /// Record that the two applicants are not the same person, so we do not ask again.
+    pub async fn dismiss(&mut self, db: &Database) -> Result<()> {
+        if self.status != DuplicateStatus::Pending.to_string() {
+            bail!(
+                "duplicate applicant {} is already {}",
+                self.duplicate_applicant_id,
+                self.status
+            );
+        }
+
+        self.resolve(db, DuplicateStatus::Dismissed).await
+    }
+
+    async fn resolve(&mut self, db: &Database, status: DuplicateStatus) -> Result<()> {
+        self.status = status.to_string();
+        self.resolved_by = AuditScope::current().actor;
+        self.resolved_at = Some(Utc::now());
+        *self = self.update(db).await?;
+
+        Ok(())
+    }
+}
+
+/// Swap the link to the duplicate for one to the primary.
+fn relink(links: &mut Vec<String>, duplicate: &Applicant, primary: &Applicant) {
+    links.retain(|id| *id != duplicate.airtable_record_id);
+    if !links.contains(&primary.airtable_record_id) {
+        links.push(primary.airtable_record_id.to_string());
+    }
+}
+
+/// The email of the applicant an email was merged into, or the email itself if it was never
+/// merged. Calendar invites and the like keep using the email of the duplicate.
+pub async fn resolve_merged_email(db: &Database, email: &str) -> String {
+    applicant_duplicates::dsl::applicant_duplicates
+        .filter(applicant_duplicates::dsl::duplicate_email.eq(email.to_string()))
+        .filter(applicant_duplicates::dsl::status.eq(DuplicateStatus::Merged.to_string()))
+        .select(applicant_duplicates::dsl::primary_email)
+        .first_async::<String>(db.pool())
+        .await
+        .unwrap_or_else(|_| email.to_string())
+}
+
+/// Look for new duplicate applicants and ask about them in Slack.
+pub async fn refresh_applicant_duplicates(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_hiring.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let applicants = Applicants::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|applicant| !applicant.is_anonymized())
+        .collect::<Vec<_>>();
+
+    for candidate in find_duplicates(&applicants, DUPLICATE_THRESHOLD) {
+        // Keep whatever was already decided about this pair. The `match_on` fields are passed in
+        // alphabetical order, so the duplicate comes before the primary.
+        if ApplicantDuplicate::get_from_db(db, candidate.duplicate.id, candidate.primary.id)
+            .await
+            .is_some()
+        {
+            continue;
+        }
+
+        let duplicate = NewApplicantDuplicate::from(&candidate).upsert(db).await?;
+        info!(
+            "found possible duplicate applicant {} of {} ({:?})",
+            duplicate.duplicate_applicant_id, duplicate.primary_applicant_id, candidate.signals
+        );
+
+        // The pair is stored either way, so a failed post only means nobody is asked about it
+        // until it shows up in Airtable.
+        let msg = duplicate.slack_message(company);
+        if let Err(e) = company.post_to_slack_channel(db, &msg).await {
+            warn!(
+                "posting duplicate applicant {} of {} to slack failed: {}",
+                duplicate.duplicate_applicant_id, duplicate.primary_applicant_id, e
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::applicants::tests::mock_applicant;
+
+    fn applicant(id: i32, name: &str, email: &str) -> Applicant {
+        let mut applicant = mock_applicant();
+        applicant.id = id;
+        applicant.name = name.to_string();
+        applicant.email = email.to_string();
+        applicant.submitted_time = Utc::now() - Duration::days(id as i64);
+        applicant
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize_email(" Jane.Doe+jobs@GMail.com "), "janedoe@gmail.com");
+        assert_eq!(normalize_email("jane.doe@example.com"), "jane.doe@example.com");
+        assert_eq!(normalize_github("https://github.com/JaneDoe/"), "janedoe");
+        assert_eq!(normalize_github("@janedoe"), "janedoe");
+        assert_eq!(normalize_phone("+1 (555) 123-4567"), "5551234567");
+        assert_eq!(normalize_phone("555-1234"), "5551234");
+        assert_eq!(normalize_phone("n/a"), "");
+        assert_eq!(normalize_name("Jane  A. Doe"), "jane a doe");
+        assert_eq!(normalize_name("Jane"), "");
+
+        let mut a = applicant(1, "Jane Doe", "jane@example.com");
+        a.linkedin = "https://www.linkedin.com/in/JaneDoe/?trk=x".to_string();
+        assert_eq!(normalize_linkedin(&a), "https://linkedin.com/in/janedoe");
+        a.linkedin = " ".to_string();
+        assert_eq!(normalize_linkedin(&a), "");
+    }
+
+    #[test]
+    fn test_find_duplicates() {
+        let mut first = applicant(2, "Jane Doe", "jane.doe@gmail.com");
+        first.phone = "+1 555 123 4567".to_string();
+        let mut second = applicant(1, "Jane Doe", "jane@work.example.com");
+        second.phone = "(555) 123-4567".to_string();
+        // Shares only a name, which is not enough.
+        let third = applicant(3, "Jane Doe", "other@example.com");
+        // Shares an email once it is normalized.
+        let fourth = applicant(4, "J. Doe", "janedoe+apply@gmail.com");
+
+        let applicants = vec![first, second, third, fourth];
+        let mut duplicates = find_duplicates(&applicants, DUPLICATE_THRESHOLD)
+            .into_iter()
+            .map(|d| (d.primary.id, d.duplicate.id, d.signals))
+            .collect::<Vec<_>>();
+        duplicates.sort();
+
+        assert_eq!(
+            duplicates,
+            vec![
+                (2, 1, vec![DuplicateSignal::Phone, DuplicateSignal::Name]),
+                (4, 2, vec![DuplicateSignal::Email]),
+            ]
+        );
+
+        let (score, _) = compare(&applicants[0], &applicants[2]);
+        assert!(score < DUPLICATE_THRESHOLD);
+    }
+
+    #[test]
+    fn test_slack_message() {
+        let mut company = crate::companies::tests::mock_company();
+        company.slack_channel_applicants = "#applicants".to_string();
+
+        let applicants = vec![
+            applicant(2, "Jane Doe", "jane.doe@gmail.com"),
+            applicant(1, "Jane Doe", "janedoe@gmail.com"),
+        ];
+        let candidates = find_duplicates(&applicants, DUPLICATE_THRESHOLD);
+        let new = NewApplicantDuplicate::from(&candidates[0]);
+        let duplicate = ApplicantDuplicate {
+            id: 7,
+            primary_applicant_id: new.primary_applicant_id,
+            duplicate_applicant_id: new.duplicate_applicant_id,
+            primary_name: new.primary_name,
+            primary_email: new.primary_email,
+            primary_role: new.primary_role,
+            duplicate_name: new.duplicate_name,
+            duplicate_email: new.duplicate_email,
+            duplicate_role: new.duplicate_role,
+            score: new.score,
+            signals: new.signals,
+            status: new.status,
+            resolved_by: new.resolved_by,
+            resolved_at: new.resolved_at,
+            cio_company_id: new.cio_company_id,
+            airtable_record_id: String::new(),
+        };
+
+        let msg = duplicate.slack_message(&company);
+        assert_eq!(msg.channel, "#applicants");
+        assert!(!msg.attachments.is_empty());
+
+        // The buttons identify the pair, not the duplicate applicant.
+        let msg = serde_json::to_string(&msg).unwrap();
+        assert!(msg.contains(r#""value":"7""#));
+        assert!(!msg.contains(r#""value":"1""#));
+    }
+
+    #[test]
+    fn test_absorb() {
+        let mut primary = applicant(2, "Jane Doe", "jane@example.com");
+        primary.link_to_reviews = vec!["rec1".to_string()];
+        let mut duplicate = applicant(1, "Jane Doe", "jane@other.example.com");
+        duplicate.role = "Product Engineering".to_string();
+        duplicate.github = "janedoe".to_string();
+        duplicate.resume = "https://example.com/resume.pdf".to_string();
+        duplicate.link_to_reviews = vec!["rec1".to_string(), "rec2".to_string()];
+        duplicate.interviews = vec!["rec3".to_string()];
+
+        absorb(&mut primary, &duplicate);
+
+        assert_eq!(primary.email, "jane@example.com");
+        assert_eq!(primary.github, "janedoe");
+        assert_eq!(primary.link_to_reviews, vec!["rec1".to_string(), "rec2".to_string()]);
+        assert_eq!(primary.interviews, vec!["rec3".to_string()]);
+        assert!(primary.interested_in.contains(&"Product Engineering".to_string()));
+    }
+}
@@ -0,0 +1,675 @@
+//! The stages an applicant moves through on their way to being hired.
+//!
+//! Every role can have its own [`Pipeline`] in the app config: the stages it has, the artifacts an
+//! applicant needs before entering a stage, and who may move applicants into it. Roles without a
+//! pipeline use the default one, which is made up of the [`Status`] values. Every move between
+//! stages is recorded as an [`ApplicantStageChange`], which is what the time in stage metrics are
+//! computed from.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    airtable::AIRTABLE_APPLICATIONS_TABLE, applicant_status::Status, applicants::Applicant, audit::AuditScope,
+    companies::Company, db::Database, schema::applicant_stage_changes,
+};
+
+/// Something an applicant needs to have before they can enter a stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApplicantArtifact {
+    Resume,
+    Materials,
+    Portfolio,
+    Interviews,
+    Reviews,
+    Offer,
+    BackgroundCheck,
+    StartDate,
+}
+
+impl ApplicantArtifact {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApplicantArtifact::Resume => "resume",
+            ApplicantArtifact::Materials => "materials",
+            ApplicantArtifact::Portfolio => "portfolio",
+            ApplicantArtifact::Interviews => "interviews",
+            ApplicantArtifact::Reviews => "reviews",
+            ApplicantArtifact::Offer => "offer",
+            ApplicantArtifact::BackgroundCheck => "background_check",
+            ApplicantArtifact::StartDate => "start_date",
+        }
+    }
+
+    /// Check if the applicant has this artifact. An offer counts once it has been signed, a
+    /// background check once it has been requested.
+    pub fn is_present(&self, applicant: &Applicant) -> bool {
+        match self {
+            ApplicantArtifact::Resume => !applicant.resume.is_empty(),
+            ApplicantArtifact::Materials => !applicant.materials.is_empty(),
+            ApplicantArtifact::Portfolio => !applicant.portfolio.is_empty() || !applicant.portfolio_pdf.is_empty(),
+            ApplicantArtifact::Interviews => !applicant.interviews.is_empty(),
+            ApplicantArtifact::Reviews => !applicant.link_to_reviews.is_empty(),
+            ApplicantArtifact::Offer => applicant.offer_completed.is_some(),
+            ApplicantArtifact::BackgroundCheck => !applicant.criminal_background_check_status.is_empty(),
+            ApplicantArtifact::StartDate => applicant.start_date.is_some(),
+        }
+    }
+}
+
+impl fmt::Display for ApplicantArtifact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct PipelineStage {
+    /// The name of the stage, this is what is stored as the status of the applicant.
+    pub name: String,
+    /// The artifacts an applicant needs to have to enter this stage.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_artifacts: Vec<ApplicantArtifact>,
+    /// The people that may move applicants into this stage, by email. Everyone may when this is
+    /// empty. Moves made in Airtable are checked against the collaborator that changed the status,
+    /// and moves made through the internal API against the viewer whose Google token comes with
+    /// the request. Moves made without a viewer, i.e. by automation, are not checked.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advanced_by: Vec<String>,
+    /// The stages an applicant in this stage may move to. They may move to any stage when this is
+    /// empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub next: Vec<String>,
+}
+
+impl PipelineStage {
+    pub fn new(name: &str) -> Self {
+        PipelineStage {
+            name: name.to_string(),
+            required_artifacts: vec![],
+            advanced_by: vec![],
+            next: vec![],
+        }
+    }
+
+    fn is_named(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name.trim())
+    }
+
+    fn allows(&self, actor: &str) -> bool {
+        self.advanced_by.is_empty()
+            || self
+                .advanced_by
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(actor))
+    }
+}
+
+/// Who is moving an applicant between stages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovedBy<'a> {
+    /// A person, by email.
+    Person(&'a str),
+    /// A person we could not identify, e.g. when Airtable does not say who changed the status.
+    /// They may not move applicants into stages that only some people may advance to.
+    Unknown,
+    /// The internal API or automation, which is trusted to move applicants anywhere the pipeline
+    /// allows.
+    Internal,
+}
+
+impl fmt::Display for MovedBy<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovedBy::Person(email) => write!(f, "{}", email),
+            MovedBy::Unknown => write!(f, "an unknown person"),
+            MovedBy::Internal => write!(f, "automation"),
+        }
+    }
+}
+
+/// The stages of hiring for a role, in the order applicants usually move through them.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct Pipeline {
+    pub stages: Vec<PipelineStage>,
+}
+
+impl Default for Pipeline {
+    /// The pipeline made up of the statuses every role used before pipelines were configurable.
+    /// Applicants may move freely between its stages.
+    fn default() -> Self {
+        let stages = [
+            Status::NeedsToBeTriaged,
+            Status::NextSteps,
+            Status::Interviewing,
+            Status::GivingOffer,
+            Status::Onboarding,
+            Status::Hired,
+            Status::Contractor,
+            Status::Deferred,
+            Status::Declined,
+            Status::Withdrawn,
+        ];
+
+        Pipeline {
+            stages: stages
+                .iter()
+                .map(|status| PipelineStage::new(&status.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Pipeline {
+    /// Find the stage for a status. Statuses that do not name a stage exactly are parsed as a
+    /// [`Status`], as the status may have been typed in by hand.
+    pub fn stage(&self, status: &str) -> Option<&PipelineStage> {
+        self.stages.iter().find(|stage| stage.is_named(status)).or_else(|| {
+            let status = Status::from_str(status).unwrap_or_default().to_string();
+            self.stages.iter().find(|stage| stage.is_named(&status))
+        })
+    }
+
+    /// Check a move of an applicant from the stage they are in to another stage, returning the
+    /// stage they are moving to. Applicants in a stage that is not part of the pipeline may move
+    /// anywhere, as they may predate the pipeline.
+    pub fn check_transition(
+        &self,
+        applicant: &Applicant,
+        from: &str,
+        to: &str,
+        moved_by: MovedBy,
+    ) -> Result<&PipelineStage, PipelineError> {
+        let to = self
+            .stages
+            .iter()
+            .find(|stage| stage.is_named(to))
+            .ok_or_else(|| PipelineError::UnknownStage(to.to_string()))?;
+
+        let from = self.stages.iter().find(|stage| stage.is_named(from));
+        if from.map(|from| from.name == to.name).unwrap_or(false) {
+            return Ok(to);
+        }
+
+        if let Some(from) = from {
+            if !from.next.is_empty() && !from.next.iter().any(|next| to.is_named(next)) {
+                return Err(PipelineError::NotAllowed {
+                    from: from.name.to_string(),
+                    to: to.name.to_string(),
+                    allowed: from.next.clone(),
+                });
+            }
+        }
+
+        let permitted = match moved_by {
+            MovedBy::Person(email) => to.allows(email),
+            MovedBy::Unknown => to.advanced_by.is_empty(),
+            MovedBy::Internal => true,
+        };
+        if !permitted {
+            return Err(PipelineError::NotPermitted {
+                actor: moved_by.to_string(),
+                stage: to.name.to_string(),
+            });
+        }
+
+        let missing = to
+            .required_artifacts
+            .iter()
+            .filter(|artifact| !artifact.is_present(applicant))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(PipelineError::MissingArtifacts {
+                stage: to.name.to_string(),
+                missing,
+            });
+        }
+
+        Ok(to)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineError {
+    /// The stage is not part of the pipeline.
+    UnknownStage(String),
+    /// The pipeline does not allow moving between the stages.
+    NotAllowed {
+        from: String,
+        to: String,
+        allowed: Vec<String>,
+    },
+    /// The person may not move applicants into the stage.
+    NotPermitted { actor: String, stage: String },
+    /// The applicant is missing artifacts the stage requires.
+    MissingArtifacts {
+        stage: String,
+        missing: Vec<ApplicantArtifact>,
+    },
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::UnknownStage(stage) => write!(f, "`{}` is not a stage of the pipeline", stage),
+            PipelineError::NotAllowed { from, to, allowed } => write!(
+                f,
+                "an applicant can not move from {} to {}, they can only move to one of: {}",
+                from,
+                to,
+                allowed.join(", ")
+            ),
+            PipelineError::NotPermitted { actor, stage } => {
+                write!(f, "{} is not allowed to move applicants to {}", actor, stage)
+            }
+            PipelineError::MissingArtifacts { stage, missing } => write!(
+                f,
+                "an applicant needs the following to move to {}: {}",
+                stage,
+                missing
+                    .iter()
+                    .map(|artifact| artifact.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+/// The "Last modified by" field of the applications table that only watches the status field.
+#[derive(Debug, Default, Deserialize)]
+struct StatusModifiedBy {
+    #[serde(default, rename = "Status last modified by")]
+    status_last_modified_by: Option<airtable_api::User>,
+}
+
+/// The email of the collaborator that last changed the status of an applicant in Airtable, if the
+/// base records it.
+pub async fn status_modified_by_in_airtable(company: &Company, record_id: &str) -> Result<Option<String>> {
+    let record: airtable_api::Record<StatusModifiedBy> = company
+        .authenticate_airtable(&company.airtable_base_id_hiring)
+        .get_record(AIRTABLE_APPLICATIONS_TABLE, record_id)
+        .await?;
+
+    Ok(record
+        .fields
+        .status_last_modified_by
+        .map(|user| user.email)
+        .filter(|email| !email.is_empty()))
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = applicant_stage_changes)]
+pub struct ApplicantStageChange {
+    pub id: i32,
+    pub cio_company_id: i32,
+    pub applicant_id: i32,
+    pub role: String,
+    pub from_stage: Option<String>,
+    pub to_stage: String,
+    pub actor: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = applicant_stage_changes)]
+pub struct NewApplicantStageChange {
+    pub cio_company_id: i32,
+    pub applicant_id: i32,
+    pub role: String,
+    pub from_stage: Option<String>,
+    pub to_stage: String,
+    pub actor: String,
+}
+
+impl NewApplicantStageChange {
+    pub async fn create(&self, db: &Database) -> Result<ApplicantStageChange> {
+        Ok(diesel::insert_into(applicant_stage_changes::table)
+            .values(self.clone())
+            .get_result_async(db.pool())
+            .await?)
+    }
+
+    /// Record that an applicant moved from a stage to the one they are in now, if they moved at
+    /// all. The actor is taken from the current audit scope.
+    pub async fn record(db: &Database, applicant: &Applicant, from: &str) -> Result<()> {
+        if applicant.status == from || applicant.status.is_empty() {
+            return Ok(());
+        }
+
+        NewApplicantStageChange {
+            cio_company_id: applicant.cio_company_id,
+            applicant_id: applicant.id,
+            role: applicant.role.to_string(),
+            from_stage: Some(from.to_string()).filter(|from| !from.is_empty()),
+            to_stage: applicant.status.to_string(),
+            actor: AuditScope::current().actor,
+        }
+        .create(db)
+        .await?;
+
+        info!(
+            "applicant {} moved from stage {:?} to {}",
+            applicant.id, from, applicant.status
+        );
+
+        Ok(())
+    }
+}
+
+impl ApplicantStageChange {
+    /// The stage changes of an applicant, oldest first.
+    pub async fn history(db: &Database, cio_company_id: i32, applicant_id: i32) -> Result<Vec<ApplicantStageChange>> {
+        Ok(applicant_stage_changes::dsl::applicant_stage_changes
+            .filter(applicant_stage_changes::dsl::cio_company_id.eq(cio_company_id))
+            .filter(applicant_stage_changes::dsl::applicant_id.eq(applicant_id))
+            .order_by(applicant_stage_changes::dsl::changed_at.asc())
+            .load_async::<ApplicantStageChange>(db.pool())
+            .await?)
+    }
+
+    /// The stage changes of the applicants for a role, oldest first.
+    pub async fn for_role(db: &Database, cio_company_id: i32, role: &str) -> Result<Vec<ApplicantStageChange>> {
+        Ok(applicant_stage_changes::dsl::applicant_stage_changes
+            .filter(applicant_stage_changes::dsl::cio_company_id.eq(cio_company_id))
+            .filter(applicant_stage_changes::dsl::role.eq(role.to_string()))
+            .order_by(applicant_stage_changes::dsl::changed_at.asc())
+            .load_async::<ApplicantStageChange>(db.pool())
+            .await?)
+    }
+}
+
+/// How long applicants spend in a stage of a pipeline.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Serialize)]
+pub struct StageMetrics {
+    pub stage: String,
+    /// The number of applicants that are in the stage right now.
+    pub current: usize,
+    /// The number of times an applicant moved on from the stage.
+    pub completed: usize,
+    /// The mean time spent in the stage by applicants that moved on from it.
+    pub mean_seconds: Option<i64>,
+    /// The median time spent in the stage by applicants that moved on from it.
+    pub median_seconds: Option<i64>,
+    /// The longest time an applicant that is in the stage right now has been in it.
+    pub longest_current_seconds: Option<i64>,
+}
+
+/// The median of sorted durations, which is the mean of the two middle ones when there is an even
+/// number of them.
+fn median(sorted: &[i64]) -> Option<i64> {
+    let middle = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((sorted[middle - 1] + sorted[middle]) / 2),
+        _ => Some(sorted[middle]),
+    }
+}
+
+/// Compute the time spent in each stage from the stage changes of a set of applicants. Stages are
+/// returned in the order of the pipeline, followed by stages that are no longer part of it.
+pub fn stage_metrics(pipeline: &Pipeline, changes: &[ApplicantStageChange], now: DateTime<Utc>) -> Vec<StageMetrics> {
+    let mut by_applicant: HashMap<i32, Vec<&ApplicantStageChange>> = HashMap::new();
+    for change in changes {
+        by_applicant.entry(change.applicant_id).or_default().push(change);
+    }
+
+    let mut durations: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    let mut current: BTreeMap<String, Vec<i64>> = BTreeMap::new();
+    for changes in by_applicant.values_mut() {
+        changes.sort_by_key(|change| change.changed_at);
+
+        for pair in changes.windows(2) {
+            durations
+                .entry(pair[0].to_stage.to_string())
+                .or_default()
+                .push((pair[1].changed_at - pair[0].changed_at).num_seconds());
+        }
+
+        if let Some(last) = changes.last() {
+            current
+                .entry(last.to_stage.to_string())
+                .or_default()
+                .push((now - last.changed_at).num_seconds());
+        }
+    }
+
+    let mut stages = pipeline
+        .stages
+        .iter()
+        .map(|stage| stage.name.to_string())
+        .collect::<Vec<_>>();
+    for stage in durations.keys().chain(current.keys()) {
+        if !stages.contains(stage) {
+            stages.push(stage.to_string());
+        }
+    }
+
+    stages
+        .into_iter()
+        .map(|stage| {
+            let mut completed = durations.remove(&stage).unwrap_or_default();
+            completed.sort_unstable();
+            let in_stage = current.remove(&stage).unwrap_or_default();
+
+            StageMetrics {
+                current: in_stage.len(),
+                completed: completed.len(),
+                mean_seconds: (!completed.is_empty()).then(|| completed.iter().sum::<i64>() / completed.len() as i64),
+                median_seconds: median(&completed),
+                longest_current_seconds: in_stage.into_iter().max(),
+                stage,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::{applicants::tests::mock_applicant, audit::SYSTEM_ACTOR};
+
+    fn mock_pipeline() -> Pipeline {
+        toml::from_str(
+            r#"
+[[stages]]
+name = "Applied"
+next = ["Phone screen", "Declined"]
+
+[[stages]]
+name = "Phone screen"
+required_artifacts = ["resume"]
+
+[[stages]]
+name = "Onsite"
+required_artifacts = ["resume", "interviews"]
+advanced_by = ["hiring-manager@example.com"]
+
+[[stages]]
+name = "Declined"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn mock_change(applicant_id: i32, to_stage: &str, changed_at: DateTime<Utc>) -> ApplicantStageChange {
+        ApplicantStageChange {
+            id: 0,
+            cio_company_id: 1,
+            applicant_id,
+            role: "Engineering".to_string(),
+            from_stage: None,
+            to_stage: to_stage.to_string(),
+            actor: SYSTEM_ACTOR.to_string(),
+            changed_at,
+        }
+    }
+
+    #[test]
+    fn test_default_pipeline_is_the_statuses() {
+        let pipeline = Pipeline::default();
+
+        assert_eq!(pipeline.stages[0].name, Status::NeedsToBeTriaged.to_string());
+        assert_eq!(
+            pipeline.stage("giving OFFER").map(|stage| stage.name.as_str()),
+            Some("Giving offer")
+        );
+        // Hand written statuses are parsed like they were before pipelines.
+        assert_eq!(
+            pipeline.stage("declined - not a fit").map(|stage| stage.name.as_str()),
+            Some("Declined")
+        );
+
+        let applicant = mock_applicant();
+        assert!(pipeline
+            .check_transition(&applicant, "Hired", "Needs to be triaged", MovedBy::Internal)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_checks_transitions() {
+        let pipeline = mock_pipeline();
+        let mut applicant = mock_applicant();
+
+        assert_eq!(
+            pipeline.check_transition(&applicant, "Applied", "Onsite", MovedBy::Internal),
+            Err(PipelineError::NotAllowed {
+                from: "Applied".to_string(),
+                to: "Onsite".to_string(),
+                allowed: vec!["Phone screen".to_string(), "Declined".to_string()],
+            })
+        );
+        assert_eq!(
+            pipeline.check_transition(&applicant, "Applied", "Offer", MovedBy::Internal),
+            Err(PipelineError::UnknownStage("Offer".to_string()))
+        );
+
+        applicant.resume = String::new();
+        assert_eq!(
+            pipeline.check_transition(&applicant, "applied", "phone screen", MovedBy::Internal),
+            Err(PipelineError::MissingArtifacts {
+                stage: "Phone screen".to_string(),
+                missing: vec![ApplicantArtifact::Resume],
+            })
+        );
+
+        applicant.resume = "https://example.com/resume.pdf".to_string();
+        assert_eq!(
+            pipeline
+                .check_transition(&applicant, "applied", "phone screen", MovedBy::Internal)
+                .map(|stage| stage.name.as_str()),
+            Ok("Phone screen")
+        );
+
+        // Only the hiring manager may move applicants onsite. People we can not identify are
+        // refused too, while automation is trusted.
+        applicant.interviews = vec!["rec123".to_string()];
+        assert_eq!(
+            pipeline.check_transition(
+                &applicant,
+                "Phone screen",
+                "Onsite",
+                MovedBy::Person("someone@example.com")
+            ),
+            Err(PipelineError::NotPermitted {
+                actor: "someone@example.com".to_string(),
+                stage: "Onsite".to_string(),
+            })
+        );
+        assert_eq!(
+            pipeline.check_transition(&applicant, "Phone screen", "Onsite", MovedBy::Unknown),
+            Err(PipelineError::NotPermitted {
+                actor: "an unknown person".to_string(),
+                stage: "Onsite".to_string(),
+            })
+        );
+        assert!(pipeline
+            .check_transition(
+                &applicant,
+                "Phone screen",
+                "Onsite",
+                MovedBy::Person("Hiring-Manager@example.com")
+            )
+            .is_ok());
+        assert!(pipeline
+            .check_transition(&applicant, "Phone screen", "Onsite", MovedBy::Internal)
+            .is_ok());
+
+        // Anyone may move applicants into stages without a list of who may advance them.
+        assert!(pipeline
+            .check_transition(&applicant, "Phone screen", "Declined", MovedBy::Unknown)
+            .is_ok());
+
+        // Applicants in a stage the pipeline does not know about may move anywhere.
+        assert!(pipeline
+            .check_transition(&applicant, "Next steps", "Declined", MovedBy::Internal)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), None);
+        assert_eq!(median(&[5]), Some(5));
+        assert_eq!(median(&[1, 2, 9]), Some(2));
+        assert_eq!(median(&[1, 2, 4, 9]), Some(3));
+    }
+
+    #[test]
+    fn test_computes_stage_metrics() {
+        let pipeline = mock_pipeline();
+        let now = Utc::now();
+        let hours_ago = |hours: i64| now - Duration::hours(hours);
+
+        let changes = vec![
+            mock_change(1, "Applied", hours_ago(10)),
+            mock_change(2, "Applied", hours_ago(9)),
+            mock_change(1, "Phone screen", hours_ago(8)),
+            mock_change(2, "Phone screen", hours_ago(5)),
+            mock_change(1, "Interviewing", hours_ago(2)),
+            mock_change(3, "Applied", hours_ago(1)),
+        ];
+
+        let metrics = stage_metrics(&pipeline, &changes, now);
+        let names = metrics.iter().map(|m| m.stage.as_str()).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec!["Applied", "Phone screen", "Onsite", "Declined", "Interviewing"]
+        );
+
+        assert_eq!(metrics[0].completed, 2);
+        assert_eq!(metrics[0].current, 1);
+        assert_eq!(metrics[0].mean_seconds, Some(3 * 3600));
+        assert_eq!(metrics[0].median_seconds, Some(3 * 3600));
+        assert_eq!(metrics[0].longest_current_seconds, Some(3600));
+
+        assert_eq!(metrics[1].completed, 1);
+        assert_eq!(metrics[1].current, 1);
+        assert_eq!(metrics[1].median_seconds, Some(6 * 3600));
+        assert_eq!(metrics[1].longest_current_seconds, Some(5 * 3600));
+
+        assert_eq!(
+            metrics[2],
+            StageMetrics {
+                stage: "Onsite".to_string(),
+                ..Default::default()
+            }
+        );
+        assert_eq!(metrics[4].current, 1);
+    }
+}
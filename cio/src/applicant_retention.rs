@@ -0,0 +1,512 @@
+//! How long we keep the personal information of applicants, and getting rid of it.
+//!
+//! A [`RetentionPolicy`] in the hiring config says what happens to applicants some months after
+//! they entered a status, for example anonymizing declined applicants after a year. Anonymizing
+//! keeps the application around for our hiring statistics but scrubs everything that identifies
+//! the person, deleting removes the application entirely. Both also clean up what we keep about the
+//! applicant elsewhere: their files in Google Drive, their Airtable records, their interviews and
+//! their upload tokens. Applicants can also ask for everything we have on them, or for all of it to
+//! be deleted, which is keyed by their email.
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Months, Utc};
+use diesel::{BoolExpressionMethods, ExpressionMethods, PgArrayExpressionMethods, QueryDsl};
+use google_drive::{traits::FileOps, Client as GoogleDrive};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    applicant_dedupe::{resolve_merged_email, ApplicantDuplicate},
+    applicant_pipeline::ApplicantStageChange,
+    applicant_reviews::ApplicantReview,
+    applicants::Applicant,
+    audit::audit,
+    companies::Company,
+    db::Database,
+    interviews::ApplicantInterview,
+    schema::{
+        applicant_duplicates, applicant_interviews, applicant_reviews, applicant_stage_changes, applicants,
+        upload_tokens,
+    },
+};
+
+/// The domain of the email addresses we give anonymized applicants. Emails have to stay unique, so
+/// we cannot clear them.
+pub const ANONYMIZED_EMAIL_DOMAIN: &str = "anonymized.invalid";
+
+/// What to do with an applicant once their data is past its retention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Scrub everything that identifies the applicant, keeping their role, status and scores.
+    Anonymize,
+    /// Delete the applicant and everything we have on them.
+    Delete,
+}
+
+impl RetentionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionAction::Anonymize => "anonymize",
+            RetentionAction::Delete => "delete",
+        }
+    }
+}
+
+/// Applies an action to applicants some months after they entered one of the statuses.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct RetentionRule {
+    /// The statuses the rule applies to, matched case insensitively.
+    pub statuses: Vec<String>,
+    pub after_months: u32,
+    pub action: RetentionAction,
+}
+
+/// The retention rules for applicants. Without any rules we keep applicants forever.
+#[derive(Debug, Default, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub rules: Vec<RetentionRule>,
+}
+
+impl RetentionPolicy {
+    /// The action that is due for an applicant that has been in their status since `since`, if
+    /// any. Deleting wins when more than one rule is due, and an applicant that is already
+    /// anonymized is never anonymized again.
+    pub fn due(&self, applicant: &Applicant, since: DateTime<Utc>, now: DateTime<Utc>) -> Option<RetentionAction> {
+        self.rules
+            .iter()
+            .filter(|rule| {
+                rule.statuses
+                    .iter()
+                    .any(|status| status.trim().eq_ignore_ascii_case(applicant.status.trim()))
+            })
+            .filter(|rule| {
+                since
+                    .checked_add_months(Months::new(rule.after_months))
+                    .map(|expires| expires <= now)
+                    .unwrap_or(false)
+            })
+            .map(|rule| rule.action)
+            .filter(|action| *action == RetentionAction::Delete || !applicant.is_anonymized())
+            .max()
+    }
+}
+
+impl Applicant {
+    /// If the personal information of this applicant has been scrubbed.
+    pub fn is_anonymized(&self) -> bool {
+        self.email.ends_with(&format!("@{}", ANONYMIZED_EMAIL_DOMAIN))
+    }
+
+    /// The ids of the files in Google Drive we keep for the applicant.
+    pub fn drive_file_ids(&self) -> Vec<String> {
+        [
+            &self.resume,
+            &self.materials,
+            &self.portfolio_pdf,
+            &self.interview_packet,
+        ]
+        .iter()
+        .filter_map(|url| drive_file_id(url))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+    }
+
+    /// Scrub everything that identifies the applicant. What they applied for, how far they got
+    /// and how they were scored is kept.
+    pub fn anonymize(&mut self) {
+        self.name = format!("Anonymized applicant {}", self.id);
+        self.email = format!("applicant-{}@{}", self.id, ANONYMIZED_EMAIL_DOMAIN);
+
+        for field in [
+            &mut self.phone,
+            &mut self.country_code,
+            &mut self.location,
+            &mut self.geocode_cache,
+            &mut self.github,
+            &mut self.gitlab,
+            &mut self.linkedin,
+            &mut self.portfolio,
+            &mut self.portfolio_pdf,
+            &mut self.website,
+            &mut self.resume,
+            &mut self.materials,
+            &mut self.resume_contents,
+            &mut self.materials_contents,
+            &mut self.work_samples,
+            &mut self.writing_samples,
+            &mut self.analysis_samples,
+            &mut self.presentation_samples,
+            &mut self.exploratory_samples,
+            &mut self.question_technically_challenging,
+            &mut self.question_proud_of,
+            &mut self.question_happiest,
+            &mut self.question_unhappiest,
+            &mut self.question_value_reflected,
+            &mut self.question_value_violated,
+            &mut self.question_values_in_tension,
+            &mut self.question_why_oxide,
+            &mut self.interview_packet,
+        ] {
+            field.clear();
+        }
+
+        self.latitude = 0.0;
+        self.longitude = 0.0;
+    }
+}
+
+/// The id of a Google Drive file from the links we store, if it is one.
+fn drive_file_id(url: &str) -> Option<String> {
+    let id = if let Some(id) = url.strip_prefix("https://drive.google.com/open?id=") {
+        id
+    } else {
+        url.strip_prefix("https://drive.google.com/file/d/")?
+            .trim_end_matches("/view")
+    };
+
+    if id.is_empty() {
+        return None;
+    }
+
+    Some(id.to_string())
+}
+
+/// When the applicant entered their current status. Applicants that moved before we recorded
+/// stage changes fall back to when they were sent a rejection, and then to when they applied.
+pub async fn entered_status_at(db: &Database, applicant: &Applicant) -> Result<DateTime<Utc>> {
+    let history = ApplicantStageChange::history(db, applicant.cio_company_id, applicant.id).await?;
+
+    Ok(history
+        .iter()
+        .rev()
+        .find(|change| change.to_stage == applicant.status)
+        .map(|change| change.changed_at)
+        .or(applicant.rejection_sent_date_time)
+        .unwrap_or(applicant.submitted_time))
+}
+
+/// Anonymize or delete an applicant, along with everything we keep about them elsewhere.
+pub async fn erase_applicant(
+    db: &Database,
+    company: &Company,
+    drive_client: &GoogleDrive,
+    mut applicant: Applicant,
+    action: RetentionAction,
+) -> Result<()> {
+    let object_id = applicant.id.to_string();
+
+    let result = async {
+        for id in applicant.drive_file_ids() {
+            delete_drive_file(db, company, drive_client, &id).await?;
+        }
+
+        diesel::delete(upload_tokens::dsl::upload_tokens.filter(upload_tokens::dsl::email.eq(applicant.email.clone())))
+            .execute_async(db.pool())
+            .await?;
+
+        // The interviews carry the applicant's name and email.
+        let interviews = applicant_interviews::dsl::applicant_interviews
+            .filter(applicant_interviews::dsl::cio_company_id.eq(applicant.cio_company_id))
+            .filter(
+                applicant_interviews::dsl::applicant
+                    .contains(vec![applicant.airtable_record_id.to_string()])
+                    .or(applicant_interviews::dsl::email.eq(applicant.email.to_string())),
+            )
+            .load_async::<ApplicantInterview>(db.pool())
+            .await?;
+
+        // Pairs of duplicates carry the names and emails of both applicants.
+        let duplicates = applicant_duplicates::dsl::applicant_duplicates
+            .filter(
+                applicant_duplicates::dsl::primary_applicant_id
+                    .eq(applicant.id)
+                    .or(applicant_duplicates::dsl::duplicate_applicant_id.eq(applicant.id)),
+            )
+            .load_async::<ApplicantDuplicate>(db.pool())
+            .await?;
+        for duplicate in duplicates {
+            duplicate.delete(db).await?;
+        }
+
+        match action {
+            RetentionAction::Anonymize => {
+                applicant.anonymize();
+
+                for mut interview in interviews {
+                    interview.name = applicant.name.to_string();
+                    interview.email = String::new();
+                    interview.update(db).await?;
+                }
+
+                applicant.update(db).await?;
+            }
+            RetentionAction::Delete => {
+                for interview in interviews {
+                    interview.delete(db).await?;
+                }
+
+                let reviews = applicant_reviews::dsl::applicant_reviews
+                    .filter(applicant_reviews::dsl::cio_company_id.eq(applicant.cio_company_id))
+                    .filter(applicant_reviews::dsl::applicant.contains(vec![applicant.airtable_record_id.to_string()]))
+                    .load_async::<ApplicantReview>(db.pool())
+                    .await?;
+                for review in reviews {
+                    review.delete(db).await?;
+                }
+
+                diesel::delete(
+                    applicant_stage_changes::dsl::applicant_stage_changes
+                        .filter(applicant_stage_changes::dsl::applicant_id.eq(applicant.id)),
+                )
+                .execute_async(db.pool())
+                .await?;
+
+                applicant.delete(db).await?;
+            }
+        }
+
+        Ok::<(), anyhow::Error>(())
+    }
+    .await;
+
+    audit(db, company, "applicants", action.as_str(), &object_id, result).await?;
+    info!("{} applicant {}", action.as_str(), object_id);
+
+    Ok(())
+}
+
+async fn delete_drive_file(db: &Database, company: &Company, drive_client: &GoogleDrive, id: &str) -> Result<()> {
+    let result = drive_client
+        .files()
+        .delete(
+            id, false, // enforce_single_parent
+            true,  // supports_all_drives
+            true,  // supports_team_drives
+        )
+        .await
+        .map(|_| ())
+        .or_else(|err| {
+            // The file might have been removed by hand already.
+            if err.to_string().contains("404") {
+                Ok(())
+            } else {
+                Err(err.into())
+            }
+        });
+
+    audit(db, company, "google_drive", "delete_file", id, result).await
+}
+
+/// Apply the retention policy to all the applicants of a company, returning how many applicants
+/// were anonymized or deleted.
+pub async fn enforce_applicant_retention(db: &Database, company: &Company, policy: &RetentionPolicy) -> Result<usize> {
+    if policy.rules.is_empty() {
+        // Return early.
+        return Ok(0);
+    }
+
+    let drive_client = company.authenticate_google_drive(db).await?;
+    let now = Utc::now();
+    let statuses = policy
+        .rules
+        .iter()
+        .flat_map(|rule| rule.statuses.iter())
+        .map(|status| status.trim().to_lowercase())
+        .collect::<BTreeSet<_>>();
+
+    let candidates = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .load_async::<Applicant>(db.pool())
+        .await?
+        .into_iter()
+        .filter(|applicant| statuses.contains(&applicant.status.trim().to_lowercase()));
+
+    let mut erased = 0;
+    for applicant in candidates {
+        let since = entered_status_at(db, &applicant).await?;
+        if let Some(action) = policy.due(&applicant, since, now) {
+            let id = applicant.id;
+            if let Err(err) = erase_applicant(db, company, &drive_client, applicant, action).await {
+                // Keep going, we will try this applicant again the next time around.
+                warn!("failed to {} applicant {}: {}", action.as_str(), id, err);
+                continue;
+            }
+
+            erased += 1;
+        }
+    }
+
+    Ok(erased)
+}
+
+/// Everything we keep about someone that applied.
+#[derive(Debug, Clone, JsonSchema, Serialize)]
+pub struct ApplicantDataExport {
+    pub email: String,
+    pub applicants: Vec<Applicant>,
+    pub reviews: Vec<ApplicantReview>,
+    pub interviews: Vec<ApplicantInterview>,
+    pub stage_changes: Vec<ApplicantStageChange>,
+}
+
+/// The applicants for an email, including those they were merged into.
+async fn applicants_for_email(db: &Database, company: &Company, email: &str) -> Result<Vec<Applicant>> {
+    let emails = vec![email.to_string(), resolve_merged_email(db, email).await];
+
+    Ok(applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .filter(applicants::dsl::email.eq_any(emails))
+        .order_by(applicants::dsl::id.asc())
+        .load_async::<Applicant>(db.pool())
+        .await?)
+}
+
+/// Collect everything we keep about the person with this email.
+pub async fn export_applicant_data(db: &Database, company: &Company, email: &str) -> Result<ApplicantDataExport> {
+    let applicants = applicants_for_email(db, company, email).await?;
+
+    let mut export = ApplicantDataExport {
+        email: email.to_string(),
+        applicants: vec![],
+        reviews: vec![],
+        interviews: vec![],
+        stage_changes: vec![],
+    };
+
+    for applicant in applicants {
+        let record_id = vec![applicant.airtable_record_id.to_string()];
+
+        export.reviews.extend(
+            applicant_reviews::dsl::applicant_reviews
+                .filter(applicant_reviews::dsl::cio_company_id.eq(company.id))
+                .filter(applicant_reviews::dsl::applicant.contains(record_id.clone()))
+                .load_async::<ApplicantReview>(db.pool())
+                .await?,
+        );
+        export.interviews.extend(
+            applicant_interviews::dsl::applicant_interviews
+                .filter(applicant_interviews::dsl::cio_company_id.eq(company.id))
+                .filter(applicant_interviews::dsl::applicant.contains(record_id))
+                .load_async::<ApplicantInterview>(db.pool())
+                .await?,
+        );
+        export
+            .stage_changes
+            .extend(ApplicantStageChange::history(db, company.id, applicant.id).await?);
+
+        // Audit events are kept forever, so they name the applicant by id rather than by email.
+        audit(db, company, "applicants", "export", &applicant.id.to_string(), Ok(())).await?;
+        export.applicants.push(applicant);
+    }
+
+    Ok(export)
+}
+
+/// Delete everything we keep about the person with this email, returning how many applications
+/// were deleted.
+pub async fn delete_applicant_data(db: &Database, company: &Company, email: &str) -> Result<usize> {
+    let applicants = applicants_for_email(db, company, email).await?;
+    let drive_client = company.authenticate_google_drive(db).await?;
+
+    // They might have asked for an upload token without ever applying.
+    diesel::delete(upload_tokens::dsl::upload_tokens.filter(upload_tokens::dsl::email.eq(email.to_string())))
+        .execute_async(db.pool())
+        .await?;
+
+    let count = applicants.len();
+    for applicant in applicants {
+        erase_applicant(db, company, &drive_client, applicant, RetentionAction::Delete).await?;
+    }
+
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::applicants::tests::mock_applicant;
+
+    fn mock_policy() -> RetentionPolicy {
+        toml::from_str(
+            r#"
+[[rules]]
+statuses = ["Declined", "Withdrawn"]
+after_months = 6
+action = "anonymize"
+
+[[rules]]
+statuses = ["declined"]
+after_months = 24
+action = "delete"
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_retention_due() {
+        let policy = mock_policy();
+        let now = Utc::now();
+        let mut applicant = mock_applicant();
+        applicant.status = "Declined".to_string();
+
+        assert_eq!(policy.due(&applicant, now - Duration::days(30), now), None);
+        assert_eq!(
+            policy.due(&applicant, now - Duration::days(200), now),
+            Some(RetentionAction::Anonymize)
+        );
+        assert_eq!(
+            policy.due(&applicant, now - Duration::days(800), now),
+            Some(RetentionAction::Delete)
+        );
+
+        // Anonymized applicants are only ever deleted.
+        applicant.anonymize();
+        assert!(applicant.is_anonymized());
+        assert_eq!(policy.due(&applicant, now - Duration::days(200), now), None);
+
+        // Statuses without a rule are kept.
+        applicant.status = "Hired".to_string();
+        assert_eq!(policy.due(&applicant, now - Duration::days(800), now), None);
+        assert_eq!(
+            RetentionPolicy::default().due(&applicant, now - Duration::days(800), now),
+            None
+        );
+    }
+
+    #[test]
+    fn test_anonymize() {
+        let mut applicant = mock_applicant();
+        applicant.id = 42;
+        applicant.phone = "+1 555 123 4567".to_string();
+        applicant.latitude = 37.8;
+        applicant.resume = "https://drive.google.com/open?id=resume-id".to_string();
+        applicant.materials = "https://drive.google.com/file/d/materials-id/view".to_string();
+        applicant.portfolio = "https://example.com".to_string();
+        applicant.scoring_yes_count = 2;
+
+        assert_eq!(
+            applicant.drive_file_ids(),
+            vec!["materials-id".to_string(), "resume-id".to_string()]
+        );
+
+        applicant.anonymize();
+
+        assert_eq!(applicant.name, "Anonymized applicant 42");
+        assert_eq!(applicant.email, "applicant-42@anonymized.invalid");
+        assert!(applicant.phone.is_empty());
+        assert!(applicant.resume.is_empty());
+        assert_eq!(applicant.latitude, 0.0);
+        assert!(applicant.drive_file_ids().is_empty());
+        assert_eq!(applicant.role, "Engineering");
+        assert_eq!(applicant.scoring_yes_count, 2);
+    }
+}
@@ -0,0 +1,812 @@
+//! The rubric reviewers score applicants with, and how the scores add up.
+//!
+//! A review picks one [`Evaluation`], optionally the [`Rationale`] behind a pass or a no, and the
+//! values the applicant's materials reflect, violate or hold in tension. The [`Rubric`] in the
+//! hiring config says what every evaluation is worth, which of them need a rationale, and which
+//! values reviewers can pick from; submitted reviews are checked against it. From the reviews in
+//! the database we count the scores of every applicant, keep the reviewer leaderboard, and compute
+//! how calibrated every reviewer is: how their evaluations are spread, how far they are from the
+//! other reviewers of the same applicants, and how often they agreed with the outcome.
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::info;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    applicant_reviews::{ApplicantReview, NewApplicantReview},
+    applicant_status::Status,
+    applicants::{Applicant, ApplicantReviewer, NewApplicantReviewer},
+    companies::Company,
+    configs::User,
+    db::Database,
+    schema::{applicant_reviewers, applicant_reviews, applicants},
+};
+
+/// What a reviewer thinks we should do with an applicant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Evaluation {
+    EmphaticYes,
+    Yes,
+    Pass,
+    No,
+    NotApplicable,
+}
+
+impl Evaluation {
+    pub const ALL: [Evaluation; 5] = [
+        Evaluation::EmphaticYes,
+        Evaluation::Yes,
+        Evaluation::Pass,
+        Evaluation::No,
+        Evaluation::NotApplicable,
+    ];
+
+    /// The label of the evaluation in the review form.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Evaluation::EmphaticYes => "Emphatic Yes",
+            Evaluation::Yes => "Yes",
+            Evaluation::Pass => "Pass",
+            Evaluation::No => "No",
+            Evaluation::NotApplicable => "N/A",
+        }
+    }
+
+    /// Parse an evaluation the way the review form submits it, the label followed by a colon and
+    /// its description, e.g. `Yes: ...`.
+    pub fn parse(evaluation: &str) -> Option<Evaluation> {
+        let label = evaluation.split(':').next().unwrap_or_default().trim();
+
+        Evaluation::ALL
+            .into_iter()
+            .find(|evaluation| evaluation.label().eq_ignore_ascii_case(label))
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// Why a reviewer passed on or said no to an applicant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rationale {
+    InsufficientExperience,
+    InapplicableExperience,
+    JobFunctionNotYetNeeded,
+    UnderwhelmingMaterials,
+}
+
+impl Rationale {
+    pub const ALL: [Rationale; 4] = [
+        Rationale::InsufficientExperience,
+        Rationale::InapplicableExperience,
+        Rationale::JobFunctionNotYetNeeded,
+        Rationale::UnderwhelmingMaterials,
+    ];
+
+    /// The label of the rationale in the review form.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rationale::InsufficientExperience => "Insufficient experience",
+            Rationale::InapplicableExperience => "Inapplicable experience",
+            Rationale::JobFunctionNotYetNeeded => "Job function not yet needed",
+            Rationale::UnderwhelmingMaterials => "Underwhelming materials",
+        }
+    }
+
+    /// Parse a rationale from the review form, which may follow the label with an explanation.
+    pub fn parse(rationale: &str) -> Option<Rationale> {
+        let rationale = rationale.trim().to_lowercase();
+
+        Rationale::ALL
+            .into_iter()
+            .find(|r| rationale.starts_with(&r.label().to_lowercase()))
+    }
+}
+
+/// How an evaluation counts in the rubric.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct RubricEvaluation {
+    pub evaluation: Evaluation,
+    /// What the evaluation is worth. Positive scores are for advancing the applicant, negative
+    /// scores for declining them. Evaluations without a score, like N/A, do not count towards
+    /// calibration.
+    #[serde(default)]
+    pub score: Option<i32>,
+    /// Whether reviewers have to give a rationale for the evaluation.
+    #[serde(default)]
+    pub requires_rationale: bool,
+}
+
+/// The rubric reviews are scored with.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct Rubric {
+    /// The evaluations reviewers can give. Evaluations missing from the list are rejected.
+    #[serde(default = "default_evaluations")]
+    pub evaluations: Vec<RubricEvaluation>,
+    /// The values reviewers pick from. Any value is accepted if this is empty.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+impl Default for Rubric {
+    fn default() -> Self {
+        Rubric {
+            evaluations: default_evaluations(),
+            values: vec![],
+        }
+    }
+}
+
+fn default_evaluations() -> Vec<RubricEvaluation> {
+    [
+        (Evaluation::EmphaticYes, Some(2)),
+        (Evaluation::Yes, Some(1)),
+        (Evaluation::Pass, Some(-1)),
+        (Evaluation::No, Some(-2)),
+        (Evaluation::NotApplicable, None),
+    ]
+    .into_iter()
+    .map(|(evaluation, score)| RubricEvaluation {
+        evaluation,
+        score,
+        requires_rationale: false,
+    })
+    .collect()
+}
+
+impl Rubric {
+    /// How the rubric counts the evaluation of a review, if it is one the rubric has.
+    pub fn evaluation(&self, evaluation: &str) -> Option<&RubricEvaluation> {
+        let evaluation = Evaluation::parse(evaluation)?;
+
+        self.evaluations.iter().find(|e| e.evaluation == evaluation)
+    }
+
+    /// The score of a review.
+    pub fn score(&self, evaluation: &str) -> Option<i32> {
+        self.evaluation(evaluation).and_then(|evaluation| evaluation.score)
+    }
+
+    fn has_value(&self, value: &str) -> bool {
+        self.values.is_empty() || self.values.iter().any(|v| v.eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Check a submitted review against the rubric.
+    pub fn validate(&self, review: &NewApplicantReview) -> Result<(), RubricError> {
+        for (field, value) in [
+            ("name", review.name.as_str()),
+            ("reviewer", review.reviewer.as_str()),
+            ("evaluation", review.evaluation.as_str()),
+        ] {
+            if value.trim().is_empty() {
+                return Err(RubricError::Missing(field));
+            }
+        }
+        if review.applicant.is_empty() {
+            return Err(RubricError::Missing("applicant"));
+        }
+
+        let evaluation = self
+            .evaluation(&review.evaluation)
+            .ok_or_else(|| RubricError::UnknownEvaluation(review.evaluation.to_string()))?;
+
+        if let Some(rationale) = review.rationale.iter().find(|r| Rationale::parse(r).is_none()) {
+            return Err(RubricError::UnknownRationale(rationale.to_string()));
+        }
+        if evaluation.requires_rationale && review.rationale.is_empty() {
+            return Err(RubricError::RationaleRequired(evaluation.evaluation));
+        }
+
+        if let Some(value) = [&review.value_reflected, &review.value_violated]
+            .into_iter()
+            .chain(review.values_in_tension.iter())
+            .find(|value| !value.is_empty() && !self.has_value(value))
+        {
+            return Err(RubricError::UnknownValue(value.to_string()));
+        }
+        if !review.values_in_tension.is_empty()
+            && (review.values_in_tension.len() != 2
+                || review.values_in_tension[0].eq_ignore_ascii_case(&review.values_in_tension[1]))
+        {
+            return Err(RubricError::ValuesInTension(review.values_in_tension.clone()));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RubricError {
+    /// The review is missing a field every review needs.
+    Missing(&'static str),
+    /// The evaluation is not one of the rubric.
+    UnknownEvaluation(String),
+    /// The rationale is not one of the rubric.
+    UnknownRationale(String),
+    /// The evaluation needs a rationale but the review has none.
+    RationaleRequired(Evaluation),
+    /// The value is not one of the rubric.
+    UnknownValue(String),
+    /// The values in tension are not a pair of different values.
+    ValuesInTension(Vec<String>),
+}
+
+impl fmt::Display for RubricError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RubricError::Missing(field) => write!(f, "the review is missing its {}", field),
+            RubricError::UnknownEvaluation(evaluation) => {
+                write!(f, "`{}` is not an evaluation of the rubric", evaluation)
+            }
+            RubricError::UnknownRationale(rationale) => write!(f, "`{}` is not a rationale of the rubric", rationale),
+            RubricError::RationaleRequired(evaluation) => {
+                write!(f, "a review evaluating {} needs a rationale", evaluation)
+            }
+            RubricError::UnknownValue(value) => write!(f, "`{}` is not a value of the rubric", value),
+            RubricError::ValuesInTension(values) => write!(
+                f,
+                "the values in tension have to be two different values, not: {}",
+                values.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RubricError {}
+
+/// How many reviews gave every evaluation and rationale.
+#[derive(Debug, Default, Clone, PartialEq, Eq, JsonSchema, Serialize)]
+pub struct ReviewCounts {
+    pub evaluations: i32,
+    pub emphatic_yes: i32,
+    pub yes: i32,
+    pub pass: i32,
+    pub no: i32,
+    pub not_applicable: i32,
+    pub insufficient_experience: i32,
+    pub inapplicable_experience: i32,
+    pub job_function_not_yet_needed: i32,
+    pub underwhelming_materials: i32,
+}
+
+impl ReviewCounts {
+    pub fn add(&mut self, review: &ApplicantReview) {
+        self.evaluations += 1;
+
+        match Evaluation::parse(&review.evaluation) {
+            Some(Evaluation::EmphaticYes) => self.emphatic_yes += 1,
+            Some(Evaluation::Yes) => self.yes += 1,
+            Some(Evaluation::Pass) => self.pass += 1,
+            Some(Evaluation::No) => self.no += 1,
+            Some(Evaluation::NotApplicable) => self.not_applicable += 1,
+            None => {}
+        }
+
+        for rationale in review.rationale.iter().filter_map(|r| Rationale::parse(r)) {
+            match rationale {
+                Rationale::InsufficientExperience => self.insufficient_experience += 1,
+                Rationale::InapplicableExperience => self.inapplicable_experience += 1,
+                Rationale::JobFunctionNotYetNeeded => self.job_function_not_yet_needed += 1,
+                Rationale::UnderwhelmingMaterials => self.underwhelming_materials += 1,
+            }
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a ApplicantReview> for ReviewCounts {
+    fn from_iter<I: IntoIterator<Item = &'a ApplicantReview>>(reviews: I) -> Self {
+        let mut counts = ReviewCounts::default();
+        for review in reviews {
+            counts.add(review);
+        }
+
+        counts
+    }
+}
+
+impl Applicant {
+    /// Set the scores of the applicant from their reviews.
+    pub fn apply_reviews(&mut self, reviews: &[ApplicantReview]) {
+        let counts = reviews.iter().collect::<ReviewCounts>();
+
+        self.scoring_evaluations_count = counts.evaluations;
+        self.scoring_enthusiastic_yes_count = counts.emphatic_yes;
+        self.scoring_yes_count = counts.yes;
+        self.scoring_pass_count = counts.pass;
+        self.scoring_no_count = counts.no;
+        self.scoring_not_applicable_count = counts.not_applicable;
+        self.scoring_insufficient_experience_count = counts.insufficient_experience;
+        self.scoring_inapplicable_experience_count = counts.inapplicable_experience;
+        self.scoring_job_function_yet_needed_count = counts.job_function_not_yet_needed;
+        self.scoring_underwhelming_materials_count = counts.underwhelming_materials;
+
+        for review in reviews {
+            // TODO: actually do the majority if they differ in value but for now YOLO.
+            if !review.value_reflected.is_empty() {
+                self.value_reflected = review.value_reflected.to_string();
+            }
+            if !review.value_violated.is_empty() {
+                self.value_violated = review.value_violated.to_string();
+            }
+            if !review.values_in_tension.is_empty() {
+                self.values_in_tension = review.values_in_tension.clone();
+            }
+
+            // The reviewer completed their scoring, so they are no longer waiting on it.
+            if !self.scorers_completed.contains(&review.reviewer) {
+                self.scorers_completed.push(review.reviewer.to_string());
+            }
+            self.scorers.retain(|scorer| *scorer != review.reviewer);
+        }
+    }
+}
+
+/// Whether we ended up advancing an applicant or declining them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Advanced,
+    Declined,
+}
+
+impl Outcome {
+    /// The outcome of an applicant, if there is one yet.
+    pub fn of(applicant: &Applicant) -> Option<Outcome> {
+        match Status::from_str(&applicant.status) {
+            Ok(Status::Hired) | Ok(Status::Onboarding) | Ok(Status::GivingOffer) | Ok(Status::Contractor) => {
+                Some(Outcome::Advanced)
+            }
+            Ok(Status::Declined) => Some(Outcome::Declined),
+            _ => None,
+        }
+    }
+}
+
+/// How a reviewer's evaluations compare to those of the other reviewers and to the outcomes.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Serialize)]
+pub struct ReviewerCalibration {
+    pub reviewer: String,
+    pub evaluations: usize,
+    /// How many times the reviewer gave every evaluation.
+    pub distribution: BTreeMap<Evaluation, usize>,
+    /// The mean score of the reviewer's evaluations.
+    pub mean_score: Option<f64>,
+    /// How much higher, on average, the reviewer scores applicants than the other reviewers of the
+    /// same applicants. Negative if the reviewer is harsher.
+    pub mean_deviation: Option<f64>,
+    /// How many of the reviewed applicants have an outcome, and how many of those the reviewer
+    /// scored the way it turned out: a positive score for advanced applicants, otherwise for
+    /// declined ones.
+    pub decided: usize,
+    pub agreed: usize,
+    pub agreement: Option<f64>,
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// The calibration of every reviewer, given the outcomes of the applicants by their Airtable record
+/// id. Reviewers are ordered by how many evaluations they did.
+pub fn calibrate(
+    rubric: &Rubric,
+    reviews: &[ApplicantReview],
+    outcomes: &HashMap<String, Outcome>,
+) -> Vec<ReviewerCalibration> {
+    // The scores by applicant, to compare the reviewers against each other.
+    let mut by_applicant: HashMap<&str, Vec<(&str, i32)>> = HashMap::new();
+    for review in reviews {
+        if let (Some(applicant), Some(score)) = (review.applicant.first(), rubric.score(&review.evaluation)) {
+            by_applicant
+                .entry(applicant.as_str())
+                .or_default()
+                .push((review.reviewer.as_str(), score));
+        }
+    }
+
+    let mut by_reviewer: BTreeMap<&str, Vec<&ApplicantReview>> = BTreeMap::new();
+    for review in reviews {
+        by_reviewer.entry(review.reviewer.as_str()).or_default().push(review);
+    }
+
+    let mut calibrations = by_reviewer
+        .into_iter()
+        .map(|(reviewer, reviews)| {
+            let mut distribution = BTreeMap::new();
+            let mut scores = vec![];
+            let mut deviations = vec![];
+            let mut decided = 0;
+            let mut agreed = 0;
+
+            for review in &reviews {
+                if let Some(evaluation) = Evaluation::parse(&review.evaluation) {
+                    *distribution.entry(evaluation).or_insert(0) += 1;
+                }
+
+                let (score, applicant) = match (rubric.score(&review.evaluation), review.applicant.first()) {
+                    (Some(score), Some(applicant)) => (score, applicant),
+                    _ => continue,
+                };
+                scores.push(score as f64);
+
+                let others = by_applicant
+                    .get(applicant.as_str())
+                    .map(|scores| {
+                        scores
+                            .iter()
+                            .filter(|(other, _)| *other != reviewer)
+                            .map(|(_, score)| *score as f64)
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                if let Some(others) = mean(&others) {
+                    deviations.push(score as f64 - others);
+                }
+
+                if let Some(outcome) = outcomes.get(applicant) {
+                    decided += 1;
+                    if (score > 0) == (*outcome == Outcome::Advanced) {
+                        agreed += 1;
+                    }
+                }
+            }
+
+            ReviewerCalibration {
+                reviewer: reviewer.to_string(),
+                evaluations: reviews.len(),
+                distribution,
+                mean_score: mean(&scores),
+                mean_deviation: mean(&deviations),
+                decided,
+                agreed,
+                agreement: if decided > 0 {
+                    Some(agreed as f64 / decided as f64)
+                } else {
+                    None
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    calibrations.sort_by_key(|calibration| Reverse(calibration.evaluations));
+
+    calibrations
+}
+
+async fn company_reviews(db: &Database, company: &Company) -> Result<Vec<ApplicantReview>> {
+    Ok(applicant_reviews::dsl::applicant_reviews
+        .filter(applicant_reviews::dsl::cio_company_id.eq(company.id))
+        .load_async::<ApplicantReview>(db.pool())
+        .await?)
+}
+
+/// The calibration of every reviewer of the company.
+pub async fn reviewer_calibration(
+    db: &Database,
+    company: &Company,
+    rubric: &Rubric,
+) -> Result<Vec<ReviewerCalibration>> {
+    let reviews = company_reviews(db, company).await?;
+
+    let outcomes = applicants::dsl::applicants
+        .filter(applicants::dsl::cio_company_id.eq(company.id))
+        .load_async::<Applicant>(db.pool())
+        .await?
+        .into_iter()
+        .filter_map(|applicant| Outcome::of(&applicant).map(|outcome| (applicant.airtable_record_id, outcome)))
+        .collect::<HashMap<_, _>>();
+
+    Ok(calibrate(rubric, &reviews, &outcomes))
+}
+
+/// The name of a reviewer on the leaderboard: the name they already have there, the name of the
+/// user with their email, or else their email.
+async fn reviewer_name(db: &Database, company: &Company, email: &str) -> String {
+    if let Some(reviewer) = ApplicantReviewer::get_from_db(db, email.to_string()).await {
+        reviewer.name
+    } else if let Some(user) = User::get_from_db(
+        db,
+        company.id,
+        email
+            .trim_end_matches(&company.gsuite_domain)
+            .trim_end_matches('@')
+            .to_string(),
+    )
+    .await
+    {
+        user.full_name()
+    } else {
+        email.to_string()
+    }
+}
+
+async fn leaderboard_entry(
+    db: &Database,
+    company: &Company,
+    email: String,
+    counts: ReviewCounts,
+) -> NewApplicantReviewer {
+    NewApplicantReviewer {
+        name: reviewer_name(db, company, &email).await,
+        email,
+        evaluations: counts.evaluations,
+        emphatic_yes: counts.emphatic_yes,
+        yes: counts.yes,
+        pass: counts.pass,
+        no: counts.no,
+        not_applicable: counts.not_applicable,
+        cio_company_id: company.id,
+    }
+}
+
+/// The reviewer leaderboard of the company, counted from the reviews in the database. Reviewers are
+/// ordered by how many evaluations they did.
+pub async fn reviewer_leaderboard(db: &Database, company: &Company) -> Result<Vec<NewApplicantReviewer>> {
+    let reviews = company_reviews(db, company).await?;
+
+    let mut counts: BTreeMap<String, ReviewCounts> = applicant_reviewers::dsl::applicant_reviewers
+        .filter(applicant_reviewers::dsl::cio_company_id.eq(company.id))
+        .load_async::<ApplicantReviewer>(db.pool())
+        .await?
+        .into_iter()
+        .map(|reviewer| (reviewer.email, ReviewCounts::default()))
+        .collect();
+    for review in reviews.iter().filter(|review| !review.reviewer.is_empty()) {
+        counts.entry(review.reviewer.to_string()).or_default().add(review);
+    }
+
+    let mut leaderboard = vec![];
+    for (email, counts) in counts {
+        leaderboard.push(leaderboard_entry(db, company, email, counts).await);
+    }
+
+    leaderboard.sort_by_key(|reviewer| Reverse(reviewer.evaluations));
+
+    Ok(leaderboard)
+}
+
+/// Store a leaderboard entry, unless it is already up to date.
+async fn store_leaderboard_entry(db: &Database, reviewer: NewApplicantReviewer) -> Result<()> {
+    let existing = ApplicantReviewer::get_from_db(db, reviewer.email.to_string()).await;
+    if existing.map(|existing| NewApplicantReviewer::from(existing) == reviewer) == Some(true) {
+        return Ok(());
+    }
+
+    reviewer.upsert(db).await?;
+
+    Ok(())
+}
+
+/// Store the reviewer leaderboard counted from the reviews in the database.
+pub async fn refresh_reviewer_leaderboard(db: &Database, company: &Company) -> Result<()> {
+    if company.airtable_base_id_hiring.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    for reviewer in reviewer_leaderboard(db, company).await? {
+        store_leaderboard_entry(db, reviewer).await?;
+    }
+
+    info!("updated the reviewer leaderboard for company {}", company.id);
+
+    Ok(())
+}
+
+/// Store the leaderboard entry of a single reviewer, counted from their reviews in the database.
+/// Used when a review is submitted, so only the reviewer's own entry needs to be counted again;
+/// the whole leaderboard is refreshed by the refresh-applicants job.
+pub async fn refresh_reviewer_leaderboard_entry(db: &Database, company: &Company, email: &str) -> Result<()> {
+    if company.airtable_base_id_hiring.is_empty() || email.is_empty() {
+        // Return early.
+        return Ok(());
+    }
+
+    let mut counts = ReviewCounts::default();
+    for review in applicant_reviews::dsl::applicant_reviews
+        .filter(applicant_reviews::dsl::cio_company_id.eq(company.id))
+        .filter(applicant_reviews::dsl::reviewer.eq(email.to_string()))
+        .load_async::<ApplicantReview>(db.pool())
+        .await?
+    {
+        counts.add(&review);
+    }
+
+    store_leaderboard_entry(db, leaderboard_entry(db, company, email.to_string(), counts).await).await?;
+
+    info!(
+        "updated the reviewer leaderboard entry of {} for company {}",
+        email, company.id
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{calibrate, Evaluation, Outcome, Rationale, ReviewCounts, Rubric, RubricError};
+    use crate::{
+        applicant_reviews::{ApplicantReview, NewApplicantReview},
+        applicants::tests::mock_applicant,
+    };
+
+    fn mock_review(id: i32, reviewer: &str, applicant: &str, evaluation: &str) -> ApplicantReview {
+        ApplicantReview {
+            id,
+            name: format!("review {}", id),
+            value_reflected: String::new(),
+            value_violated: String::new(),
+            values_in_tension: vec![],
+            evaluation: evaluation.to_string(),
+            rationale: vec![],
+            notes: String::new(),
+            reviewer: reviewer.to_string(),
+            applicant: vec![applicant.to_string()],
+            link_to_leaderboard: vec![],
+            cio_company_id: 1,
+            airtable_record_id: String::new(),
+        }
+    }
+
+    fn mock_submission() -> NewApplicantReview {
+        NewApplicantReview {
+            name: "Test User - reviewer@example.com".to_string(),
+            value_reflected: "Candor".to_string(),
+            value_violated: String::new(),
+            values_in_tension: vec!["Rigor".to_string(), "Urgency".to_string()],
+            evaluation: "Yes: the applicant should advance.".to_string(),
+            rationale: vec![],
+            notes: String::new(),
+            reviewer: "reviewer@example.com".to_string(),
+            applicant: vec!["recApplicant".to_string()],
+            link_to_leaderboard: vec![],
+            cio_company_id: 1,
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Evaluation::parse("Emphatic Yes: hire them."),
+            Some(Evaluation::EmphaticYes)
+        );
+        assert_eq!(Evaluation::parse("yes: sure"), Some(Evaluation::Yes));
+        assert_eq!(Evaluation::parse("N/A: not my area"), Some(Evaluation::NotApplicable));
+        assert_eq!(Evaluation::parse("Maybe: who knows"), None);
+
+        assert_eq!(
+            Rationale::parse("Job function not yet needed (we will be later)"),
+            Some(Rationale::JobFunctionNotYetNeeded)
+        );
+        assert_eq!(Rationale::parse("Too expensive"), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut rubric = Rubric::default();
+        assert_eq!(rubric.validate(&mock_submission()), Ok(()));
+
+        let mut review = mock_submission();
+        review.reviewer = String::new();
+        assert_eq!(rubric.validate(&review), Err(RubricError::Missing("reviewer")));
+
+        let mut review = mock_submission();
+        review.evaluation = "Maybe: who knows".to_string();
+        assert!(matches!(
+            rubric.validate(&review),
+            Err(RubricError::UnknownEvaluation(_))
+        ));
+
+        let mut review = mock_submission();
+        review.evaluation = "No: not a fit.".to_string();
+        review.rationale = vec!["Too expensive".to_string()];
+        assert!(matches!(
+            rubric.validate(&review),
+            Err(RubricError::UnknownRationale(_))
+        ));
+
+        rubric.evaluations[3].requires_rationale = true;
+        review.rationale = vec![];
+        assert_eq!(
+            rubric.validate(&review),
+            Err(RubricError::RationaleRequired(Evaluation::No))
+        );
+        review.rationale = vec!["Underwhelming materials".to_string()];
+        assert_eq!(rubric.validate(&review), Ok(()));
+
+        let mut review = mock_submission();
+        review.values_in_tension = vec!["Rigor".to_string(), "rigor".to_string()];
+        assert!(matches!(rubric.validate(&review), Err(RubricError::ValuesInTension(_))));
+
+        rubric.values = vec!["Candor".to_string(), "Rigor".to_string()];
+        let review = mock_submission();
+        assert_eq!(
+            rubric.validate(&review),
+            Err(RubricError::UnknownValue("Urgency".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_apply_reviews() {
+        let mut applicant = mock_applicant();
+        applicant.scorers = vec!["a@example.com".to_string(), "c@example.com".to_string()];
+
+        let mut no = mock_review(2, "b@example.com", "rec1", "No: not a fit.");
+        no.rationale = vec![
+            "Insufficient experience".to_string(),
+            "Underwhelming materials".to_string(),
+        ];
+        no.value_violated = "Candor".to_string();
+        let reviews = vec![mock_review(1, "a@example.com", "rec1", "Emphatic Yes: hire them."), no];
+
+        applicant.apply_reviews(&reviews);
+
+        assert_eq!(applicant.scoring_evaluations_count, 2);
+        assert_eq!(applicant.scoring_enthusiastic_yes_count, 1);
+        assert_eq!(applicant.scoring_no_count, 1);
+        assert_eq!(applicant.scoring_yes_count, 0);
+        assert_eq!(applicant.scoring_insufficient_experience_count, 1);
+        assert_eq!(applicant.scoring_underwhelming_materials_count, 1);
+        assert_eq!(applicant.value_violated, "Candor");
+        assert_eq!(applicant.scorers, vec!["c@example.com".to_string()]);
+        assert_eq!(
+            applicant.scorers_completed,
+            vec!["a@example.com".to_string(), "b@example.com".to_string()]
+        );
+
+        let counts = reviews.iter().collect::<ReviewCounts>();
+        assert_eq!(counts.evaluations, 2);
+        assert_eq!(counts.pass, 0);
+    }
+
+    #[test]
+    fn test_calibrate() {
+        let rubric = Rubric::default();
+        let reviews = vec![
+            mock_review(1, "a@example.com", "rec1", "Emphatic Yes: hire them."),
+            mock_review(2, "b@example.com", "rec1", "Yes: sure."),
+            mock_review(3, "a@example.com", "rec2", "Yes: sure."),
+            mock_review(4, "b@example.com", "rec2", "No: not a fit."),
+            mock_review(5, "a@example.com", "rec3", "N/A: not my area."),
+        ];
+        let outcomes = HashMap::from([
+            ("rec1".to_string(), Outcome::Advanced),
+            ("rec2".to_string(), Outcome::Declined),
+        ]);
+
+        let calibrations = calibrate(&rubric, &reviews, &outcomes);
+        assert_eq!(calibrations.len(), 2);
+
+        let a = &calibrations[0];
+        assert_eq!(a.reviewer, "a@example.com");
+        assert_eq!(a.evaluations, 3);
+        assert_eq!(a.distribution.get(&Evaluation::NotApplicable), Some(&1));
+        assert_eq!(a.mean_score, Some(1.5));
+        // 2 - 1 on rec1 and 1 - -2 on rec2.
+        assert_eq!(a.mean_deviation, Some(2.0));
+        assert_eq!((a.decided, a.agreed, a.agreement), (2, 1, Some(0.5)));
+
+        let b = &calibrations[1];
+        assert_eq!(b.mean_deviation, Some(-2.0));
+        assert_eq!((b.decided, b.agreed, b.agreement), (2, 2, Some(1.0)));
+    }
+}
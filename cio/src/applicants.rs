@@ -1,5 +1,5 @@
 #![allow(clippy::from_over_into)]
-use std::{env, process::Command, str::FromStr};
+use std::str::FromStr;
 
 use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
@@ -19,22 +19,24 @@ use schemars::JsonSchema;
 use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
-    FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
+    blocks::{ContextBlock, SectionBlock},
+    FormattedMessage, MessageAttachment,
 };
-use tokio::fs;
-use tokio::io::AsyncWriteExt;
 
 use crate::{
     airtable::{AIRTABLE_APPLICATIONS_TABLE, AIRTABLE_REVIEWER_LEADERBOARD_TABLE},
     app_config::{AppConfig, ApplyConfig, Letter, NewHireIssue},
+    applicant_pipeline::NewApplicantStageChange,
     applicant_reviews::ApplicantReview,
+    audit::audit,
     companies::Company,
     configs::User,
     core::UpdateAirtableRecord,
     db::Database,
     enclose,
     interviews::ApplicantInterview,
-    schema::{applicant_interviews, applicant_reviewers, applicants, users},
+    schema::{applicant_interviews, applicant_reviewers, applicant_reviews, applicants, users},
+    text_extraction::{extract_text, ExtractError, ExtractLimits},
     utils::{check_if_github_issue_exists, truncate},
 };
 
@@ -372,73 +374,13 @@ impl From<NewApplicant> for FormattedMessage {
             info_msg += &format!(" | <{}|website>", item.website,);
         }
 
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: get_color_based_on_status(&item.status),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks: vec![
-                    MessageBlock {
-                        block_type: MessageBlockType::Section,
-                        text: Some(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: intro_msg,
-                        }),
-                        elements: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Context,
-                        elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: info_msg,
-                        })],
-                        text: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Context,
-                        elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: values_msg,
-                        })],
-                        text: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Context,
-                        elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: status_msg,
-                        })],
-                        text: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                ],
-            }],
-        }
+        FormattedMessage::new().attachment(
+            MessageAttachment::colored(get_color_based_on_status(&item.status))
+                .block(SectionBlock::markdown(intro_msg))
+                .block(ContextBlock::new().markdown(info_msg))
+                .block(ContextBlock::new().markdown(values_msg))
+                .block(ContextBlock::new().markdown(status_msg)),
+        )
     }
 }
 
@@ -458,9 +400,17 @@ impl Applicant {
         configs_issues: &[octorust::types::IssueSimple],
         app_config: AppConfig,
     ) -> Result<()> {
+        if self.is_anonymized() {
+            // There is nothing left to refresh.
+            return Ok(());
+        }
+
         // Initialize the GSuite sheets client.
         let drive_client = company.authenticate_google_drive(db).await?;
 
+        // The stage the applicant was in when we last saw them, to record any move since.
+        let previous_status = self.status.to_string();
+
         self.keep_fields_from_airtable(db).await;
 
         // Expand the application.
@@ -477,6 +427,7 @@ impl Applicant {
         // Update airtable and the database again, we want to save our status just in
         // case there is an error.
         self.update(db).await?;
+        NewApplicantStageChange::record(db, self, &previous_status).await?;
 
         // Send the follow up email if we need to, this will also update the database.
         self.send_email_follow_up_if_necessary(db, app_config.apply).await?;
@@ -641,92 +592,12 @@ impl Applicant {
         // We have now handled people that are either in the process of onboarding or hired.
         // For everyone else we only have work to do if they actually have reviews
         if !self.link_to_reviews.is_empty() {
-            for record_id in &self.link_to_reviews {
-                // Get the record.
-                // TODO: get these from the database.
-                let record: airtable_api::Record<crate::applicant_reviews::ApplicantReview> = airtable
-                    .get_record(crate::airtable::AIRTABLE_REVIEWS_TABLE, record_id)
-                    .await
-                    .unwrap();
-
-                // Set the values if they are not empty.
-                // TODO: actually do the majority if they differ in value but for now YOLO.
-                if !record.fields.value_reflected.is_empty() {
-                    self.value_reflected = record.fields.value_reflected.to_string();
-                }
-                if !record.fields.value_violated.is_empty() {
-                    self.value_violated = record.fields.value_violated.to_string();
-                }
-                if !record.fields.values_in_tension.is_empty() {
-                    self.values_in_tension = record.fields.values_in_tension.clone();
-                }
-
-                // Add the scoring count.
-                self.scoring_evaluations_count += 1;
-
-                // Up the scores for the relevant evaluations.
-                if record.fields.evaluation.to_lowercase().starts_with("emphatic yes:") {
-                    self.scoring_enthusiastic_yes_count += 1;
-                }
-                if record.fields.evaluation.to_lowercase().starts_with("yes:") {
-                    self.scoring_yes_count += 1;
-                }
-                if record.fields.evaluation.to_lowercase().starts_with("pass:") {
-                    self.scoring_pass_count += 1;
-                }
-                if record.fields.evaluation.to_lowercase().starts_with("no:") {
-                    self.scoring_no_count += 1;
-                }
-                if record.fields.evaluation.to_lowercase().starts_with("n/a:") {
-                    self.scoring_not_applicable_count += 1;
-                }
-
-                // Add in the rationale.
-                if record
-                    .fields
-                    .evaluation
-                    .to_lowercase()
-                    .starts_with("insufficient experience")
-                {
-                    self.scoring_insufficient_experience_count += 1;
-                }
-                if record
-                    .fields
-                    .evaluation
-                    .to_lowercase()
-                    .starts_with("inapplicable experience")
-                {
-                    self.scoring_inapplicable_experience_count += 1;
-                }
-                if record
-                    .fields
-                    .evaluation
-                    .to_lowercase()
-                    .starts_with("job function not yet needed")
-                {
-                    self.scoring_job_function_yet_needed_count += 1;
-                }
-                if record
-                    .fields
-                    .evaluation
-                    .to_lowercase()
-                    .starts_with("underwhelming materials")
-                {
-                    self.scoring_underwhelming_materials_count += 1;
-                }
-
-                // If we don't already have the review in reviewers completed,
-                // add them.
-                if !self.scorers_completed.contains(&record.fields.reviewer) {
-                    self.scorers_completed.push(record.fields.reviewer.to_string());
-                }
+            let reviews = applicant_reviews::dsl::applicant_reviews
+                .filter(applicant_reviews::dsl::airtable_record_id.eq_any(self.link_to_reviews.clone()))
+                .load_async::<ApplicantReview>(db.pool())
+                .await?;
 
-                // If this reviewer was assigned, remove them since they completed scoring.
-                if self.scorers.contains(&record.fields.reviewer) {
-                    let index = self.scorers.iter().position(|r| *r == record.fields.reviewer).unwrap();
-                    self.scorers.remove(index);
-                }
-            }
+            self.apply_reviews(&reviews);
 
             log::info!("Updating scores for applicant {}", self.id);
 
@@ -1201,86 +1072,22 @@ pub async fn get_file_contents(drive_client: &GoogleDrive, url: &str) -> Result<
     let mime_type = drive_file.mime_type;
     let name = drive_file.name;
 
-    let mut path = env::temp_dir();
-    let mut output = env::temp_dir();
-
-    let result: String = if mime_type == "application/pdf" {
-        // Get the PDF contents from Drive.
-        let contents = drive_client.files().download_by_id(&id).await?.body;
-
-        path.push(format!("{}.pdf", id));
+    let contents = drive_client.files().download_by_id(&id).await?.body;
 
-        let mut file = fs::File::create(&path).await?;
-        file.write_all(&contents).await?;
+    // Extracting the text can take a while for large documents, so keep it off of the runtime.
+    let result = tokio::task::spawn_blocking(enclose! { (name, mime_type) move || {
+        extract_text(&name, &mime_type, &contents, &ExtractLimits::default())
+    }})
+    .await?;
 
-        read_pdf(&name, path.clone()).await?
-    } else {
-        let contents = drive_client.files().download_by_id(&id).await?.body;
-        path.push(&name);
-
-        let mut file = fs::File::create(&path).await?;
-        file.write_all(&contents).await?;
-
-        output.push(format!("{}.txt", id));
-
-        match tokio::task::spawn_blocking(enclose! { (output, path) move || {Command::new("pandoc")
-        .args(["-o", output.clone().to_str().unwrap(), path.to_str().unwrap()])
-        .output()}})
-        .await?
-        {
-            Ok(_) => (),
-            Err(e) => {
-                warn!("pandoc failed: {}", e);
-                return Ok("".to_string());
-            }
-        }
-        fs::read_to_string(output.clone()).await?
-    };
-
-    // Delete the temporary file, if it exists.
-    for p in vec![path, output] {
-        if p.exists() && !p.is_dir() {
-            fs::remove_file(p).await?;
-        }
-    }
-
-    Ok(result.trim().to_string())
-}
-
-async fn read_pdf(name: &str, path: std::path::PathBuf) -> Result<String> {
-    let mut output = env::temp_dir();
-    output.push(&format!("tempfile-{}.txt", name));
-
-    // Extract the text from the PDF
-    let cmd_output = tokio::task::spawn_blocking(enclose! { (output, path) move || {Command::new("pdftotext")
-    .args(["-enc", "UTF-8", path.to_str().unwrap(), output.to_str().unwrap()])
-    .output()}})
-    .await??;
-
-    let result = match fs::read_to_string(output.clone()).await {
-        Ok(r) => r,
-        Err(e) => {
-            warn!(
-                "running pdf2text failed: {} | name: {}, path: {}\nstdout: {}\nstderr: {}",
-                e,
-                name,
-                path.as_path().display(),
-                String::from_utf8(cmd_output.stdout)?,
-                String::from_utf8(cmd_output.stderr)?,
-            );
-
-            "".to_string()
-        }
-    };
-
-    // Delete the temporary file, if it exists.
-    for p in vec![path, output] {
-        if p.exists() && !p.is_dir() {
-            fs::remove_file(p).await?;
+    match result {
+        Ok(text) => Ok(text),
+        Err(ExtractError::Unsupported(kind)) => {
+            warn!("cannot extract the text of `{}`, it is a {}", name, kind);
+            Ok("".to_string())
         }
+        Err(e) => bail!("extracting the text of `{}` failed: {}", name, e),
     }
-
-    Ok(result)
 }
 
 /// The data type for a ApplicantReviewer.
@@ -1495,7 +1302,7 @@ impl Applicant {
             let sendgrid_client = SendGrid::new_from_env();
 
             // Send the message.
-            sendgrid_client
+            let result = sendgrid_client
                 .mail_send()
                 .send_plain_text(
                     &letter.subject,
@@ -1505,7 +1312,18 @@ impl Applicant {
                     &letter.bcc,
                     &letter.from,
                 )
-                .await?;
+                .await
+                .map_err(anyhow::Error::from);
+            let company = self.company(db).await?;
+            audit(
+                db,
+                &company,
+                "sendgrid",
+                "send_rejection_email",
+                &format!("applicant:{}", self.id),
+                result,
+            )
+            .await?;
 
             log::info!(
                 "Applicant {} in {:?} has been sent a rejection letter.",
@@ -1547,7 +1365,7 @@ impl Applicant {
             let letter = config.create_received_letter(self);
 
             // Send them an email.
-            self.send_email_recieved_application_to_applicant(&letter).await?;
+            self.send_email_recieved_application_to_applicant(db, &letter).await?;
             self.sent_email_received = true;
             // Update it in the database just in case.
             self.update(db).await?;
@@ -1667,7 +1485,7 @@ The applicants Airtable \
         let sendgrid_client = SendGrid::new_from_env();
 
         // Send the message.
-        sendgrid_client
+        let result = sendgrid_client
             .mail_send()
             .send_plain_text(
                 &format!("New {} Application: {}", self.role, self.name),
@@ -1677,18 +1495,28 @@ The applicants Airtable \
                 &[],
                 &format!("applications@{}", company.gsuite_domain),
             )
-            .await?;
-
-        Ok(())
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(
+            db,
+            &company,
+            "sendgrid",
+            "send_email",
+            &format!("applicant:{}", self.id),
+            result,
+        )
+        .await
     }
 
     /// Send an email to the applicant that we recieved their application.
-    async fn send_email_recieved_application_to_applicant(&self, letter: &Letter) -> Result<()> {
+    async fn send_email_recieved_application_to_applicant(&self, db: &Database, letter: &Letter) -> Result<()> {
+        let company = self.company(db).await?;
         // Initialize the SendGrid client.
         let sendgrid_client = SendGrid::new_from_env();
 
         // Send the message.
-        sendgrid_client
+        let result = sendgrid_client
             .mail_send()
             .send_plain_text(
                 &letter.subject,
@@ -1698,9 +1526,18 @@ The applicants Airtable \
                 &letter.bcc,
                 &letter.from,
             )
-            .await?;
-
-        Ok(())
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(
+            db,
+            &company,
+            "sendgrid",
+            "send_email",
+            &format!("applicant:{}", self.id),
+            result,
+        )
+        .await
     }
 
     /// Parse the questions from the materials.
@@ -2042,6 +1879,8 @@ The applicants Airtable \
             self.status = crate::applicant_status::Status::Onboarding.to_string();
             // Update them in case something fails.
             self.update(db).await?;
+            NewApplicantStageChange::record(db, self, &crate::applicant_status::Status::GivingOffer.to_string())
+                .await?;
 
             // Request their background check, if we have not already.
             if self.criminal_background_check_status.is_empty() {
@@ -0,0 +1,439 @@
+//! A record of every side effect that we have on an external service.
+//!
+//! Writes to external services (creating accounts, sending email, purchasing labels, issuing
+//! certificates, ...) are recorded as [`AuditEvent`]s. The actor, request id, and saga id of an
+//! event are taken from the [`AuditScope`] that the write happens in, so the code performing the
+//! write only needs to describe what it did.
+use std::{fmt, future::Future, ops::Deref, str::from_utf8};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, Output, ToSql},
+    sql_types::VarChar,
+    ExpressionMethods, FromSqlRow, Insertable, QueryDsl, Queryable,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_config::AppConfig,
+    companies::Company,
+    configs::{ExternalServices, Group, User},
+    db::Database,
+    providers::ProviderWriteOps,
+    schema::audit_events,
+};
+
+/// The actor recorded for events that happen outside of any request, e.g. in scheduled syncs.
+pub const SYSTEM_ACTOR: &str = "cio";
+
+tokio::task_local! {
+    static AUDIT_SCOPE: AuditScope;
+}
+
+/// Who (or what) is responsible for the side effects happening in the current task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditScope {
+    /// The verified actor, e.g. the holder of the token a request was authenticated with.
+    pub actor: String,
+    pub request_id: String,
+    pub saga_id: String,
+    /// Who the caller says is behind the request, when we can not verify it. Recorded next to the
+    /// actor, but never in its place.
+    pub claimed_by: String,
+}
+
+impl Default for AuditScope {
+    fn default() -> Self {
+        AuditScope {
+            actor: SYSTEM_ACTOR.to_string(),
+            request_id: String::new(),
+            saga_id: String::new(),
+            claimed_by: String::new(),
+        }
+    }
+}
+
+impl AuditScope {
+    pub fn for_request(actor: &str, request_id: &str) -> Self {
+        AuditScope {
+            actor: actor.to_string(),
+            request_id: request_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// The scope of a saga, attributed to whoever triggered it (e.g. the person that clicked a
+    /// Slack button) or [`SYSTEM_ACTOR`] for scheduled runs.
+    pub fn for_saga(saga_id: &str, actor: &str) -> Self {
+        AuditScope {
+            actor: actor.to_string(),
+            saga_id: saga_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Annotate the scope with who the caller says is behind the request.
+    pub fn claimed_by(mut self, claimed_by: &str) -> Self {
+        self.claimed_by = claimed_by.to_string();
+        self
+    }
+
+    /// Run the future with this scope attached to every audit event it records.
+    pub async fn run<F: Future>(self, f: F) -> F::Output {
+        AUDIT_SCOPE.scope(self, f).await
+    }
+
+    /// The scope of the current task, or the system scope if none has been set.
+    pub fn current() -> Self {
+        AUDIT_SCOPE.try_with(|scope| scope.clone()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize, FromSqlRow, AsExpression)]
+#[serde(rename_all = "lowercase")]
+#[diesel(sql_type = VarChar)]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+        }
+    }
+}
+
+impl fmt::Display for AuditOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl ToSql<VarChar, Pg> for AuditOutcome {
+    fn to_sql(&self, out: &mut Output<Pg>) -> serialize::Result {
+        <str as ToSql<VarChar, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<VarChar, Pg> for AuditOutcome {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes() {
+            b"success" => Ok(AuditOutcome::Success),
+            b"failure" => Ok(AuditOutcome::Failure),
+            unknown => Err(format!(
+                "Encountered unknown audit outcome {:?} in database. Unable to deserialize.",
+                from_utf8(unknown)
+            )
+            .into()),
+        }
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = audit_events)]
+pub struct AuditEvent {
+    pub id: i32,
+    pub cio_company_id: i32,
+    pub actor: String,
+    pub service: String,
+    pub action: String,
+    pub object_id: String,
+    pub request_id: String,
+    pub saga_id: String,
+    pub outcome: AuditOutcome,
+    pub error: String,
+    pub created_at: DateTime<Utc>,
+    pub claimed_by: String,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = audit_events)]
+pub struct NewAuditEvent {
+    pub cio_company_id: i32,
+    pub actor: String,
+    pub service: String,
+    pub action: String,
+    pub object_id: String,
+    pub request_id: String,
+    pub saga_id: String,
+    pub outcome: AuditOutcome,
+    pub error: String,
+    pub claimed_by: String,
+}
+
+impl NewAuditEvent {
+    /// Describe the result of a write to an external service, in the current [`AuditScope`].
+    pub fn new<T>(company: &Company, service: &str, action: &str, object_id: &str, result: &Result<T>) -> Self {
+        let scope = AuditScope::current();
+
+        let (outcome, error) = match result {
+            Ok(_) => (AuditOutcome::Success, String::new()),
+            Err(err) => (AuditOutcome::Failure, format!("{}", err)),
+        };
+
+        NewAuditEvent {
+            cio_company_id: company.id,
+            actor: scope.actor,
+            service: service.to_string(),
+            action: action.to_string(),
+            object_id: object_id.to_string(),
+            request_id: scope.request_id,
+            saga_id: scope.saga_id,
+            outcome,
+            error,
+            claimed_by: scope.claimed_by,
+        }
+    }
+
+    pub async fn create(&self, db: &Database) -> Result<AuditEvent> {
+        Ok(diesel::insert_into(audit_events::table)
+            .values(self.clone())
+            .get_result_async(db.pool())
+            .await?)
+    }
+}
+
+/// Record the result of a write to an external service and hand the result back to the caller.
+/// Failing to record the event is logged but never fails the write itself.
+pub async fn audit<T>(
+    db: &Database,
+    company: &Company,
+    service: &str,
+    action: &str,
+    object_id: &str,
+    result: Result<T>,
+) -> Result<T> {
+    let event = NewAuditEvent::new(company, service, action, object_id, &result);
+
+    if let Err(err) = event.create(db).await {
+        log::warn!(
+            "Failed to record audit event {} {} `{}` ({}). err: {:?}",
+            service,
+            action,
+            object_id,
+            event.outcome,
+            err
+        );
+    }
+
+    result
+}
+
+/// Filters for querying the audit log. Empty filters match everything.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct AuditQuery {
+    #[serde(default)]
+    pub actor: Option<String>,
+    #[serde(default)]
+    pub service: Option<String>,
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub object_id: Option<String>,
+    #[serde(default)]
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub saga_id: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<AuditOutcome>,
+    #[serde(default)]
+    pub since: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+/// The maximum number of events returned by a single query.
+const MAX_AUDIT_QUERY_LIMIT: i64 = 1000;
+
+impl AuditQuery {
+    /// Find the matching events for a company, most recent first.
+    pub async fn run(&self, db: &Database, cio_company_id: i32) -> Result<Vec<AuditEvent>> {
+        let mut query = audit_events::dsl::audit_events
+            .filter(audit_events::dsl::cio_company_id.eq(cio_company_id))
+            .into_boxed();
+
+        if let Some(actor) = &self.actor {
+            query = query.filter(audit_events::dsl::actor.eq(actor.to_string()));
+        }
+        if let Some(service) = &self.service {
+            query = query.filter(audit_events::dsl::service.eq(service.to_string()));
+        }
+        if let Some(action) = &self.action {
+            query = query.filter(audit_events::dsl::action.eq(action.to_string()));
+        }
+        if let Some(object_id) = &self.object_id {
+            query = query.filter(audit_events::dsl::object_id.eq(object_id.to_string()));
+        }
+        if let Some(request_id) = &self.request_id {
+            query = query.filter(audit_events::dsl::request_id.eq(request_id.to_string()));
+        }
+        if let Some(saga_id) = &self.saga_id {
+            query = query.filter(audit_events::dsl::saga_id.eq(saga_id.to_string()));
+        }
+        if let Some(outcome) = self.outcome {
+            query = query.filter(audit_events::dsl::outcome.eq(outcome));
+        }
+        if let Some(since) = self.since {
+            query = query.filter(audit_events::dsl::created_at.ge(since));
+        }
+        if let Some(until) = self.until {
+            query = query.filter(audit_events::dsl::created_at.lt(until));
+        }
+
+        let limit = self
+            .limit
+            .unwrap_or(MAX_AUDIT_QUERY_LIMIT)
+            .clamp(1, MAX_AUDIT_QUERY_LIMIT);
+
+        Ok(query
+            .order_by(audit_events::dsl::created_at.desc())
+            .limit(limit)
+            .load_async::<AuditEvent>(db.pool())
+            .await?)
+    }
+}
+
+/// Wraps a provider so that every write it makes is recorded in the audit log. Anything that is
+/// not a write is passed through to the underlying client.
+pub struct Audited<P> {
+    db: Database,
+    service: ExternalServices,
+    inner: P,
+}
+
+impl<P> Audited<P> {
+    pub fn new(db: &Database, service: ExternalServices, inner: P) -> Self {
+        Audited {
+            db: db.clone(),
+            service,
+            inner,
+        }
+    }
+}
+
+impl<P> Deref for Audited<P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<P> Audited<P> {
+    pub(crate) async fn record<T>(
+        &self,
+        company: &Company,
+        action: &str,
+        object_id: &str,
+        result: Result<T>,
+    ) -> Result<T> {
+        audit(&self.db, company, self.service.as_str(), action, object_id, result).await
+    }
+}
+
+#[async_trait]
+impl<P> ProviderWriteOps for Audited<P>
+where
+    P: ProviderWriteOps + Send + Sync,
+{
+    async fn ensure_user(&self, db: &Database, company: &Company, user: &User, config: &AppConfig) -> Result<String> {
+        let result = self.inner.ensure_user(db, company, user, config).await;
+        self.record(company, "ensure_user", &user.email, result).await
+    }
+
+    async fn ensure_group(&self, db: &Database, company: &Company, group: &Group) -> Result<()> {
+        let result = self.inner.ensure_group(db, company, group).await;
+        self.record(company, "ensure_group", &group.name, result).await
+    }
+
+    async fn check_user_is_member_of_group(&self, company: &Company, user: &User, group: &str) -> Result<bool> {
+        // Reads are not side effects, there is nothing to record.
+        self.inner.check_user_is_member_of_group(company, user, group).await
+    }
+
+    async fn add_user_to_group(&self, company: &Company, user: &User, group: &str) -> Result<()> {
+        let result = self.inner.add_user_to_group(company, user, group).await;
+        self.record(
+            company,
+            "add_user_to_group",
+            &format!("{}:{}", group, user.email),
+            result,
+        )
+        .await
+    }
+
+    async fn remove_user_from_group(&self, company: &Company, user: &User, group: &str) -> Result<()> {
+        let result = self.inner.remove_user_from_group(company, user, group).await;
+        self.record(
+            company,
+            "remove_user_from_group",
+            &format!("{}:{}", group, user.email),
+            result,
+        )
+        .await
+    }
+
+    async fn delete_user(&self, db: &Database, company: &Company, user: &User) -> Result<()> {
+        let result = self.inner.delete_user(db, company, user).await;
+        self.record(company, "delete_user", &user.email, result).await
+    }
+
+    async fn delete_group(&self, company: &Company, group: &Group) -> Result<()> {
+        let result = self.inner.delete_group(company, group).await;
+        self.record(company, "delete_group", &group.name, result).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditScope, SYSTEM_ACTOR};
+
+    #[tokio::test]
+    async fn test_audit_scope_defaults_to_system() {
+        assert_eq!(AuditScope::current().actor, SYSTEM_ACTOR);
+    }
+
+    #[tokio::test]
+    async fn test_audit_scope_is_task_local() {
+        let scope = AuditScope::for_request("jane", "req-1");
+
+        let current = scope.clone().run(async { AuditScope::current() }).await;
+        assert_eq!(current, scope);
+
+        let nested = scope
+            .run(async {
+                AuditScope::for_saga("saga-1", SYSTEM_ACTOR)
+                    .run(async { AuditScope::current() })
+                    .await
+            })
+            .await;
+        assert_eq!(nested.saga_id, "saga-1");
+        assert_eq!(nested.actor, SYSTEM_ACTOR);
+
+        let triggered = AuditScope::for_saga("saga-2", "slack:jane")
+            .run(async { AuditScope::current() })
+            .await;
+        assert_eq!(triggered.saga_id, "saga-2");
+        assert_eq!(triggered.actor, "slack:jane");
+
+        let claimed = AuditScope::for_request("airtable", "req-2")
+            .claimed_by("jane@example.com")
+            .run(async { AuditScope::current() })
+            .await;
+        assert_eq!(claimed.actor, "airtable");
+        assert_eq!(claimed.claimed_by, "jane@example.com");
+
+        assert_eq!(AuditScope::current(), AuditScope::default());
+    }
+}
@@ -29,6 +29,7 @@ use tokio::{
 
 use crate::{
     airtable::AIRTABLE_CERTIFICATES_TABLE,
+    audit::audit,
     companies::Company,
     core::UpdateAirtableRecord,
     db::Database,
@@ -301,7 +302,16 @@ impl NewCertificate {
         company: &'a Company,
         storage: &'a [Box<dyn SslCertificateStorage>],
     ) -> Result<()> {
-        let renewed_certificate = self.create_cert(company).await?;
+        let renewed_certificate = self.create_cert(company).await;
+        let renewed_certificate = audit(
+            db,
+            company,
+            "acme",
+            "issue_certificate",
+            &self.domain,
+            renewed_certificate,
+        )
+        .await?;
 
         log::info!("Renewed certificate for {}", self.domain);
 
@@ -470,14 +470,23 @@ impl Company {
                 .first_async::<APIToken>(db.pool())
                 .await
             {
+                let mut bot_token = bot_token.decrypted(self).await?;
+                let user_token = user_token.decrypted(self).await?;
+
+                // Only apps with token rotation enabled get a refresh token, their bot tokens
+                // expire after 12 hours.
+                if !bot_token.refresh_token.is_empty() && bot_token.is_expired() {
+                    // Only refresh the token if it is expired.
+                    bot_token = bot_token.refresh_decrypted(db, self).await?;
+                }
+
                 // Initialize the Slack client.
                 let slack = Slack::new_from_env(
                     bot_token.company_id.to_string(),
                     bot_token.access_token,
                     user_token.access_token,
-                );
-                // Slack does not give you refresh tokens.
-                // So we don't need to do any song and dance to refresh.
+                )
+                .with_refresh_token(bot_token.refresh_token);
 
                 return Ok(slack);
             }
@@ -504,31 +513,15 @@ impl Company {
     /// Authenticate with Zoom.
     pub async fn authenticate_zoom(&self, db: &Database) -> Result<Zoom> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "zoom".to_string()).await {
-            // Initialize the Zoom client.
-            let zoom = Zoom::new_from_env(t.access_token.to_string(), t.refresh_token.to_string());
-
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "zoom").await? {
             if t.is_expired() {
-                // Update the token if it is expired.
-                let nt = zoom.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
+                // Only refresh the token if it is expired.
+                t = t.refresh_decrypted(db, self).await?;
             }
 
+            // Initialize the Zoom client.
+            let zoom = Zoom::new_from_env(t.access_token.to_string(), t.refresh_token.to_string());
+
             return Ok(zoom);
         }
 
@@ -538,30 +531,15 @@ impl Company {
     /// Authenticate with Zoho.
     pub async fn authenticate_zoho(&self, db: &Database) -> Result<Zoho> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "zoho".to_string()).await {
-            // Initialize the Zoho client.
-            let zoho = Zoho::new_with_keys_from_env(&t.access_token, Some(&t.refresh_token));
-
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "zoho").await? {
             if t.is_expired() {
-                // Update the token if it is expired. In theory the refresh token never expires
-                let nt = zoho.refresh_access_token().await?;
-
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-
-                t.last_updated_at = Utc::now();
-
-                t.expand();
-
-                // Update the token in the database.
-                t.update(db).await?;
+                // Only refresh the token if it is expired.
+                t = t.refresh_decrypted(db, self).await?;
             }
 
+            // Initialize the Zoho client.
+            let zoho = Zoho::new_with_keys_from_env(&t.access_token, Some(&t.refresh_token));
+
             return Ok(zoho);
         }
 
@@ -578,36 +556,20 @@ impl Company {
     /// Authenticate with DocuSign.
     pub async fn authenticate_docusign(&self, db: &Database) -> Result<DocuSign> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "docusign".to_string()).await {
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "docusign").await? {
+            if t.is_expired() {
+                // Only refresh the token if it is expired.
+                t = t.refresh_decrypted(db, self).await?;
+            }
+
             // Initialize the DocuSign client.
-            let mut ds = DocuSign::new_from_env(
+            let ds = DocuSign::new_from_env(
                 t.access_token.to_string(),
                 t.refresh_token.to_string(),
                 t.company_id.to_string(),
                 t.endpoint.to_string(),
             );
 
-            if t.is_expired() {
-                // Only refresh the token if it is expired.
-                let nt = ds.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.x_refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.x_refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
-            }
-
             return Ok(ds);
         }
 
@@ -617,7 +579,12 @@ impl Company {
     /// Authenticate with Gusto.
     pub async fn authenticate_gusto(&self, db: &Database) -> Result<(Gusto, String)> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "gusto".to_string()).await {
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "gusto").await? {
+            if t.is_expired() {
+                // Only refresh the token if it is expired.
+                t = t.refresh_decrypted(db, self).await?;
+            }
+
             // Initialize the Gusto client.
             let gusto = Gusto::new_from_env(
                 t.access_token.to_string(),
@@ -625,27 +592,6 @@ impl Company {
                 gusto_api::RootProductionServer {},
             );
 
-            if t.is_expired() {
-                // Only refresh the token if it is expired.
-                let nt = gusto.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
-            }
-
             return Ok((gusto, t.company_id.to_string()));
         }
 
@@ -665,7 +611,7 @@ impl Company {
         }
 
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "tripactions".to_string()).await {
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "tripactions").await? {
             // Initialize the TripActions client.
             let mut ta = TripActions::new(
                 self.tripactions_client_id.to_string(),
@@ -691,7 +637,7 @@ impl Company {
                 t.last_updated_at = Utc::now();
                 t.expand();
                 // Update the token in the database.
-                t.update(db).await?;
+                t.update_encrypted(db, self).await?;
             }
 
             return Ok(ta);
@@ -725,7 +671,7 @@ impl Company {
         };
 
         token.expand();
-        token.upsert(db).await?;
+        token.upsert_encrypted(db, self).await?;
 
         Ok(ta)
     }
@@ -733,35 +679,19 @@ impl Company {
     /// Authenticate with QuickBooks.
     pub async fn authenticate_quickbooks(&self, db: &Database) -> Result<QuickBooks> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "quickbooks".to_string()).await {
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "quickbooks").await? {
+            if t.is_expired() {
+                // Only refresh the token if it is expired.
+                t = t.refresh_decrypted(db, self).await?;
+            }
+
             // Initialize the QuickBooks client.
-            let mut qb = QuickBooks::new_from_env(
+            let qb = QuickBooks::new_from_env(
                 t.company_id.to_string(),
                 t.access_token.to_string(),
                 t.refresh_token.to_string(),
             );
 
-            if t.is_expired() {
-                // Only refresh the token if it is expired.
-                let nt = qb.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.x_refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.x_refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
-            }
-
             return Ok(qb);
         }
 
@@ -771,32 +701,16 @@ impl Company {
     /// Authenticate Google Admin.
     pub async fn authenticate_google_admin(&self, db: &Database) -> Result<GoogleAdmin> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "google".to_string()).await {
-            // Initialize the client.
-            let mut g = GoogleAdmin::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
-            g.set_auto_access_token_refresh(true);
-
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "google").await? {
             if t.is_expired() {
                 // Only refresh the token if it is expired.
-                let nt = g.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
+                t = t.refresh_decrypted(db, self).await?;
             }
 
+            // Initialize the client.
+            let mut g = GoogleAdmin::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
+            g.set_auto_access_token_refresh(true);
+
             return Ok(g);
         }
 
@@ -806,32 +720,16 @@ impl Company {
     /// Authenticate Google Calendar.
     pub async fn authenticate_google_calendar(&self, db: &Database) -> Result<GoogleCalendar> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "google".to_string()).await {
-            // Initialize the client.
-            let mut g = GoogleCalendar::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
-            g.set_auto_access_token_refresh(true);
-
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "google").await? {
             if t.is_expired() {
                 // Only refresh the token if it is expired.
-                let nt = g.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
+                t = t.refresh_decrypted(db, self).await?;
             }
 
+            // Initialize the client.
+            let mut g = GoogleCalendar::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
+            g.set_auto_access_token_refresh(true);
+
             return Ok(g);
         }
 
@@ -851,32 +749,16 @@ impl Company {
     /// Authenticate Google Drive.
     pub async fn authenticate_google_drive(&self, db: &Database) -> Result<GoogleDrive> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "google".to_string()).await {
-            // Initialize the client.
-            let mut g = GoogleDrive::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
-            g.set_auto_access_token_refresh(true);
-
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "google").await? {
             if t.is_expired() {
                 // Only refresh the token if it is expired.
-                let nt = g.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
+                t = t.refresh_decrypted(db, self).await?;
             }
 
+            // Initialize the client.
+            let mut g = GoogleDrive::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
+            g.set_auto_access_token_refresh(true);
+
             return Ok(g);
         }
 
@@ -936,32 +818,16 @@ impl Company {
     /// Authenticate Google Sheets.
     pub async fn authenticate_google_sheets(&self, db: &Database) -> Result<GoogleSheets> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "google".to_string()).await {
-            // Initialize the client.
-            let mut g = GoogleSheets::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
-            g.set_auto_access_token_refresh(true);
-
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "google").await? {
             if t.is_expired() {
                 // Only refresh the token if it is expired.
-                let nt = g.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
+                t = t.refresh_decrypted(db, self).await?;
             }
 
+            // Initialize the client.
+            let mut g = GoogleSheets::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
+            g.set_auto_access_token_refresh(true);
+
             return Ok(g);
         }
 
@@ -971,33 +837,17 @@ impl Company {
     /// Authenticate Google Groups Settings.
     pub async fn authenticate_google_groups_settings(&self, db: &Database) -> Result<GoogleGroupsSettings> {
         // Get the APIToken from the database.
-        if let Some(mut t) = APIToken::get_from_db(db, self.id, "google".to_string()).await {
+        if let Some(mut t) = APIToken::get_decrypted(db, self, "google").await? {
+            if t.is_expired() {
+                // Only refresh the token if it is expired.
+                t = t.refresh_decrypted(db, self).await?;
+            }
+
             // Initialize the client.
             let mut g =
                 GoogleGroupsSettings::new_from_env(t.access_token.to_string(), t.refresh_token.to_string()).await;
             g.set_auto_access_token_refresh(true);
 
-            if t.is_expired() {
-                // Only refresh the token if it is expired.
-                let nt = g.refresh_access_token().await?;
-                if !nt.access_token.is_empty() {
-                    t.access_token = nt.access_token.to_string();
-                }
-                if nt.expires_in > 0 {
-                    t.expires_in = nt.expires_in as i32;
-                }
-                if !nt.refresh_token.is_empty() {
-                    t.refresh_token = nt.refresh_token.to_string();
-                }
-                if nt.refresh_token_expires_in > 0 {
-                    t.refresh_token_expires_in = nt.refresh_token_expires_in as i32;
-                }
-                t.last_updated_at = Utc::now();
-                t.expand();
-                // Update the token in the database.
-                t.update(db).await?;
-            }
-
             return Ok(g);
         }
 
@@ -1081,6 +931,22 @@ impl Company {
         ))
     }
 
+    pub async fn authenticate_gcs(&self) -> Result<Storage<HttpsConnector<HttpConnector>>> {
+        let gcp_auth = self.authenticate_gcp().await?;
+
+        Ok(Storage::new(
+            hyper::Client::builder().build(
+                hyper_rustls::HttpsConnectorBuilder::new()
+                    .with_native_roots()
+                    .https_or_http()
+                    .enable_http1()
+                    .enable_http2()
+                    .build(),
+            ),
+            gcp_auth,
+        ))
+    }
+
     pub async fn cert_storage(&self) -> Result<Vec<Box<dyn SslCertificateStorage>>> {
         let gcp_auth = self.authenticate_gcp().await?;
 
@@ -0,0 +1,609 @@
+//! Proposing changes to the configs repository.
+//!
+//! The configs repository is the source of truth for links, groups, and resources. Rather than
+//! writing to the database directly, changes requested through the API are written to a new
+//! branch of the configs repository and a pull request is opened for review. Once the pull
+//! request is merged, the usual `push` webhook syncs the change everywhere else.
+use std::{fmt, str::from_utf8};
+
+use anyhow::Result;
+use chrono::Utc;
+use log::info;
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    companies::Company,
+    configs::{GroupConfig, LinkConfig, NewResourceConfig},
+    utils::{create_or_update_file_in_github_repo, get_file_content_from_repo},
+};
+
+/// The name of the repository that holds the configuration files.
+const CONFIGS_REPO: &str = "configs";
+
+/// An entry in one of the files of the configs repository.
+pub trait ConfigEntry: Serialize + DeserializeOwned {
+    /// The top-level table that holds entries of this type. New entries go in the file of the
+    /// `configs/` directory that already has this table, or in one named after it (with a `.toml`
+    /// extension) if none does.
+    const SECTION: &'static str;
+
+    /// A human readable name for the entry, used in branch names and pull request titles.
+    const KIND: &'static str;
+
+    /// Fields that are computed when the configs are synced and should never be written back
+    /// to the configs repository.
+    const COMPUTED_FIELDS: &'static [&'static str];
+
+    /// The name that uniquely identifies the entry.
+    fn entry_name(&self) -> &str;
+}
+
+impl ConfigEntry for LinkConfig {
+    const SECTION: &'static str = "links";
+    const KIND: &'static str = "link";
+    const COMPUTED_FIELDS: &'static [&'static str] = &["name", "short_link", "cio_company_id"];
+
+    fn entry_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl ConfigEntry for GroupConfig {
+    const SECTION: &'static str = "groups";
+    const KIND: &'static str = "group";
+    const COMPUTED_FIELDS: &'static [&'static str] = &["link", "cio_company_id"];
+
+    fn entry_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl ConfigEntry for NewResourceConfig {
+    const SECTION: &'static str = "resources";
+    const KIND: &'static str = "resource";
+    const COMPUTED_FIELDS: &'static [&'static str] = &["link_to_building", "cio_company_id"];
+
+    fn entry_name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A change to a single entry in the configs repository.
+pub enum ConfigChange<T> {
+    /// Add a new entry. Fails if an entry with the same name already exists.
+    Create(T),
+    /// Update an existing entry with a JSON merge patch (RFC 7396).
+    Update(Value),
+    /// Remove an existing entry.
+    Delete,
+}
+
+impl<T> ConfigChange<T> {
+    fn verb(&self) -> &'static str {
+        match self {
+            ConfigChange::Create(_) => "Add",
+            ConfigChange::Update(_) => "Update",
+            ConfigChange::Delete => "Remove",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigChangeError {
+    /// The entry to update or delete does not exist.
+    NotFound(String),
+    /// The entry to create already exists.
+    AlreadyExists(String),
+    /// The requested change does not produce a valid entry.
+    Invalid(String),
+    /// Talking to GitHub or reading the existing configs failed.
+    Other(anyhow::Error),
+}
+
+impl fmt::Display for ConfigChangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigChangeError::NotFound(name) => write!(f, "{} does not exist", name),
+            ConfigChangeError::AlreadyExists(name) => write!(f, "{} already exists", name),
+            ConfigChangeError::Invalid(reason) => write!(f, "invalid change: {}", reason),
+            ConfigChangeError::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigChangeError {}
+
+impl From<anyhow::Error> for ConfigChangeError {
+    fn from(err: anyhow::Error) -> Self {
+        ConfigChangeError::Other(err)
+    }
+}
+
+/// The pull request that was opened against the configs repository for a change.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ConfigChangeProposal {
+    pub branch: String,
+    pub pull_request_number: i64,
+    pub pull_request_url: String,
+}
+
+/// Write the change to a new branch of the configs repository and open a pull request for it.
+pub async fn propose_config_change<T: ConfigEntry>(
+    github: &octorust::Client,
+    company: &Company,
+    name: &str,
+    change: ConfigChange<T>,
+) -> Result<ConfigChangeProposal, ConfigChangeError> {
+    let owner = &company.github_org;
+
+    let default_branch = github
+        .repos()
+        .get(owner, CONFIGS_REPO)
+        .await
+        .map_err(anyhow::Error::from)?
+        .body
+        .default_branch;
+
+    // The configs are read by concatenating every file in `configs/`, so the section can live in
+    // any of them.
+    let entries = github
+        .repos()
+        .get_content_vec_entries(owner, CONFIGS_REPO, "/configs/", &default_branch)
+        .await
+        .map_err(anyhow::Error::from)?
+        .body;
+    let mut files = vec![];
+    for entry in entries.into_iter().filter(|entry| entry.type_ == "file") {
+        let (contents, _) =
+            get_file_content_from_repo(github, owner, CONFIGS_REPO, &default_branch, &entry.path).await?;
+        let contents = from_utf8(&contents).map_err(anyhow::Error::from)?.to_string();
+        files.push((format!("/{}", entry.path.trim_start_matches('/')), contents));
+    }
+
+    let (path, contents) = config_file::<T>(files, name)?;
+
+    let title = format!("{} {} `{}`", change.verb(), T::KIND, name);
+    let updated = apply_config_change::<T>(&contents, name, change)?;
+
+    // Branch off of the current head of the default branch.
+    let head = github
+        .git()
+        .get_ref(owner, CONFIGS_REPO, &format!("heads/{}", default_branch))
+        .await
+        .map_err(anyhow::Error::from)?
+        .body;
+
+    let branch = format!(
+        "cio-api/{}-{}-{}",
+        T::KIND,
+        slugify(name),
+        Utc::now().format("%Y%m%d%H%M%S")
+    );
+
+    github
+        .git()
+        .create_ref(
+            owner,
+            CONFIGS_REPO,
+            &octorust::types::GitCreateRefRequest {
+                key: String::new(),
+                ref_: format!("refs/heads/{}", branch),
+                sha: head.object.sha,
+            },
+        )
+        .await
+        .map_err(anyhow::Error::from)?;
+
+    create_or_update_file_in_github_repo(github, owner, CONFIGS_REPO, &branch, &path, updated.into_bytes()).await?;
+
+    let pull = github
+        .pulls()
+        .create(
+            owner,
+            CONFIGS_REPO,
+            &octorust::types::PullsCreateRequest {
+                title,
+                head: format!("{}:{}", owner, branch),
+                base: default_branch,
+                body: format!(
+                    "This change to `{}` was requested through the CIO API. Once merged it will be \
+                     synced like any other change to the configs.",
+                    path.trim_start_matches('/')
+                ),
+                draft: Some(false),
+                maintainer_can_modify: Some(true),
+                issue: 0,
+            },
+        )
+        .await
+        .map_err(anyhow::Error::from)?
+        .body;
+
+    info!(
+        "[configs] opened pull request #{} for {} `{}` on branch {}",
+        pull.number,
+        T::KIND,
+        name,
+        branch
+    );
+
+    Ok(ConfigChangeProposal {
+        branch,
+        pull_request_number: pull.number,
+        pull_request_url: pull.html_url,
+    })
+}
+
+/// Pick the file of the configs repository a change to the entry goes in, from the paths and
+/// contents of the files in `configs/`: the file that has the entry, or else the first file that
+/// has its section, or else a new file named after the section.
+fn config_file<T: ConfigEntry>(files: Vec<(String, String)>, name: &str) -> Result<(String, String)> {
+    let mut section_file = None;
+    for (path, contents) in files {
+        if find_entry(&contents, T::SECTION, name)?.is_some() {
+            return Ok((path, contents));
+        }
+
+        let parsed: toml::Value = toml::from_str(&contents)?;
+        if section_file.is_none() && parsed.get(T::SECTION).is_some() {
+            section_file = Some((path, contents));
+        }
+    }
+
+    Ok(section_file.unwrap_or_else(|| (format!("/configs/{}.toml", T::SECTION), String::new())))
+}
+
+/// Apply a change to the contents of a configs file, returning the new contents. Only the block
+/// for the changed entry is rewritten so that comments and formatting elsewhere in the file are
+/// left untouched.
+pub fn apply_config_change<T: ConfigEntry>(
+    contents: &str,
+    name: &str,
+    change: ConfigChange<T>,
+) -> Result<String, ConfigChangeError> {
+    let existing = find_entry(contents, T::SECTION, name)?;
+
+    match change {
+        ConfigChange::Create(entry) => {
+            if existing.is_some() {
+                return Err(ConfigChangeError::AlreadyExists(name.to_string()));
+            }
+
+            if name.trim().is_empty() {
+                return Err(ConfigChangeError::Invalid("a name is required".to_string()));
+            }
+
+            if !T::COMPUTED_FIELDS.contains(&"name") && entry.entry_name() != name {
+                return Err(ConfigChangeError::Invalid(format!(
+                    "name `{}` does not match `{}`",
+                    entry.entry_name(),
+                    name
+                )));
+            }
+
+            let rendered = render_entry(T::SECTION, name, &entry, T::COMPUTED_FIELDS)?;
+            Ok(replace_block(contents, T::SECTION, name, Some(&rendered)))
+        }
+        ConfigChange::Update(patch) => {
+            let (key, current) = existing.ok_or_else(|| ConfigChangeError::NotFound(name.to_string()))?;
+
+            let mut merged = serde_json::to_value(current).map_err(anyhow::Error::from)?;
+            merge_patch(&mut merged, patch);
+
+            let entry: T = serde_json::from_value(merged).map_err(|e| ConfigChangeError::Invalid(e.to_string()))?;
+
+            if !T::COMPUTED_FIELDS.contains(&"name") && entry.entry_name() != name {
+                return Err(ConfigChangeError::Invalid(
+                    "entries can not be renamed, delete and re-create them instead".to_string(),
+                ));
+            }
+
+            let rendered = render_entry(T::SECTION, &key, &entry, T::COMPUTED_FIELDS)?;
+            Ok(replace_block(contents, T::SECTION, &key, Some(&rendered)))
+        }
+        ConfigChange::Delete => {
+            let (key, _) = existing.ok_or_else(|| ConfigChangeError::NotFound(name.to_string()))?;
+            Ok(replace_block(contents, T::SECTION, &key, None))
+        }
+    }
+}
+
+/// Find an entry in a configs file, first by its table key and then by its `name` field. Returns
+/// the table key along with the entry.
+fn find_entry(contents: &str, section: &str, name: &str) -> Result<Option<(String, toml::Value)>> {
+    let parsed: toml::Value = toml::from_str(contents)?;
+
+    let table = match parsed.get(section).and_then(|s| s.as_table()) {
+        Some(table) => table,
+        None => return Ok(None),
+    };
+
+    if let Some(entry) = table.get(name) {
+        return Ok(Some((name.to_string(), entry.clone())));
+    }
+
+    Ok(table
+        .iter()
+        .find(|(_, entry)| entry.get("name").and_then(|n| n.as_str()) == Some(name))
+        .map(|(key, entry)| (key.to_string(), entry.clone())))
+}
+
+/// Render a single entry as a TOML table with a `[section.key]` header. Fields are written in
+/// the order they are declared on the entry type.
+fn render_entry<T: Serialize>(section: &str, key: &str, entry: &T, computed: &[&str]) -> Result<String> {
+    let body = toml::to_string(entry)?;
+    let fields = body
+        .lines()
+        .filter(|line| match line.split_once('=') {
+            Some((field, _)) => !computed.contains(&field.trim()),
+            None => !line.trim().is_empty(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(format!("[{}.{}]\n{}", section, quote_key(key), fields))
+}
+
+/// Quote a TOML key if it is not a valid bare key.
+fn quote_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
+
+/// Parse a `[section.key]` table header, returning the section and key.
+fn parse_header(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.starts_with("[[") || !line.starts_with('[') || !line.ends_with(']') {
+        return None;
+    }
+
+    let (section, key) = line[1..line.len() - 1].split_once('.')?;
+    Some((section.trim().to_string(), key.trim().trim_matches('"').to_string()))
+}
+
+/// Replace (or remove, if `rendered` is `None`) the block for `[section.key]`. If the block does
+/// not exist yet, the rendered entry is appended to the end of the file.
+fn replace_block(contents: &str, section: &str, key: &str, rendered: Option<&str>) -> String {
+    let mut lines: Vec<&str> = contents.lines().collect();
+
+    let start = lines.iter().position(|line| {
+        parse_header(line)
+            .map(|(s, k)| s == section && k == key)
+            .unwrap_or(false)
+    });
+
+    match start {
+        Some(start) => {
+            let mut end = lines[start + 1..]
+                .iter()
+                .position(|line| line.trim_start().starts_with('['))
+                .map(|offset| start + 1 + offset)
+                .unwrap_or(lines.len());
+
+            // Blank lines and comments right above the next header belong to that header.
+            while end > start + 1 && (lines[end - 1].trim().is_empty() || lines[end - 1].trim().starts_with('#')) {
+                end -= 1;
+            }
+
+            match rendered {
+                Some(rendered) => {
+                    lines.splice(start..end, rendered.lines());
+                }
+                None => {
+                    // Also drop the blank line that separated the removed block from the next one.
+                    if end < lines.len() && lines[end].trim().is_empty() {
+                        end += 1;
+                    }
+                    lines.drain(start..end);
+                }
+            }
+        }
+        None => {
+            if let Some(rendered) = rendered {
+                while lines.last().map(|line| line.trim().is_empty()).unwrap_or(false) {
+                    lines.pop();
+                }
+                if !lines.is_empty() {
+                    lines.push("");
+                }
+                lines.extend(rendered.lines());
+            }
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Apply a JSON merge patch (RFC 7396). Unlike `utils::merge_json` arrays are replaced rather
+/// than appended to, and `null` removes a field.
+fn merge_patch(target: &mut Value, patch: Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(Default::default());
+            }
+            let target = target.as_object_mut().unwrap();
+            for (k, v) in patch {
+                if v.is_null() {
+                    target.remove(&k);
+                } else {
+                    merge_patch(target.entry(k).or_insert(Value::Null), v);
+                }
+            }
+        }
+        patch => *target = patch,
+    }
+}
+
+/// Turn a name into something that is safe to use in a branch name.
+fn slugify(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>()
+        .trim_matches('-')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{apply_config_change, config_file, slugify, ConfigChange, ConfigChangeError};
+    use crate::configs::{GroupConfig, LinkConfig};
+
+    const LINKS: &str = r#"# Links for the company.
+
+[links.rfd]
+description = "RFDs"
+link = "https://rfd.example.com"
+
+# The handbook.
+[links.handbook]
+description = "The handbook"
+link = "https://handbook.example.com"
+aliases = ["hb"]
+"#;
+
+    #[test]
+    fn test_create_link_appends_block() {
+        let link = LinkConfig {
+            description: "The docs".to_string(),
+            link: "https://docs.example.com".to_string(),
+            ..Default::default()
+        };
+
+        let updated = apply_config_change(LINKS, "docs", ConfigChange::Create(link)).unwrap();
+
+        assert!(updated.starts_with(LINKS));
+        assert!(
+            updated.ends_with("\n\n[links.docs]\ndescription = \"The docs\"\nlink = \"https://docs.example.com\"\n")
+        );
+        assert!(!updated.contains("cio_company_id"));
+    }
+
+    #[test]
+    fn test_create_existing_link_fails() {
+        let result = apply_config_change(LINKS, "rfd", ConfigChange::Create(LinkConfig::default()));
+        assert!(matches!(result, Err(ConfigChangeError::AlreadyExists(_))));
+    }
+
+    #[test]
+    fn test_update_link_only_touches_its_block() {
+        let updated = apply_config_change::<LinkConfig>(
+            LINKS,
+            "rfd",
+            ConfigChange::Update(json!({ "link": "https://rfd.example.org", "aliases": ["rfds"] })),
+        )
+        .unwrap();
+
+        assert_eq!(
+            updated,
+            r#"# Links for the company.
+
+[links.rfd]
+description = "RFDs"
+link = "https://rfd.example.org"
+aliases = ["rfds"]
+
+# The handbook.
+[links.handbook]
+description = "The handbook"
+link = "https://handbook.example.com"
+aliases = ["hb"]
+"#
+        );
+    }
+
+    #[test]
+    fn test_update_invalid_link_fails() {
+        let result = apply_config_change::<LinkConfig>(LINKS, "rfd", ConfigChange::Update(json!({ "link": 5 })));
+        assert!(matches!(result, Err(ConfigChangeError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_delete_link() {
+        let updated = apply_config_change::<LinkConfig>(LINKS, "rfd", ConfigChange::Delete).unwrap();
+
+        assert_eq!(
+            updated,
+            r#"# Links for the company.
+
+# The handbook.
+[links.handbook]
+description = "The handbook"
+link = "https://handbook.example.com"
+aliases = ["hb"]
+"#
+        );
+
+        let result = apply_config_change::<LinkConfig>(LINKS, "missing", ConfigChange::Delete);
+        assert!(matches!(result, Err(ConfigChangeError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_group_found_by_name_and_can_not_be_renamed() {
+        let groups = "[groups.engineering]\nname = \"eng\"\ndescription = \"Engineering\"\n";
+
+        let updated = apply_config_change::<GroupConfig>(
+            groups,
+            "eng",
+            ConfigChange::Update(json!({ "members": ["alice", "bob"] })),
+        )
+        .unwrap();
+        assert!(updated.starts_with("[groups.engineering]\nname = \"eng\"\n"));
+        assert!(updated.contains("members = [\"alice\", \"bob\"]"));
+
+        let result =
+            apply_config_change::<GroupConfig>(groups, "eng", ConfigChange::Update(json!({ "name": "engineering" })));
+        assert!(matches!(result, Err(ConfigChangeError::Invalid(_))));
+    }
+
+    #[test]
+    fn test_config_file() {
+        let files = vec![
+            (
+                "/configs/groups.toml".to_string(),
+                "[groups.eng]\ndescription = \"Eng\"\n".to_string(),
+            ),
+            ("/configs/company.toml".to_string(), LINKS.to_string()),
+            (
+                "/configs/more-links.toml".to_string(),
+                "[links.docs]\nlink = \"https://docs.example.com\"\n".to_string(),
+            ),
+        ];
+
+        // The file that has the entry, whatever its name.
+        let (path, _) = config_file::<LinkConfig>(files.clone(), "docs").unwrap();
+        assert_eq!(path, "/configs/more-links.toml");
+        let (path, contents) = config_file::<LinkConfig>(files.clone(), "handbook").unwrap();
+        assert_eq!(path, "/configs/company.toml");
+        assert_eq!(contents, LINKS);
+
+        // New entries go in the first file with the section.
+        let (path, _) = config_file::<LinkConfig>(files.clone(), "new").unwrap();
+        assert_eq!(path, "/configs/company.toml");
+
+        // Or in a new file when no file has the section.
+        let (path, contents) = config_file::<LinkConfig>(files[..1].to_vec(), "new").unwrap();
+        assert_eq!(path, "/configs/links.toml");
+        assert!(contents.is_empty());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Some Link.Name"), "some-link-name");
+    }
+}
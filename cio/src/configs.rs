@@ -35,13 +35,14 @@ use crate::{
     },
     app_config::{AppConfig, OnboardingConfig},
     applicants::Applicant,
+    audit::{audit, AuditScope, Audited},
     certs::{Certificate, Certificates, GitHubBackend, NewCertificate},
     companies::Company,
     core::UpdateAirtableRecord,
     db::Database,
     features::Features,
     gsuite::{update_gsuite_building, update_gsuite_calendar_resource},
-    providers::{ProviderReadOps, ProviderWriteOps},
+    providers::{ProviderReadOps, ProviderWriteOps, SlackMemberships},
     schema::{applicants, buildings, groups, links, resources, users},
     shipments::NewOutboundShipment,
     utils::{get_file_content_from_repo, get_github_user_public_ssh_keys},
@@ -83,6 +84,7 @@ pub enum ExternalServices {
     Google,
     Okta,
     Ramp,
+    Slack,
     Zoom,
 }
 
@@ -94,6 +96,7 @@ impl ExternalServices {
             ExternalServices::Google => "google",
             ExternalServices::Okta => "okta",
             ExternalServices::Ramp => "ramp",
+            ExternalServices::Slack => "slack",
             ExternalServices::Zoom => "zoom",
         }
     }
@@ -103,18 +106,26 @@ impl ExternalServices {
         db: &Database,
         company: &Company,
     ) -> Result<Box<dyn ProviderWriteOps + Send + Sync>> {
+        // Every writer records its writes in the audit log.
         Ok(match self {
             // We don't need a base id here since we are only using the enterprise api features.
-            ExternalServices::Airtable => Box::new(company.authenticate_airtable("")),
-            ExternalServices::GitHub => Box::new(company.authenticate_github()?),
-            ExternalServices::Google => Box::new(company.authenticate_google_admin(db).await?),
-            ExternalServices::Okta => Box::new(
+            ExternalServices::Airtable => Box::new(Audited::new(db, self.clone(), company.authenticate_airtable(""))),
+            ExternalServices::GitHub => Box::new(Audited::new(db, self.clone(), company.authenticate_github()?)),
+            ExternalServices::Google => Box::new(Audited::new(
+                db,
+                self.clone(),
+                company.authenticate_google_admin(db).await?,
+            )),
+            ExternalServices::Okta => Box::new(Audited::new(
+                db,
+                self.clone(),
                 company
                     .authenticate_okta()
                     .ok_or_else(|| anyhow::anyhow!("Failed to instantiate Okta client"))?,
-            ),
-            ExternalServices::Ramp => Box::new(company.authenticate_ramp()?),
-            ExternalServices::Zoom => Box::new(company.authenticate_zoom(db).await?),
+            )),
+            ExternalServices::Ramp => Box::new(Audited::new(db, self.clone(), company.authenticate_ramp()?)),
+            ExternalServices::Slack => Box::new(Audited::new(db, self.clone(), company.authenticate_slack(db).await?)),
+            ExternalServices::Zoom => Box::new(Audited::new(db, self.clone(), company.authenticate_zoom(db).await?)),
         })
     }
 }
@@ -127,6 +138,7 @@ impl fmt::Display for ExternalServices {
             ExternalServices::Google => write!(f, "Google"),
             ExternalServices::Okta => write!(f, "Okta"),
             ExternalServices::Ramp => write!(f, "Ramp"),
+            ExternalServices::Slack => write!(f, "Slack"),
             ExternalServices::Zoom => write!(f, "Zoom"),
         }
     }
@@ -146,6 +158,7 @@ impl FromSql<VarChar, Pg> for ExternalServices {
             b"google" => Ok(ExternalServices::Google),
             b"okta" => Ok(ExternalServices::Okta),
             b"ramp" => Ok(ExternalServices::Ramp),
+            b"slack" => Ok(ExternalServices::Slack),
             b"zoom" => Ok(ExternalServices::Zoom),
             unknown_service => Err(format!(
                 "Encountered unknown external service value {:?} in database. Unable to deserialize.",
@@ -384,25 +397,44 @@ impl UserConfig {
         zoom_users_pending: &HashMap<String, zoom_api::types::UsersResponse>,
         gusto_users: &HashMap<String, gusto_api::types::Employee>,
         gusto_users_by_id: &HashMap<String, gusto_api::types::Employee>,
+        slack_memberships: &Option<SlackMemberships>,
     ) -> Result<()> {
+        // All of the provider clients below record their writes in the audit log.
+        let github = Audited::new(db, ExternalServices::GitHub, github.clone());
+
         // Get everything we need to authenticate with GSuite.
         // Initialize the GSuite client.
-        let gsuite = company.authenticate_google_admin(db).await?;
+        let gsuite = Audited::new(
+            db,
+            ExternalServices::Google,
+            company.authenticate_google_admin(db).await?,
+        );
 
         // We don't need a base id here since we are only using the enterprise api features.
-        let airtable_auth = company.authenticate_airtable("");
+        let airtable_auth = Audited::new(db, ExternalServices::Airtable, company.authenticate_airtable(""));
 
         // Initialize the Gusto client.
         let gusto_auth = company.authenticate_gusto(db).await;
 
         // Initialize the Okta client.
-        let okta_auth = company.authenticate_okta();
+        let okta_auth = company
+            .authenticate_okta()
+            .map(|okta| Audited::new(db, ExternalServices::Okta, okta));
 
         // Initialize the Ramp client.
-        let ramp = company.authenticate_ramp()?;
+        let ramp = Audited::new(db, ExternalServices::Ramp, company.authenticate_ramp()?);
 
         // Initialize the Zoom client.
-        let zoom_auth = company.authenticate_zoom(db).await;
+        let zoom_auth = company
+            .authenticate_zoom(db)
+            .await
+            .map(|zoom| Audited::new(db, ExternalServices::Zoom, zoom));
+
+        // Initialize the Slack client.
+        let slack_auth = company
+            .authenticate_slack(db)
+            .await
+            .map(|slack| Audited::new(db, ExternalServices::Slack, slack));
 
         // Set the user's email.
         self.email = format!("{}@{}", self.username, company.gsuite_domain);
@@ -585,6 +617,17 @@ impl UserConfig {
             }
         }
 
+        // Sync the user's Slack channels with their groups, their user groups are synced for all
+        // users at once after this.
+        if let (Ok(slack), Some(memberships)) = (&slack_auth, slack_memberships) {
+            if let Err(e) = slack
+                .ensure_user_with_memberships(company, &new_user, memberships)
+                .await
+            {
+                warn!("Failed to ensure slack user `{}`: {}", new_user.id, e);
+            }
+        }
+
         // Deprovision this user explicitly from any service they should not have access to
         for denied_service in &new_user.denied_services {
             match denied_service.get_provider_writer(db, company).await {
@@ -995,7 +1038,7 @@ impl User {
         let aliases = self.aliases.join(", ");
 
         // Send the message.
-        sendgrid
+        let result = sendgrid
             .mail_send()
             .send_plain_text(
                 &format!("Your New Email Account: {}", self.email),
@@ -1031,7 +1074,10 @@ xoxo,
                 &[],
                 &format!("admin@{}", company.gsuite_domain),
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(db, &company, "sendgrid", "send_email", &self.recovery_email, result).await?;
 
         Ok(())
     }
@@ -1050,7 +1096,7 @@ xoxo,
 
         let letter = config.create_welcome_letter(&company, self, password);
 
-        sendgrid
+        let result = sendgrid
             .mail_send()
             .send_plain_text(
                 &letter.subject,
@@ -1060,7 +1106,10 @@ xoxo,
                 &letter.bcc,
                 &letter.from,
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(db, &company, "sendgrid", "send_email", &self.recovery_email, result).await?;
 
         Ok(())
     }
@@ -1096,7 +1145,7 @@ let jess@{} know what your GitHub handle is.",
         }
 
         // Send the message.
-        sendgrid
+        let result = sendgrid
             .mail_send()
             .send_plain_text(
                 &format!("Your New Email Account: {}", self.email),
@@ -1172,7 +1221,10 @@ xoxo,
                 &[],
                 &format!("admin@{}", company.gsuite_domain),
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(db, &company, "sendgrid", "send_email", &self.recovery_email, result).await?;
 
         Ok(())
     }
@@ -1332,6 +1384,11 @@ pub struct GroupConfig {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub repos: Vec<String>,
 
+    /// Slack channels the members of this group should be in. Channels that do not exist yet are
+    /// created as private channels.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub slack_channels: Vec<String>,
+
     /// allow_external_members: Identifies whether members external to your
     /// organization can join the group. Possible values are:
     /// - true: G Suite users external to your organization can become
@@ -1771,11 +1828,15 @@ pub async fn sync_users(
 ) -> Result<()> {
     // Get everything we need to authenticate with GSuite.
     // Initialize the GSuite client.
-    let gsuite = company.authenticate_google_admin(db).await?;
+    let gsuite = Audited::new(
+        db,
+        ExternalServices::Google,
+        company.authenticate_google_admin(db).await?,
+    );
     let gcal = company.authenticate_google_calendar(db).await?;
 
     // We don't need a base id here since we are only using the enterprise api features.
-    let airtable_auth = company.authenticate_airtable("");
+    let airtable_auth = Audited::new(db, ExternalServices::Airtable, company.authenticate_airtable(""));
 
     // Initialize the Gusto client.
     let mut gusto_users: HashMap<String, gusto_api::types::Employee> = HashMap::new();
@@ -1795,7 +1856,9 @@ pub async fn sync_users(
 
     // Initialize the Okta client.
     let mut okta_users: HashMap<String, okta::types::User> = HashMap::new();
-    let okta_auth = company.authenticate_okta();
+    let okta_auth = company
+        .authenticate_okta()
+        .map(|okta| Audited::new(db, ExternalServices::Okta, okta));
     if let Some(ref okta) = okta_auth {
         let gu = okta.list_provider_users(company).await?;
         for g in gu {
@@ -1806,7 +1869,7 @@ pub async fn sync_users(
     // Initialize the Ramp client.
     let mut ramp_users: HashMap<String, ramp_minimal_api::User> = HashMap::new();
     let mut ramp_departments: HashMap<String, ramp_minimal_api::Department> = HashMap::new();
-    let ramp = company.authenticate_ramp()?;
+    let ramp = Audited::new(db, ExternalServices::Ramp, company.authenticate_ramp()?);
     let ru = ramp.list_provider_users(company).await?;
     for r in ru {
         ramp_users.insert(r.email.to_string(), r);
@@ -1819,7 +1882,10 @@ pub async fn sync_users(
     // Initialize the Zoom client.
     let mut zoom_users: HashMap<String, zoom_api::types::UsersResponse> = HashMap::new();
     let mut zoom_users_pending: HashMap<String, zoom_api::types::UsersResponse> = HashMap::new();
-    let zoom_auth = company.authenticate_zoom(db).await;
+    let zoom_auth = company
+        .authenticate_zoom(db)
+        .await
+        .map(|zoom| Audited::new(db, ExternalServices::Zoom, zoom));
     if let Ok(ref zoom) = zoom_auth {
         match zoom.list_provider_users(company).await {
             Ok(active_users) => {
@@ -1854,6 +1920,24 @@ pub async fn sync_users(
         }
     }
 
+    // Initialize the Slack client.
+    let slack_auth = company
+        .authenticate_slack(db)
+        .await
+        .map(|slack| Audited::new(db, ExternalServices::Slack, slack));
+
+    // Get the Slack user groups and channels once, rather than for every user.
+    let slack_memberships = match slack_auth {
+        Ok(ref slack) => match SlackMemberships::fetch(slack, db, company).await {
+            Ok(memberships) => Some(memberships),
+            Err(e) => {
+                warn!("getting slack memberships for company {} failed: {}", company.name, e);
+                None
+            }
+        },
+        Err(_) => None,
+    };
+
     // Get the existing GSuite users.
     let gsuite_users = gsuite.list_provider_users(company).await?;
     let mut gsuite_users_map: BTreeMap<String, GSuiteUser> = BTreeMap::new();
@@ -1910,7 +1994,8 @@ pub async fn sync_users(
             .skip(skip)
             .take(take)
             .map(|(_, mut user)| {
-                tokio::spawn(crate::enclose! { (db, company, config, github, gsuite_users_map, okta_users, ramp_users, zoom_users, zoom_users_pending, gusto_users, gusto_users_by_id) async move {
+                // Spawned tasks do not inherit the audit scope, so carry it over explicitly.
+                tokio::spawn(AuditScope::current().run(crate::enclose! { (db, company, config, github, gsuite_users_map, okta_users, ramp_users, zoom_users, zoom_users_pending, gusto_users, gusto_users_by_id, slack_memberships) async move {
                 user.sync(
                     &db,
                     &company,
@@ -1923,9 +2008,10 @@ pub async fn sync_users(
                     &zoom_users_pending,
                     &gusto_users,
                     &gusto_users_by_id,
+                    &slack_memberships,
                 )
                 .await
-                }})
+                }}))
             })
             .collect();
 
@@ -1957,6 +2043,24 @@ pub async fn sync_users(
         user_map.keys()
     );
 
+    // Update the members of the Slack user groups once for all users, rather than once per user.
+    // Users that are about to be removed are left to `delete_user`.
+    if let (Ok(slack), Some(memberships)) = (&slack_auth, &slack_memberships) {
+        let synced_users = Users::get_from_db(db, company.id)
+            .await?
+            .0
+            .into_iter()
+            .filter(|user| !user_map.contains_key(&user.username))
+            .collect::<Vec<_>>();
+
+        if let Err(e) = slack.sync_usergroups(company, &synced_users, memberships).await {
+            warn!("syncing slack user groups for company {} failed: {}", company.name, e);
+        }
+    }
+
+    // Record removals from GitHub in the audit log like every other provider.
+    let github = Audited::new(db, ExternalServices::GitHub, github.clone());
+
     // Remove any users that should no longer be in the database.
     // This is found by the remaining users that are in the map since we removed
     // the existing repos from the map above.
@@ -2063,8 +2167,18 @@ pub async fn sync_users(
             // will disallow them from logging in. And we want their purchase history so
             // we don't want to delete them.
 
-            // TODO: Delete the user from Slack.
+            // Remove the user from the Slack user groups and private channels of their groups.
             // Removing SSO (GSuite/Okta) will disallow them from logging in.
+            if let Ok(ref slack) = slack_auth {
+                match slack.delete_user(db, company, &user).await {
+                    Ok(_) => {
+                        info!("Removed user {} from Slack groups", username);
+                    }
+                    Err(err) => {
+                        warn!("Failed to remove user {} from Slack groups. err: {:?}", username, err);
+                    }
+                }
+            }
 
             // Delete the user from Zoom.
             if let Ok(ref zoom) = zoom_auth {
@@ -2171,10 +2285,12 @@ pub async fn sync_buildings(
         building.delete(db).await?;
 
         // Delete the building from GSuite.
-        gsuite
+        let result = gsuite
             .resources()
             .buildings_delete(&company.gsuite_account_id, &name)
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+        audit(db, company, "google", "delete_building", &name, result).await?;
         info!("deleted building from gsuite: {}", name);
     }
     info!("updated configs buildings in the database");
@@ -2197,10 +2313,12 @@ pub async fn sync_buildings(
                 // If the building does not exist in our map we need to delete
                 // them from GSuite.
                 info!("deleting building {} from gsuite", id);
-                gsuite
+                let result = gsuite
                     .resources()
                     .buildings_delete(&company.gsuite_account_id, &id)
-                    .await?;
+                    .await
+                    .map_err(anyhow::Error::from);
+                audit(db, company, "google", "delete_building", &id, result).await?;
 
                 info!("deleted building from gsuite: {}", id);
                 continue;
@@ -2211,7 +2329,7 @@ pub async fn sync_buildings(
         let new_b = update_gsuite_building(&b, &building, &id);
 
         // Update the building with the given settings.
-        gsuite
+        let result = gsuite
             .resources()
             .buildings_update(
                 &company.gsuite_account_id,
@@ -2219,7 +2337,9 @@ pub async fn sync_buildings(
                 gsuite_api::types::CoordinatesSource::SourceUnspecified,
                 &new_b,
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+        audit(db, company, "google", "update_building", &id, result).await?;
 
         // Remove the building from the database map and continue.
         // This allows us to add all the remaining new building after.
@@ -2235,14 +2355,16 @@ pub async fn sync_buildings(
 
         let new_b = update_gsuite_building(&b, &building, &id);
 
-        gsuite
+        let result = gsuite
             .resources()
             .buildings_insert(
                 &company.gsuite_account_id,
                 gsuite_api::types::CoordinatesSource::SourceUnspecified,
                 &new_b,
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+        audit(db, company, "google", "create_building", &id, result).await?;
 
         info!("created building from gsuite: {}", id);
     }
@@ -2338,10 +2460,12 @@ pub async fn sync_resources(
         let new_r = update_gsuite_calendar_resource(&r, &resource, &r.resource_id);
 
         // Update the resource with the given settings.
-        gsuite
+        let result = gsuite
             .resources()
             .calendars_update(&company.gsuite_account_id, &new_r.resource_id, &new_r)
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+        audit(db, company, "google", "update_calendar_resource", &id, result).await?;
 
         // Remove the resource from the database map and continue.
         // This allows us to add all the remaining new resource after.
@@ -2357,10 +2481,12 @@ pub async fn sync_resources(
 
         let new_r = update_gsuite_calendar_resource(&r, &resource, &id);
 
-        gsuite
+        let result = gsuite
             .resources()
             .calendars_insert(&company.gsuite_account_id, &new_r)
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+        audit(db, company, "google", "create_calendar_resource", &id, result).await?;
 
         info!("created conference room in gsuite: {}", id);
     }
@@ -2378,11 +2504,22 @@ pub async fn sync_resources(
 pub async fn sync_groups(db: &Database, groups: BTreeMap<String, GroupConfig>, company: &Company) -> Result<()> {
     // Get everything we need to authenticate with GSuite.
     // Initialize the GSuite client.
-    let gsuite = company.authenticate_google_admin(db).await?;
+    let gsuite = Audited::new(
+        db,
+        ExternalServices::Google,
+        company.authenticate_google_admin(db).await?,
+    );
 
-    let github = company.authenticate_github()?;
+    let github = Audited::new(db, ExternalServices::GitHub, company.authenticate_github()?);
+
+    let okta_auth = company
+        .authenticate_okta()
+        .map(|okta| Audited::new(db, ExternalServices::Okta, okta));
 
-    let okta_auth = company.authenticate_okta();
+    let slack_auth = company
+        .authenticate_slack(db)
+        .await
+        .map(|slack| Audited::new(db, ExternalServices::Slack, slack));
 
     // Get all the groups.
     let db_groups = Groups::get_from_db(db, company.id).await?;
@@ -2433,6 +2570,14 @@ pub async fn sync_groups(db: &Database, groups: BTreeMap<String, GroupConfig>, c
                 okta.ensure_group(db, company, &g).await?;
             }
         }
+
+        if let Ok(ref slack) = slack_auth {
+            if g.supports_provisioning_in(&ExternalServices::Slack) {
+                if let Err(err) = slack.ensure_group(db, company, &g).await {
+                    warn!("Failed to ensure Slack user group `{}`: {}", g.name, err);
+                }
+            }
+        }
     }
 
     // Update groups in airtable.
@@ -2863,6 +3008,12 @@ pub mod tests {
             },
             serde_json::from_str::<ServiceWrapper>("{\"service\": \"ramp\"}").unwrap()
         );
+        assert_eq!(
+            ServiceWrapper {
+                service: ExternalServices::Slack
+            },
+            serde_json::from_str::<ServiceWrapper>("{\"service\": \"slack\"}").unwrap()
+        );
         assert_eq!(
             ServiceWrapper {
                 service: ExternalServices::Zoom
@@ -2910,6 +3061,14 @@ pub mod tests {
             .unwrap()
             .as_str()
         );
+        assert_eq!(
+            "{\"service\":\"slack\"}",
+            serde_json::to_string(&ServiceWrapper {
+                service: ExternalServices::Slack
+            })
+            .unwrap()
+            .as_str()
+        );
         assert_eq!(
             "{\"service\":\"zoom\"}",
             serde_json::to_string(&ServiceWrapper {
@@ -2942,6 +3101,7 @@ denied_services = [
     'google',
     'okta',
     'ramp',
+    'slack',
     'zoom'
 ]
 recovery_email = 'testuser@localhost'
@@ -2966,6 +3126,7 @@ manager = 'orb'
                 ExternalServices::Google,
                 ExternalServices::Okta,
                 ExternalServices::Ramp,
+                ExternalServices::Slack,
                 ExternalServices::Zoom
             ]
         );
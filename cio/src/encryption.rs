@@ -0,0 +1,447 @@
+//! Envelope encryption for secrets that we store in the database.
+//!
+//! Every value is sealed with its own randomly generated data key using AES-256-GCM. The data key
+//! is then wrapped by a [`KeyEncryptionKey`], which is either a key held in Google Cloud KMS or a
+//! key read from a local file, and stored alongside the ciphertext. An encrypted value looks like:
+//!
+//! ```text
+//! enc:v1:<key id>:<base64 wrapped data key>:<base64 nonce + ciphertext>
+//! ```
+//!
+//! Values without the prefix are treated as plaintext, which lets rows written before encryption
+//! was turned on keep working until they are re-encrypted.
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use google_storage1::{hyper::client::HttpConnector, hyper_rustls::HttpsConnector};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    digest::{digest, SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+use yup_oauth2::authenticator::Authenticator;
+
+use crate::companies::Company;
+
+/// The prefix of every encrypted value.
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// The length in bytes of both data keys and local key encryption keys.
+const KEY_LEN: usize = 32;
+
+/// A key that wraps (encrypts) and unwraps the per-value data keys.
+#[async_trait]
+pub trait KeyEncryptionKey: Send + Sync {
+    /// An identifier for the key, stored with every value it wraps.
+    fn id(&self) -> &str;
+
+    async fn wrap(&self, data_key: &[u8]) -> Result<Vec<u8>>;
+
+    async fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// A key encryption key read from a file on local disk.
+pub struct LocalKey {
+    id: String,
+    key: Vec<u8>,
+}
+
+impl LocalKey {
+    /// Create a key from 32 raw bytes. The id is derived from the key itself so that rotating
+    /// the key file is detected.
+    pub fn new(key: &[u8]) -> Result<Self> {
+        if key.len() != KEY_LEN {
+            bail!("local encryption keys must be {} bytes, found {}", KEY_LEN, key.len());
+        }
+
+        let fingerprint = digest(&SHA256, key);
+
+        Ok(LocalKey {
+            id: format!("local-{}", hex::encode(&fingerprint.as_ref()[..8])),
+            key: key.to_vec(),
+        })
+    }
+
+    /// Read a base64 encoded key from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(path.as_ref())?;
+        let key = base64::decode(contents.trim())?;
+
+        Self::new(&key)
+    }
+}
+
+#[async_trait]
+impl KeyEncryptionKey for LocalKey {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    async fn wrap(&self, data_key: &[u8]) -> Result<Vec<u8>> {
+        seal(&self.key, data_key)
+    }
+
+    async fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>> {
+        open(&self.key, wrapped)
+    }
+}
+
+/// A key encryption key held in Google Cloud KMS. The key never leaves KMS, data keys are sent
+/// to it to be wrapped and unwrapped.
+pub struct GoogleCloudKms {
+    /// The full resource name of the key,
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*`.
+    key_name: String,
+    auth: Authenticator<HttpsConnector<HttpConnector>>,
+    client: reqwest::Client,
+}
+
+#[derive(Debug, Serialize)]
+struct KmsEncryptRequest {
+    plaintext: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct KmsEncryptResponse {
+    ciphertext: String,
+}
+
+#[derive(Debug, Serialize)]
+struct KmsDecryptRequest {
+    ciphertext: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct KmsDecryptResponse {
+    plaintext: String,
+}
+
+impl GoogleCloudKms {
+    pub fn new(key_name: &str, auth: Authenticator<HttpsConnector<HttpConnector>>) -> Self {
+        GoogleCloudKms {
+            key_name: key_name.to_string(),
+            auth,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn call<B: Serialize, R: for<'de> Deserialize<'de>>(&self, method: &str, body: &B) -> Result<R> {
+        let token = self.auth.token(&["https://www.googleapis.com/auth/cloudkms"]).await?;
+        let token = token.token().ok_or_else(|| anyhow!("failed to retrieve GCP token"))?;
+
+        let resp = self
+            .client
+            .post(format!(
+                "https://cloudkms.googleapis.com/v1/{}:{}",
+                self.key_name, method
+            ))
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await?;
+
+        if !resp.status().is_success() {
+            bail!(
+                "KMS {} with key {} failed: {} {}",
+                method,
+                self.key_name,
+                resp.status(),
+                resp.text().await?
+            );
+        }
+
+        Ok(resp.json().await?)
+    }
+}
+
+#[async_trait]
+impl KeyEncryptionKey for GoogleCloudKms {
+    fn id(&self) -> &str {
+        &self.key_name
+    }
+
+    async fn wrap(&self, data_key: &[u8]) -> Result<Vec<u8>> {
+        let resp: KmsEncryptResponse = self
+            .call(
+                "encrypt",
+                &KmsEncryptRequest {
+                    plaintext: base64::encode(data_key),
+                },
+            )
+            .await?;
+
+        Ok(base64::decode(resp.ciphertext)?)
+    }
+
+    async fn unwrap(&self, wrapped: &[u8]) -> Result<Vec<u8>> {
+        let resp: KmsDecryptResponse = self
+            .call(
+                "decrypt",
+                &KmsDecryptRequest {
+                    ciphertext: base64::encode(wrapped),
+                },
+            )
+            .await?;
+
+        Ok(base64::decode(resp.plaintext)?)
+    }
+}
+
+/// Encrypts and decrypts secrets with a current key encryption key. Previous keys can be added so
+/// that values written before a key rotation can still be read, and then re-encrypted.
+pub struct SecretCipher {
+    keys: Vec<Box<dyn KeyEncryptionKey>>,
+}
+
+impl SecretCipher {
+    pub fn new(current: Box<dyn KeyEncryptionKey>) -> Self {
+        SecretCipher { keys: vec![current] }
+    }
+
+    pub fn with_previous_key(mut self, key: Box<dyn KeyEncryptionKey>) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// Build the cipher from the environment. `CIO_TOKEN_KMS_KEY` selects a Google Cloud KMS key,
+    /// otherwise `CIO_TOKEN_KEY_FILE` must point at a local key file. `CIO_TOKEN_PREVIOUS_KEY_FILE`
+    /// optionally points at the key file that was in use before a rotation.
+    pub async fn from_env(company: &Company) -> Result<Self> {
+        let current: Box<dyn KeyEncryptionKey> = if let Ok(key_name) = std::env::var("CIO_TOKEN_KMS_KEY") {
+            Box::new(GoogleCloudKms::new(&key_name, company.authenticate_gcp().await?))
+        } else if let Ok(path) = std::env::var("CIO_TOKEN_KEY_FILE") {
+            Box::new(LocalKey::from_file(path)?)
+        } else {
+            bail!("no token encryption key configured, set CIO_TOKEN_KMS_KEY or CIO_TOKEN_KEY_FILE");
+        };
+
+        let mut cipher = SecretCipher::new(current);
+
+        if let Ok(path) = std::env::var("CIO_TOKEN_PREVIOUS_KEY_FILE") {
+            cipher = cipher.with_previous_key(Box::new(LocalKey::from_file(path)?));
+        }
+
+        Ok(cipher)
+    }
+
+    fn current(&self) -> &dyn KeyEncryptionKey {
+        self.keys[0].as_ref()
+    }
+
+    /// Encrypt a value. Empty values are left empty so that we can still tell when a token is
+    /// missing without decrypting it.
+    pub async fn encrypt(&self, plaintext: &str) -> Result<String> {
+        if plaintext.is_empty() || is_encrypted(plaintext) {
+            return Ok(plaintext.to_string());
+        }
+
+        let mut data_key = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut data_key)
+            .map_err(|_| anyhow!("failed to generate data key"))?;
+
+        let sealed = seal(&data_key, plaintext.as_bytes())?;
+        let wrapped = self.current().wrap(&data_key).await?;
+
+        Ok(format!(
+            "{}{}:{}:{}",
+            ENCRYPTED_PREFIX,
+            self.current().id(),
+            base64::encode(wrapped),
+            base64::encode(sealed)
+        ))
+    }
+
+    /// Decrypt a value. Values that are not encrypted are returned as is.
+    pub async fn decrypt(&self, value: &str) -> Result<String> {
+        let (key_id, wrapped, sealed) = match parse(value)? {
+            Some(parts) => parts,
+            None => return Ok(value.to_string()),
+        };
+
+        let key = self
+            .keys
+            .iter()
+            .find(|k| k.id() == key_id)
+            .ok_or_else(|| anyhow!("value was encrypted with unknown key `{}`", key_id))?;
+
+        let data_key = key.unwrap(&wrapped).await?;
+        let plaintext = open(&data_key, &sealed)?;
+
+        Ok(String::from_utf8(plaintext)?)
+    }
+
+    /// Re-encrypt a value with the current key. Returns `None` if the value is already encrypted
+    /// with the current key.
+    pub async fn reencrypt(&self, value: &str) -> Result<Option<String>> {
+        if value.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some((key_id, _, _)) = parse(value)? {
+            if key_id == self.current().id() {
+                return Ok(None);
+            }
+        }
+
+        let plaintext = self.decrypt(value).await?;
+
+        Ok(Some(self.encrypt(&plaintext).await?))
+    }
+}
+
+static TOKEN_CIPHER: OnceCell<SecretCipher> = OnceCell::const_new();
+
+/// The cipher for API tokens, built from the environment the first time it is needed.
+pub async fn token_cipher(company: &Company) -> Result<&'static SecretCipher> {
+    TOKEN_CIPHER.get_or_try_init(|| SecretCipher::from_env(company)).await
+}
+
+/// Decrypt an API token secret. Plaintext values are returned as is without building the cipher, so
+/// that rows written before encryption was turned on can be read without a key configured.
+pub async fn decrypt_token(company: &Company, value: &str) -> Result<String> {
+    if !is_encrypted(value) {
+        return Ok(value.to_string());
+    }
+
+    token_cipher(company).await?.decrypt(value).await
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// The key id, wrapped data key, and sealed contents of an encrypted value.
+type EncryptedParts<'a> = (&'a str, Vec<u8>, Vec<u8>);
+
+/// Split an encrypted value into its parts, or `None` if it is not encrypted.
+fn parse(value: &str) -> Result<Option<EncryptedParts<'_>>> {
+    let rest = match value.strip_prefix(ENCRYPTED_PREFIX) {
+        Some(rest) => rest,
+        None => return Ok(None),
+    };
+
+    let mut parts = rest.rsplitn(3, ':');
+    let (sealed, wrapped, key_id) = match (parts.next(), parts.next(), parts.next()) {
+        (Some(sealed), Some(wrapped), Some(key_id)) => (sealed, wrapped, key_id),
+        _ => bail!("malformed encrypted value"),
+    };
+
+    Ok(Some((key_id, base64::decode(wrapped)?, base64::decode(sealed)?)))
+}
+
+fn seal(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow!("invalid key"))?);
+
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("failed to generate nonce"))?;
+
+    let mut in_out = plaintext.to_vec();
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut in_out)
+        .map_err(|_| anyhow!("failed to encrypt value"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(in_out);
+
+    Ok(sealed)
+}
+
+fn open(key: &[u8], sealed: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("encrypted value is too short");
+    }
+
+    let key = LessSafeKey::new(UnboundKey::new(&AES_256_GCM, key).map_err(|_| anyhow!("invalid key"))?);
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("invalid nonce"))?;
+
+    let mut in_out = ciphertext.to_vec();
+    let plaintext = key
+        .open_in_place(nonce, Aad::empty(), &mut in_out)
+        .map_err(|_| anyhow!("failed to decrypt value"))?;
+
+    Ok(plaintext.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cipher(seed: u8) -> SecretCipher {
+        SecretCipher::new(Box::new(LocalKey::new(&[seed; KEY_LEN]).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let cipher = cipher(1);
+
+        let encrypted = cipher.encrypt("xoxb-secret").await.unwrap();
+        assert!(is_encrypted(&encrypted));
+        assert!(!encrypted.contains("xoxb-secret"));
+        assert_eq!(cipher.decrypt(&encrypted).await.unwrap(), "xoxb-secret");
+
+        // Every value gets its own data key and nonce.
+        assert_ne!(cipher.encrypt("xoxb-secret").await.unwrap(), encrypted);
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_and_empty_values() {
+        let cipher = cipher(1);
+
+        assert_eq!(cipher.encrypt("").await.unwrap(), "");
+        assert_eq!(cipher.decrypt("plaintext-token").await.unwrap(), "plaintext-token");
+    }
+
+    #[tokio::test]
+    async fn test_decrypt_token_without_key() {
+        let company = crate::companies::tests::mock_company();
+
+        assert_eq!(decrypt_token(&company, "").await.unwrap(), "");
+        assert_eq!(
+            decrypt_token(&company, "plaintext-token").await.unwrap(),
+            "plaintext-token"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_key() {
+        let encrypted = cipher(1).encrypt("secret").await.unwrap();
+
+        assert!(cipher(2).decrypt(&encrypted).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_tampered_value() {
+        let cipher = cipher(1);
+        let mut encrypted = cipher.encrypt("secret").await.unwrap();
+
+        let last = encrypted.pop().unwrap();
+        encrypted.push(if last == 'A' { 'B' } else { 'A' });
+
+        assert!(cipher.decrypt(&encrypted).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reencrypt_after_rotation() {
+        let old = cipher(1);
+        let encrypted = old.encrypt("secret").await.unwrap();
+
+        let rotated = cipher(2).with_previous_key(Box::new(LocalKey::new(&[1; KEY_LEN]).unwrap()));
+
+        assert_eq!(rotated.decrypt(&encrypted).await.unwrap(), "secret");
+
+        let reencrypted = rotated.reencrypt(&encrypted).await.unwrap().unwrap();
+        assert_eq!(cipher(2).decrypt(&reencrypted).await.unwrap(), "secret");
+        assert_eq!(rotated.reencrypt(&reencrypted).await.unwrap(), None);
+
+        // Plaintext values are always re-encrypted.
+        assert!(rotated.reencrypt("plaintext").await.unwrap().is_some());
+        assert_eq!(rotated.reencrypt("").await.unwrap(), None);
+    }
+}
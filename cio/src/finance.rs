@@ -9,7 +9,8 @@ use macros::db;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
-    FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
+    blocks::{ContextBlock, HeaderBlock, SectionBlock},
+    FormattedMessage, MessageAttachment,
 };
 
 use crate::{
@@ -105,51 +106,11 @@ impl UpdateAirtableRecord<SoftwareVendor> for SoftwareVendor {
 /// Convert the vendor into a Slack message.
 impl From<NewSoftwareVendor> for FormattedMessage {
     fn from(item: NewSoftwareVendor) -> Self {
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: Default::default(),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks: vec![
-                    MessageBlock {
-                        block_type: MessageBlockType::Header,
-                        text: Some(MessageBlockText {
-                            text_type: MessageType::PlainText,
-                            text: item.name.to_string(),
-                        }),
-                        elements: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Context,
-                        elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: format!("Vendors | {} | {} | _just now_", item.category, item.status),
-                        })],
-                        text: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                ],
-            }],
-        }
+        FormattedMessage::new().attachment(
+            MessageAttachment::default().block(HeaderBlock::new(&item.name)).block(
+                ContextBlock::new().markdown(format!("Vendors | {} | {} | _just now_", item.category, item.status)),
+            ),
+        )
     }
 }
 
@@ -180,27 +141,17 @@ impl NewSoftwareVendor {
             let mut msg: FormattedMessage = self.clone().into();
 
             // Add text.
-            let text = MessageBlock {
-                block_type: MessageBlockType::Section,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: format!(
-                        "price changed from `{}` users @ `${}` to `{}` users @ `${}`, total: `${}`",
-                        self.users,
-                        self.cost_per_user_per_month,
-                        new,
-                        new_cost_per_user,
-                        new as f32 * new_cost_per_user
-                    ),
-                }),
-                elements: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            };
+            let text = SectionBlock::markdown(format!(
+                "price changed from `{}` users @ `${}` to `{}` users @ `${}`, total: `${}`",
+                self.users,
+                self.cost_per_user_per_month,
+                new,
+                new_cost_per_user,
+                new as f32 * new_cost_per_user
+            ));
 
             // Set our accessory.
-            msg.attachments[0].blocks.insert(1, text);
+            msg.attachments[0].blocks.insert(1, text.into());
 
             if self.users < new || self.cost_per_user_per_month < new_cost_per_user {
                 msg.attachments[0].color = crate::colors::Colors::Blue.to_string();
@@ -9,8 +9,8 @@ use macros::db;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
-    FormattedMessage, MessageAttachment, MessageBlock, MessageBlockAccessory, MessageBlockText, MessageBlockType,
-    MessageType,
+    blocks::{ButtonElement, ContextBlock, SectionBlock},
+    FormattedMessage, MessageAttachment,
 };
 
 use crate::{
@@ -103,89 +103,33 @@ impl From<NewFunction> for FormattedMessage {
             context += &format!(" | _completed {}_", human_date);
         }
 
-        let mut blocks = vec![
-            MessageBlock {
-                block_type: MessageBlockType::Section,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text,
-                }),
-                elements: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            },
-            MessageBlock {
-                block_type: MessageBlockType::Context,
-                elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: context,
-                })],
-                text: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            },
-        ];
+        let mut section = SectionBlock::markdown(text);
+        let mut logs = None;
 
         if item.status == octorust::types::JobStatus::Completed.to_string()
             && item.conclusion != octorust::types::Conclusion::Success.to_string()
         {
             // Add a button to rerun the function.
-            let button = MessageBlockAccessory {
-                accessory_type: MessageType::Button,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: format!("Re-run {}", item.name),
-                }),
-                action_id: "function".to_string(),
-                value: item.name.to_string(),
-                image_url: Default::default(),
-                alt_text: Default::default(),
-            };
-
-            blocks[0].accessory = Some(button);
+            section =
+                section.accessory(ButtonElement::new(format!("Re-run {}", item.name), "function").value(&item.name));
 
             if !item.logs.is_empty() {
-                let logs = MessageBlock {
-                    block_type: MessageBlockType::Context,
-                    elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                        text_type: MessageType::PlainText,
-                        // We can only send max 3000 chars.
-                        text: crate::utils::tail(&item.logs, 3000),
-                    })],
-                    text: Default::default(),
-                    accessory: Default::default(),
-                    block_id: Default::default(),
-                    fields: Default::default(),
-                };
-
-                blocks.push(logs);
+                // We can only send max 3000 chars.
+                logs = Some(ContextBlock::new().plain(crate::utils::tail(&item.logs, 3000)));
             }
         }
 
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: get_color_based_from_status_and_conclusion(&item.status, &item.conclusion),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks,
-            }],
+        let mut attachment = MessageAttachment::colored(get_color_based_from_status_and_conclusion(
+            &item.status,
+            &item.conclusion,
+        ))
+        .block(section)
+        .block(ContextBlock::new().markdown(context));
+        if let Some(logs) = logs {
+            attachment = attachment.block(logs);
         }
+
+        FormattedMessage::new().attachment(attachment)
     }
 }
 
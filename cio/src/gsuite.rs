@@ -1,6 +1,6 @@
 use std::{collections::HashMap, time};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, Result};
 use gsuite_api::{
     types::{
         Building as GSuiteBuilding, BuildingAddress, CalendarResource as GSuiteCalendarResource, Group as GSuiteGroup,
@@ -13,6 +13,7 @@ use log::info;
 use serde_json::Value;
 
 use crate::{
+    audit::audit,
     companies::Company,
     configs::{Building, Group, Resource, User},
     db::Database,
@@ -219,6 +220,7 @@ pub async fn update_gsuite_user(gu: &GSuiteUser, user: &User, change_password: b
 
 /// Update a user's aliases in GSuite to match our database.
 pub async fn update_user_aliases(
+    db: &Database,
     gsuite: &GSuite,
     u: &GSuiteUser,
     aliases: Vec<String>,
@@ -236,7 +238,7 @@ pub async fn update_user_aliases(
 
     // Update the user's aliases.
     for alias in formatted_aliases {
-        match gsuite
+        let result = match gsuite
             .users()
             .aliases_insert(
                 &u.primary_email,
@@ -250,15 +252,29 @@ pub async fn update_user_aliases(
             )
             .await
         {
-            Ok(_) => (),
+            Ok(_) => Ok(()),
             Err(e) => {
                 if e.to_string().contains("Entity already exists") {
                     // Ignore the error.
                     continue;
                 }
-                bail!("updating gsuite user {} aliases failed: {}", u.primary_email, e);
+                Err(anyhow!(
+                    "updating gsuite user {} aliases failed: {}",
+                    u.primary_email,
+                    e
+                ))
             }
-        }
+        };
+
+        audit(
+            db,
+            company,
+            "google",
+            "add_user_alias",
+            &format!("{}:{}", u.primary_email, alias),
+            result,
+        )
+        .await?;
     }
 
     info!("updated GSuite user `{}` aliases", u.primary_email);
@@ -266,7 +282,7 @@ pub async fn update_user_aliases(
 }
 
 /// Update a user's groups in GSuite to match our database.
-pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &Company) -> Result<()> {
+pub async fn update_user_google_groups(db: &Database, gsuite: &GSuite, user: &User, company: &Company) -> Result<()> {
     // Get all the GSuite groups.
     let gsuite_groups = gsuite.list_provider_groups(company).await?;
 
@@ -274,7 +290,16 @@ pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &C
     for group in &user.groups {
         // Ensure that this is a valid group before performing operations
         if let Some(gsuite_group) = gsuite_groups.iter().find(|g| &g.name == group) {
-            gsuite.add_user_to_group(company, user, &gsuite_group.name).await?;
+            let result = gsuite.add_user_to_group(company, user, &gsuite_group.name).await;
+            audit(
+                db,
+                company,
+                "google",
+                "add_user_to_group",
+                &format!("{}:{}", gsuite_group.name, user.email),
+                result,
+            )
+            .await?;
         }
     }
 
@@ -293,7 +318,16 @@ pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &C
         // They are a member of the team.
         // We need to remove them.
         if is_member {
-            gsuite.remove_user_from_group(company, user, &group.name).await?;
+            let result = gsuite.remove_user_from_group(company, user, &group.name).await;
+            audit(
+                db,
+                company,
+                "google",
+                "remove_user_from_group",
+                &format!("{}:{}", group.name, user.email),
+                result,
+            )
+            .await?;
         }
     }
 
@@ -301,7 +335,7 @@ pub async fn update_user_google_groups(gsuite: &GSuite, user: &User, company: &C
 }
 
 /// Update a group's aliases in GSuite to match our configuration files.
-pub async fn update_group_aliases(gsuite: &GSuite, g: &GSuiteGroup) -> Result<()> {
+pub async fn update_group_aliases(db: &Database, gsuite: &GSuite, g: &GSuiteGroup, company: &Company) -> Result<()> {
     if g.aliases.is_empty() {
         // return early
         return Ok(());
@@ -309,7 +343,7 @@ pub async fn update_group_aliases(gsuite: &GSuite, g: &GSuiteGroup) -> Result<()
 
     // Update the user's aliases.
     for alias in &g.aliases {
-        match gsuite
+        let result = match gsuite
             .groups()
             .aliases_insert(
                 &g.email,
@@ -323,15 +357,25 @@ pub async fn update_group_aliases(gsuite: &GSuite, g: &GSuiteGroup) -> Result<()
             )
             .await
         {
-            Ok(_) => (),
+            Ok(_) => Ok(()),
             Err(e) => {
                 if e.to_string().contains("Entity already exists") {
                     // Ignore the error.
                     continue;
                 }
-                bail!("updating gsuite group {} aliases failed: {}", g.email, e);
+                Err(anyhow!("updating gsuite group {} aliases failed: {}", g.email, e))
             }
-        }
+        };
+
+        audit(
+            db,
+            company,
+            "google",
+            "add_group_alias",
+            &format!("{}:{}", g.email, alias),
+            result,
+        )
+        .await?;
     }
 
     info!("updated gsuite group aliases: {}", g.email);
@@ -368,17 +412,27 @@ pub async fn update_google_group_settings(db: &Database, group: &Group, company:
     settings.who_can_contact_owner = "ALL_IN_DOMAIN_CAN_CONTACT".to_string();
 
     // Update the group with the given settings.
-    let result2 = ggs
+    let mut result2 = ggs
         .groups()
         .update(google_groups_settings::types::Alt::Json, &email, &settings)
         .await;
     if result2.is_err() {
         // Try again.
         tokio::time::sleep(time::Duration::from_secs(1)).await;
-        ggs.groups()
+        result2 = ggs
+            .groups()
             .update(google_groups_settings::types::Alt::Json, &email, &settings)
-            .await?;
+            .await;
     }
+    audit(
+        db,
+        company,
+        "google",
+        "update_group_settings",
+        &email,
+        result2.map(|_| ()).map_err(anyhow::Error::from),
+    )
+    .await?;
 
     info!("updated gsuite groups settings {}", group.name);
 
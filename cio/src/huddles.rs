@@ -10,6 +10,7 @@ use sendgrid_api::{traits::MailOps, Client as SendGrid};
 
 use crate::{
     airtable::{AIRTABLE_DISCUSSION_TOPICS_TABLE, AIRTABLE_MEETING_SCHEDULE_TABLE},
+    audit::audit,
     companies::Company,
     configs::{get_configs_from_repo, User},
     core::{DiscussionTopic, Meeting, MeetingReminderEmailData},
@@ -354,17 +355,20 @@ pub async fn send_huddle_reminders(db: &Database, company: &Company) -> Result<(
                 // Initialize the SendGrid client.
                 let sendgrid = SendGrid::new_from_env();
                 // Send the email.
-                sendgrid
+                let recipient = format!("{}@{}", huddle.email, company.gsuite_domain);
+                let result = sendgrid
                     .mail_send()
                     .send_plain_text(
                         &format!("Reminder {} huddle tomorrow", slug),
                         template,
-                        &[format!("{}@{}", huddle.email, company.gsuite_domain)],
+                        &[recipient.to_string()],
                         &[],
                         &[],
                         &format!("huddle-reminders@{}", company.gsuite_domain),
                     )
-                    .await?;
+                    .await
+                    .map_err(anyhow::Error::from);
+                audit(db, company, "sendgrid", "send_email", &recipient, result).await?;
 
                 info!(
                     "successfully sent {} huddle reminder email to {}@{}",
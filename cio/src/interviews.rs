@@ -24,6 +24,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     airtable::AIRTABLE_INTERVIEWS_TABLE,
+    applicant_dedupe::resolve_merged_email,
+    applicant_pipeline::NewApplicantStageChange,
     applicants::Applicant,
     companies::Company,
     configs::{User, Users},
@@ -169,15 +171,19 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
                     && !email.ends_with(&company.gsuite_domain)
                     && !email.ends_with(&company.domain)
                 {
+                    // The invite might use the email of an applicant that has since been
+                    // merged into another one.
+                    let applicant_email = resolve_merged_email(db, &attendee.email).await;
+
                     // Make sure it is and that we shouldn't just skip it.
                     if let Ok(_a) = applicants::dsl::applicants
-                        .filter(applicants::dsl::email.eq(attendee.email.to_string()))
+                        .filter(applicants::dsl::email.eq(applicant_email.to_string()))
                         .first_async::<Applicant>(db.pool())
                         .await
                     {
                         // It must be the person being interviewed.
                         // See if we can get the Applicant record ID for them.
-                        interview.email = attendee.email.to_string();
+                        interview.email = applicant_email;
 
                         info!("Got applicant email: {}, event: {}", interview.email, event.summary);
 
@@ -281,8 +287,10 @@ pub async fn refresh_interviews(db: &Database, company: &Company) -> Result<()>
                 {
                     // This is done in applicants refresh as well, but let's do it here as well just in
                     // case.
+                    let previous_status = a.status.to_string();
                     a.status = crate::applicant_status::Status::Interviewing.to_string();
                     a.update(db).await?;
+                    NewApplicantStageChange::record(db, &a, &previous_status).await?;
                 }
                 interview.applicant = vec![a.airtable_record_id];
                 interview.name = a.name.to_string();
@@ -8,7 +8,10 @@ use chrono::NaiveDate;
 use macros::db;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use slack_chat_api::{
+    blocks::{ContextBlock, SectionBlock},
+    FormattedMessage,
+};
 
 use crate::{
     airtable::{AIRTABLE_JOURNAL_CLUB_MEETINGS_TABLE, AIRTABLE_JOURNAL_CLUB_PAPERS_TABLE},
@@ -61,17 +64,8 @@ pub struct NewJournalClubMeeting {
 /// Convert the journal club meeting into Slack message.
 impl From<NewJournalClubMeeting> for FormattedMessage {
     fn from(item: NewJournalClubMeeting) -> Self {
-        let mut objects = vec![MessageBlock {
-            block_type: MessageBlockType::Section,
-            text: Some(MessageBlockText {
-                text_type: MessageType::Markdown,
-                text: format!("<{}|*{}*>", item.issue, item.title),
-            }),
-            elements: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        }];
+        let mut msg =
+            FormattedMessage::new().block(SectionBlock::markdown(format!("<{}|*{}*>", item.issue, item.title)));
 
         let mut text = format!(
             "<https://github.com/{}|@{}> | issue date: {} | status: *{}*",
@@ -84,30 +78,10 @@ impl From<NewJournalClubMeeting> for FormattedMessage {
         if meeting_date != *"01/01/1969" {
             text += &format!(" | meeting date: {}", meeting_date);
         }
-        objects.push(MessageBlock {
-            block_type: MessageBlockType::Context,
-            elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                text_type: MessageType::Markdown,
-                text,
-            })],
-            text: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        });
+        msg = msg.block(ContextBlock::new().markdown(text));
 
         if !item.recording.is_empty() {
-            objects.push(MessageBlock {
-                block_type: MessageBlockType::Context,
-                elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: format!("<{}|Meeting recording>", item.recording),
-                })],
-                text: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            });
+            msg = msg.block(ContextBlock::new().markdown(format!("<{}|Meeting recording>", item.recording)));
         }
 
         for paper in item.papers.clone() {
@@ -117,24 +91,10 @@ impl From<NewJournalClubMeeting> for FormattedMessage {
             if p.title == item.title {
                 title = "Paper".to_string();
             }
-            objects.push(MessageBlock {
-                block_type: MessageBlockType::Context,
-                elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: format!("<{}|{}>", p.link, title),
-                })],
-                text: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            });
+            msg = msg.block(ContextBlock::new().markdown(format!("<{}|{}>", p.link, title)));
         }
 
-        FormattedMessage {
-            channel: Default::default(),
-            attachments: Default::default(),
-            blocks: objects,
-        }
+        msg
     }
 }
 
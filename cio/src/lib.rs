@@ -6,18 +6,24 @@ pub mod airtable;
 pub mod analytics;
 pub mod api_tokens;
 pub mod app_config;
+pub mod applicant_dedupe;
+pub mod applicant_pipeline;
+pub mod applicant_retention;
 pub mod applicant_reviews;
+pub mod applicant_rubric;
 pub mod applicant_status;
 pub mod applicant_uploads;
 pub mod applicants;
 pub mod application_form;
 pub mod asset_inventory;
+pub mod audit;
 pub mod auth_logins;
 pub mod certs;
 pub mod cloud_dns;
 pub mod cloudflare;
 pub mod colors;
 pub mod companies;
+pub mod config_changes;
 pub mod configs;
 pub mod core;
 pub mod customers;
@@ -26,6 +32,7 @@ pub mod dns_providers;
 pub mod dns_proxy;
 #[macro_use]
 pub mod enclose;
+pub mod encryption;
 pub mod features;
 pub mod finance;
 pub mod functions;
@@ -55,6 +62,7 @@ pub mod swag_inventory;
 pub mod swag_store;
 pub mod tailscale;
 pub mod templates;
+pub mod text_extraction;
 pub mod travel;
 pub mod utils;
 pub mod zoho;
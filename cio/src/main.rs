@@ -1,11 +1,19 @@
 #![recursion_limit = "256"]
 use std::fs::File;
 
+use anyhow::Result;
 use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
 use cio_api::{
+    applicant_pipeline::{stage_metrics, ApplicantStageChange, StageMetrics},
     applicants::{Applicant, Applicants},
     auth_logins::{AuthUser, AuthUsers},
-    configs::{Building, Buildings, Group, Groups, Link, Links, Resource, ResourceCategory, Resources, User, Users},
+    companies::Company,
+    config_changes::{propose_config_change, ConfigChange, ConfigChangeError, ConfigChangeProposal, ConfigEntry},
+    configs::{
+        get_configs_from_repo, Building, Buildings, Group, GroupConfig, Groups, Link, LinkConfig, Links,
+        NewResourceConfig, Resource, ResourceCategory, Resources, User, Users,
+    },
     db::Database,
     journal_clubs::{JournalClubMeeting, JournalClubMeetings},
     mailing_list::{MailingListSubscriber, MailingListSubscribers},
@@ -15,9 +23,12 @@ use cio_api::{
 };
 use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
 use dropshot::{
-    endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseOk,
-    HttpServerStarter, RequestContext,
+    endpoint, ApiDescription, ConfigDropshot, ConfigLogging, ConfigLoggingLevel, HttpError, HttpResponseAccepted,
+    HttpResponseOk, HttpServerStarter, Path, Query, RequestContext, TypedBody,
 };
+use dropshot_verify_request::bearer::{Bearer, BearerProvider};
+use schemars::JsonSchema;
+use serde::Deserialize;
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
@@ -62,6 +73,17 @@ async fn main() -> Result<(), String> {
     api.register(api_get_mailing_list_subscribers).unwrap();
     api.register(api_get_rfds).unwrap();
     api.register(api_get_users).unwrap();
+    api.register(api_get_stage_metrics).unwrap();
+
+    api.register(api_create_group).unwrap();
+    api.register(api_update_group).unwrap();
+    api.register(api_delete_group).unwrap();
+    api.register(api_create_link).unwrap();
+    api.register(api_update_link).unwrap();
+    api.register(api_delete_link).unwrap();
+    api.register(api_create_resource).unwrap();
+    api.register(api_update_resource).unwrap();
+    api.register(api_delete_resource).unwrap();
 
     // Print the OpenAPI Spec to stdout.
     let mut api_definition = &mut api.openapi("CIO API", "0.0.1");
@@ -308,3 +330,271 @@ async fn api_get_users(rqctx: RequestContext<Context>) -> Result<HttpResponseOk<
 
     Ok(HttpResponseOk(Users::get_from_db(db, 1).await.unwrap().0))
 }
+
+#[derive(Deserialize, JsonSchema)]
+struct StageMetricsQueryParams {
+    /// The role to compute the metrics for.
+    role: String,
+}
+
+/**
+ * Fetch how long applicants for a role spend in each stage of its pipeline.
+ */
+#[endpoint {
+    method = GET,
+    path = "/hiring/stages/metrics",
+}]
+async fn api_get_stage_metrics(
+    rqctx: RequestContext<Context>,
+    query: Query<StageMetricsQueryParams>,
+) -> Result<HttpResponseOk<Vec<StageMetrics>>, HttpError> {
+    let api_context = rqctx.context();
+    let db = &api_context.db;
+    let role = query.into_inner().role;
+
+    let company = Company::get_by_id(db, 1).await.map_err(|err| {
+        log::error!("Failed to lookup company. err: {:?}", err);
+        HttpError::for_internal_error("".to_string())
+    })?;
+
+    // The pipelines live in the configs repo, so read them from there like the webhooky server.
+    let github = company.authenticate_github().map_err(|err| {
+        log::error!("Failed to authenticate with GitHub. err: {:?}", err);
+        HttpError::for_internal_error("".to_string())
+    })?;
+    let pipeline = get_configs_from_repo(&github, &company)
+        .await
+        .map_err(|err| {
+            log::error!("Failed to read the configs repo. err: {:?}", err);
+            HttpError::for_internal_error("".to_string())
+        })?
+        .app_config
+        .hiring
+        .pipeline(&role);
+
+    let changes = ApplicantStageChange::for_role(db, company.id, &role)
+        .await
+        .map_err(|err| {
+            log::error!("Failed to lookup stage changes for `{}`. err: {:?}", role, err);
+            HttpError::for_internal_error("".to_string())
+        })?;
+
+    Ok(HttpResponseOk(stage_metrics(&pipeline, &changes, chrono::Utc::now())))
+}
+
+/**
+ * The token that callers must present to make changes through the API.
+ */
+struct WriteToken;
+
+#[async_trait]
+impl BearerProvider for WriteToken {
+    async fn token() -> Result<String> {
+        Ok(std::env::var("CIO_API_WRITE_BEARER")?)
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ConfigEntryPathParams {
+    name: String,
+}
+
+/**
+ * Open a pull request against the configs repo for the given change.
+ */
+async fn propose_change<T: ConfigEntry>(
+    api_context: &Context,
+    name: &str,
+    change: ConfigChange<T>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let db = &api_context.db;
+
+    let company = Company::get_by_id(db, 1).await.map_err(|err| {
+        log::error!("Failed to lookup company. err: {:?}", err);
+        HttpError::for_internal_error("".to_string())
+    })?;
+    let github = company.authenticate_github().map_err(|err| {
+        log::error!("Failed to authenticate with GitHub. err: {:?}", err);
+        HttpError::for_internal_error("".to_string())
+    })?;
+
+    match propose_config_change(&github, &company, name, change).await {
+        Ok(proposal) => Ok(HttpResponseAccepted(proposal)),
+        Err(ConfigChangeError::NotFound(name)) => Err(HttpError::for_not_found(
+            None,
+            format!("{} `{}` does not exist", T::KIND, name),
+        )),
+        Err(ConfigChangeError::AlreadyExists(name)) => Err(HttpError::for_client_error(
+            None,
+            http::StatusCode::CONFLICT,
+            format!("{} `{}` already exists", T::KIND, name),
+        )),
+        Err(ConfigChangeError::Invalid(reason)) => Err(HttpError::for_bad_request(None, reason)),
+        Err(ConfigChangeError::Other(err)) => {
+            log::error!("Failed to propose change to {} `{}`. err: {:?}", T::KIND, name, err);
+            Err(HttpError::for_internal_error("".to_string()))
+        }
+    }
+}
+
+/**
+ * Propose a new Google group by opening a pull request against the configs repo.
+ */
+#[endpoint {
+    method = POST,
+    path = "/groups",
+}]
+async fn api_create_group(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    body: TypedBody<GroupConfig>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let group = body.into_inner();
+    let name = group.name.to_string();
+
+    propose_change(rqctx.context(), &name, ConfigChange::Create(group)).await
+}
+
+/**
+ * Propose changes to a Google group by opening a pull request against the configs repo. The
+ * body is applied to the existing group as a JSON merge patch.
+ */
+#[endpoint {
+    method = PATCH,
+    path = "/groups/{name}",
+}]
+async fn api_update_group(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    path_params: Path<ConfigEntryPathParams>,
+    body: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let name = path_params.into_inner().name;
+
+    propose_change::<GroupConfig>(rqctx.context(), &name, ConfigChange::Update(body.into_inner())).await
+}
+
+/**
+ * Propose removing a Google group by opening a pull request against the configs repo.
+ */
+#[endpoint {
+    method = DELETE,
+    path = "/groups/{name}",
+}]
+async fn api_delete_group(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    path_params: Path<ConfigEntryPathParams>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let name = path_params.into_inner().name;
+
+    propose_change::<GroupConfig>(rqctx.context(), &name, ConfigChange::Delete).await
+}
+
+/**
+ * Propose a new internal link by opening a pull request against the configs repo.
+ */
+#[endpoint {
+    method = POST,
+    path = "/links",
+}]
+async fn api_create_link(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    body: TypedBody<LinkConfig>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let link = body.into_inner();
+    let name = link.name.to_string();
+
+    propose_change(rqctx.context(), &name, ConfigChange::Create(link)).await
+}
+
+/**
+ * Propose changes to an internal link by opening a pull request against the configs repo. The
+ * body is applied to the existing link as a JSON merge patch.
+ */
+#[endpoint {
+    method = PATCH,
+    path = "/links/{name}",
+}]
+async fn api_update_link(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    path_params: Path<ConfigEntryPathParams>,
+    body: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let name = path_params.into_inner().name;
+
+    propose_change::<LinkConfig>(rqctx.context(), &name, ConfigChange::Update(body.into_inner())).await
+}
+
+/**
+ * Propose removing an internal link by opening a pull request against the configs repo.
+ */
+#[endpoint {
+    method = DELETE,
+    path = "/links/{name}",
+}]
+async fn api_delete_link(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    path_params: Path<ConfigEntryPathParams>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let name = path_params.into_inner().name;
+
+    propose_change::<LinkConfig>(rqctx.context(), &name, ConfigChange::Delete).await
+}
+
+/**
+ * Propose a new resource by opening a pull request against the configs repo.
+ */
+#[endpoint {
+    method = POST,
+    path = "/resources",
+}]
+async fn api_create_resource(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    body: TypedBody<NewResourceConfig>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let resource = body.into_inner();
+    let name = resource.name.to_string();
+
+    propose_change(rqctx.context(), &name, ConfigChange::Create(resource)).await
+}
+
+/**
+ * Propose changes to a resource by opening a pull request against the configs repo. The body is
+ * applied to the existing resource as a JSON merge patch.
+ */
+#[endpoint {
+    method = PATCH,
+    path = "/resources/{name}",
+}]
+async fn api_update_resource(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    path_params: Path<ConfigEntryPathParams>,
+    body: TypedBody<serde_json::Value>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let name = path_params.into_inner().name;
+
+    propose_change::<NewResourceConfig>(rqctx.context(), &name, ConfigChange::Update(body.into_inner())).await
+}
+
+/**
+ * Propose removing a resource by opening a pull request against the configs repo.
+ */
+#[endpoint {
+    method = DELETE,
+    path = "/resources/{name}",
+}]
+async fn api_delete_resource(
+    rqctx: RequestContext<Context>,
+    _auth: Bearer<WriteToken>,
+    path_params: Path<ConfigEntryPathParams>,
+) -> Result<HttpResponseAccepted<ConfigChangeProposal>, HttpError> {
+    let name = path_params.into_inner().name;
+
+    propose_change::<NewResourceConfig>(rqctx.context(), &name, ConfigChange::Delete).await
+}
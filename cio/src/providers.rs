@@ -2,12 +2,17 @@ use anyhow::{bail, Result};
 use async_trait::async_trait;
 use log::{info, warn};
 use sodiumoxide::{base64, crypto::hash};
-use std::convert::TryInto;
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::TryInto,
+    sync::{Arc, Mutex, OnceLock},
+};
 
 use crate::{
     app_config::AppConfig,
+    audit::{audit, Audited},
     companies::Company,
-    configs::{ExternalServices, Group, User},
+    configs::{ExternalServices, Group, Groups, User},
     db::Database,
     octorust_utils::{into_octorust_error, OctorustErrorKind},
 };
@@ -591,7 +596,7 @@ impl ProviderWriteOps for gsuite_api::Client {
                 // crate::gsuite::update_user_aliases(self, &gsuite_user, user.aliases.clone(), company).await?;
 
                 // Add the user to their teams and groups.
-                crate::gsuite::update_user_google_groups(self, user, company).await?;
+                crate::gsuite::update_user_google_groups(db, self, user, company).await?;
 
                 // info!("updated user `{}` in GSuite", user.id);
 
@@ -614,16 +619,22 @@ impl ProviderWriteOps for gsuite_api::Client {
         // Make sure it is set to true.
         let gsuite_user = crate::gsuite::update_gsuite_user(&u, user, true, company).await;
 
-        let new_gsuite_user = self.users().insert(&gsuite_user).await?.body;
+        let result = self
+            .users()
+            .insert(&gsuite_user)
+            .await
+            .map(|response| response.body)
+            .map_err(anyhow::Error::from);
+        let new_gsuite_user = audit(db, company, "google", "create_user", &user.email, result).await?;
 
         // Send an email to the new user.
         // Do this here in case another step fails.
         user.send_email_new_gsuite_user(db, &gsuite_user.password, &config.onboarding)
             .await?;
 
-        crate::gsuite::update_user_aliases(self, &gsuite_user, user.aliases.clone(), company).await?;
+        crate::gsuite::update_user_aliases(db, self, &gsuite_user, user.aliases.clone(), company).await?;
 
-        crate::gsuite::update_user_google_groups(self, user, company).await?;
+        crate::gsuite::update_user_google_groups(db, self, user, company).await?;
 
         info!("created user `{}` in GSuite", user.email);
 
@@ -647,11 +658,15 @@ impl ProviderWriteOps for gsuite_api::Client {
                 }
                 google_group.aliases = aliases;
 
-                self.groups()
+                let result = self
+                    .groups()
                     .update(&format!("{}@{}", group.name, company.gsuite_domain), &google_group)
-                    .await?;
+                    .await
+                    .map(|_| ())
+                    .map_err(anyhow::Error::from);
+                audit(db, company, "google", "update_group", &group.name, result).await?;
 
-                crate::gsuite::update_group_aliases(self, &google_group).await?;
+                crate::gsuite::update_group_aliases(db, self, &google_group, company).await?;
 
                 // Update the groups settings.
                 crate::gsuite::update_google_group_settings(db, group, company).await?;
@@ -685,9 +700,15 @@ impl ProviderWriteOps for gsuite_api::Client {
         }
         g.aliases = aliases;
 
-        let new_group = self.groups().insert(&g).await?.body;
+        let result = self
+            .groups()
+            .insert(&g)
+            .await
+            .map(|response| response.body)
+            .map_err(anyhow::Error::from);
+        let new_group = audit(db, company, "google", "create_group", &group.name, result).await?;
 
-        crate::gsuite::update_group_aliases(self, &new_group).await?;
+        crate::gsuite::update_group_aliases(db, self, &new_group, company).await?;
 
         // Update the groups settings.
         crate::gsuite::update_google_group_settings(db, group, company).await?;
@@ -1659,6 +1680,495 @@ impl ProviderReadOps for airtable_api::Airtable {
     }
 }
 
+#[async_trait]
+impl ProviderWriteOps for slack_chat_api::Slack {
+    async fn ensure_user(&self, db: &Database, company: &Company, user: &User, _config: &AppConfig) -> Result<String> {
+        let memberships = SlackMemberships::fetch(self, db, company).await?;
+        let slack_user_id = ensure_slack_user(self, user, &memberships).await?;
+        if slack_user_id.is_empty() {
+            return Ok(slack_user_id);
+        }
+
+        // Only this user is synced, the other members of the user groups are kept.
+        let users = vec![(slack_user_id.to_string(), user.groups.clone())];
+        for usergroup in memberships.usergroups_to_update(&users) {
+            update_slack_usergroup(self, &usergroup.handle, &users).await?;
+            info!("updated the members of slack user group `{}`", usergroup.handle);
+        }
+
+        Ok(slack_user_id)
+    }
+
+    async fn ensure_group(&self, _db: &Database, _company: &Company, group: &Group) -> Result<()> {
+        // User groups are matched on their handle, which is the name of the group. This is what
+        // people use to mention the group, e.g. `@eng`.
+        match find_slack_usergroup(self, &group.name).await? {
+            Some(usergroup) => {
+                if usergroup.is_disabled() {
+                    self.enable_usergroup(&usergroup.id).await?;
+                    info!("enabled slack user group `{}`", group.name);
+                }
+
+                if usergroup.name != group.name || usergroup.description != group.description {
+                    self.update_usergroup(&usergroup.id, &group.name, &group.name, &group.description)
+                        .await?;
+                    info!("updated slack user group `{}`", group.name);
+                }
+            }
+            None => {
+                self.create_usergroup(&group.name, &group.name, &group.description)
+                    .await?;
+                info!("created slack user group `{}`", group.name);
+            }
+        }
+
+        if group.slack_channels.is_empty() {
+            return Ok(());
+        }
+
+        // Create any of the group's channels that do not exist yet.
+        let channels = self.list_all_channels().await?;
+        for name in group.slack_channels.iter().map(|channel| slack_channel_name(channel)) {
+            if channels.iter().any(|channel| channel.name == name) {
+                continue;
+            }
+
+            match self.create_channel(&name, true).await {
+                Ok(_) => info!("created slack channel `{}` for group `{}`", name, group.name),
+                // Private channels are only listed if we are a member of them.
+                Err(err) if err.to_string().contains("name_taken") => warn!(
+                    "slack channel `{}` for group `{}` exists, but the app is not a member of it",
+                    name, group.name
+                ),
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn check_user_is_member_of_group(&self, _company: &Company, user: &User, group: &str) -> Result<bool> {
+        let slack_user = match self.lookup_user_by_email(&user.email).await? {
+            Some(slack_user) => slack_user,
+            None => return Ok(false),
+        };
+
+        match find_slack_usergroup(self, group).await? {
+            Some(usergroup) if !usergroup.is_disabled() => {
+                Ok(self.list_usergroup_users(&usergroup.id).await?.contains(&slack_user.id))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn add_user_to_group(&self, _company: &Company, user: &User, group: &str) -> Result<()> {
+        let slack_user = match self.lookup_user_by_email(&user.email).await? {
+            Some(slack_user) => slack_user,
+            // User does not have a slack account, return early.
+            None => return Ok(()),
+        };
+
+        add_to_slack_usergroup(self, group, &slack_user.id).await?;
+
+        info!("added `{}` to slack user group `{}`", user.id, group);
+
+        Ok(())
+    }
+
+    async fn remove_user_from_group(&self, _company: &Company, user: &User, group: &str) -> Result<()> {
+        let slack_user = match self.lookup_user_by_email(&user.email).await? {
+            Some(slack_user) => slack_user,
+            // User does not have a slack account, return early.
+            None => return Ok(()),
+        };
+
+        if let Some(usergroup) = find_slack_usergroup(self, group).await? {
+            remove_from_slack_usergroup(self, &usergroup.handle, &slack_user.id).await?;
+
+            info!("removed `{}` from slack user group `{}`", user.id, group);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_user(&self, db: &Database, company: &Company, user: &User) -> Result<()> {
+        let slack_user = match self.lookup_user_by_email(&user.email).await? {
+            Some(slack_user) => slack_user,
+            // Return early.
+            None => return Ok(()),
+        };
+
+        // Deactivating the account is left to SSO, removing them from GSuite or Okta will
+        // disallow them from logging in. We only remove them from what we manage.
+        let groups = slack_groups(db, company).await?;
+        for usergroup in self.list_usergroups(true).await? {
+            if !usergroup.is_disabled()
+                && usergroup.users.contains(&slack_user.id)
+                && groups.iter().any(|group| group.name == usergroup.handle)
+            {
+                remove_from_slack_usergroup(self, &usergroup.handle, &slack_user.id).await?;
+                info!("removed `{}` from slack user group `{}`", user.id, usergroup.handle);
+            }
+        }
+
+        let managed_channels = groups
+            .iter()
+            .flat_map(|group| group.slack_channels.iter().map(|channel| slack_channel_name(channel)))
+            .collect::<BTreeSet<_>>();
+
+        if managed_channels.is_empty() {
+            return Ok(());
+        }
+
+        for channel in self.list_all_channels().await? {
+            if channel.is_private
+                && managed_channels.contains(&channel.name)
+                && self.list_channel_members(&channel.id).await?.contains(&slack_user.id)
+            {
+                self.kick_from_channel(&channel.id, &slack_user.id).await?;
+                info!("removed `{}` from slack channel `{}`", user.id, channel.name);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_group(&self, _company: &Company, group: &Group) -> Result<()> {
+        // User groups can not be deleted, only disabled.
+        if let Some(usergroup) = find_slack_usergroup(self, &group.name).await? {
+            if !usergroup.is_disabled() {
+                self.disable_usergroup(&usergroup.id).await?;
+            }
+        }
+
+        info!("disabled slack user group `{}`", group.name);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ProviderReadOps for slack_chat_api::Slack {
+    type ProviderUser = slack_chat_api::User;
+    type ProviderGroup = slack_chat_api::UserGroup;
+
+    async fn list_provider_users(&self, _company: &Company) -> Result<Vec<slack_chat_api::User>> {
+        self.list_users().await
+    }
+
+    async fn list_provider_groups(&self, _company: &Company) -> Result<Vec<slack_chat_api::UserGroup>> {
+        self.list_usergroups(true).await
+    }
+}
+
+/// The user groups and managed channels of a Slack workspace, with their members. Listing these
+/// takes a call per channel, so a sync fetches them once and checks every user against them.
+#[derive(Debug, Clone, Default)]
+pub struct SlackMemberships {
+    /// The groups that are provisioned in Slack.
+    groups: Vec<Group>,
+    /// The user groups of the workspace, including disabled ones.
+    usergroups: Vec<slack_chat_api::UserGroup>,
+    /// The channels that are managed through a group, with the ids of their members.
+    channels: Vec<(slack_chat_api::Channel, Vec<String>)>,
+}
+
+impl SlackMemberships {
+    pub async fn fetch(slack: &slack_chat_api::Slack, db: &Database, company: &Company) -> Result<Self> {
+        let groups = slack_groups(db, company).await?;
+        let usergroups = slack.list_usergroups(true).await?;
+
+        // Every channel that is managed through a group.
+        let managed_channels = groups
+            .iter()
+            .flat_map(|group| group.slack_channels.iter().map(|channel| slack_channel_name(channel)))
+            .collect::<BTreeSet<_>>();
+
+        let mut channels = vec![];
+        if !managed_channels.is_empty() {
+            for channel in slack.list_all_channels().await? {
+                if managed_channels.contains(&channel.name) {
+                    let members = slack.list_channel_members(&channel.id).await?;
+                    channels.push((channel, members));
+                }
+            }
+        }
+
+        Ok(SlackMemberships {
+            groups,
+            usergroups,
+            channels,
+        })
+    }
+
+    /// The managed user groups whose members are not what they should be for `users`, which are
+    /// the Slack ids of our users with the groups they are a member of.
+    fn usergroups_to_update(&self, users: &[(String, Vec<String>)]) -> Vec<&slack_chat_api::UserGroup> {
+        self.usergroups
+            .iter()
+            .filter(|usergroup| self.groups.iter().any(|group| group.name == usergroup.handle))
+            .filter(|usergroup| usergroup_members(usergroup, users) != current_usergroup_members(usergroup))
+            .collect()
+    }
+
+    /// The channels to invite a Slack user to and to remove them from, for them to be in the
+    /// channels of the groups they are a member of. Anyone can join a public channel, so users are
+    /// only removed from private ones.
+    fn channel_changes(
+        &self,
+        user_groups: &[String],
+        slack_user_id: &str,
+    ) -> (Vec<&slack_chat_api::Channel>, Vec<&slack_chat_api::Channel>) {
+        // The channels of the groups the user is a member of.
+        let user_channels = self
+            .groups
+            .iter()
+            .filter(|group| user_groups.contains(&group.name))
+            .flat_map(|group| group.slack_channels.iter().map(|channel| slack_channel_name(channel)))
+            .collect::<BTreeSet<_>>();
+
+        let mut invite = vec![];
+        let mut kick = vec![];
+
+        for (channel, members) in &self.channels {
+            let is_member = members.iter().any(|id| id == slack_user_id);
+
+            if user_channels.contains(&channel.name) {
+                if !is_member {
+                    invite.push(channel);
+                }
+            } else if is_member && channel.is_private {
+                kick.push(channel);
+            }
+        }
+
+        (invite, kick)
+    }
+}
+
+/// Sync the channels a user is a member of with their groups. Users join Slack through SSO or an
+/// invite, we only manage what they are a member of. User groups are updated for all users at
+/// once, see `Audited<Slack>::sync_usergroups`.
+pub async fn ensure_slack_user(
+    slack: &slack_chat_api::Slack,
+    user: &User,
+    memberships: &SlackMemberships,
+) -> Result<String> {
+    if user.denied_services.contains(&ExternalServices::Slack) {
+        log::info!(
+            "User {} is denied access to {}. Exiting provisioning.",
+            user.id,
+            ExternalServices::Slack
+        );
+
+        return Ok(String::new());
+    }
+
+    let slack_user = match slack.lookup_user_by_email(&user.email).await? {
+        Some(slack_user) if !slack_user.deleted => slack_user,
+        _ => {
+            info!("user `{}` does not have a slack account, skipping", user.id);
+            return Ok(String::new());
+        }
+    };
+
+    let (invite, kick) = memberships.channel_changes(&user.groups, &slack_user.id);
+    for channel in invite {
+        slack
+            .invite_to_channel(&channel.id, &[slack_user.id.to_string()])
+            .await?;
+        info!("invited `{}` to slack channel `{}`", user.id, channel.name);
+    }
+    for channel in kick {
+        slack.kick_from_channel(&channel.id, &slack_user.id).await?;
+        info!("removed `{}` from slack channel `{}`", user.id, channel.name);
+    }
+
+    Ok(slack_user.id)
+}
+
+impl Audited<slack_chat_api::Slack> {
+    /// Like `ensure_user`, but checks the user against memberships that were fetched once for a
+    /// whole sync.
+    pub async fn ensure_user_with_memberships(
+        &self,
+        company: &Company,
+        user: &User,
+        memberships: &SlackMemberships,
+    ) -> Result<String> {
+        let result = ensure_slack_user(self, user, memberships).await;
+        self.record(company, "ensure_user", &user.email, result).await
+    }
+
+    /// Sync the members of the managed user groups with the groups of `users`. Every user group
+    /// that changed is updated once with its whole member list, rather than once per user.
+    pub async fn sync_usergroups(
+        &self,
+        company: &Company,
+        users: &[User],
+        memberships: &SlackMemberships,
+    ) -> Result<()> {
+        let users = slack_user_groups(self, users).await?;
+
+        let mut failed = vec![];
+        for usergroup in memberships.usergroups_to_update(&users) {
+            let result = update_slack_usergroup(self, &usergroup.handle, &users).await;
+            if let Err(e) = self
+                .record(company, "update_usergroup_users", &usergroup.handle, result)
+                .await
+            {
+                warn!("updating slack user group `{}` failed: {}", usergroup.handle, e);
+                failed.push(usergroup.handle.to_string());
+                continue;
+            }
+
+            info!("updated the members of slack user group `{}`", usergroup.handle);
+        }
+
+        if !failed.is_empty() {
+            bail!("failed to update slack user groups: {}", failed.join(", "));
+        }
+
+        Ok(())
+    }
+}
+
+/// The Slack ids of our users, with the groups they are a member of. Users without a Slack
+/// account, or that are denied one, are left out.
+async fn slack_user_groups(slack: &slack_chat_api::Slack, users: &[User]) -> Result<Vec<(String, Vec<String>)>> {
+    let slack_ids = slack
+        .list_users()
+        .await?
+        .into_iter()
+        .filter(|slack_user| !slack_user.deleted && !slack_user.profile.email.is_empty())
+        .map(|slack_user| (slack_user.profile.email.to_lowercase(), slack_user.id))
+        .collect::<HashMap<_, _>>();
+
+    Ok(users
+        .iter()
+        .filter(|user| !user.denied_services.contains(&ExternalServices::Slack))
+        .filter_map(|user| {
+            slack_ids
+                .get(&user.email.to_lowercase())
+                .map(|id| (id.to_string(), user.groups.clone()))
+        })
+        .collect())
+}
+
+/// The groups that are provisioned in Slack.
+async fn slack_groups(db: &Database, company: &Company) -> Result<Vec<Group>> {
+    Ok(Groups::get_from_db(db, company.id)
+        .await?
+        .into_iter()
+        .filter(|group| group.supports_provisioning_in(&ExternalServices::Slack))
+        .collect())
+}
+
+/// Slack channel names are lowercase and are written with a leading `#` in the configs.
+fn slack_channel_name(channel: &str) -> String {
+    channel.trim_start_matches('#').to_lowercase()
+}
+
+/// Find the Slack user group for one of our groups, including disabled ones. The members of the
+/// user group are listed with it, as updating them writes back the whole list.
+async fn find_slack_usergroup(slack: &slack_chat_api::Slack, group: &str) -> Result<Option<slack_chat_api::UserGroup>> {
+    Ok(slack
+        .list_usergroups(true)
+        .await?
+        .into_iter()
+        .find(|usergroup| usergroup.handle == group))
+}
+
+/// The members of a user group, sorted. A disabled user group still lists its last members, but
+/// has none.
+fn current_usergroup_members(usergroup: &slack_chat_api::UserGroup) -> Vec<String> {
+    if usergroup.is_disabled() {
+        return vec![];
+    }
+
+    let mut members = usergroup.users.clone();
+    members.sort();
+    members.dedup();
+    members
+}
+
+/// The members a user group should have for `users`, sorted. Members that are not one of `users`
+/// (e.g. bots, or people outside of our configs) are kept.
+fn usergroup_members(usergroup: &slack_chat_api::UserGroup, users: &[(String, Vec<String>)]) -> Vec<String> {
+    let mut members = current_usergroup_members(usergroup)
+        .into_iter()
+        .filter(|member| !users.iter().any(|(id, _)| id == member))
+        .collect::<Vec<_>>();
+    members.extend(
+        users
+            .iter()
+            .filter(|(_, groups)| groups.contains(&usergroup.handle))
+            .map(|(id, _)| id.to_string()),
+    );
+
+    members.sort();
+    members.dedup();
+    members
+}
+
+/// Setting the members of a user group means reading them and writing all of them back, so the
+/// updates of a user group are made one at a time.
+async fn lock_slack_usergroup(handle: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    static LOCKS: OnceLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> = OnceLock::new();
+
+    let lock = LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(handle.to_string())
+        .or_default()
+        .clone();
+
+    lock.lock_owned().await
+}
+
+/// Update the members of a user group for `users`. The user group is fetched again once it is
+/// locked, so that changes made since it was listed are kept.
+async fn update_slack_usergroup(
+    slack: &slack_chat_api::Slack,
+    handle: &str,
+    users: &[(String, Vec<String>)],
+) -> Result<()> {
+    let _lock = lock_slack_usergroup(handle).await;
+
+    let usergroup = match find_slack_usergroup(slack, handle).await? {
+        Some(usergroup) => usergroup,
+        None => bail!("slack user group `{}` does not exist", handle),
+    };
+
+    let members = usergroup_members(&usergroup, users);
+    if members == current_usergroup_members(&usergroup) {
+        return Ok(());
+    }
+
+    // Slack does not allow a user group without any users, so disable it instead.
+    if members.is_empty() {
+        slack.disable_usergroup(&usergroup.id).await?;
+        return Ok(());
+    }
+
+    // A user group is disabled when its last member is removed, so it starts out empty again.
+    if usergroup.is_disabled() {
+        slack.enable_usergroup(&usergroup.id).await?;
+    }
+    slack.update_usergroup_users(&usergroup.id, &members).await?;
+
+    Ok(())
+}
+
+async fn add_to_slack_usergroup(slack: &slack_chat_api::Slack, handle: &str, user_id: &str) -> Result<()> {
+    update_slack_usergroup(slack, handle, &[(user_id.to_string(), vec![handle.to_string()])]).await
+}
+
+async fn remove_from_slack_usergroup(slack: &slack_chat_api::Slack, handle: &str, user_id: &str) -> Result<()> {
+    update_slack_usergroup(slack, handle, &[(user_id.to_string(), vec![])]).await
+}
+
 /*
  *
  * Keep as empty boiler plate for now.
@@ -1703,3 +2213,153 @@ impl ProviderWriteOps<ramp_api::types::User, ()> for ramp_api::Client {
 }
 
 */
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::{current_usergroup_members, slack_channel_name, usergroup_members, SlackMemberships};
+    use crate::configs::Group;
+
+    fn mock_group(name: &str, slack_channels: &[&str]) -> Group {
+        serde_json::from_value(json!({
+            "name": name,
+            "slack_channels": slack_channels,
+        }))
+        .unwrap()
+    }
+
+    fn mock_usergroup(handle: &str, users: &[&str], disabled: bool) -> slack_chat_api::UserGroup {
+        slack_chat_api::UserGroup {
+            id: format!("S{}", handle.to_uppercase()),
+            handle: handle.to_string(),
+            users: users.iter().map(|user| user.to_string()).collect(),
+            date_delete: if disabled { 1 } else { 0 },
+            ..Default::default()
+        }
+    }
+
+    fn mock_channel(name: &str, is_private: bool) -> slack_chat_api::Channel {
+        slack_chat_api::Channel {
+            id: format!("C{}", name.to_uppercase()),
+            name: name.to_string(),
+            is_private,
+            ..Default::default()
+        }
+    }
+
+    fn mock_memberships() -> SlackMemberships {
+        SlackMemberships {
+            groups: vec![
+                mock_group("eng", &["#eng", "#Eng-Private"]),
+                mock_group("hiring", &["#hiring"]),
+                mock_group("ops", &[]),
+                mock_group("finance", &["#finance"]),
+            ],
+            usergroups: vec![
+                mock_usergroup("eng", &["U1"], false),
+                mock_usergroup("hiring", &["U1", "U2"], false),
+                mock_usergroup("ops", &["U1"], true),
+                // Not managed through a group.
+                mock_usergroup("social", &["U2"], false),
+            ],
+            channels: vec![
+                (mock_channel("eng", false), vec!["U2".to_string()]),
+                (mock_channel("eng-private", true), vec!["U1".to_string()]),
+                (mock_channel("hiring", false), vec!["U2".to_string()]),
+                (mock_channel("finance", true), vec!["U2".to_string()]),
+            ],
+        }
+    }
+
+    fn handles(usergroups: Vec<&slack_chat_api::UserGroup>) -> Vec<&str> {
+        usergroups.iter().map(|usergroup| usergroup.handle.as_str()).collect()
+    }
+
+    fn names(channels: Vec<&slack_chat_api::Channel>) -> Vec<&str> {
+        channels.iter().map(|channel| channel.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_slack_channel_name() {
+        assert_eq!(slack_channel_name("#eng"), "eng");
+        assert_eq!(slack_channel_name("#Eng-Private"), "eng-private");
+        assert_eq!(slack_channel_name("hiring"), "hiring");
+    }
+
+    #[test]
+    fn test_usergroups_to_update() {
+        let memberships = mock_memberships();
+        let groups = vec!["eng".to_string(), "ops".to_string(), "social".to_string()];
+
+        // User groups without a group (or groups without a user group) are left alone.
+        let users = vec![("U1".to_string(), groups.clone())];
+        assert_eq!(handles(memberships.usergroups_to_update(&users)), vec!["hiring", "ops"]);
+
+        let users = vec![("U1".to_string(), groups.clone()), ("U2".to_string(), groups)];
+        assert_eq!(
+            handles(memberships.usergroups_to_update(&users)),
+            vec!["eng", "hiring", "ops"]
+        );
+
+        let users = vec![("U1".to_string(), vec!["eng".to_string(), "hiring".to_string()])];
+        assert!(memberships.usergroups_to_update(&users).is_empty());
+    }
+
+    #[test]
+    fn test_usergroup_members() {
+        let eng = mock_usergroup("eng", &["U3", "U1", "BOT"], false);
+
+        // Members that are not one of the synced users are kept.
+        let users = vec![("U1".to_string(), vec![]), ("U2".to_string(), vec!["eng".to_string()])];
+        assert_eq!(usergroup_members(&eng, &users), vec!["BOT", "U2", "U3"]);
+        assert_eq!(usergroup_members(&eng, &[]), current_usergroup_members(&eng));
+
+        // Disabled user groups have no members.
+        let ops = mock_usergroup("ops", &["U1"], true);
+        assert!(current_usergroup_members(&ops).is_empty());
+        assert!(usergroup_members(&ops, &[]).is_empty());
+        assert_eq!(
+            usergroup_members(&ops, &[("U2".to_string(), vec!["ops".to_string()])]),
+            vec!["U2"]
+        );
+    }
+
+    #[test]
+    fn test_usergroup_member_changes() {
+        let eng = mock_usergroup("eng", &["U3", "U1", "BOT"], false);
+
+        // Adding or removing one member, as `add_to_slack_usergroup` and
+        // `remove_from_slack_usergroup` do, keeps the existing members.
+        assert_eq!(
+            usergroup_members(&eng, &[("U2".to_string(), vec!["eng".to_string()])]),
+            vec!["BOT", "U1", "U2", "U3"]
+        );
+        assert_eq!(
+            usergroup_members(&eng, &[("U1".to_string(), vec![])]),
+            vec!["BOT", "U3"]
+        );
+        assert_eq!(
+            usergroup_members(&eng, &[("U1".to_string(), vec!["eng".to_string()])]),
+            vec!["BOT", "U1", "U3"]
+        );
+    }
+
+    #[test]
+    fn test_channel_changes() {
+        let memberships = mock_memberships();
+
+        let (invite, kick) = memberships.channel_changes(&["eng".to_string()], "U1");
+        assert_eq!(names(invite), vec!["eng"]);
+        assert!(kick.is_empty());
+
+        // Users are only removed from private channels, anyone can join a public one.
+        let (invite, kick) = memberships.channel_changes(&["eng".to_string()], "U2");
+        assert_eq!(names(invite), vec!["eng-private"]);
+        assert_eq!(names(kick), vec!["finance"]);
+
+        let (invite, kick) = memberships.channel_changes(&["hiring".to_string(), "finance".to_string()], "U2");
+        assert!(invite.is_empty());
+        assert!(kick.is_empty());
+    }
+}
@@ -7,7 +7,10 @@ use macros::db;
 use mailerlite::SubscriberFieldValue;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use slack_chat_api::{FormattedMessage, MessageBlock, MessageBlockText, MessageBlockType, MessageType};
+use slack_chat_api::{
+    blocks::{ContextBlock, SectionBlock},
+    FormattedMessage,
+};
 
 use crate::{
     airtable::AIRTABLE_RACK_LINE_SIGNUPS_TABLE, companies::Company, core::UpdateAirtableRecord, db::Database,
@@ -94,21 +97,6 @@ impl From<NewRackLineSubscriber> for FormattedMessage {
 
         let msg = String::default();
 
-        let mut interest: MessageBlock = Default::default();
-        if !item.interest.is_empty() {
-            interest = MessageBlock {
-                block_type: MessageBlockType::Section,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: format!("\n>{}", item.interest),
-                }),
-                elements: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            };
-        }
-
         let mut context = "".to_string();
         if !item.company.is_empty() {
             context += &format!("works at {} | ", item.company);
@@ -118,42 +106,13 @@ impl From<NewRackLineSubscriber> for FormattedMessage {
         }
         context += &format!("subscribed to rack line {}", time);
 
-        let mut message = FormattedMessage {
-            channel: Default::default(),
-            attachments: Default::default(),
-            blocks: vec![
-                MessageBlock {
-                    block_type: MessageBlockType::Section,
-                    text: Some(MessageBlockText {
-                        text_type: MessageType::Markdown,
-                        text: msg,
-                    }),
-                    elements: Default::default(),
-                    accessory: Default::default(),
-                    block_id: Default::default(),
-                    fields: Default::default(),
-                },
-                MessageBlock {
-                    block_type: MessageBlockType::Context,
-                    elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                        text_type: MessageType::Markdown,
-                        text: context,
-                    })],
-                    text: Default::default(),
-                    accessory: Default::default(),
-                    block_id: Default::default(),
-                    fields: Default::default(),
-                },
-            ],
-        };
+        let mut message = FormattedMessage::new().block(SectionBlock::markdown(msg));
 
-        if item.interest.is_empty() {
-            return message;
+        if !item.interest.is_empty() {
+            message = message.block(SectionBlock::markdown(format!("\n>{}", item.interest)));
         }
 
-        message.blocks.insert(1, interest);
-
-        message
+        message.block(ContextBlock::new().markdown(context))
     }
 }
 
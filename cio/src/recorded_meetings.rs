@@ -13,12 +13,14 @@ use macros::db;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
-    FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
+    blocks::{ContextBlock, HeaderBlock, SectionBlock},
+    FormattedMessage, MessageAttachment,
 };
 use zoom_api::types::GetAccountCloudRecordingResponseMeetingsFilesFileType;
 
 use crate::{
     airtable::AIRTABLE_RECORDED_MEETINGS_TABLE,
+    api_tokens::APIToken,
     companies::Company,
     configs::User,
     core::UpdateAirtableRecord,
@@ -110,66 +112,13 @@ impl From<NewRecordedMeeting> for FormattedMessage {
 
         context += &format!(" | _started {}_ | _ended {}_", started, ended);
 
-        let mut blocks = vec![MessageBlock {
-            block_type: MessageBlockType::Header,
-            text: Some(MessageBlockText {
-                text_type: MessageType::PlainText,
-                text: item.name.to_string(),
-            }),
-            elements: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        }];
+        let mut attachment = MessageAttachment::default().block(HeaderBlock::new(&item.name));
 
         if !item.description.is_empty() {
-            blocks.push(MessageBlock {
-                block_type: MessageBlockType::Section,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: item.description,
-                }),
-                elements: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            });
+            attachment = attachment.block(SectionBlock::markdown(item.description));
         }
 
-        blocks.push(MessageBlock {
-            block_type: MessageBlockType::Context,
-            elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                text_type: MessageType::Markdown,
-                text: context,
-            })],
-            text: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        });
-
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: Default::default(),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks,
-            }],
-        }
+        FormattedMessage::new().attachment(attachment.block(ContextBlock::new().markdown(context)))
     }
 }
 
@@ -246,8 +195,12 @@ pub async fn refresh_zoom_recorded_meetings(db: &Database, company: &Company) ->
         .body
         .id;
 
-    // We need the zoom token to download the URL.
-    let at = zoom.refresh_access_token().await?;
+    // We need the zoom token to download the URL. Authenticating above refreshed it if it had
+    // expired, refreshing it again here would throw away the refresh token we store.
+    let at = match APIToken::get_decrypted(db, company, "zoom").await? {
+        Some(at) => at,
+        None => bail!("no zoom token"),
+    };
 
     for meeting in recordings {
         if meeting.topic.is_empty() {
@@ -0,0 +1,1488 @@
+//! A pure-Rust converter for the subset of AsciiDoc that RFDs are written in.
+//!
+//! The output mirrors the embedded HTML (`--no-header-footer`) that asciidoctor generates, so that
+//! the RFD site can style it the same way. Documents are converted line by line: block structure
+//! (sections, lists, delimited blocks, tables) is handled by [`Converter::blocks`] and inline
+//! formatting (quotes, links, cross references, footnotes) by [`Converter::inline`].
+use regex::{Captures, Regex};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+/// Convert an AsciiDoc document to an HTML fragment.
+pub fn asciidoc_to_html(content: &str) -> String {
+    // The markers are private use code points, so a source could contain them (e.g. a glyph of an
+    // icon font). They are dropped so that they can't be mistaken for converted content.
+    let content = content.replace([PLACEHOLDER, XREF], "");
+    let lines = content.lines().collect::<Vec<_>>();
+
+    let mut converter = Converter::default();
+    let mut out = String::new();
+
+    let start = converter.header(&lines, &mut out);
+    converter.blocks(&lines[start..], true, &mut out);
+    converter.close_sections(0, &mut out);
+    converter.footnotes(&mut out);
+
+    converter.finish(out)
+}
+
+macro_rules! regex {
+    ($pattern:expr) => {{
+        static RE: OnceLock<Regex> = OnceLock::new();
+        compiled(&RE, $pattern)
+    }};
+}
+
+fn compiled(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+/// Marks the start and end of content that has already been converted and must not be touched
+/// by later substitutions. It is removed from sources before they are converted.
+const PLACEHOLDER: char = '\u{E000}';
+
+/// Marks a cross reference without text, resolved once every section id is known. Like
+/// [`PLACEHOLDER`] it is removed from sources before they are converted.
+const XREF: char = '\u{E001}';
+
+const ADMONITIONS: [&str; 5] = ["NOTE", "TIP", "IMPORTANT", "WARNING", "CAUTION"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Unordered,
+    Ordered,
+}
+
+/// Attributes, ids and titles that apply to the next block.
+#[derive(Debug, Default)]
+struct Pending {
+    id: Option<String>,
+    title: Option<String>,
+    style: Option<String>,
+    attrs: Vec<String>,
+}
+
+impl Pending {
+    fn has_option(&self, option: &str) -> bool {
+        self.attrs.iter().any(|attr| {
+            attr == &format!("%{}", option)
+                || (attr.starts_with("options=") && attr.contains(option))
+                || (attr.starts_with("opts=") && attr.contains(option))
+        }) || self
+            .style
+            .as_deref()
+            .map(|s| s.ends_with(&format!("%{}", option)))
+            .unwrap_or(false)
+    }
+
+    fn named(&self, name: &str) -> Option<String> {
+        self.attrs.iter().find_map(|attr| {
+            attr.strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+                .map(|value| value.trim_matches('"').to_string())
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct Converter {
+    attributes: HashMap<String, String>,
+    /// Section titles by id, for cross references without text.
+    refs: HashMap<String, String>,
+    ids: HashSet<String>,
+    sections: Vec<usize>,
+    section_numbers: Vec<usize>,
+    footnotes: Vec<String>,
+    placeholders: Vec<String>,
+    figures: usize,
+    tables: usize,
+    examples: usize,
+}
+
+impl Converter {
+    /// Read the document header: attribute entries, the title and the author line. Returns the
+    /// index of the first line of the body.
+    fn header(&mut self, lines: &[&str], out: &mut String) -> usize {
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+
+            if line.trim().is_empty() || self.attribute_entry(line) || is_comment(line) {
+                i += 1;
+                continue;
+            }
+
+            if let Some(title) = line.strip_prefix("= ") {
+                i += 1;
+
+                // The author and revision lines directly follow the title
+                while i < lines.len() && !lines[i].trim().is_empty() && !lines[i].starts_with(':') {
+                    i += 1;
+                }
+
+                // Attributes may be set between the author line and the body
+                while i < lines.len() && (lines[i].trim().is_empty() || self.attribute_entry(lines[i])) {
+                    i += 1;
+                }
+
+                if self.attributes.contains_key("showtitle") {
+                    let title = self.inline(title.trim());
+                    out.push_str(&format!("<h1>{}</h1>\n", title));
+                }
+            }
+
+            break;
+        }
+
+        i
+    }
+
+    /// Apply an attribute entry such as `:state: published` or `:toc!:`.
+    fn attribute_entry(&mut self, line: &str) -> bool {
+        match regex!(r"^:(!?)([\w][\w-]*)(!?):(?:\s+(.*))?$").captures(line) {
+            Some(captures) => {
+                let name = captures[2].to_lowercase();
+
+                if !captures[1].is_empty() || !captures[3].is_empty() {
+                    self.attributes.remove(&name);
+                } else {
+                    let value = captures.get(4).map(|v| v.as_str().trim()).unwrap_or_default();
+                    let value = self.substitute_attributes(value);
+                    self.attributes.insert(name, value);
+                }
+
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Convert a sequence of blocks. Sections may only appear at the top level of the document.
+    fn blocks(&mut self, lines: &[&str], top: bool, out: &mut String) {
+        let mut pending = Pending::default();
+        let mut i = 0;
+
+        while i < lines.len() {
+            let line = lines[i];
+            let trimmed = line.trim_end();
+
+            if trimmed.trim().is_empty() {
+                i += 1;
+                continue;
+            }
+
+            // Comments
+            if trimmed == "////" {
+                i = closing_delimiter(lines, i, trimmed) + 1;
+                continue;
+            }
+            if is_comment(trimmed) {
+                i += 1;
+                continue;
+            }
+
+            // Directives that we can not resolve
+            if regex!(r"^(include|ifdef|ifndef|ifeval|endif|toc)::").is_match(trimmed) {
+                i += 1;
+                continue;
+            }
+
+            if self.attribute_entry(trimmed) {
+                i += 1;
+                continue;
+            }
+
+            // Block anchors, attributes and titles apply to the next block
+            if let Some(captures) = regex!(r"^\[\[([\w:.-]+)(?:,\s*(.*))?\]\]$").captures(trimmed) {
+                pending.id = Some(captures[1].to_string());
+                i += 1;
+                continue;
+            }
+            if let Some(captures) = regex!(r"^\[([^\[\]]*)\]$").captures(trimmed) {
+                self.block_attributes(&captures[1], &mut pending);
+                i += 1;
+                continue;
+            }
+            if let Some(title) = block_title(trimmed) {
+                pending.title = Some(title.to_string());
+                i += 1;
+                continue;
+            }
+
+            // Sections
+            if let Some(captures) = regex!(r"^(={2,6})\s+(\S.*)$").captures(trimmed) {
+                let level = captures[1].len() - 1;
+                let title = captures[2].trim_end_matches([' ', '=']).to_string();
+
+                if pending.style.as_deref() == Some("discrete") || pending.style.as_deref() == Some("float") {
+                    let id = self.section_id(&title, pending.id.take());
+                    let title = self.inline(&title);
+                    out.push_str(&format!(
+                        "<h{level} id=\"{id}\" class=\"discrete\">{title}</h{level}>\n",
+                        level = level + 1,
+                        id = id,
+                        title = title
+                    ));
+                } else if top {
+                    self.section(level, &title, pending.id.take(), out);
+                } else {
+                    self.paragraph(&[trimmed], &pending, out);
+                }
+
+                pending = Pending::default();
+                i += 1;
+                continue;
+            }
+
+            // Markdown style fenced code blocks
+            if let Some(language) = trimmed.strip_prefix("```") {
+                let end = closing_delimiter(lines, i, trimmed);
+                let fenced = Pending {
+                    id: pending.id.take(),
+                    title: pending.title.take(),
+                    style: Some("source".to_string()),
+                    attrs: Some(language.trim().to_string())
+                        .filter(|l| !l.is_empty())
+                        .into_iter()
+                        .collect(),
+                };
+                self.listing_block(&lines[i + 1..end], &fenced, out);
+                pending = Pending::default();
+                i = end + 1;
+                continue;
+            }
+
+            // Delimited blocks
+            if let Some(kind) = delimiter(trimmed) {
+                let end = closing_delimiter(lines, i, trimmed);
+                self.delimited_block(kind, &lines[i + 1..end], &pending, out);
+                pending = Pending::default();
+                i = end + 1;
+                continue;
+            }
+
+            if trimmed == "'''" || trimmed == "---" || trimmed == "***" {
+                out.push_str("<hr>\n");
+                pending = Pending::default();
+                i += 1;
+                continue;
+            }
+            if trimmed == "<<<" {
+                out.push_str("<div style=\"page-break-after: always;\"></div>\n");
+                pending = Pending::default();
+                i += 1;
+                continue;
+            }
+
+            if let Some(captures) = regex!(r"^image::([^\[\s]+)\[(.*)\]$").captures(trimmed) {
+                self.image_block(&captures[1], &captures[2], &pending, out);
+                pending = Pending::default();
+                i += 1;
+                continue;
+            }
+
+            if list_marker(line).is_some() {
+                i = self.list(lines, i, &[], &pending, out);
+                pending = Pending::default();
+                continue;
+            }
+
+            if dlist_item(line).is_some() {
+                i = self.dlist(lines, i, &pending, out);
+                pending = Pending::default();
+                continue;
+            }
+
+            // Paragraphs, which are literal when indented
+            let end = paragraph_end(lines, i);
+            if line.starts_with(' ') || line.starts_with('\t') || pending.style.as_deref() == Some("literal") {
+                let text = unindent(&lines[i..end]);
+                self.literal_block(&text, &pending, out);
+            } else {
+                self.paragraph(&lines[i..end], &pending, out);
+            }
+
+            pending = Pending::default();
+            i = end;
+        }
+    }
+
+    /// Parse a block attribute line such as `[source,rust]`, `[#id.role]` or `[NOTE]`.
+    fn block_attributes(&mut self, list: &str, pending: &mut Pending) {
+        for (index, attr) in split_attributes(list).into_iter().enumerate() {
+            if index == 0 && !attr.contains('=') {
+                // The first positional attribute is the style, with optional id, roles and options
+                let mut style = attr.as_str();
+                if let Some(position) = style.find(['#', '.', '%']) {
+                    let (name, shorthand) = style.split_at(position);
+                    for part in regex!(r"([#.%])([^#.%]+)").captures_iter(shorthand) {
+                        match &part[1] {
+                            "#" => pending.id = Some(part[2].to_string()),
+                            "%" => pending.attrs.push(format!("%{}", &part[2])),
+                            _ => (),
+                        }
+                    }
+                    style = name;
+                }
+
+                if !style.is_empty() {
+                    pending.style = Some(style.to_string());
+                }
+            } else {
+                pending.attrs.push(attr);
+            }
+        }
+    }
+
+    fn section(&mut self, level: usize, title: &str, id: Option<String>, out: &mut String) {
+        self.close_sections(level, out);
+
+        let id = self.section_id(title, id);
+        self.refs.insert(id.clone(), title.to_string());
+
+        let number = if self.attributes.contains_key("numbered") || self.attributes.contains_key("sectnums") {
+            self.section_numbers.truncate(level);
+            while self.section_numbers.len() < level {
+                self.section_numbers.push(0);
+            }
+            self.section_numbers[level - 1] += 1;
+
+            self.section_numbers
+                .iter()
+                .map(|n| format!("{}.", n))
+                .collect::<Vec<_>>()
+                .join("")
+                + " "
+        } else {
+            String::new()
+        };
+
+        let title = self.inline(title);
+        out.push_str(&format!(
+            "<div class=\"sect{level}\">\n<h{h} id=\"{id}\">{number}{title}</h{h}>\n",
+            level = level,
+            h = level + 1,
+            id = id,
+            number = number,
+            title = title
+        ));
+
+        if level == 1 {
+            out.push_str("<div class=\"sectionbody\">\n");
+        }
+
+        self.sections.push(level);
+    }
+
+    /// Close every open section at `level` or deeper.
+    fn close_sections(&mut self, level: usize, out: &mut String) {
+        while let Some(open) = self.sections.last().copied() {
+            if open < level {
+                break;
+            }
+
+            if open == 1 {
+                out.push_str("</div>\n");
+            }
+            out.push_str("</div>\n");
+            self.sections.pop();
+        }
+    }
+
+    /// Generate a section id the way asciidoctor does: `_` followed by the lowercased title with
+    /// runs of spaces, dots and dashes replaced by `_` and other punctuation removed.
+    fn section_id(&mut self, title: &str, explicit: Option<String>) -> String {
+        if let Some(id) = explicit {
+            self.ids.insert(id.clone());
+            return id;
+        }
+
+        let lowered = title.to_lowercase();
+        let stripped =
+            regex!(r"<[^>]+>|&(?:[a-z][a-z]+\d{0,2}|#\d{2,5}|#x[\da-f]{2,4});|[^ \w.-]+").replace_all(&lowered, "");
+        let base = format!("_{}", regex!(r"[ .-]+").replace_all(&stripped, "_"));
+        let base = base.trim_end_matches('_').to_string();
+
+        let mut id = base.clone();
+        let mut n = 2;
+        while self.ids.contains(&id) {
+            id = format!("{}_{}", base, n);
+            n += 1;
+        }
+
+        self.ids.insert(id.clone());
+        id
+    }
+
+    fn paragraph(&mut self, lines: &[&str], pending: &Pending, out: &mut String) {
+        let text = lines.iter().map(|line| line.trim()).collect::<Vec<_>>().join("\n");
+
+        // Admonition paragraphs, e.g. `NOTE: Some text`
+        let admonition = ADMONITIONS.iter().find(|name| text.starts_with(&format!("{}: ", name)));
+        let style = pending.style.as_deref().filter(|style| ADMONITIONS.contains(style));
+
+        if let Some(name) = admonition.copied().or(style) {
+            let text = text.strip_prefix(&format!("{}: ", name)).unwrap_or(&text);
+            let content = self.inline(text);
+            self.admonition(name, &content, pending, out);
+            return;
+        }
+
+        if pending.style.as_deref() == Some("quote") {
+            let content = self.inline(&text);
+            self.quote(&content, pending, out);
+            return;
+        }
+
+        out.push_str(&self.open_div("paragraph", pending));
+        self.title(pending, out);
+        out.push_str(&format!("<p>{}</p>\n</div>\n", self.inline(&text)));
+    }
+
+    fn admonition(&mut self, name: &str, content: &str, pending: &Pending, out: &mut String) {
+        let label = title_case(name);
+        let icon = if self
+            .attributes
+            .get("icons")
+            .map(|icons| icons == "font")
+            .unwrap_or(false)
+        {
+            format!("<i class=\"fa icon-{}\" title=\"{}\"></i>", name.to_lowercase(), label)
+        } else {
+            format!("<div class=\"title\">{}</div>", label)
+        };
+
+        out.push_str(&self.open_div(&format!("admonitionblock {}", name.to_lowercase()), pending));
+        out.push_str("<table>\n<tr>\n<td class=\"icon\">\n");
+        out.push_str(&icon);
+        out.push_str("\n</td>\n<td class=\"content\">\n");
+        if let Some(title) = &pending.title {
+            let title = self.inline(title);
+            out.push_str(&format!("<div class=\"title\">{}</div>\n", title));
+        }
+        out.push_str(content);
+        out.push_str("\n</td>\n</tr>\n</table>\n</div>\n");
+    }
+
+    fn quote(&mut self, content: &str, pending: &Pending, out: &mut String) {
+        out.push_str(&self.open_div("quoteblock", pending));
+        self.title(pending, out);
+        out.push_str("<blockquote>\n");
+        out.push_str(content);
+        out.push_str("\n</blockquote>\n");
+
+        // `[quote, attribution, citation]`
+        let attribution = pending.attrs.first().filter(|attr| !attr.contains('='));
+        let citation = pending.attrs.get(1).filter(|attr| !attr.contains('='));
+        if attribution.is_some() || citation.is_some() {
+            out.push_str("<div class=\"attribution\">\n");
+            if let Some(attribution) = attribution {
+                out.push_str(&format!("&#8212; {}", self.inline(attribution)));
+            }
+            if let Some(citation) = citation {
+                out.push_str(&format!("<br>\n<cite>{}</cite>", self.inline(citation)));
+            }
+            out.push_str("\n</div>\n");
+        }
+
+        out.push_str("</div>\n");
+    }
+
+    fn delimited_block(&mut self, kind: char, lines: &[&str], pending: &Pending, out: &mut String) {
+        match kind {
+            '-' => self.listing_block(lines, pending, out),
+            '.' => {
+                let text = lines.join("\n");
+                self.literal_block(&text, pending, out)
+            }
+            '+' => {
+                out.push_str(&lines.join("\n"));
+                out.push('\n');
+            }
+            '_' => {
+                let mut content = String::new();
+                self.blocks(lines, false, &mut content);
+                self.quote(content.trim_end(), pending, out);
+            }
+            '=' if pending
+                .style
+                .as_deref()
+                .map(|s| ADMONITIONS.contains(&s))
+                .unwrap_or(false) =>
+            {
+                let mut content = String::new();
+                self.blocks(lines, false, &mut content);
+                let name = pending.style.clone().unwrap_or_default();
+                self.admonition(&name, content.trim_end(), pending, out);
+            }
+            '=' | '*' | 'o' => {
+                let class = match kind {
+                    '=' => "exampleblock",
+                    '*' => "sidebarblock",
+                    _ => "openblock",
+                };
+
+                out.push_str(&self.open_div(class, pending));
+                if kind == '=' {
+                    if let Some(title) = &pending.title {
+                        self.examples += 1;
+                        let title = self.inline(title);
+                        out.push_str(&format!(
+                            "<div class=\"title\">Example {}. {}</div>\n",
+                            self.examples, title
+                        ));
+                    }
+                } else {
+                    self.title(pending, out);
+                }
+                out.push_str("<div class=\"content\">\n");
+                self.blocks(lines, false, out);
+                out.push_str("</div>\n</div>\n");
+            }
+            '|' => self.table(lines, pending, out),
+            _ => (),
+        }
+    }
+
+    fn listing_block(&mut self, lines: &[&str], pending: &Pending, out: &mut String) {
+        let code = escape(&lines.join("\n"));
+
+        // `[source,rust]` or the shorthand `[,rust]`
+        let language = match pending.style.as_deref() {
+            Some("source") | None => pending.attrs.first().filter(|attr| !attr.contains('=')).cloned(),
+            _ => None,
+        };
+
+        out.push_str(&self.open_div("listingblock", pending));
+        self.title(pending, out);
+        out.push_str("<div class=\"content\">\n");
+
+        match (pending.style.as_deref(), language) {
+            (_, Some(language)) => out.push_str(&format!(
+                "<pre class=\"highlight\"><code class=\"language-{lang}\" data-lang=\"{lang}\">{code}</code></pre>\n",
+                lang = language,
+                code = code
+            )),
+            (Some("source"), None) => out.push_str(&format!("<pre class=\"highlight\"><code>{}</code></pre>\n", code)),
+            (Some(style), None) => out.push_str(&format!("<pre class=\"{}\">{}</pre>\n", style, code)),
+            (None, None) => out.push_str(&format!("<pre>{}</pre>\n", code)),
+        }
+
+        out.push_str("</div>\n</div>\n");
+    }
+
+    fn literal_block(&mut self, text: &str, pending: &Pending, out: &mut String) {
+        let class = match pending.style.as_deref() {
+            Some(style) if style != "literal" => style.to_string(),
+            _ => String::new(),
+        };
+
+        out.push_str(&self.open_div("literalblock", pending));
+        self.title(pending, out);
+        out.push_str("<div class=\"content\">\n");
+        if class.is_empty() {
+            out.push_str(&format!("<pre>{}</pre>\n", escape(text)));
+        } else {
+            out.push_str(&format!("<pre class=\"{}\">{}</pre>\n", class, escape(text)));
+        }
+        out.push_str("</div>\n</div>\n");
+    }
+
+    fn image_block(&mut self, target: &str, attrs: &str, pending: &Pending, out: &mut String) {
+        let img = self.image(target, attrs);
+
+        out.push_str(&self.open_div("imageblock", pending));
+        out.push_str(&format!("<div class=\"content\">\n{}\n</div>\n", img));
+        if let Some(title) = &pending.title {
+            self.figures += 1;
+            let title = self.inline(title);
+            out.push_str(&format!(
+                "<div class=\"title\">Figure {}. {}</div>\n",
+                self.figures, title
+            ));
+        }
+        out.push_str("</div>\n");
+    }
+
+    /// An `<img>` for the target and attributes of an image macro, e.g. `diagram.svg[Diagram,400]`.
+    fn image(&mut self, target: &str, attrs: &str) -> String {
+        let attrs = split_attributes(attrs);
+        let positional = attrs.iter().filter(|attr| !attr.contains('=')).collect::<Vec<_>>();
+
+        let alt = positional
+            .first()
+            .map(|alt| alt.to_string())
+            .filter(|alt| !alt.is_empty())
+            .unwrap_or_else(|| {
+                let file = target.rsplit('/').next().unwrap_or(target);
+                let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file);
+                stem.replace(['-', '_'], " ")
+            });
+
+        let mut img = format!("<img src=\"{}\" alt=\"{}\"", escape(target), escape(&alt));
+        if let Some(width) = positional.get(1).filter(|w| !w.is_empty()) {
+            img.push_str(&format!(" width=\"{}\"", escape(width)));
+        }
+        if let Some(height) = positional.get(2).filter(|h| !h.is_empty()) {
+            img.push_str(&format!(" height=\"{}\"", escape(height)));
+        }
+        img.push('>');
+
+        img
+    }
+
+    /// Convert a list starting at line `start`. `parents` are the markers of the lists that this
+    /// list is nested in. Returns the index of the first line after the list.
+    fn list(
+        &mut self,
+        lines: &[&str],
+        start: usize,
+        parents: &[(ListKind, String)],
+        pending: &Pending,
+        out: &mut String,
+    ) -> usize {
+        let (kind, marker, _) = list_marker(lines[start]).unwrap();
+
+        match kind {
+            ListKind::Unordered => {
+                out.push_str(&self.open_div("ulist", pending));
+                self.title(pending, out);
+                out.push_str("<ul>\n");
+            }
+            ListKind::Ordered => {
+                let style = match marker.len() {
+                    2 => "loweralpha",
+                    3 => "lowerroman",
+                    4 => "upperalpha",
+                    5 => "upperroman",
+                    _ => "arabic",
+                };
+                out.push_str(&self.open_div(&format!("olist {}", style), pending));
+                self.title(pending, out);
+                out.push_str(&format!("<ol class=\"{}\"", style));
+                if let Some(start) = pending.named("start") {
+                    out.push_str(&format!(" start=\"{}\"", escape(&start)));
+                }
+                out.push_str(">\n");
+            }
+        }
+
+        let mut ancestors = parents.to_vec();
+        ancestors.push((kind, marker.clone()));
+
+        let mut i = start;
+        while i < lines.len() {
+            match list_marker(lines[i]) {
+                Some((k, m, text)) if k == kind && m == marker => {
+                    i += 1;
+
+                    // Lines directly following the item belong to its text
+                    let mut item = vec![text];
+                    while i < lines.len()
+                        && !lines[i].trim().is_empty()
+                        && lines[i].trim() != "+"
+                        && list_marker(lines[i]).is_none()
+                        && dlist_item(lines[i]).is_none()
+                        && delimiter(lines[i].trim_end()).is_none()
+                        && !is_block_attribute(lines[i])
+                    {
+                        item.push(lines[i].trim());
+                        i += 1;
+                    }
+
+                    let item = item.join("\n");
+                    let checklist = regex!(r"^\[([ xX*])\]\s+(.*)$").captures(&item).map(|captures| {
+                        let checked = &captures[1] != " ";
+                        (checked, captures[2].to_string())
+                    });
+
+                    match checklist {
+                        Some((checked, text)) => {
+                            let mark = if checked { "&#10003;" } else { "&#10063;" };
+                            out.push_str(&format!("<li>\n<p>{} {}</p>\n", mark, self.inline(&text)));
+                        }
+                        None => out.push_str(&format!("<li>\n<p>{}</p>\n", self.inline(&item))),
+                    }
+
+                    // Blocks attached with a list continuation and nested lists
+                    loop {
+                        if i < lines.len() && lines[i].trim() == "+" {
+                            let end = attached_block_end(lines, i + 1);
+                            self.blocks(&lines[i + 1..end], false, out);
+                            i = end;
+                            continue;
+                        }
+
+                        let next = skip_blank(lines, i);
+                        if next < lines.len() {
+                            if let Some((k, m, _)) = list_marker(lines[next]) {
+                                if !ancestors.iter().any(|(pk, pm)| *pk == k && *pm == m) {
+                                    i = self.list(lines, next, &ancestors, &Pending::default(), out);
+                                    continue;
+                                }
+                            }
+                        }
+
+                        break;
+                    }
+
+                    out.push_str("</li>\n");
+                }
+                Some(_) => break,
+                None => {
+                    let next = skip_blank(lines, i);
+                    match list_marker(lines.get(next).copied().unwrap_or_default()) {
+                        Some((k, m, _)) if next > i && k == kind && m == marker => i = next,
+                        _ => break,
+                    }
+                }
+            }
+        }
+
+        match kind {
+            ListKind::Unordered => out.push_str("</ul>\n</div>\n"),
+            ListKind::Ordered => out.push_str("</ol>\n</div>\n"),
+        }
+
+        i
+    }
+
+    /// Convert a description list starting at line `start`. Returns the index of the first line
+    /// after the list.
+    fn dlist(&mut self, lines: &[&str], start: usize, pending: &Pending, out: &mut String) -> usize {
+        out.push_str(&self.open_div("dlist", pending));
+        self.title(pending, out);
+        out.push_str("<dl>\n");
+
+        let mut i = start;
+        while i < lines.len() {
+            let (term, text) = match dlist_item(lines[i]) {
+                Some(item) => item,
+                None => {
+                    let next = skip_blank(lines, i);
+                    if next > i && next < lines.len() && dlist_item(lines[next]).is_some() {
+                        i = next;
+                        continue;
+                    }
+                    break;
+                }
+            };
+            i += 1;
+
+            out.push_str(&format!("<dt class=\"hdlist1\">{}</dt>\n", self.inline(&term)));
+
+            let mut definition = if text.is_empty() { vec![] } else { vec![text.as_str()] };
+            let next = skip_blank(lines, i);
+            if definition.is_empty() && next < lines.len() && is_indented_text(lines[next]) {
+                i = next;
+            }
+            while i < lines.len()
+                && !lines[i].trim().is_empty()
+                && lines[i].trim() != "+"
+                && list_marker(lines[i]).is_none()
+                && dlist_item(lines[i]).is_none()
+                && delimiter(lines[i].trim_end()).is_none()
+            {
+                definition.push(lines[i].trim());
+                i += 1;
+            }
+
+            out.push_str("<dd>\n");
+            if !definition.is_empty() {
+                let definition = definition.join("\n");
+                out.push_str(&format!("<p>{}</p>\n", self.inline(&definition)));
+            }
+
+            loop {
+                if i < lines.len() && lines[i].trim() == "+" {
+                    let end = attached_block_end(lines, i + 1);
+                    self.blocks(&lines[i + 1..end], false, out);
+                    i = end;
+                    continue;
+                }
+
+                let next = skip_blank(lines, i);
+                if next < lines.len() && list_marker(lines[next]).is_some() {
+                    i = self.list(lines, next, &[], &Pending::default(), out);
+                    continue;
+                }
+
+                break;
+            }
+            out.push_str("</dd>\n");
+        }
+
+        out.push_str("</dl>\n</div>\n");
+
+        i
+    }
+
+    fn table(&mut self, lines: &[&str], pending: &Pending, out: &mut String) {
+        // Split the table into cells. Cells start with `|`, optionally prefixed by a cell spec such
+        // as `a` (AsciiDoc content) or `2+` (column span, which is not supported).
+        let mut cells: Vec<(bool, String)> = vec![];
+        let mut first_row_cells = 0;
+        let mut first_row_done = false;
+        let mut implicit_header = false;
+
+        for (index, line) in lines.iter().enumerate() {
+            if line.trim().is_empty() {
+                if !cells.is_empty() && !first_row_done {
+                    first_row_done = true;
+                    implicit_header = index == 1 || lines[..index].iter().filter(|l| !l.trim().is_empty()).count() == 1;
+                }
+                if let Some((true, text)) = cells.last_mut() {
+                    text.push('\n');
+                }
+                continue;
+            }
+
+            let mut parts = line.split('|');
+            let leading = parts.next().unwrap_or_default();
+            let mut spec = String::new();
+
+            if regex!(r"^(\d+[+*])?[aehlmsv]?$").is_match(leading.trim()) {
+                spec = leading.trim().to_string();
+            } else if let Some((_, text)) = cells.last_mut() {
+                text.push('\n');
+                text.push_str(leading.trim());
+            }
+
+            let parts = parts.collect::<Vec<_>>();
+            for (n, part) in parts.iter().enumerate() {
+                let asciidoc = spec.ends_with('a');
+                let mut text = part.to_string();
+                spec.clear();
+
+                // The end of a cell may hold the spec of the next cell
+                if n + 1 < parts.len() {
+                    if let Some(captures) = regex!(r"\s((?:\d+[+*])?[aehlmsv])$").captures(part) {
+                        spec = captures[1].to_string();
+                        text = part[..part.len() - spec.len()].to_string();
+                    }
+                }
+
+                cells.push((asciidoc, text.trim().to_string()));
+            }
+
+            if !first_row_done {
+                first_row_cells = cells.len();
+            }
+        }
+
+        let columns = pending
+            .named("cols")
+            .map(|cols| column_widths(&cols))
+            .unwrap_or_else(|| vec![1.0; first_row_cells.max(1)]);
+
+        let has_header = (implicit_header && !pending.has_option("noheader")) || pending.has_option("header");
+
+        out.push_str("<table class=\"tableblock frame-all grid-all stretch\"");
+        if let Some(id) = &pending.id {
+            out.push_str(&format!(" id=\"{}\"", id));
+        }
+        out.push_str(">\n");
+        if let Some(title) = &pending.title {
+            self.tables += 1;
+            let title = self.inline(title);
+            out.push_str(&format!(
+                "<caption class=\"title\">Table {}. {}</caption>\n",
+                self.tables, title
+            ));
+        }
+
+        out.push_str("<colgroup>\n");
+        let total: f64 = columns.iter().sum();
+        let mut remaining = 100.0;
+        for (index, width) in columns.iter().enumerate() {
+            let percent = if index + 1 == columns.len() {
+                remaining
+            } else {
+                let percent = (width / total * 100.0 * 10000.0).round() / 10000.0;
+                remaining -= percent;
+                percent
+            };
+            out.push_str(&format!("<col style=\"width: {}%;\">\n", format_width(percent)));
+        }
+        out.push_str("</colgroup>\n");
+
+        let mut rows = cells.chunks(columns.len()).peekable();
+
+        if has_header {
+            if let Some(row) = rows.next() {
+                out.push_str("<thead>\n<tr>\n");
+                for (_, text) in row {
+                    let text = self.inline(text);
+                    out.push_str(&format!(
+                        "<th class=\"tableblock halign-left valign-top\">{}</th>\n",
+                        text
+                    ));
+                }
+                out.push_str("</tr>\n</thead>\n");
+            }
+        }
+
+        if rows.peek().is_some() {
+            out.push_str("<tbody>\n");
+            for row in rows {
+                out.push_str("<tr>\n");
+                for (asciidoc, text) in row {
+                    out.push_str("<td class=\"tableblock halign-left valign-top\">");
+                    if *asciidoc {
+                        let cell_lines = text.lines().collect::<Vec<_>>();
+                        out.push_str("<div class=\"content\">");
+                        self.blocks(&cell_lines, false, out);
+                        out.push_str("</div>");
+                    } else {
+                        for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+                            let paragraph = self.inline(paragraph.trim());
+                            out.push_str(&format!("<p class=\"tableblock\">{}</p>", paragraph));
+                        }
+                    }
+                    out.push_str("</td>\n");
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</tbody>\n");
+        }
+
+        out.push_str("</table>\n");
+    }
+
+    fn open_div(&self, class: &str, pending: &Pending) -> String {
+        match &pending.id {
+            Some(id) => format!("<div id=\"{}\" class=\"{}\">\n", id, class),
+            None => format!("<div class=\"{}\">\n", class),
+        }
+    }
+
+    fn title(&mut self, pending: &Pending, out: &mut String) {
+        if let Some(title) = &pending.title {
+            let title = self.inline(title);
+            out.push_str(&format!("<div class=\"title\">{}</div>\n", title));
+        }
+    }
+
+    fn footnotes(&mut self, out: &mut String) {
+        if self.footnotes.is_empty() {
+            return;
+        }
+
+        out.push_str("<div id=\"footnotes\">\n<hr>\n");
+        for (index, text) in self.footnotes.iter().enumerate() {
+            out.push_str(&format!(
+                "<div class=\"footnote\" id=\"_footnotedef_{n}\">\n<a href=\"#_footnoteref_{n}\">{n}</a>. {text}\n</div>\n",
+                n = index + 1,
+                text = text
+            ));
+        }
+        out.push_str("</div>\n");
+    }
+
+    /// Resolve cross references and restore the content that was protected from substitutions.
+    fn finish(&self, out: String) -> String {
+        let refs = &self.refs;
+        let out = regex!("\u{E001}([^\u{E001}]*)\u{E001}").replace_all(&out, |captures: &Captures| {
+            let id = &captures[1];
+            let text = refs
+                .get(id)
+                .map(|title| escape(title))
+                .unwrap_or_else(|| format!("[{}]", id));
+            format!("<a href=\"#{}\">{}</a>", id, text)
+        });
+
+        let mut out = out.to_string();
+
+        // Placeholders may contain other placeholders, e.g. a footnote with inline code. Stop once
+        // a pass has nothing left to restore, rather than looping on a marker that is not one.
+        let placeholder = regex!("\u{E000}(\\d+)\u{E000}");
+        while placeholder.is_match(&out) {
+            out = placeholder
+                .replace_all(&out, |captures: &Captures| {
+                    let index = captures[1].parse::<usize>().unwrap_or_default();
+                    self.placeholders.get(index).cloned().unwrap_or_default()
+                })
+                .to_string();
+        }
+
+        out
+    }
+
+    fn protect(&mut self, html: String) -> String {
+        self.placeholders.push(html);
+        format!("{p}{}{p}", self.placeholders.len() - 1, p = PLACEHOLDER)
+    }
+
+    fn substitute_attributes(&self, text: &str) -> String {
+        regex!(r"\{([\w][\w-]*)\}")
+            .replace_all(text, |captures: &Captures| {
+                match self.attributes.get(&captures[1].to_lowercase()) {
+                    Some(value) => value.to_string(),
+                    None => captures[0].to_string(),
+                }
+            })
+            .to_string()
+    }
+
+    /// Apply inline substitutions in the same order as asciidoctor: special characters, quotes,
+    /// attribute references, replacements and finally macros.
+    fn inline(&mut self, text: &str) -> String {
+        // Passthroughs and inline code are never formatted
+        let text = regex!(r"\+\+\+(.+?)\+\+\+|pass:\[(.*?)\]").replace_all(text, |captures: &Captures| {
+            let raw = captures
+                .get(1)
+                .or_else(|| captures.get(2))
+                .map(|m| m.as_str())
+                .unwrap_or_default();
+            self.protect(raw.to_string())
+        });
+        let text = regex!(r"(^|[^\w`])`\+(.+?)\+`|(^|[^\w`])`([^`\s](?:[^`]*[^`\s])?)`")
+            .replace_all(&text, |captures: &Captures| {
+                let (prefix, code) = match captures.get(2) {
+                    Some(code) => (captures.get(1), code.as_str()),
+                    None => (captures.get(3), captures.get(4).map(|m| m.as_str()).unwrap_or_default()),
+                };
+                let prefix = prefix.map(|m| m.as_str()).unwrap_or_default();
+                format!(
+                    "{}{}",
+                    escape(prefix),
+                    self.protect(format!("<code>{}</code>", escape(code)))
+                )
+            })
+            .to_string();
+
+        let text = escape(&text);
+        let text = quotes(&text);
+        let text = self.substitute_attributes(&text);
+        let text = replacements(&text);
+        let text = self.macros(&text);
+
+        regex!(r"(?m) \+$").replace_all(&text, "<br>").to_string()
+    }
+
+    fn macros(&mut self, text: &str) -> String {
+        // Footnotes
+        let text = regex!(r"(?s)footnote:([\w-]*)\[(.*?)\]").replace_all(text, |captures: &Captures| {
+            self.footnotes.push(captures[2].to_string());
+            let n = self.footnotes.len();
+            let html = format!(
+                "<sup class=\"footnote\">[<a id=\"_footnoteref_{n}\" class=\"footnote\" href=\"#_footnotedef_{n}\" title=\"View footnote.\">{n}</a>]</sup>",
+                n = n
+            );
+            self.protect(html)
+        });
+
+        // Cross references: <<id>>, <<id,text>> and xref:id[text]
+        let text = regex!(r"&lt;&lt;([\w#:./-]+?)(?:,\s*(.+?))?&gt;&gt;|xref:([\w#:./-]+)\[(.*?)\]").replace_all(
+            &text,
+            |captures: &Captures| {
+                let (target, label) = match captures.get(1) {
+                    Some(target) => (target.as_str(), captures.get(2).map(|m| m.as_str())),
+                    None => (
+                        &captures[3],
+                        captures.get(4).map(|m| m.as_str()).filter(|l| !l.is_empty()),
+                    ),
+                };
+
+                let href = if target.contains(".adoc") || target.contains('#') {
+                    target.replace(".adoc", ".html")
+                } else {
+                    format!("#{}", target)
+                };
+
+                match label {
+                    Some(label) => self.protect(format!("<a href=\"{}\">{}</a>", href, label)),
+                    None => format!("{x}{}{x}", target, x = XREF),
+                }
+            },
+        );
+
+        // Inline images
+        let text = regex!(r"image:([^:\s\[][^\s\[]*)\[(.*?)\]").replace_all(&text, |captures: &Captures| {
+            let img = self.image(&captures[1], &captures[2]);
+            self.protect(format!("<span class=\"image\">{}</span>", img))
+        });
+
+        // Links to URLs, with or without the link macro and text
+        let text = regex!(r"(link:)?((?:https?|ftp|irc)://(?:[^\s\[\]<>&]|&amp;)+)(?:\[(.*?)\])?").replace_all(
+            &text,
+            |captures: &Captures| {
+                let mut url = captures[2].to_string();
+                match captures.get(3) {
+                    Some(label) if !label.as_str().is_empty() => {
+                        self.protect(format!("<a href=\"{}\">{}</a>", url, label.as_str()))
+                    }
+                    Some(_) => self.protect(format!("<a href=\"{url}\" class=\"bare\">{url}</a>", url = url)),
+                    None => {
+                        // Trailing punctuation is not part of a bare URL
+                        let mut trailing = String::new();
+                        while url.ends_with(['.', ',', ';', ':', ')', '!', '?']) {
+                            trailing.insert(0, url.pop().unwrap());
+                        }
+                        let link = self.protect(format!("<a href=\"{url}\" class=\"bare\">{url}</a>", url = url));
+                        format!("{}{}", link, trailing)
+                    }
+                }
+            },
+        );
+
+        // Links relative to the document
+        let text = regex!(r"link:([^\s\[]+)\[(.*?)\]").replace_all(&text, |captures: &Captures| {
+            let label = if captures[2].is_empty() {
+                &captures[1]
+            } else {
+                &captures[2]
+            };
+            self.protect(format!("<a href=\"{}\">{}</a>", &captures[1], label))
+        });
+
+        // Email addresses
+        let text = regex!(r"(^|[\s(>])([\w.%+-]+@[\w-]+(?:\.[\w-]+)+)").replace_all(&text, |captures: &Captures| {
+            let link = self.protect(format!("<a href=\"mailto:{email}\">{email}</a>", email = &captures[2]));
+            format!("{}{}", &captures[1], link)
+        });
+
+        text.to_string()
+    }
+}
+
+/// Apply strong, emphasis and monospace quotes. Unconstrained (doubled) quotes apply anywhere,
+/// constrained quotes only around whole words.
+fn quotes(text: &str) -> String {
+    let text = regex!(r"\*\*(.+?)\*\*").replace_all(text, "<strong>$1</strong>");
+    let text = regex!(r"__(.+?)__").replace_all(&text, "<em>$1</em>");
+    let text = regex!(r"##(.+?)##").replace_all(&text, "<mark>$1</mark>");
+
+    let text = constrained(&text, '*', "strong");
+    let text = constrained(&text, '_', "em");
+    let text = regex!(r"\^(\S+?)\^").replace_all(&text, "<sup>$1</sup>");
+    regex!(r"~(\S+?)~").replace_all(&text, "<sub>$1</sub>").to_string()
+}
+
+/// Replace `marker`text`marker` with a tag, where the opening marker is not preceded by a word
+/// character and the closing marker is not followed by one.
+fn constrained(text: &str, marker: char, tag: &str) -> String {
+    let chars = text.chars().collect::<Vec<_>>();
+    let is_word = |c: Option<&char>| c.map(|c| c.is_alphanumeric() || *c == marker).unwrap_or(false);
+
+    let mut out = String::with_capacity(text.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let opens = chars[i] == marker
+            && !is_word(if i > 0 { chars.get(i - 1) } else { None })
+            && chars
+                .get(i + 1)
+                .map(|c| !c.is_whitespace() && *c != marker)
+                .unwrap_or(false);
+
+        if opens {
+            let close = (i + 1..chars.len()).find(|&j| {
+                chars[j] == marker
+                    && !chars[j - 1].is_whitespace()
+                    && !is_word(chars.get(j + 1))
+                    && (j + 1 >= chars.len() || chars[j + 1] != marker)
+                    && chars[j - 1] != '\n'
+            });
+
+            if let Some(close) = close {
+                let inner = chars[i + 1..close].iter().collect::<String>();
+                if !inner.contains("\n\n") {
+                    out.push_str(&format!("<{tag}>{inner}</{tag}>", tag = tag, inner = inner));
+                    i = close + 1;
+                    continue;
+                }
+            }
+        }
+
+        out.push(chars[i]);
+        i += 1;
+    }
+
+    out
+}
+
+/// Typographic replacements on text that has already been escaped.
+fn replacements(text: &str) -> String {
+    let text = text
+        .replace("(C)", "&#169;")
+        .replace("(R)", "&#174;")
+        .replace("(TM)", "&#8482;")
+        .replace("...", "&#8230;&#8203;")
+        .replace("-&gt;", "&#8594;")
+        .replace("=&gt;", "&#8658;")
+        .replace("&lt;-", "&#8592;")
+        .replace("&lt;=", "&#8656;");
+
+    let text = regex!(r"(^|\s)--(\s|$)").replace_all(&text, "$1&#8212;$2");
+    let text = regex!(r"(\w)--(\w)").replace_all(&text, "$1&#8212;&#8203;$2");
+    regex!(r"(\w)'(\w)").replace_all(&text, "$1&#8217;$2").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn title_case(name: &str) -> String {
+    let lower = name.to_lowercase();
+    let mut chars = lower.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().collect::<String>() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//") && !line.starts_with("///")
+}
+
+fn is_block_attribute(line: &str) -> bool {
+    regex!(r"^\[[^\[\]]*\]$").is_match(line.trim_end())
+}
+
+fn is_indented_text(line: &str) -> bool {
+    (line.starts_with(' ') || line.starts_with('\t')) && !line.trim().is_empty() && list_marker(line).is_none()
+}
+
+/// The title of a block, e.g. `.A diagram of the system`.
+fn block_title(line: &str) -> Option<&str> {
+    let title = line.strip_prefix('.')?;
+    match title.chars().next() {
+        Some(c) if !c.is_whitespace() && c != '.' => Some(title),
+        _ => None,
+    }
+}
+
+/// The kind of delimited block that a line opens, if any. Open blocks (`--`) are returned as `o`.
+fn delimiter(line: &str) -> Option<char> {
+    if line == "--" {
+        return Some('o');
+    }
+    if line == "|===" {
+        return Some('|');
+    }
+    if line.starts_with("```") {
+        return Some('`');
+    }
+
+    let first = line.chars().next()?;
+    if line.len() >= 4 && "-._=*+".contains(first) && line.chars().all(|c| c == first) {
+        Some(first)
+    } else {
+        None
+    }
+}
+
+/// The index of the line that closes the delimited block opened at `start`, or the end of the
+/// document if the block is never closed.
+fn closing_delimiter(lines: &[&str], start: usize, delimiter: &str) -> usize {
+    // Fenced code blocks are closed by a fence without a language
+    let delimiter = if delimiter.starts_with("```") { "```" } else { delimiter };
+
+    (start + 1..lines.len())
+        .find(|&i| lines[i].trim_end() == delimiter)
+        .unwrap_or(lines.len())
+}
+
+/// The index of the line that ends a paragraph starting at `start`.
+fn paragraph_end(lines: &[&str], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < lines.len() {
+        let line = lines[i].trim_end();
+        if line.trim().is_empty() || delimiter(line).is_some() || is_block_attribute(line) {
+            break;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// The index of the line that ends a block attached to a list item with `+`.
+fn attached_block_end(lines: &[&str], start: usize) -> usize {
+    match lines.get(start).map(|line| line.trim_end()) {
+        Some(line) if delimiter(line).is_some() => closing_delimiter(lines, start, line) + 1,
+        Some(line) if is_block_attribute(line) || block_title(line).is_some() => {
+            attached_block_end(lines, start + 1).max(start + 1)
+        }
+        Some(_) => {
+            let mut i = start;
+            while i < lines.len()
+                && !lines[i].trim().is_empty()
+                && lines[i].trim() != "+"
+                && list_marker(lines[i]).is_none()
+            {
+                i += 1;
+            }
+            i
+        }
+        None => start,
+    }
+}
+
+fn skip_blank(lines: &[&str], start: usize) -> usize {
+    let mut i = start;
+    while i < lines.len() && lines[i].trim().is_empty() {
+        i += 1;
+    }
+    i
+}
+
+/// Remove the common leading whitespace of the lines of a literal paragraph.
+fn unindent(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The kind, marker and text of a list item, e.g. `** nested` or `. first`.
+fn list_marker(line: &str) -> Option<(ListKind, String, &str)> {
+    let captures = regex!(r"^\s*(\*{1,5}|-|\.{1,5}|\d+\.)\s+(\S.*)$").captures(line)?;
+    let marker = captures.get(1)?.as_str();
+    let text = captures.get(2)?.as_str().trim_end();
+
+    if marker.starts_with('*') || marker == "-" {
+        Some((ListKind::Unordered, marker.to_string(), text))
+    } else if marker.starts_with('.') {
+        Some((ListKind::Ordered, marker.to_string(), text))
+    } else {
+        Some((ListKind::Ordered, ".".to_string(), text))
+    }
+}
+
+/// The term and definition of a description list item, e.g. `CPU:: Central processing unit`.
+fn dlist_item(line: &str) -> Option<(String, String)> {
+    if line.starts_with(' ') || line.starts_with('\t') || line.starts_with("//") {
+        return None;
+    }
+
+    let captures = regex!(r"^(.*?[^:]):{2,4}(?:\s+(.*))?$").captures(line.trim_end())?;
+    let term = captures[1].trim();
+
+    // Avoid treating URLs and attribute entries as terms
+    if term.is_empty() || term.starts_with(':') || term.contains("://") {
+        return None;
+    }
+
+    Some((
+        term.to_string(),
+        captures
+            .get(2)
+            .map(|m| m.as_str().trim().to_string())
+            .unwrap_or_default(),
+    ))
+}
+
+/// Split a block attribute list on commas, keeping quoted values together.
+fn split_attributes(list: &str) -> Vec<String> {
+    let mut attrs = vec![];
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in list.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            ',' if !quoted => {
+                attrs.push(current.trim().to_string());
+                current.clear();
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if !current.trim().is_empty() || !attrs.is_empty() {
+        attrs.push(current.trim().to_string());
+    }
+
+    attrs
+}
+
+/// The relative widths of the columns in a `cols` attribute, e.g. `1,2`, `3*` or `<1,^2a`.
+fn column_widths(cols: &str) -> Vec<f64> {
+    let mut widths = vec![];
+
+    for col in cols.split([',', ';']) {
+        let col = col.trim();
+        let (count, spec) = match col.split_once('*') {
+            Some((count, spec)) => (count.parse::<usize>().unwrap_or(1), spec),
+            None => (1, col),
+        };
+
+        let width = regex!(r"(\d+)")
+            .captures(spec)
+            .and_then(|captures| captures[1].parse::<f64>().ok())
+            .unwrap_or(1.0);
+
+        widths.extend(vec![width; count]);
+    }
+
+    if widths.is_empty() {
+        widths.push(1.0);
+    }
+
+    widths
+}
+
+fn format_width(percent: f64) -> String {
+    let formatted = format!("{:.4}", percent);
+    formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_section_ids() {
+        let mut converter = Converter::default();
+
+        assert_eq!(converter.section_id("Background", None), "_background");
+        assert_eq!(converter.section_id("The First Option", None), "_the_first_option");
+        assert_eq!(converter.section_id("What's `next`?", None), "_whats_next");
+        assert_eq!(converter.section_id("Background", None), "_background_2");
+        assert_eq!(converter.section_id("Ignored", Some("custom".to_string())), "custom");
+    }
+
+    #[test]
+    fn converts_inline_formatting() {
+        let html = asciidoc_to_html("Some *bold*, _emphasis_ and `a < b` text -- with a https://example.com[link].");
+
+        assert_eq!(
+            html,
+            "<div class=\"paragraph\">\n<p>Some <strong>bold</strong>, <em>emphasis</em> and <code>a &lt; b</code> text &#8212; with a <a href=\"https://example.com\">link</a>.</p>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn drops_stray_markers() {
+        assert_eq!(asciidoc_to_html("\u{E000}"), "");
+        assert_eq!(
+            asciidoc_to_html("a \u{E000} b `c` \u{E000}0\u{E000} \u{E000}x\u{E001}"),
+            "<div class=\"paragraph\">\n<p>a  b <code>c</code> 0 x</p>\n</div>\n"
+        );
+    }
+
+    #[test]
+    fn leaves_words_with_markers_alone() {
+        let html = asciidoc_to_html("snake_case_name and 2*3*4 stay as is");
+
+        assert_eq!(
+            html,
+            "<div class=\"paragraph\">\n<p>snake_case_name and 2*3*4 stay as is</p>\n</div>\n"
+        );
+    }
+}
@@ -1,54 +1,535 @@
-use anyhow::Result;
-use chrono::{Duration, Utc};
-use sendgrid_api::{traits::MailOps, Client as SendGrid};
+//! The weekly digest of changes to RFDs.
+//!
+//! Changes are grouped in to newly created RFDs, RFDs that changed state and RFDs whose content was
+//! updated. The digest is emailed to each configured subscriber, limited to the RFDs they asked
+//! for, and optionally posted in full to a Slack channel.
+use std::collections::BTreeMap;
 
-use super::RFDs;
-use crate::companies::Company;
-use crate::db::Database;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use handlebars::Handlebars;
+use log::{info, warn};
+use sendgrid_api::{
+    types::{Content, FromEmailObject, Personalizations, PostMailSendRequest, ReplyTo},
+    Client as SendGrid,
+};
+use serde::Serialize;
+use serde_json::json;
+use slack_chat_api::{blocks::SectionBlock, FormattedMessage};
 
-/// Create a changelog email for the RFDs.
-pub async fn send_rfd_changelog(db: &Database, company: &Company) -> Result<()> {
-    let rfds = RFDs::get_from_db(db, company.id).await?;
+use super::{RFDState, RFDStateChange, RFDs};
+use crate::{
+    app_config::{RFDChangelogSubscriber, RFDConfig},
+    audit::audit,
+    companies::Company,
+    db::Database,
+};
 
-    if rfds.0.is_empty() {
-        // Return early.
-        return Ok(());
+/// Slack rejects section blocks with more text than this.
+const SLACK_SECTION_LIMIT: usize = 3000;
+
+static TEMPLATE_CHANGELOG_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<body style="font-family: -apple-system, Helvetica, Arial, sans-serif; line-height: 1.5;">
+<h1>RFD changelog</h1>
+<p>Changes to RFDs from {{since}} to {{until}}.</p>
+{{#each sections}}
+<h2>{{title}}</h2>
+<ul>
+{{#each entries}}
+<li>
+<a href="{{url}}">{{name}}</a>{{#if change.from}} moved from <code>{{change.from}}</code> to <code>{{change.to}}</code>{{/if}}{{#if change.state}} in <code>{{change.state}}</code>{{/if}}
+{{#if commits}}
+<ul>
+{{#each commits}}
+<li><a href="{{url}}">{{message}}</a>{{#if author}} by @{{author}}{{/if}}</li>
+{{/each}}
+</ul>
+{{/if}}
+</li>
+{{/each}}
+</ul>
+{{/each}}
+</body>
+</html>
+"#;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RFDChangelogCommit {
+    pub message: String,
+    pub author: Option<String>,
+    pub sha: String,
+    pub url: String,
+}
+
+/// How an RFD changed over the period of the changelog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RFDChange {
+    New { state: RFDState },
+    StateChange { from: RFDState, to: RFDState },
+    Updated,
+}
+
+impl RFDChange {
+    /// Summarise the state changes of a single RFD, oldest first. An RFD that ends up back in the
+    /// state it started in is considered updated.
+    pub fn from_state_changes(changes: &[RFDStateChange]) -> RFDChange {
+        match (changes.first(), changes.last()) {
+            (Some(first), Some(last)) => match first.from_state {
+                None => RFDChange::New { state: last.to_state },
+                Some(from) if from != last.to_state => RFDChange::StateChange {
+                    from,
+                    to: last.to_state,
+                },
+                Some(_) => RFDChange::Updated,
+            },
+            _ => RFDChange::Updated,
+        }
     }
 
-    let github = company.authenticate_github()?;
-    let seven_days_ago = Utc::now() - Duration::days(7);
-    let week_format = format!(
-        "from {} to {}",
-        seven_days_ago.format("%m-%d-%Y"),
-        Utc::now().format("%m-%d-%Y")
-    );
+    fn summary(&self) -> String {
+        match self {
+            RFDChange::New { state } => format!(" was created in {}", state),
+            RFDChange::StateChange { from, to } => format!(" moved from {} to {}", from, to),
+            RFDChange::Updated => String::new(),
+        }
+    }
+}
 
-    let mut changelog = format!("Changes to RFDs for the week {}:\n", week_format);
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RFDChangelogEntry {
+    pub number: i32,
+    pub name: String,
+    pub url: String,
+    pub authors: String,
+    pub labels: Vec<String>,
+    pub change: RFDChange,
+    pub commits: Vec<RFDChangelogCommit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RFDChangelog {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub entries: Vec<RFDChangelogEntry>,
+}
 
-    // Iterate over the RFDs.
-    for rfd in rfds {
-        let changes = rfd.get_weekly_changelog(&github, seven_days_ago, company).await?;
-        let url = format!("https://rfd.shared.oxide.computer/rfd/{}", rfd.number_string);
-        if !changes.is_empty() {
-            changelog += &format!("\n{} {}\n{}", rfd.name, url, changes);
+#[derive(Debug, Serialize)]
+struct RFDChangelogSection<'a> {
+    title: &'static str,
+    entries: Vec<&'a RFDChangelogEntry>,
+}
+
+impl RFDChangelog {
+    /// Collect the changes made to the RFDs of a company between `since` and `until`.
+    pub async fn collect(
+        db: &Database,
+        company: &Company,
+        config: &RFDConfig,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<Self> {
+        let rfds = RFDs::get_from_db(db, company.id).await?;
+        let github = company.authenticate_github()?;
+
+        let mut state_changes: BTreeMap<i32, Vec<RFDStateChange>> = BTreeMap::new();
+        for change in RFDStateChange::since(db, company.id, since).await? {
+            state_changes.entry(change.rfd_number).or_default().push(change);
         }
+
+        let mut entries = vec![];
+
+        for rfd in rfds {
+            let commits = rfd.get_changelog_commits(&github, since, company).await?;
+            let changes = state_changes.remove(&rfd.number).unwrap_or_default();
+
+            if commits.is_empty() && changes.is_empty() {
+                continue;
+            }
+
+            entries.push(RFDChangelogEntry {
+                number: rfd.number,
+                name: rfd.name.to_string(),
+                url: config
+                    .rendered_url(&rfd.number_string)
+                    .unwrap_or_else(|| rfd.rendered_link.to_string()),
+                authors: rfd.authors.to_string(),
+                labels: rfd.labels.clone(),
+                change: RFDChange::from_state_changes(&changes),
+                commits,
+            });
+        }
+
+        Ok(RFDChangelog { since, until, entries })
     }
 
-    // Initialize the SendGrid clVient.
-    let sendgrid_client = SendGrid::new_from_env();
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
 
-    // Send the message.
-    sendgrid_client
-        .mail_send()
-        .send_plain_text(
-            &format!("RFD changelog for the week from {}", week_format),
-            &changelog,
-            &[format!("all@{}", company.gsuite_domain)],
-            &[],
-            &[],
-            &format!("rfds@{}", company.gsuite_domain),
+    /// The part of the changelog that the subscriber is interested in.
+    pub fn for_subscriber(&self, subscriber: &RFDChangelogSubscriber) -> RFDChangelog {
+        RFDChangelog {
+            since: self.since,
+            until: self.until,
+            entries: self
+                .entries
+                .iter()
+                .filter(|entry| subscriber.wants(&entry.labels, &entry.authors))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    fn period(&self) -> String {
+        format!(
+            "from {} to {}",
+            self.since.format("%m-%d-%Y"),
+            self.until.format("%m-%d-%Y")
         )
+    }
+
+    fn sections(&self) -> Vec<RFDChangelogSection<'_>> {
+        let section = |title, matches: fn(&RFDChange) -> bool| RFDChangelogSection {
+            title,
+            entries: self.entries.iter().filter(|entry| matches(&entry.change)).collect(),
+        };
+
+        vec![
+            section("New RFDs", |change| matches!(change, RFDChange::New { .. })),
+            section("State changes", |change| {
+                matches!(change, RFDChange::StateChange { .. })
+            }),
+            section("Updated", |change| matches!(change, RFDChange::Updated)),
+        ]
+        .into_iter()
+        .filter(|section| !section.entries.is_empty())
+        .collect()
+    }
+
+    pub fn to_html(&self) -> Result<String> {
+        Ok(Handlebars::new().render_template(
+            TEMPLATE_CHANGELOG_HTML,
+            &json!({
+                "since": self.since.format("%m-%d-%Y").to_string(),
+                "until": self.until.format("%m-%d-%Y").to_string(),
+                "sections": self.sections(),
+            }),
+        )?)
+    }
+
+    /// A plain text version of the changelog for email clients that do not display HTML.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("Changes to RFDs {}:\n", self.period());
+
+        for section in self.sections() {
+            text += &format!("\n{}\n", section.title);
+
+            for entry in section.entries {
+                text += &format!("\n{}{} {}\n", entry.name, entry.change.summary(), entry.url);
+
+                for commit in &entry.commits {
+                    match &commit.author {
+                        Some(author) => text += &format!("\t- \"{}\" by @{}\n", commit.message, author),
+                        None => text += &format!("\t- \"{}\"\n", commit.message),
+                    }
+                    text += &format!("\t\t{}\n", commit.url);
+                }
+            }
+        }
+
+        text
+    }
+
+    pub fn to_slack_message(&self, channel: &str) -> FormattedMessage {
+        let mut texts = vec![format!("*RFD changelog {}*", self.period())];
+
+        for section in self.sections() {
+            let mut text = format!("*{}*", section.title);
+
+            for entry in section.entries {
+                let line = format!(
+                    "\n• <{}|{}>{} ({} {})",
+                    entry.url,
+                    slack_escape(&entry.name),
+                    entry.change.summary(),
+                    entry.commits.len(),
+                    if entry.commits.len() == 1 { "commit" } else { "commits" }
+                );
+
+                // Start a new block rather than exceed the size limit of a single one
+                if text.len() + line.len() > SLACK_SECTION_LIMIT {
+                    texts.push(text);
+                    text = String::new();
+                }
+
+                text += &line;
+            }
+
+            texts.push(text);
+        }
+
+        FormattedMessage {
+            channel: channel.to_string(),
+            blocks: texts
+                .into_iter()
+                .map(|text| SectionBlock::markdown(text).into())
+                .collect(),
+            attachments: Default::default(),
+        }
+    }
+}
+
+/// Escape the characters that Slack uses for its own markup.
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Send an email through SendGrid with both an HTML and a plain text body.
+async fn send_html_email(subject: &str, html: &str, text: &str, to: &str, from: &str) -> Result<()> {
+    let sendgrid = SendGrid::new_from_env();
+
+    sendgrid
+        .mail_send()
+        .post(&PostMailSendRequest {
+            personalizations: vec![Personalizations {
+                to: vec![ReplyTo {
+                    email: to.to_string(),
+                    name: String::new(),
+                }],
+                subject: subject.to_string(),
+                ..Default::default()
+            }],
+            from: FromEmailObject {
+                email: from.to_string(),
+                name: String::new(),
+            },
+            subject: subject.to_string(),
+            content: vec![
+                Content {
+                    type_: "text/plain".to_string(),
+                    value: text.to_string(),
+                },
+                Content {
+                    type_: "text/html".to_string(),
+                    value: html.to_string(),
+                },
+            ],
+            ..Default::default()
+        })
         .await?;
 
     Ok(())
 }
+
+/// Send the changelog of the last week to the subscribers and Slack channel of the company.
+pub async fn send_rfd_changelog(db: &Database, company: &Company, config: &RFDConfig) -> Result<()> {
+    let until = Utc::now();
+    let since = until - Duration::days(7);
+
+    let changelog = RFDChangelog::collect(db, company, config, since, until).await?;
+
+    if changelog.is_empty() {
+        info!("No RFDs changed {}, skipping the changelog", changelog.period());
+        return Ok(());
+    }
+
+    let subject = format!("RFD changelog for the week {}", changelog.period());
+    let from = format!("rfds@{}", company.gsuite_domain);
+
+    // Keep going when an email fails, so one bad address does not keep the changelog from everyone
+    // else.
+    let mut failures = vec![];
+    for subscriber in config.changelog.subscribers(company) {
+        let changelog = changelog.for_subscriber(&subscriber);
+
+        if changelog.is_empty() {
+            continue;
+        }
+
+        let sent = match changelog.to_html() {
+            Ok(html) => {
+                let result = send_html_email(&subject, &html, &changelog.to_text(), &subscriber.email, &from).await;
+                audit(db, company, "sendgrid", "send_rfd_changelog", &subscriber.email, result).await
+            }
+            Err(err) => Err(err),
+        };
+        if let Err(err) = sent {
+            warn!("Failed to send the RFD changelog to {}: {}", subscriber.email, err);
+            failures.push(format!("{}: {}", subscriber.email, err));
+        }
+    }
+
+    if let Some(channel) = &config.changelog.slack_channel {
+        company
+            .post_to_slack_channel(db, &changelog.to_slack_message(channel))
+            .await?;
+    }
+
+    if !failures.is_empty() {
+        bail!(
+            "sending the RFD changelog failed for {} subscribers: {}",
+            failures.len(),
+            failures.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn state_change(from: Option<RFDState>, to: RFDState) -> RFDStateChange {
+        RFDStateChange {
+            id: 1,
+            cio_company_id: 1,
+            rfd_number: 1,
+            from_state: from,
+            to_state: to,
+            branch: "0001".to_string(),
+            commit_sha: String::new(),
+            actor: String::new(),
+            changed_at: Utc::now(),
+        }
+    }
+
+    fn entry(number: i32, change: RFDChange, labels: &[&str]) -> RFDChangelogEntry {
+        RFDChangelogEntry {
+            number,
+            name: format!("RFD {} <Title>", number),
+            url: format!("https://rfd.example.com/rfd/{:04}", number),
+            authors: "Jane Doe <jane@example.com>".to_string(),
+            labels: labels.iter().map(|label| label.to_string()).collect(),
+            change,
+            commits: vec![RFDChangelogCommit {
+                message: "Fix typo".to_string(),
+                author: Some("jdoe".to_string()),
+                sha: "abc123".to_string(),
+                url: "https://github.com/org/rfd/commit/abc123".to_string(),
+            }],
+        }
+    }
+
+    fn mock_changelog() -> RFDChangelog {
+        RFDChangelog {
+            since: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            until: Utc.with_ymd_and_hms(2024, 1, 8, 0, 0, 0).unwrap(),
+            entries: vec![
+                entry(1, RFDChange::Updated, &["storage"]),
+                entry(
+                    2,
+                    RFDChange::New {
+                        state: RFDState::Prediscussion,
+                    },
+                    &[],
+                ),
+                entry(
+                    3,
+                    RFDChange::StateChange {
+                        from: RFDState::Discussion,
+                        to: RFDState::Published,
+                    },
+                    &["networking"],
+                ),
+            ],
+        }
+    }
+
+    #[test]
+    fn test_summarises_state_changes() {
+        assert_eq!(RFDChange::Updated, RFDChange::from_state_changes(&[]));
+        assert_eq!(
+            RFDChange::New {
+                state: RFDState::Discussion
+            },
+            RFDChange::from_state_changes(&[
+                state_change(None, RFDState::Ideation),
+                state_change(Some(RFDState::Ideation), RFDState::Discussion),
+            ])
+        );
+        assert_eq!(
+            RFDChange::StateChange {
+                from: RFDState::Discussion,
+                to: RFDState::Committed
+            },
+            RFDChange::from_state_changes(&[
+                state_change(Some(RFDState::Discussion), RFDState::Published),
+                state_change(Some(RFDState::Published), RFDState::Committed),
+            ])
+        );
+        assert_eq!(
+            RFDChange::Updated,
+            RFDChange::from_state_changes(&[
+                state_change(Some(RFDState::Discussion), RFDState::Ideation),
+                state_change(Some(RFDState::Ideation), RFDState::Discussion),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_groups_changelog_html() {
+        let html = mock_changelog().to_html().unwrap();
+
+        let new = html.find("<h2>New RFDs</h2>").unwrap();
+        let state = html.find("<h2>State changes</h2>").unwrap();
+        let updated = html.find("<h2>Updated</h2>").unwrap();
+        assert!(new < state && state < updated);
+
+        assert!(html.contains("Changes to RFDs from 01-01-2024 to 01-08-2024."));
+        assert!(html.contains(
+            r#"<a href="https://rfd.example.com/rfd/0003">RFD 3 &lt;Title&gt;</a> moved from <code>discussion</code> to <code>published</code>"#
+        ));
+        assert!(html.contains(r#" in <code>prediscussion</code>"#));
+        assert!(html.contains(r#"<li><a href="https://github.com/org/rfd/commit/abc123">Fix typo</a> by @jdoe</li>"#));
+    }
+
+    #[test]
+    fn test_changelog_text() {
+        let text = mock_changelog().to_text();
+
+        assert!(text.starts_with("Changes to RFDs from 01-01-2024 to 01-08-2024:\n"));
+        assert!(text.contains("\nRFD 3 <Title> moved from discussion to published https://rfd.example.com/rfd/0003\n"));
+        assert!(text.contains("\t- \"Fix typo\" by @jdoe\n\t\thttps://github.com/org/rfd/commit/abc123\n"));
+    }
+
+    #[test]
+    fn test_filters_changelog_for_subscriber() {
+        let subscriber = RFDChangelogSubscriber {
+            email: "storage@example.com".to_string(),
+            labels: vec!["storage".to_string()],
+            authors: vec![],
+        };
+
+        let changelog = mock_changelog().for_subscriber(&subscriber);
+        assert_eq!(
+            vec![1],
+            changelog.entries.iter().map(|entry| entry.number).collect::<Vec<_>>()
+        );
+
+        let html = changelog.to_html().unwrap();
+        assert!(html.contains("<h2>Updated</h2>"));
+        assert!(!html.contains("<h2>New RFDs</h2>"));
+    }
+
+    #[test]
+    fn test_changelog_slack_message() {
+        let msg = mock_changelog().to_slack_message("#rfd");
+
+        assert_eq!("#rfd", msg.channel);
+        assert_eq!(4, msg.blocks.len());
+
+        let text = msg.blocks[2]
+            .as_section()
+            .and_then(|section| section.text.as_ref())
+            .unwrap();
+        assert_eq!(
+            "*State changes*\n• <https://rfd.example.com/rfd/0003|RFD 3 &lt;Title&gt;> moved from discussion to published (1 commit)",
+            text.text()
+        );
+    }
+}
@@ -8,11 +8,18 @@ use std::{
     path::{Path, PathBuf},
     process::Command,
     str::from_utf8,
+    sync::Arc,
 };
 use uuid::Uuid;
 
-use super::{GitHubRFDBranch, RFDNumber, RFDPdf};
-use crate::utils::{decode_base64, write_file};
+use super::{
+    render::{default_renderer, RFDRenderer},
+    GitHubRFDBranch, RFDNumber, RFDPdf, RFDState,
+};
+use crate::{
+    configs::User,
+    utils::{decode_base64, write_file},
+};
 
 // TODO: RFDNumber should probably be stored with the content as it doesn't parsing content with a
 // mismatched RFDNumber is pretty nonsensical.
@@ -117,10 +124,7 @@ impl<'a> RFDContent<'a> {
         branch: &GitHubRFDBranch,
     ) -> Result<RFDPdf, RFDOutputError> {
         match self {
-            Self::Asciidoc(adoc) => adoc
-                .to_pdf(title, number, branch)
-                .await
-                .map_err(RFDOutputError::Generic),
+            Self::Asciidoc(adoc) => adoc.to_pdf(title, number, branch).await,
             _ => Err(RFDOutputError::FormatNotSupported(RFDOutputFormat::Pdf)),
         }
     }
@@ -295,11 +299,247 @@ impl<'a> RFDContent<'a> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RFDLintLevel {
+    Warning,
+    Failure,
+}
+
+/// A problem with the header of an RFD, along with the line of the document it was found on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RFDLintViolation {
+    pub level: RFDLintLevel,
+    /// The 1-based line of the document the violation applies to.
+    pub line: usize,
+    pub title: String,
+    pub message: String,
+}
+
+impl RFDLintViolation {
+    fn new(level: RFDLintLevel, line: usize, title: &str, message: String) -> Self {
+        Self {
+            level,
+            line,
+            title: title.to_string(),
+            message,
+        }
+    }
+}
+
+/// A person that is allowed to author RFDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RFDKnownAuthor {
+    pub name: String,
+    pub emails: Vec<String>,
+}
+
+impl From<&User> for RFDKnownAuthor {
+    fn from(user: &User) -> Self {
+        let mut emails = vec![user.email.to_lowercase()];
+
+        // Aliases are addresses at the same domain as the primary email
+        if let Some((_, domain)) = user.email.split_once('@') {
+            emails.extend(
+                user.aliases
+                    .iter()
+                    .map(|alias| format!("{}@{}", alias, domain).to_lowercase()),
+            );
+        }
+
+        Self {
+            name: format!("{} {}", user.first_name, user.last_name).trim().to_string(),
+            emails,
+        }
+    }
+}
+
+/// The facts about the world outside of a document that the linter checks the document against.
+#[derive(Debug, Default, Clone)]
+pub struct RFDLintRules {
+    /// The people that may author an RFD. Authors are not resolved when this is empty.
+    pub authors: Vec<RFDKnownAuthor>,
+    /// The labels that may be applied to an RFD. Labels are not checked when this is empty.
+    pub labels: Vec<String>,
+}
+
+impl RFDLintRules {
+    fn resolves(&self, author: &str) -> bool {
+        let (name, email) = match author.split_once('<') {
+            Some((name, email)) => (name.trim(), Some(email.trim_end_matches('>').trim().to_lowercase())),
+            None => (author.trim(), None),
+        };
+
+        self.authors.iter().any(|known| {
+            email
+                .as_ref()
+                .map(|email| known.emails.contains(email))
+                .unwrap_or(false)
+                || (!name.is_empty() && known.name.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+impl<'a> RFDContent<'a> {
+    /// Check the header block of the document: the title, authors, state, discussion link and
+    /// labels. The returned violations are ordered by line.
+    pub fn lint(&self, number: &RFDNumber, rules: &RFDLintRules) -> Vec<RFDLintViolation> {
+        let raw = self.raw();
+        let mut violations = vec![];
+
+        // These are static regular expressions and can be safely unwrapped.
+        let (title_pattern, attribute_prefix) = match self {
+            Self::Asciidoc(_) => (Regex::new(r"(?m)^= .*$").unwrap(), ":"),
+            Self::Markdown(_) => (Regex::new(r"(?m)^# .*$").unwrap(), ""),
+        };
+        let title_format = Regex::new(r"^[=#] RFD (\d+) \S.*$").unwrap();
+        let discussion_format = Regex::new(r"^https://github\.com/[^/\s]+/[^/\s]+/pull/\d+/?$").unwrap();
+
+        let attribute = |name: &str| -> Option<(usize, String)> {
+            let pattern = Regex::new(&format!(r"(?m)^{}{}:(.*)$", attribute_prefix, name)).unwrap();
+            pattern
+                .captures(raw)
+                .and_then(|captures| captures.get(0).zip(captures.get(1)))
+                .map(|(line, value)| (line_number(raw, line.start()), value.as_str().trim().to_string()))
+        };
+
+        // Title
+        let title = title_pattern.find(raw);
+        let title_line = title.map(|title| line_number(raw, title.start())).unwrap_or(1);
+
+        match title.map(|title| title.as_str().trim_end()) {
+            None => violations.push(RFDLintViolation::new(
+                RFDLintLevel::Failure,
+                1,
+                "Missing title",
+                format!("The document needs a title of the form `RFD {} <title>`", number),
+            )),
+            Some(title) => match title_format.captures(title).and_then(|captures| captures.get(1)) {
+                Some(found) if found.as_str().parse::<i32>().ok() == Some(number.into()) => {}
+                Some(found) => violations.push(RFDLintViolation::new(
+                    RFDLintLevel::Failure,
+                    title_line,
+                    "Mismatched RFD number",
+                    format!(
+                        "The title refers to RFD {}, but the document is RFD {}",
+                        found.as_str(),
+                        number
+                    ),
+                )),
+                None => violations.push(RFDLintViolation::new(
+                    RFDLintLevel::Failure,
+                    title_line,
+                    "Invalid title",
+                    format!("The title `{}` should be of the form `RFD {} <title>`", title, number),
+                )),
+            },
+        }
+
+        // Authors
+        let authors = self.get_authors();
+        let authors_line = attribute("authors").map(|(line, _)| line).unwrap_or(title_line + 1);
+
+        if authors.is_empty() {
+            violations.push(RFDLintViolation::new(
+                RFDLintLevel::Failure,
+                authors_line,
+                "Missing authors",
+                "The document needs to list at least one author".to_string(),
+            ));
+        } else if !rules.authors.is_empty() {
+            for author in authors.split(',').map(str::trim).filter(|author| !author.is_empty()) {
+                if !rules.resolves(author) {
+                    violations.push(RFDLintViolation::new(
+                        RFDLintLevel::Warning,
+                        authors_line,
+                        "Unknown author",
+                        format!("`{}` does not match the name or email of any known user", author),
+                    ));
+                }
+            }
+        }
+
+        // State
+        let state = attribute("state");
+        match &state {
+            None => violations.push(RFDLintViolation::new(
+                RFDLintLevel::Failure,
+                title_line,
+                "Missing state",
+                "The document needs a `state` attribute".to_string(),
+            )),
+            Some((line, value)) => {
+                if let Err(err) = value.parse::<RFDState>() {
+                    violations.push(RFDLintViolation::new(
+                        RFDLintLevel::Failure,
+                        *line,
+                        "Invalid state",
+                        format!(
+                            "{}, it must be one of: {}",
+                            err,
+                            RFDState::ALL.map(|state| state.as_str()).join(", ")
+                        ),
+                    ))
+                }
+            }
+        }
+
+        // Discussion
+        match attribute("discussion") {
+            Some((line, link)) if !link.is_empty() && !discussion_format.is_match(&link) => {
+                violations.push(RFDLintViolation::new(
+                    RFDLintLevel::Failure,
+                    line,
+                    "Invalid discussion link",
+                    format!("`{}` is not a link to a GitHub pull request", link),
+                ))
+            }
+            discussion => {
+                let in_discussion =
+                    state.as_ref().and_then(|(_, state)| state.parse().ok()) == Some(RFDState::Discussion);
+                let missing = discussion.as_ref().map(|(_, link)| link.is_empty()).unwrap_or(true);
+
+                if in_discussion && missing {
+                    violations.push(RFDLintViolation::new(
+                        RFDLintLevel::Warning,
+                        discussion.map(|(line, _)| line).unwrap_or(title_line),
+                        "Missing discussion link",
+                        "RFDs in discussion should link to the pull request they are discussed in".to_string(),
+                    ))
+                }
+            }
+        }
+
+        // Labels
+        if let Some((line, labels)) = attribute("labels") {
+            if !rules.labels.is_empty() {
+                for label in labels.split(';').map(str::trim).filter(|label| !label.is_empty()) {
+                    if !rules.labels.iter().any(|known| known == label) {
+                        violations.push(RFDLintViolation::new(
+                            RFDLintLevel::Warning,
+                            line,
+                            "Unknown label",
+                            format!("`{}` is not a label of the RFD repository", label),
+                        ));
+                    }
+                }
+            }
+        }
+
+        violations.sort_by_key(|violation| violation.line);
+        violations
+    }
+}
+
+fn line_number(content: &str, offset: usize) -> usize {
+    content[..offset].matches('\n').count() + 1
+}
+
 /// The text data of an Asciidoc RFD
 #[derive(Debug)]
 pub struct RFDAsciidoc<'a> {
     content: Cow<'a, str>,
     storage_id: Uuid,
+    renderer: Option<Arc<dyn RFDRenderer>>,
 }
 
 impl<'a> RFDAsciidoc<'a> {
@@ -307,14 +547,32 @@ impl<'a> RFDAsciidoc<'a> {
         Self {
             content,
             storage_id: Uuid::new_v4(),
+            renderer: None,
         }
     }
 
+    /// Use the given renderer for all output formats instead of the default renderers
+    pub fn with_renderer(mut self, renderer: Arc<dyn RFDRenderer>) -> Self {
+        self.renderer = Some(renderer);
+        self
+    }
+
     /// Generate an HTML string by combining RFD contents with static resources that are stored for
     /// a given RFD number on a specific branch
     pub async fn to_html(&self, number: &RFDNumber, branch: &GitHubRFDBranch) -> Result<RFDHtml> {
-        self.download_images(number, branch).await?;
+        if self
+            .renderer(RFDOutputFormat::Html)
+            .requires_local_images(RFDOutputFormat::Html)
+        {
+            self.download_images(number, branch).await?;
+        }
+
+        self.render_html(number).await
+    }
 
+    /// Generate an HTML string from the RFD contents alone. Images are expected to have been
+    /// downloaded already if the renderer needs them
+    pub async fn render_html(&self, number: &RFDNumber) -> Result<RFDHtml> {
         let mut html = RFDHtml(from_utf8(&self.parse(RFDOutputFormat::Html).await?)?.to_string());
         html.clean_links(&number.as_number_string());
 
@@ -323,8 +581,20 @@ impl<'a> RFDAsciidoc<'a> {
 
     /// Generate a PDF by combining RFD contents with static resources that are stored for a given
     /// RFD number on a specific branch. Markdown documents do not support PDF generation
-    pub async fn to_pdf(&self, title: &str, number: &RFDNumber, branch: &GitHubRFDBranch) -> Result<RFDPdf> {
-        self.download_images(number, branch).await?;
+    pub async fn to_pdf(
+        &self,
+        title: &str,
+        number: &RFDNumber,
+        branch: &GitHubRFDBranch,
+    ) -> Result<RFDPdf, RFDOutputError> {
+        if self
+            .renderer(RFDOutputFormat::Pdf)
+            .requires_local_images(RFDOutputFormat::Pdf)
+        {
+            self.download_images(number, branch)
+                .await
+                .map_err(RFDOutputError::Generic)?;
+        }
 
         let content = self.parse(RFDOutputFormat::Pdf).await?;
 
@@ -341,44 +611,19 @@ impl<'a> RFDAsciidoc<'a> {
         })
     }
 
-    /// Parse the asciidoc content and generate output data of the requested format. This relies on
-    /// invoking an external asciidoctor binary to perform the actual transformation.
-    async fn parse(&self, format: RFDOutputFormat) -> Result<Vec<u8>> {
-        info!("[asciidoc] Parsing asciidoc file");
-
-        // Create the path to the local tmp file for holding the asciidoc contents
-        let storage_path = self.tmp_path();
-        let file_path = storage_path.join("contents.adoc");
-
-        // // Write the contents to a temporary file.
-        write_file(&file_path, self.content.as_bytes()).await?;
-
-        info!("[asciidoc] Wrote file to temp dir {:?}", file_path);
-
-        let cmd_output = tokio::task::spawn_blocking(enclose! { (storage_path, file_path) move || {
-            info!("[asciidoc] Shelling out to asciidoctor {:?} / {:?}", storage_path, file_path);
-            let out = format.command(&storage_path, &file_path).output();
-
-            match &out {
-                Ok(_) => info!("[asciidoc] Command succeeded {:?} / {:?}", storage_path, file_path),
-                Err(err) => info!("[asciidoc] Command failed: {} {:?} / {:?}", err, storage_path, file_path)
-            };
+    fn renderer(&self, format: RFDOutputFormat) -> Arc<dyn RFDRenderer> {
+        self.renderer.clone().unwrap_or_else(|| default_renderer(format))
+    }
 
-            out
-        }})
-        .await??;
+    /// Parse the asciidoc content and generate output data of the requested format using the
+    /// configured renderer.
+    async fn parse(&self, format: RFDOutputFormat) -> Result<Vec<u8>, RFDOutputError> {
+        info!("[asciidoc] Parsing asciidoc file");
 
-        info!("[asciidoc] Completed asciidoc rendering");
+        let renderer = self.renderer(format);
+        let result = renderer.render(&self.content, format, &self.tmp_path()).await;
 
-        let result = if cmd_output.status.success() {
-            cmd_output.stdout
-        } else {
-            bail!(
-                "[rfds] running asciidoctor failed: {} {}",
-                from_utf8(&cmd_output.stdout)?,
-                from_utf8(&cmd_output.stderr)?
-            );
-        };
+        info!("[asciidoc] Completed asciidoc rendering with {:?}", renderer);
 
         if let Err(err) = self.cleanup_tmp_path() {
             log::error!("Failed to clean up temporary working files for {:?} {:?}", format, err);
@@ -386,7 +631,7 @@ impl<'a> RFDAsciidoc<'a> {
 
         info!("[asciidoc] Finished cleanup and returning");
 
-        Ok(result)
+        result
     }
 
     /// Downloads images that are stored on the provided GitHub branch for the given RFD number.
@@ -571,6 +816,7 @@ impl RFDHtml {
 
 #[cfg(test)]
 mod tests {
+    use super::super::render::{AsciidoctorRenderer, NativeRenderer};
     use super::*;
 
     #[test]
@@ -1001,6 +1247,101 @@ sdf
         assert_eq!(expected, &rfd.get_title());
     }
 
+    fn lint_rules() -> RFDLintRules {
+        RFDLintRules {
+            authors: vec![RFDKnownAuthor {
+                name: "FirstName LastName".to_string(),
+                emails: vec!["fname@company.org".to_string()],
+            }],
+            labels: vec!["storage".to_string(), "networking".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_lint_valid_asciidoc() {
+        let content = RFDContent::new_asciidoc(
+            r#":showtitle:
+:toc: left
+:numbered:
+:icons: font
+:state: discussion
+:discussion: https://github.com/company/rfd/pull/123
+:revremark: State: {state} | {discussion}
+:authors: FirstName LastName <fname@company.org>
+:labels: storage; networking
+
+= RFD 123 Linting
+{authors}
+
+Content
+"#,
+        );
+
+        assert_eq!(Vec::<RFDLintViolation>::new(), content.lint(&123.into(), &lint_rules()));
+    }
+
+    #[test]
+    fn test_lint_invalid_asciidoc() {
+        let content = RFDContent::new_asciidoc(
+            r#":showtitle:
+:state: drafting
+:discussion: https://example.com/discussions/5
+:authors: FirstName LastName <fname@company.org>, Someone Else <someone@else.org>
+:labels: storage; compute
+
+= RFD 12 Linting
+{authors}
+"#,
+        );
+
+        let violations = content.lint(&123.into(), &lint_rules());
+
+        assert_eq!(
+            vec![
+                (RFDLintLevel::Failure, 2, "Invalid state"),
+                (RFDLintLevel::Failure, 3, "Invalid discussion link"),
+                (RFDLintLevel::Warning, 4, "Unknown author"),
+                (RFDLintLevel::Warning, 5, "Unknown label"),
+                (RFDLintLevel::Failure, 7, "Mismatched RFD number"),
+            ],
+            violations
+                .iter()
+                .map(|violation| (violation.level, violation.line, violation.title.as_str()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "`Someone Else <someone@else.org>` does not match the name or email of any known user",
+            violations[2].message
+        );
+        assert_eq!("`compute` is not a label of the RFD repository", violations[3].message);
+    }
+
+    #[test]
+    fn test_lint_markdown() {
+        let content = RFDContent::new_markdown(
+            r#"---
+state: discussion
+---
+
+# Linting without a number
+"#,
+        );
+
+        let violations = content.lint(&7.into(), &RFDLintRules::default());
+
+        assert_eq!(
+            vec![
+                (RFDLintLevel::Failure, 5, "Invalid title"),
+                (RFDLintLevel::Warning, 5, "Missing discussion link"),
+                (RFDLintLevel::Failure, 6, "Missing authors"),
+            ],
+            violations
+                .iter()
+                .map(|violation| (violation.level, violation.line, violation.title.as_str()))
+                .collect::<Vec<_>>()
+        );
+    }
+
     fn test_rfd_content() -> &'static str {
         r#"
 :showtitle:
@@ -1045,8 +1386,8 @@ in velit.
     async fn test_asciidoc_to_html() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        let rfd = RFDAsciidoc::new(Cow::Borrowed(test_rfd_content()));
-        let expected = "<h1>RFD 123 Place</h1>\n<div class=\"paragraph\">\n<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit. Nunc et dignissim nisi. Donec ut libero in\ndolor tempor aliquam quis quis nisl. Proin sit amet nunc in orci suscipit placerat. Mauris\npellentesque fringilla lacus id gravida. Donec in velit luctus, elementum mauris eu, pellentesque\nmassa. In lectus orci, vehicula at aliquet nec, elementum eu nisi. Vivamus viverra imperdiet\nmalesuada.</p>\n</div>\n<div class=\"olist arabic\">\n<ol class=\"arabic\">\n<li>\n<p>Suspendisse blandit sem ligula, ac luctus metus condimentum non. Fusce enim purus, tincidunt ut\ntortor eget, sollicitudin vestibulum sem. Proin eu velit orci.</p>\n</li>\n<li>\n<p>Proin eu finibus velit. Morbi eget blandit neque.</p>\n</li>\n</ol>\n</div>\n<div class=\"listingblock\">\n<div class=\"content\">\n<pre class=\"highlight\"><code class=\"language-mermaid\" data-lang=\"mermaid\">graph TD;\n    A--&gt;B;\n    A--&gt;C;\n    B--&gt;D;\n    C--&gt;D;</code></pre>\n</div>\n</div>\n<div class=\"olist arabic\">\n<ol class=\"arabic\">\n<li>\n<p>Maecenas molestie, quam nec lacinia porta, lectus turpis molestie quam, at fringilla neque ipsum\nin velit.</p>\n</li>\n<li>\n<p>Donec elementum luctus mauris.</p>\n</li>\n</ol>\n</div>\n";
+        let rfd = RFDAsciidoc::new(Cow::Borrowed(test_rfd_content())).with_renderer(Arc::new(AsciidoctorRenderer));
+        let expected = test_rfd_html();
 
         assert_eq!(
             expected,
@@ -1054,13 +1395,28 @@ in velit.
         );
     }
 
+    #[tokio::test]
+    async fn test_native_asciidoc_to_html() {
+        let rfd = RFDAsciidoc::new(Cow::Borrowed(test_rfd_content())).with_renderer(Arc::new(NativeRenderer));
+        let expected = test_rfd_html();
+
+        assert_eq!(
+            expected,
+            from_utf8(&rfd.parse(RFDOutputFormat::Html).await.unwrap()).unwrap()
+        );
+    }
+
+    fn test_rfd_html() -> &'static str {
+        "<h1>RFD 123 Place</h1>\n<div class=\"paragraph\">\n<p>Lorem ipsum dolor sit amet, consectetur adipiscing elit. Nunc et dignissim nisi. Donec ut libero in\ndolor tempor aliquam quis quis nisl. Proin sit amet nunc in orci suscipit placerat. Mauris\npellentesque fringilla lacus id gravida. Donec in velit luctus, elementum mauris eu, pellentesque\nmassa. In lectus orci, vehicula at aliquet nec, elementum eu nisi. Vivamus viverra imperdiet\nmalesuada.</p>\n</div>\n<div class=\"olist arabic\">\n<ol class=\"arabic\">\n<li>\n<p>Suspendisse blandit sem ligula, ac luctus metus condimentum non. Fusce enim purus, tincidunt ut\ntortor eget, sollicitudin vestibulum sem. Proin eu velit orci.</p>\n</li>\n<li>\n<p>Proin eu finibus velit. Morbi eget blandit neque.</p>\n</li>\n</ol>\n</div>\n<div class=\"listingblock\">\n<div class=\"content\">\n<pre class=\"highlight\"><code class=\"language-mermaid\" data-lang=\"mermaid\">graph TD;\n    A--&gt;B;\n    A--&gt;C;\n    B--&gt;D;\n    C--&gt;D;</code></pre>\n</div>\n</div>\n<div class=\"olist arabic\">\n<ol class=\"arabic\">\n<li>\n<p>Maecenas molestie, quam nec lacinia porta, lectus turpis molestie quam, at fringilla neque ipsum\nin velit.</p>\n</li>\n<li>\n<p>Donec elementum luctus mauris.</p>\n</li>\n</ol>\n</div>\n"
+    }
+
     // TODO: Find a way to generate a reproducable PDF across systems
     #[ignore]
     #[tokio::test]
     async fn test_asciidoc_to_pdf() {
         let _ = env_logger::builder().is_test(true).try_init();
 
-        let rfd = RFDAsciidoc::new(Cow::Borrowed(test_rfd_content()));
+        let rfd = RFDAsciidoc::new(Cow::Borrowed(test_rfd_content())).with_renderer(Arc::new(AsciidoctorRenderer));
         let pdf = rfd.parse(RFDOutputFormat::Pdf).await.unwrap();
 
         let ref_path = format!(
@@ -5,7 +5,8 @@ use csv::ReaderBuilder;
 use log::info;
 use octorust::Client as Octorust;
 use serde::Deserialize;
-use std::{borrow::Cow, fmt, future::Future, pin::Pin, str::from_utf8, sync::Arc};
+use std::{borrow::Cow, collections::BTreeSet, fmt, future::Future, pin::Pin, str::from_utf8, sync::Arc};
+use url::Url;
 
 use crate::{
     companies::Company,
@@ -14,7 +15,7 @@ use crate::{
     utils::{create_or_update_file_in_github_repo, decode_base64_to_string, get_file_content_from_repo},
 };
 
-use super::{PDFStorage, RFDContent, RFDNumber, RFDPdf};
+use super::{reserve::rfd_branch_number, PDFStorage, RFDContent, RFDNumber, RFDPdf};
 
 #[derive(Clone)]
 pub struct GitHubRFDRepo {
@@ -63,6 +64,79 @@ impl GitHubRFDRepo {
         }
     }
 
+    /// Get the numbers of the RFDs that have been claimed in the repo, either by a branch named
+    /// after the RFD number or by an open pull request coming from such a branch.
+    pub async fn get_claimed_numbers(&self) -> Result<BTreeSet<i32>> {
+        let branches = self
+            .client
+            .repos()
+            .list_all_branches(&self.owner, &self.repo, false)
+            .await?
+            .body;
+
+        let pulls = self
+            .client
+            .pulls()
+            .list_all(
+                &self.owner,
+                &self.repo,
+                octorust::types::IssuesListState::Open,
+                // head
+                "",
+                // base
+                "",
+                // sort
+                Default::default(),
+                // direction
+                Default::default(),
+            )
+            .await?
+            .body;
+
+        Ok(branches
+            .iter()
+            .map(|branch| branch.name.as_str())
+            .chain(pulls.iter().map(|pull| pull.head.ref_.as_str()))
+            .filter_map(rfd_branch_number)
+            .collect())
+    }
+
+    /// Create a new branch from the current head of the default branch. GitHub refuses to create
+    /// a ref that already exists, so this fails if the branch has already been created.
+    pub async fn create_branch(&self, branch: &str) -> Result<GitHubRFDBranch> {
+        let head = self
+            .client
+            .git()
+            .get_ref(&self.owner, &self.repo, &format!("heads/{}", self.default_branch))
+            .await?
+            .body;
+
+        self.client
+            .git()
+            .create_ref(
+                &self.owner,
+                &self.repo,
+                &octorust::types::GitCreateRefRequest {
+                    key: String::new(),
+                    ref_: format!("refs/heads/{}", branch),
+                    sha: head.object.sha,
+                },
+            )
+            .await?;
+
+        Ok(self.branch(branch.to_string()))
+    }
+
+    /// Delete a branch.
+    pub async fn delete_branch(&self, branch: &str) -> Result<()> {
+        self.client
+            .git()
+            .delete_ref(&self.owner, &self.repo, &format!("heads/{}", branch))
+            .await?;
+
+        Ok(())
+    }
+
     /// Read the remote rfd.csv file stored in GitHub and return a map from RFD number to RFD. The
     /// RFDs returned may or may have already been persisted
     pub async fn get_rfd_sync_updates(&self) -> Result<Vec<GitHubRFDUpdate>> {
@@ -99,6 +173,7 @@ impl GitHubRFDRepo {
                     GitHubRFDUpdate {
                         number,
                         branch: self.branch(branch_name),
+                        commit: None,
                     }
                 })
             })
@@ -348,8 +423,15 @@ impl PDFStorage for GitHubRFDBranch {
             &rfd_path,
             pdf.contents.to_vec(),
         )
-        .await
-        .map(|_| "".to_string())
+        .await?;
+
+        let mut url = Url::parse("https://github.com")?;
+        url.path_segments_mut()
+            .map_err(|_| anyhow!("Unable to construct a GitHub URL for {}", rfd_path))?
+            .extend([self.owner.as_str(), self.repo.as_str(), "blob", self.branch.as_str()])
+            .extend(rfd_path.trim_start_matches('/').split('/'));
+
+        Ok(url.to_string())
     }
 }
 
@@ -370,6 +452,15 @@ pub struct GitHubRFDReadmeLocation {
 pub struct GitHubRFDUpdate {
     pub number: RFDNumber,
     pub branch: GitHubRFDBranch,
+    /// The pushed commit that triggered the update. Updates from syncs do not have one.
+    pub commit: Option<GitHubRFDCommit>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GitHubRFDCommit {
+    pub sha: String,
+    /// The login of the user that pushed the commit.
+    pub actor: String,
 }
 
 impl GitHubRFDUpdate {
@@ -1,20 +1,40 @@
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+mod asciidoc;
 mod changelog;
 mod content;
 pub mod drive;
 mod github;
 mod model;
 mod pdf;
+mod references;
+mod render;
+mod reserve;
 mod search;
+mod state;
 
-pub use changelog::send_rfd_changelog;
-pub use content::{RFDContent, RFDOutputError, RFDOutputFormat};
-pub use github::{GitHubRFDBranch, GitHubRFDReadme, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate};
+pub use changelog::{send_rfd_changelog, RFDChange, RFDChangelog, RFDChangelogCommit, RFDChangelogEntry};
+pub use content::{
+    RFDAsciidoc, RFDContent, RFDHtml, RFDKnownAuthor, RFDLintLevel, RFDLintRules, RFDLintViolation, RFDOutputError,
+    RFDOutputFormat,
+};
+pub use github::{
+    GitHubRFDBranch, GitHubRFDCommit, GitHubRFDReadme, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate,
+};
 pub use model::{NewRFD, RFDEntry, RFDIndexEntry, RFDs, RemoteRFD, RFD};
-pub use pdf::{PDFStorage, RFDPdf};
-pub use search::{IndexDocument, RFDSearchIndex};
+pub use pdf::{GcsPDFStorage, LocalPDFStorage, PDFStorage, PDFStorageTarget, RFDPdf, RFDPdfLinks, RFDPdfUpload};
+pub use references::{
+    dependency_graph, extract_references, NewRFDReference, RFDReference, RFDReferenceEdge, RFDReferenceSummary,
+    RFDReferences,
+};
+pub use render::{default_renderer, AsciidoctorRenderer, NativeRenderer, RFDRenderer, RFDRendererKind};
+pub use reserve::{next_rfd_number, reserve_next_rfd, rfd_branch_number, rfd_readme_template, RFDReservation};
+pub use search::{
+    reindex_rfds, search_rfds, IndexDocument, RFDSearchHit, RFDSearchIndex, RFDSearchRequest, RFDSearchResults,
+    ReindexSummary, RfdId,
+};
+pub use state::{check_transition, InvalidRFDState, InvalidRFDTransition, NewRFDStateChange, RFDState, RFDStateChange};
 
 #[derive(Debug, Copy, Clone, Deserialize, Serialize)]
 pub struct RFDNumber(i32);
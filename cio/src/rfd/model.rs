@@ -13,7 +13,10 @@ use crate::{
     airtable::AIRTABLE_RFD_TABLE,
     companies::Company,
     core::UpdateAirtableRecord,
-    rfd::{GitHubRFDBranch, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate, RFDContent},
+    rfd::{
+        GitHubRFDBranch, GitHubRFDReadmeLocation, GitHubRFDRepo, GitHubRFDUpdate, RFDChangelogCommit, RFDContent,
+        RFDPdfLinks, RFDState,
+    },
     schema::rfds as r_f_ds,
     schema::rfds,
     utils::truncate,
@@ -84,6 +87,10 @@ pub struct NewRFD {
     pub pdf_link_github: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub pdf_link_google_drive: String,
+    /// pdf_links are the links to the generated PDF in each of the configured storage locations
+    #[serde(default, skip_serializing_if = "RFDPdfLinks::is_empty")]
+    #[partial(RFDIndexEntry(skip))]
+    pub pdf_links: RFDPdfLinks,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub labels: Vec<String>,
     /// The CIO company ID.
@@ -199,6 +206,7 @@ impl NewRFD {
                 // while new PDFs are generated
                 pdf_link_github: Default::default(),
                 pdf_link_google_drive: Default::default(),
+                pdf_links: Default::default(),
                 labels: Default::default(),
                 cio_company_id: company.id,
             },
@@ -220,24 +228,23 @@ impl NewRFD {
 }
 
 impl RFD {
-    /// Get a changelog for the RFD.
-    pub async fn get_weekly_changelog(
+    /// Get the commits made to the RFD since the given time.
+    pub async fn get_changelog_commits(
         &self,
         github: &octorust::Client,
         since: DateTime<Utc>,
         company: &Company,
-    ) -> Result<String> {
+    ) -> Result<Vec<RFDChangelogCommit>> {
         let owner = &company.github_org;
         let repo = "rfd";
         let r = github.repos().get(owner, repo).await?.body;
-        let mut changelog = String::new();
 
         let mut branch = self.number_string.to_string();
         if self.link.contains(&format!("/{}/", r.default_branch)) {
             branch = r.default_branch.to_string();
         }
 
-        // Get the commits from the last seven days to the file.
+        // Get the commits from the given time to the file.
         let commits = match github
             .repos()
             .list_all_commits(
@@ -259,24 +266,19 @@ impl RFD {
             }
         };
 
-        for commit in commits {
-            let message: Vec<&str> = commit.commit.message.lines().collect();
-            if !message.is_empty() {
-                if let Some(author) = commit.author {
-                    changelog += &format!(
-                        "\t- \"{}\" by @{}\n\t\thttps://github.com/oxidecomputer/rfd/commit/{}\n",
-                        message[0], author.login, commit.sha
-                    );
-                } else {
-                    changelog += &format!(
-                        "\t- \"{}\"\n\t\thttps://github.com/oxidecomputer/rfd/commit/{}\n",
-                        message[0], commit.sha
-                    );
-                }
-            }
-        }
-
-        Ok(changelog)
+        Ok(commits
+            .into_iter()
+            .filter_map(|commit| {
+                let message = commit.commit.message.lines().next()?.to_string();
+
+                Some(RFDChangelogCommit {
+                    message,
+                    author: commit.author.map(|author| author.login),
+                    url: format!("https://github.com/{}/{}/commit/{}", owner, repo, commit.sha),
+                    sha: commit.sha,
+                })
+            })
+            .collect())
     }
 
     /// Get the filename for the PDF of the RFD.
@@ -289,9 +291,9 @@ impl RFD {
     }
 
     /// Update an RFDs state.
-    pub fn update_state(&mut self, state: &str) -> Result<()> {
+    pub fn update_state(&mut self, state: RFDState) -> Result<()> {
         let mut content = RFDContent::new(&self.content)?;
-        content.update_state(state);
+        content.update_state(state.as_str());
 
         self.content = content.into_inner();
         self.state = state.to_string();
@@ -299,6 +301,23 @@ impl RFD {
         Ok(())
     }
 
+    /// Put back the state of an earlier version of the RFD, in both the state we store and the state
+    /// in the document, so that the two do not disagree.
+    pub fn restore_state(&mut self, previous: &RFD) -> Result<()> {
+        let mut content = RFDContent::new(&self.content)?;
+        content.update_state(&previous.state);
+
+        self.content = content.into_inner();
+        self.state = previous.state.to_string();
+
+        Ok(())
+    }
+
+    /// The state of the RFD, if it is one of the known states.
+    pub fn parsed_state(&self) -> Option<RFDState> {
+        self.state.parse().ok()
+    }
+
     /// Update an RFDs discussion link.
     pub fn update_discussion(&mut self, link: &str) -> Result<()> {
         let mut content = RFDContent::new(&self.content)?;
@@ -330,6 +349,7 @@ impl RFD {
         let update = GitHubRFDUpdate {
             number: self.number.into(),
             branch: self.branch(company).await?,
+            commit: None,
         };
 
         Ok(update)
@@ -347,6 +367,42 @@ impl UpdateAirtableRecord<RFD> for RFD {
         self.content = truncate(&self.content, 100000);
         self.html = "".to_string();
 
+        // Airtable only tracks the GitHub and Google Drive links.
+        self.pdf_links = Default::default();
+
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::RFD;
+
+    fn mock_rfd(state: &str) -> RFD {
+        serde_json::from_value(json!({
+            "number": 123,
+            "title": "Test",
+            "state": state,
+            "link": "https://github.com/company/rfd/tree/0123/rfd/0123",
+            "content": format!(
+                ":showtitle:\n:toc: left\n:numbered:\n:icons: font\n:state: {}\n:discussion:\n\n= RFD 123 Test\n",
+                state
+            ),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_restore_state() {
+        let previous = mock_rfd("prediscussion");
+        let mut rfd = mock_rfd("published");
+
+        rfd.restore_state(&previous).unwrap();
+
+        assert_eq!(rfd.state, "prediscussion");
+        assert_eq!(rfd.content, previous.content);
+        assert_eq!(rfd.content().unwrap().get_state(), "prediscussion");
+    }
+}
@@ -1,13 +1,29 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use log::info;
+use diesel::{
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+    sql_types::Jsonb,
+    FromSqlRow,
+};
+use google_storage1::{
+    api::{Object, Storage},
+    hyper::client::HttpConnector,
+    hyper_rustls::HttpsConnector,
+};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Write, path::PathBuf};
+use url::Url;
 
-use crate::{companies::Company, db::Database, features::Features};
+use crate::{companies::Company, db::Database, features::Features, utils::write_file};
 
 use super::{GitHubRFDRepo, RFDNumber};
 
 #[async_trait]
-pub trait PDFStorage {
+pub trait PDFStorage: Send + Sync {
     async fn store_rfd_pdf(&self, pdf: &RFDPdf) -> Result<String>;
 }
 
@@ -18,45 +34,356 @@ pub struct RFDPdf {
     pub contents: Vec<u8>,
 }
 
-pub struct RFDPdfUpload {
-    pub github_url: Option<String>,
-    pub google_drive_url: Option<String>,
+/// The locations that RFD PDFs are uploaded to, configured per company under `[rfd]` in the app
+/// config:
+///
+/// ```toml
+/// [[rfd.pdf_storage]]
+/// type = "gcs"
+///
+/// [[rfd.pdf_storage]]
+/// type = "local"
+/// path = "/var/lib/rfd/pdfs"
+/// url = "https://rfd.example.com/pdfs"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PDFStorageTarget {
+    /// The `pdfs` directory on the default branch of the RFD repo.
+    #[serde(rename = "github")]
+    GitHub,
+    /// The `rfds` folder of the "Automated Documents" shared drive.
+    GoogleDrive,
+    /// A GCS bucket. Defaults to the bucket that RFD images are copied to.
+    Gcs {
+        #[serde(default)]
+        bucket: Option<String>,
+    },
+    /// A directory on the local filesystem, optionally served from `url`.
+    Local {
+        path: PathBuf,
+        #[serde(default)]
+        url: Option<String>,
+    },
 }
 
-impl RFDPdf {
-    /// Upload the PDF to GitHub and/or Google Drive depending on which backends are supported
-    pub async fn upload(&self, db: &Database, company: &Company) -> Result<RFDPdfUpload> {
-        if Features::is_enabled("RFD_PDFS_IN_GITHUB") || Features::is_enabled("RFD_PDFS_IN_GOOGLE_DRIVE") {
-            // Create or update the file in the github repository.
-            let github_url = if Features::is_enabled("RFD_PDFS_IN_GITHUB") {
+impl PDFStorageTarget {
+    /// The key that the URL of a PDF stored in this target is recorded under.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PDFStorageTarget::GitHub => "github",
+            PDFStorageTarget::GoogleDrive => "google_drive",
+            PDFStorageTarget::Gcs { .. } => "gcs",
+            PDFStorageTarget::Local { .. } => "local",
+        }
+    }
+
+    /// The targets enabled by the `RFD_PDFS_IN_GITHUB` and `RFD_PDFS_IN_GOOGLE_DRIVE` flags. These
+    /// are used for companies that have not configured any targets.
+    pub fn from_features() -> Vec<PDFStorageTarget> {
+        let mut targets = vec![];
+
+        if Features::is_enabled("RFD_PDFS_IN_GITHUB") {
+            targets.push(PDFStorageTarget::GitHub);
+        }
+
+        if Features::is_enabled("RFD_PDFS_IN_GOOGLE_DRIVE") {
+            targets.push(PDFStorageTarget::GoogleDrive);
+        }
+
+        targets
+    }
+
+    pub async fn storage(&self, db: &Database, company: &Company) -> Result<Box<dyn PDFStorage>> {
+        Ok(match self {
+            PDFStorageTarget::GitHub => {
                 let repo = GitHubRFDRepo::new(company).await?;
-                let branch = repo.branch(repo.default_branch.clone());
+                Box::new(repo.branch(repo.default_branch.clone()))
+            }
+            PDFStorageTarget::GoogleDrive => Box::new(company.authenticate_google_drive(db).await?),
+            PDFStorageTarget::Gcs { bucket } => {
+                let bucket = bucket.clone().unwrap_or_else(|| company.rfd_static_storage());
+                Box::new(GcsPDFStorage::new(company.authenticate_gcs().await?, bucket))
+            }
+            PDFStorageTarget::Local { path, url } => Box::new(LocalPDFStorage {
+                path: path.clone(),
+                url: url.clone(),
+            }),
+        })
+    }
+}
 
-                Some(branch.store_rfd_pdf(self).await?)
-            } else {
-                None
-            };
+/// The URLs of the stored copies of an RFD PDF, keyed by the name of the storage target.
+#[derive(Debug, Default, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize, FromSqlRow, AsExpression)]
+#[serde(transparent)]
+#[diesel(sql_type = Jsonb)]
+pub struct RFDPdfLinks(pub BTreeMap<String, String>);
 
-            let google_drive_url = if Features::is_enabled("RFD_PDFS_IN_GOOGLE_DRIVE") {
-                Some(company.authenticate_google_drive(db).await?.store_rfd_pdf(self).await?)
-            } else {
-                None
-            };
+impl RFDPdfLinks {
+    pub fn get(&self, target: &str) -> Option<&str> {
+        self.0.get(target).map(|url| url.as_str())
+    }
 
-            Ok(RFDPdfUpload {
-                github_url,
-                google_drive_url,
-            })
-        } else {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Record the URLs of a new upload. Links to targets that the PDF was not uploaded to are
+    /// kept, as the copies stored there are still available.
+    pub fn extend(&mut self, upload: RFDPdfUpload) {
+        self.0.extend(upload.urls);
+    }
+}
+
+impl ToSql<Jsonb, Pg> for RFDPdfLinks {
+    fn to_sql(&self, out: &mut Output<Pg>) -> serialize::Result {
+        // The jsonb binary format is a version byte followed by the JSON text
+        out.write_all(&[1])?;
+        serde_json::to_writer(out, &self.0)?;
+
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Jsonb, Pg> for RFDPdfLinks {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        match bytes.as_bytes().split_first() {
+            Some((1, json)) => Ok(RFDPdfLinks(serde_json::from_slice(json)?)),
+            _ => Err("Unsupported JSONB encoding version".into()),
+        }
+    }
+}
+
+/// The URLs that a PDF was stored at, keyed by the name of the storage target.
+#[derive(Debug, Default)]
+pub struct RFDPdfUpload {
+    pub urls: BTreeMap<String, String>,
+}
+
+impl RFDPdf {
+    /// Upload the PDF to each of the given storage targets. A failure to store to one target does
+    /// not prevent storing to the others, and the upload only fails if every target failed.
+    pub async fn upload(&self, db: &Database, company: &Company, targets: &[PDFStorageTarget]) -> Result<RFDPdfUpload> {
+        if targets.is_empty() {
             info!(
                 "No RFD PDF storage locations are configured. Skipping PDF generation for RFD {}.",
                 self.number.as_number_string()
             );
 
-            Ok(RFDPdfUpload {
-                github_url: None,
-                google_drive_url: None,
-            })
+            return Ok(RFDPdfUpload::default());
+        }
+
+        let mut upload = RFDPdfUpload::default();
+        let mut errors = vec![];
+
+        for target in targets {
+            let result = match target.storage(db, company).await {
+                Ok(storage) => storage.store_rfd_pdf(self).await,
+                Err(err) => Err(err),
+            };
+
+            match result {
+                Ok(url) => {
+                    upload.urls.insert(target.name().to_string(), url);
+                }
+                Err(err) => {
+                    warn!(
+                        "Failed to store PDF for RFD {} in {}: {:?}",
+                        self.number.as_number_string(),
+                        target.name(),
+                        err
+                    );
+                    errors.push(format!("{}: {}", target.name(), err));
+                }
+            }
+        }
+
+        if upload.urls.is_empty() {
+            Err(anyhow!(
+                "Failed to store PDF for RFD {} in any location: {}",
+                self.number.as_number_string(),
+                errors.join(", ")
+            ))
+        } else {
+            Ok(upload)
         }
     }
 }
+
+/// The path that an RFD PDF is stored at within a bucket.
+fn gcs_object_name(pdf: &RFDPdf) -> String {
+    format!("rfd/{}/latest/{}", pdf.number, pdf.filename)
+}
+
+/// A public URL for an object in a bucket, with each path segment percent encoded.
+fn gcs_object_url(bucket: &str, object_name: &str) -> Result<String> {
+    let mut url = Url::parse("https://storage.googleapis.com")?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow!("Unable to construct a URL for GCS object {}", object_name))?
+        .push(bucket)
+        .extend(object_name.split('/'));
+
+    Ok(url.to_string())
+}
+
+pub struct GcsPDFStorage {
+    client: Storage<HttpsConnector<HttpConnector>>,
+    bucket: String,
+}
+
+impl GcsPDFStorage {
+    pub fn new(client: Storage<HttpsConnector<HttpConnector>>, bucket: String) -> Self {
+        Self { client, bucket }
+    }
+}
+
+#[async_trait]
+impl PDFStorage for GcsPDFStorage {
+    async fn store_rfd_pdf(&self, pdf: &RFDPdf) -> Result<String> {
+        let object_name = gcs_object_name(pdf);
+        let cursor = std::io::Cursor::new(pdf.contents.clone());
+
+        self.client
+            .objects()
+            .insert(Object::default(), &self.bucket)
+            .name(&object_name)
+            .upload(cursor, "application/pdf".parse().unwrap())
+            .await?;
+
+        info!(
+            "Wrote PDF for RFD {} to gs://{}/{}",
+            pdf.number, self.bucket, object_name
+        );
+
+        gcs_object_url(&self.bucket, &object_name)
+    }
+}
+
+pub struct LocalPDFStorage {
+    pub path: PathBuf,
+    pub url: Option<String>,
+}
+
+impl LocalPDFStorage {
+    fn url_for(&self, filename: &str) -> Result<String> {
+        match &self.url {
+            Some(base) => {
+                let mut url = Url::parse(base)?;
+                url.path_segments_mut()
+                    .map_err(|_| anyhow!("{} can not be used as a base URL for RFD PDFs", base))?
+                    .pop_if_empty()
+                    .push(filename);
+
+                Ok(url.to_string())
+            }
+            None => Url::from_file_path(self.path.join(filename))
+                .map(|url| url.to_string())
+                .map_err(|_| anyhow!("{:?} is not an absolute path", self.path)),
+        }
+    }
+}
+
+#[async_trait]
+impl PDFStorage for LocalPDFStorage {
+    async fn store_rfd_pdf(&self, pdf: &RFDPdf) -> Result<String> {
+        let url = self.url_for(&pdf.filename)?;
+        write_file(&self.path.join(&pdf.filename), &pdf.contents).await?;
+
+        Ok(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_pdf() -> RFDPdf {
+        RFDPdf {
+            number: 12.into(),
+            filename: "RFD 0012 Storage Layout.pdf".to_string(),
+            contents: b"%PDF-1.7".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_parses_storage_targets() {
+        #[derive(Deserialize)]
+        struct Config {
+            pdf_storage: Vec<PDFStorageTarget>,
+        }
+
+        let config: Config = toml::from_str(
+            r#"
+[[pdf_storage]]
+type = "github"
+
+[[pdf_storage]]
+type = "google_drive"
+
+[[pdf_storage]]
+type = "gcs"
+
+[[pdf_storage]]
+type = "gcs"
+bucket = "other-bucket"
+
+[[pdf_storage]]
+type = "local"
+path = "/var/lib/rfd/pdfs"
+url = "https://rfd.example.com/pdfs"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                PDFStorageTarget::GitHub,
+                PDFStorageTarget::GoogleDrive,
+                PDFStorageTarget::Gcs { bucket: None },
+                PDFStorageTarget::Gcs {
+                    bucket: Some("other-bucket".to_string())
+                },
+                PDFStorageTarget::Local {
+                    path: PathBuf::from("/var/lib/rfd/pdfs"),
+                    url: Some("https://rfd.example.com/pdfs".to_string())
+                },
+            ],
+            config.pdf_storage
+        );
+    }
+
+    #[test]
+    fn test_gcs_object_url() {
+        let pdf = test_pdf();
+
+        assert_eq!(
+            "https://storage.googleapis.com/rfd-static/rfd/12/latest/RFD%200012%20Storage%20Layout.pdf",
+            gcs_object_url("rfd-static", &gcs_object_name(&pdf)).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stores_pdf_locally() {
+        let dir = std::env::temp_dir().join(format!("rfd-pdfs-{}", uuid::Uuid::new_v4()));
+        let pdf = test_pdf();
+
+        let storage = LocalPDFStorage {
+            path: dir.clone(),
+            url: Some("https://rfd.example.com/pdfs/".to_string()),
+        };
+
+        assert_eq!(
+            "https://rfd.example.com/pdfs/RFD%200012%20Storage%20Layout.pdf",
+            storage.store_rfd_pdf(&pdf).await.unwrap()
+        );
+        assert_eq!(pdf.contents, std::fs::read(dir.join(&pdf.filename)).unwrap());
+
+        let storage = LocalPDFStorage {
+            path: dir.clone(),
+            url: None,
+        };
+        assert!(storage.store_rfd_pdf(&pdf).await.unwrap().starts_with("file:///"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
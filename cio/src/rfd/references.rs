@@ -0,0 +1,403 @@
+//! References between RFDs.
+//!
+//! RFDs refer to each other by name ("RFD 123"), by short link (`https://123.rfd.oxide.computer`)
+//! or by a link to the rendered site or repository (`.../rfd/0123`). The references of each RFD are
+//! extracted from its content when it is updated and stored as edges in `rfd_references`, which
+//! lets us answer which RFDs build on a given one.
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    ops::DerefMut,
+};
+
+use anyhow::Result;
+use async_bb8_diesel::{AsyncConnection, AsyncRunQueryDsl};
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, Insertable, QueryDsl, Queryable, RunQueryDsl};
+use regex::Regex;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{RFDContent, RFDState};
+use crate::{
+    db::Database,
+    schema::{rfd_references, rfds},
+};
+
+/// Extract the numbers of the RFDs referenced by the content, excluding the RFD itself. Comments
+/// and code blocks are skipped as they are not part of the rendered document.
+pub fn extract_references(content: &RFDContent, number: i32) -> BTreeSet<i32> {
+    let text = match content {
+        RFDContent::Asciidoc(_) => strip_asciidoc(content.raw()),
+        RFDContent::Markdown(_) => strip_markdown(content.raw()),
+    };
+
+    // These are static regular expressions and can be safely unwrapped.
+    let patterns = [
+        Regex::new(r"\bRFD[ -]?0*([1-9][0-9]{0,3})\b").unwrap(),
+        Regex::new(r"https?://0*([1-9][0-9]{0,3})\.rfd\.").unwrap(),
+        Regex::new(r"/rfd/0*([1-9][0-9]{0,3})\b").unwrap(),
+    ];
+
+    patterns
+        .iter()
+        .flat_map(|pattern| pattern.captures_iter(&text))
+        .filter_map(|captures| captures.get(1)?.as_str().parse::<i32>().ok())
+        .filter(|reference| *reference != number)
+        .collect()
+}
+
+/// Remove comments, listing and literal blocks, and inline code from Asciidoc content.
+fn strip_asciidoc(content: &str) -> String {
+    let mut text = String::new();
+    let mut delimiter: Option<&str> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_end();
+
+        match delimiter {
+            Some(open) if trimmed == open => delimiter = None,
+            Some(_) => {}
+            None if ["////", "----", "....", "```"].contains(&trimmed) => delimiter = Some(trimmed),
+            None if trimmed.starts_with("//") => {}
+            None => {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+    }
+
+    strip_inline_code(&text)
+}
+
+/// Remove fenced code blocks, HTML comments and inline code from Markdown content.
+fn strip_markdown(content: &str) -> String {
+    let mut text = String::new();
+    let mut fence: Option<&str> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+
+        match fence {
+            Some(open) if trimmed.starts_with(open) => fence = None,
+            Some(_) => {}
+            None if trimmed.starts_with("```") => fence = Some("```"),
+            None if trimmed.starts_with("~~~") => fence = Some("~~~"),
+            None => {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+    }
+
+    // This is a static regular expression and can be safely unwrapped.
+    let comments = Regex::new(r"(?s)<!--.*?-->").unwrap();
+    strip_inline_code(&comments.replace_all(&text, ""))
+}
+
+fn strip_inline_code(text: &str) -> String {
+    // This is a static regular expression and can be safely unwrapped.
+    let code = Regex::new(r"`[^`\n]*`").unwrap();
+    code.replace_all(text, "").to_string()
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = rfd_references)]
+pub struct RFDReference {
+    pub id: i32,
+    pub cio_company_id: i32,
+    pub source_rfd_number: i32,
+    pub target_rfd_number: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_references)]
+pub struct NewRFDReference {
+    pub cio_company_id: i32,
+    pub source_rfd_number: i32,
+    pub target_rfd_number: i32,
+}
+
+impl RFDReference {
+    /// Replace the stored references of an RFD with the given targets. The old references are
+    /// removed and the new ones added in one transaction, so readers never see an RFD without its
+    /// references.
+    pub async fn replace_for(db: &Database, cio_company_id: i32, source: i32, targets: &BTreeSet<i32>) -> Result<()> {
+        let references = targets
+            .iter()
+            .map(|target| NewRFDReference {
+                cio_company_id,
+                source_rfd_number: source,
+                target_rfd_number: *target,
+            })
+            .collect::<Vec<_>>();
+
+        db.pool()
+            .transaction(move |mut conn| {
+                diesel::delete(
+                    rfd_references::dsl::rfd_references
+                        .filter(rfd_references::dsl::cio_company_id.eq(cio_company_id))
+                        .filter(rfd_references::dsl::source_rfd_number.eq(source)),
+                )
+                .execute(conn.deref_mut())?;
+
+                if !references.is_empty() {
+                    diesel::insert_into(rfd_references::table)
+                        .values(references)
+                        .execute(conn.deref_mut())?;
+                }
+
+                Ok::<(), anyhow::Error>(())
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// All of the references between the RFDs of a company.
+    pub async fn for_company(db: &Database, cio_company_id: i32) -> Result<Vec<RFDReference>> {
+        Ok(rfd_references::dsl::rfd_references
+            .filter(rfd_references::dsl::cio_company_id.eq(cio_company_id))
+            .order_by((
+                rfd_references::dsl::source_rfd_number.asc(),
+                rfd_references::dsl::target_rfd_number.asc(),
+            ))
+            .load_async::<RFDReference>(db.pool())
+            .await?)
+    }
+}
+
+/// A referenced or referencing RFD. The name and state are missing if the RFD is not known, for
+/// instance because the reference has a typo in it.
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct RFDReferenceSummary {
+    pub number: i32,
+    pub name: Option<String>,
+    pub state: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, JsonSchema, Deserialize, Serialize)]
+pub struct RFDReferenceEdge {
+    pub source: i32,
+    pub target: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct RFDReferences {
+    pub number: i32,
+    /// The RFDs referenced by this RFD.
+    pub references: Vec<RFDReferenceSummary>,
+    /// The RFDs that reference this RFD.
+    pub referenced_by: Vec<RFDReferenceSummary>,
+    /// Every reference reachable by following references from this RFD.
+    pub graph: Vec<RFDReferenceEdge>,
+    /// Problems with the references of this RFD, such as references to abandoned RFDs.
+    pub warnings: Vec<String>,
+}
+
+impl RFDReferences {
+    /// Load the references of an RFD of a company.
+    pub async fn for_rfd(db: &Database, cio_company_id: i32, number: i32) -> Result<Self> {
+        let edges = RFDReference::for_company(db, cio_company_id)
+            .await?
+            .into_iter()
+            .map(|reference| RFDReferenceEdge {
+                source: reference.source_rfd_number,
+                target: reference.target_rfd_number,
+            })
+            .collect::<Vec<_>>();
+
+        let rfds = rfds::dsl::rfds
+            .filter(rfds::dsl::cio_company_id.eq(cio_company_id))
+            .select((rfds::dsl::number, rfds::dsl::name, rfds::dsl::state))
+            .load_async::<(i32, String, String)>(db.pool())
+            .await?
+            .into_iter()
+            .map(|(number, name, state)| (number, (name, state)))
+            .collect::<BTreeMap<_, _>>();
+
+        Ok(Self::build(number, &edges, &rfds))
+    }
+
+    /// Build the references of an RFD from all of the reference edges and the names and states of
+    /// the RFDs, keyed by number.
+    pub fn build(number: i32, edges: &[RFDReferenceEdge], rfds: &BTreeMap<i32, (String, String)>) -> Self {
+        let summary = |number: i32| RFDReferenceSummary {
+            number,
+            name: rfds.get(&number).map(|(name, _)| name.to_string()),
+            state: rfds.get(&number).map(|(_, state)| state.to_string()),
+        };
+
+        let references = edges
+            .iter()
+            .filter(|edge| edge.source == number)
+            .map(|edge| summary(edge.target))
+            .collect::<Vec<_>>();
+
+        let referenced_by = edges
+            .iter()
+            .filter(|edge| edge.target == number)
+            .map(|edge| summary(edge.source))
+            .collect::<Vec<_>>();
+
+        let warnings = references
+            .iter()
+            .filter_map(|reference| match (&reference.name, &reference.state) {
+                (None, _) => Some(format!("RFD {} does not exist", reference.number)),
+                (Some(name), Some(state)) if state.parse::<RFDState>() == Ok(RFDState::Abandoned) => {
+                    Some(format!("{} has been abandoned", name))
+                }
+                _ => None,
+            })
+            .collect();
+
+        RFDReferences {
+            number,
+            references,
+            referenced_by,
+            graph: dependency_graph(number, edges),
+            warnings,
+        }
+    }
+}
+
+/// The edges reachable from `root` by following references, in the order they are discovered.
+pub fn dependency_graph(root: i32, edges: &[RFDReferenceEdge]) -> Vec<RFDReferenceEdge> {
+    let mut outgoing: BTreeMap<i32, Vec<RFDReferenceEdge>> = BTreeMap::new();
+    for edge in edges {
+        outgoing.entry(edge.source).or_default().push(*edge);
+    }
+
+    let mut graph = vec![];
+    let mut visited = BTreeSet::from([root]);
+    let mut queue = VecDeque::from([root]);
+
+    while let Some(number) = queue.pop_front() {
+        for edge in outgoing.get(&number).into_iter().flatten() {
+            graph.push(*edge);
+
+            if visited.insert(edge.target) {
+                queue.push_back(edge.target);
+            }
+        }
+    }
+
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(source: i32, target: i32) -> RFDReferenceEdge {
+        RFDReferenceEdge { source, target }
+    }
+
+    #[test]
+    fn test_extracts_asciidoc_references() {
+        let content = RFDContent::new_asciidoc(
+            r#":showtitle:
+:toc: left
+:numbered:
+:icons: font
+:state: discussion
+:discussion: https://github.com/oxidecomputer/rfd/pull/412
+:authors: Jane Doe <jane@example.com>
+
+= RFD 123 References
+Jane Doe <jane@example.com>
+
+This builds on RFD 4 and <<RFD-0026>>, see https://9.rfd.oxide.computer and
+link:https://rfd.shared.oxide.computer/rfd/0053#_background[the background].
+The source lives at https://github.com/oxidecomputer/rfd/tree/master/rfd/0301/README.adoc.
+
+// RFD 77 is a comment
+////
+RFD 78 is in a comment block
+////
+
+----
+RFD 79 is in a listing block
+----
+
+Write `RFD 80` to reference an RFD. RFD123 is this RFD. The RFDs and HTTP 1000 are not references.
+"#,
+        );
+
+        assert_eq!(BTreeSet::from([4, 9, 26, 53, 301]), extract_references(&content, 123));
+    }
+
+    #[test]
+    fn test_extracts_markdown_references() {
+        let content = RFDContent::new_markdown(
+            r#"---
+authors: Jane Doe <jane@example.com>
+state: discussion
+---
+
+# RFD 7 Markdown
+
+As described in [RFD 2](https://2.rfd.oxide.computer), and unlike RFD 0010.
+
+<!-- RFD 11 is commented out -->
+
+```
+RFD 12 is in a code block
+```
+
+~~~rust
+let rfd = "RFD 13";
+~~~
+"#,
+        );
+
+        assert_eq!(BTreeSet::from([2, 10]), extract_references(&content, 7));
+    }
+
+    #[test]
+    fn test_builds_references() {
+        let edges = vec![
+            edge(1, 2),
+            edge(1, 3),
+            edge(2, 3),
+            edge(3, 4),
+            edge(4, 1),
+            edge(5, 1),
+            edge(6, 9),
+        ];
+        let rfds = BTreeMap::from([
+            (1, ("RFD 1 One".to_string(), "published".to_string())),
+            (2, ("RFD 2 Two".to_string(), "abandoned".to_string())),
+            (3, ("RFD 3 Three".to_string(), "discussion".to_string())),
+            (4, ("RFD 4 Four".to_string(), "committed".to_string())),
+            (5, ("RFD 5 Five".to_string(), "ideation".to_string())),
+        ]);
+
+        let references = RFDReferences::build(1, &edges, &rfds);
+
+        assert_eq!(
+            vec![2, 3],
+            references.references.iter().map(|r| r.number).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![4, 5],
+            references.referenced_by.iter().map(|r| r.number).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![edge(1, 2), edge(1, 3), edge(2, 3), edge(3, 4), edge(4, 1)],
+            references.graph
+        );
+        assert_eq!(vec!["RFD 2 Two has been abandoned".to_string()], references.warnings);
+
+        let dangling = RFDReferences::build(6, &edges, &rfds);
+        assert_eq!(
+            vec![RFDReferenceSummary {
+                number: 9,
+                name: None,
+                state: None,
+            }],
+            dangling.references
+        );
+        assert_eq!(vec!["RFD 9 does not exist".to_string()], dangling.warnings);
+    }
+}
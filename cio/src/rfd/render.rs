@@ -0,0 +1,180 @@
+//! Renderers that turn the Asciidoc source of an RFD into HTML or PDF output.
+//!
+//! Rendering used to always shell out to asciidoctor. That is still the default, and still the
+//! only way to produce PDFs, but HTML can also be rendered in process by [`NativeRenderer`], which
+//! does not need a Ruby toolchain. Set `RFD_HTML_RENDERER=native` to use it.
+use anyhow::anyhow;
+use async_trait::async_trait;
+use log::info;
+use std::{env, fmt, path::Path, str::FromStr, sync::Arc};
+
+use super::{
+    asciidoc::asciidoc_to_html,
+    content::{RFDOutputError, RFDOutputFormat},
+};
+use crate::utils::write_file;
+
+#[async_trait]
+pub trait RFDRenderer: fmt::Debug + Send + Sync {
+    /// Whether images embedded in the document need to be downloaded to the working directory
+    /// before rendering.
+    fn requires_local_images(&self, format: RFDOutputFormat) -> bool;
+
+    /// Render the Asciidoc `content` into the requested format. `working_dir` is a scratch
+    /// directory that holds any downloaded images.
+    async fn render(
+        &self,
+        content: &str,
+        format: RFDOutputFormat,
+        working_dir: &Path,
+    ) -> Result<Vec<u8>, RFDOutputError>;
+}
+
+/// Renders by invoking the external `asciidoctor` and `asciidoctor-pdf` binaries.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsciidoctorRenderer;
+
+#[async_trait]
+impl RFDRenderer for AsciidoctorRenderer {
+    fn requires_local_images(&self, _format: RFDOutputFormat) -> bool {
+        true
+    }
+
+    async fn render(
+        &self,
+        content: &str,
+        format: RFDOutputFormat,
+        working_dir: &Path,
+    ) -> Result<Vec<u8>, RFDOutputError> {
+        let working_dir = working_dir.to_path_buf();
+        let file_path = working_dir.join("contents.adoc");
+
+        // Write the contents to a temporary file.
+        write_file(&file_path, content.as_bytes())
+            .await
+            .map_err(RFDOutputError::Generic)?;
+
+        info!("[asciidoc] Wrote file to temp dir {:?}", file_path);
+
+        let cmd_output = tokio::task::spawn_blocking(enclose! { (working_dir, file_path) move || {
+            info!("[asciidoc] Shelling out to asciidoctor {:?} / {:?}", working_dir, file_path);
+            let out = format.command(&working_dir, &file_path).output();
+
+            match &out {
+                Ok(_) => info!("[asciidoc] Command succeeded {:?} / {:?}", working_dir, file_path),
+                Err(err) => info!("[asciidoc] Command failed: {} {:?} / {:?}", err, working_dir, file_path)
+            };
+
+            out
+        }})
+        .await
+        .map_err(|err| RFDOutputError::Generic(err.into()))?
+        .map_err(|err| RFDOutputError::Generic(err.into()))?;
+
+        if cmd_output.status.success() {
+            Ok(cmd_output.stdout)
+        } else {
+            Err(RFDOutputError::Generic(anyhow!(
+                "[rfds] running asciidoctor failed: {} {}",
+                String::from_utf8_lossy(&cmd_output.stdout),
+                String::from_utf8_lossy(&cmd_output.stderr)
+            )))
+        }
+    }
+}
+
+/// Renders HTML in process with a pure Rust Asciidoc converter. Images are referenced rather than
+/// embedded, so nothing needs to be downloaded. PDF output is not supported.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NativeRenderer;
+
+#[async_trait]
+impl RFDRenderer for NativeRenderer {
+    fn requires_local_images(&self, _format: RFDOutputFormat) -> bool {
+        false
+    }
+
+    async fn render(
+        &self,
+        content: &str,
+        format: RFDOutputFormat,
+        _working_dir: &Path,
+    ) -> Result<Vec<u8>, RFDOutputError> {
+        match format {
+            RFDOutputFormat::Html => Ok(asciidoc_to_html(content).into_bytes()),
+            RFDOutputFormat::Pdf => Err(RFDOutputError::FormatNotSupported(format)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RFDRendererKind {
+    Asciidoctor,
+    Native,
+}
+
+impl RFDRendererKind {
+    /// The renderer to use for HTML output, as configured by the `RFD_HTML_RENDERER` environment
+    /// variable. Unknown values fall back to asciidoctor.
+    pub fn from_env() -> Self {
+        env::var("RFD_HTML_RENDERER")
+            .ok()
+            .and_then(|kind| kind.parse().ok())
+            .unwrap_or(RFDRendererKind::Asciidoctor)
+    }
+
+    pub fn renderer(&self) -> Arc<dyn RFDRenderer> {
+        match self {
+            RFDRendererKind::Asciidoctor => Arc::new(AsciidoctorRenderer),
+            RFDRendererKind::Native => Arc::new(NativeRenderer),
+        }
+    }
+}
+
+impl FromStr for RFDRendererKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "asciidoctor" => Ok(RFDRendererKind::Asciidoctor),
+            "native" => Ok(RFDRendererKind::Native),
+            other => Err(anyhow!("unknown RFD renderer `{}`", other)),
+        }
+    }
+}
+
+/// The renderer to use for a format when one has not been set explicitly. PDFs are always rendered
+/// by asciidoctor-pdf.
+pub fn default_renderer(format: RFDOutputFormat) -> Arc<dyn RFDRenderer> {
+    match format {
+        RFDOutputFormat::Html => RFDRendererKind::from_env().renderer(),
+        RFDOutputFormat::Pdf => RFDRendererKind::Asciidoctor.renderer(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_renderer_kinds() {
+        assert_eq!("native".parse::<RFDRendererKind>().unwrap(), RFDRendererKind::Native);
+        assert_eq!(
+            " Asciidoctor ".parse::<RFDRendererKind>().unwrap(),
+            RFDRendererKind::Asciidoctor
+        );
+        assert!("pandoc".parse::<RFDRendererKind>().is_err());
+    }
+
+    #[tokio::test]
+    async fn native_renderer_does_not_support_pdfs() {
+        let result = NativeRenderer
+            .render("= RFD 1 Test", RFDOutputFormat::Pdf, Path::new("."))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(RFDOutputError::FormatNotSupported(RFDOutputFormat::Pdf))
+        ));
+    }
+}
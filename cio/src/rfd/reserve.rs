@@ -0,0 +1,220 @@
+//! Reserving the number of a new RFD.
+//!
+//! A new RFD starts its life on a branch named after its number. Reserving a number picks the next
+//! number that is not used by an RFD in the database, a branch in the repo or an open pull request,
+//! and then claims it by creating the branch. GitHub refuses to create a branch that already
+//! exists, so if two reservations race for the same number only one of them wins and the other
+//! moves on to the next number.
+use std::collections::BTreeSet;
+
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use diesel::{ExpressionMethods, QueryDsl};
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::{GitHubRFDRepo, RFDNumber};
+use crate::{companies::Company, db::Database, schema::rfds, utils::create_or_update_file_in_github_repo};
+
+/// The number of times we try the next number when a branch we want to create already exists.
+const MAX_RESERVE_ATTEMPTS: i32 = 5;
+
+/// A newly reserved RFD along with links to where its source lives.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RFDReservation {
+    pub number: i32,
+    pub number_string: String,
+    pub title: String,
+    pub branch: String,
+    /// Link to the RFD directory on the new branch.
+    pub tree_link: String,
+    /// Link to the templated README on the new branch.
+    pub readme_link: String,
+    /// Link to open a pull request from the new branch once the RFD is ready for discussion.
+    pub pull_request_link: String,
+}
+
+/// Parse the RFD number out of a branch name. RFD branches are named after the four digit form of
+/// the number, e.g. `0042`.
+pub fn rfd_branch_number(branch: &str) -> Option<i32> {
+    if branch.len() == 4 && branch.chars().all(|c| c.is_ascii_digit()) {
+        branch.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// The next RFD number after all of the numbers that are already taken. Gaps are never filled as a
+/// missing number may belong to an RFD that was abandoned before it was merged.
+pub fn next_rfd_number(taken: &BTreeSet<i32>) -> i32 {
+    taken.iter().next_back().map(|max| max + 1).unwrap_or(1)
+}
+
+/// Render the README a new RFD starts out with.
+pub fn rfd_readme_template(number: &RFDNumber, title: &str, authors: &[String]) -> String {
+    format!(
+        r#":showtitle:
+:toc: left
+:numbered:
+:icons: font
+:state: prediscussion
+:authors: {}
+:sectlinks:
+
+= RFD {} {}
+{{authors}}
+
+[WARNING]
+This RFD has been reserved but not yet written.
+
+== Background
+
+== Proposal
+
+== Open questions
+"#,
+        authors.join(", "),
+        number,
+        title.trim()
+    )
+}
+
+/// Reserve the next free RFD number for a company, create its branch and commit a templated
+/// README to it. The branch is deleted again if the README can not be committed.
+pub async fn reserve_next_rfd(
+    db: &Database,
+    company: &Company,
+    title: &str,
+    authors: &[String],
+) -> Result<RFDReservation> {
+    let title = title.trim();
+    if title.is_empty() {
+        bail!("a title is required to reserve an RFD");
+    }
+
+    let repo = GitHubRFDRepo::new(company).await?;
+
+    let mut taken = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(company.id))
+        .select(rfds::dsl::number)
+        .load_async::<i32>(db.pool())
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+    taken.extend(repo.get_claimed_numbers().await?);
+
+    let mut number = next_rfd_number(&taken);
+
+    for _ in 0..MAX_RESERVE_ATTEMPTS {
+        let rfd_number = RFDNumber::from(number);
+        let branch_name = rfd_number.as_number_string();
+
+        let branch = match repo.create_branch(&branch_name).await {
+            Ok(branch) => branch,
+            Err(err) => {
+                // If the branch exists someone else claimed this number between us reading the
+                // claimed numbers and creating the branch, so move on to the next one.
+                if repo.branch(branch_name.clone()).exists_in_remote().await {
+                    warn!(
+                        "RFD branch {} was claimed concurrently, trying the next number",
+                        branch_name
+                    );
+                    number += 1;
+                    continue;
+                }
+
+                return Err(err);
+            }
+        };
+
+        let readme_path = format!("{}/README.adoc", rfd_number.repo_directory());
+        if let Err(err) = create_or_update_file_in_github_repo(
+            branch.client(),
+            &repo.owner,
+            &repo.repo,
+            &branch_name,
+            &readme_path,
+            rfd_readme_template(&rfd_number, title, authors).into_bytes(),
+        )
+        .await
+        {
+            // A branch without a README still claims the number, so give the number back.
+            if let Err(delete_err) = repo.delete_branch(&branch_name).await {
+                warn!(
+                    "failed to delete RFD branch {} after its README could not be created: {}",
+                    branch_name, delete_err
+                );
+            }
+
+            return Err(err);
+        }
+
+        info!("reserved RFD {} `{}` for company {}", number, title, company.id);
+
+        let tree_link = format!(
+            "https://github.com/{}/{}/tree/{}{}",
+            repo.owner,
+            repo.repo,
+            branch_name,
+            rfd_number.repo_directory()
+        );
+
+        return Ok(RFDReservation {
+            number,
+            number_string: branch_name.clone(),
+            title: title.to_string(),
+            readme_link: format!(
+                "https://github.com/{}/{}/blob/{}{}",
+                repo.owner, repo.repo, branch_name, readme_path
+            ),
+            tree_link,
+            pull_request_link: format!(
+                "https://github.com/{}/{}/compare/{}...{}?expand=1",
+                repo.owner, repo.repo, repo.default_branch, branch_name
+            ),
+            branch: branch_name,
+        });
+    }
+
+    bail!(
+        "failed to reserve an RFD number after {} attempts, every candidate was claimed concurrently",
+        MAX_RESERVE_ATTEMPTS
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rfd::RFDContent;
+
+    #[test]
+    fn test_rfd_branch_number() {
+        assert_eq!(rfd_branch_number("0042"), Some(42));
+        assert_eq!(rfd_branch_number("1234"), Some(1234));
+        assert_eq!(rfd_branch_number("master"), None);
+        assert_eq!(rfd_branch_number("42"), None);
+        assert_eq!(rfd_branch_number("00042"), None);
+        assert_eq!(rfd_branch_number("0042-fix"), None);
+    }
+
+    #[test]
+    fn test_next_rfd_number() {
+        assert_eq!(next_rfd_number(&BTreeSet::new()), 1);
+        assert_eq!(next_rfd_number(&[1, 2, 5].into_iter().collect()), 6);
+    }
+
+    #[test]
+    fn test_rfd_readme_template() {
+        let readme = rfd_readme_template(
+            &RFDNumber::from(42),
+            " Widget sprockets ",
+            &["Jane Doe <jane@example.com>".to_string()],
+        );
+        let content = RFDContent::new_asciidoc(&readme);
+
+        assert_eq!(content.get_title(), "Widget sprockets");
+        assert_eq!(content.get_state(), "prediscussion");
+        assert_eq!(content.get_authors(), "Jane Doe <jane@example.com>");
+    }
+}
@@ -1,19 +1,138 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::Utc;
+use diesel::{ExpressionMethods, QueryDsl};
 use hmac::{Hmac, Mac};
 use md5::Md5;
-use meilisearch_minimal_api::{IndexClient, MeiliClient, SearchQuery};
+use meilisearch_minimal_api::{Index, IndexClient, IndexSettings, MeiliClient, Pagination, SearchQuery};
 use parse_rfd::{parse, ParsedDoc, Section};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::{cmp::min, collections::HashMap};
+use std::{
+    cmp::min,
+    collections::{HashMap, HashSet},
+};
 
-use super::RFDNumber;
+use super::{RFDNumber, RFDs, RFD};
+use crate::{companies::Company, db::Database, schema::rfds};
+
+/// The number of documents sent to Meilisearch per request while reindexing.
+const REINDEX_BATCH_SIZE: usize = 500;
+
+/// The upper bound of sections we expect a single RFD to be split into. Searches return no more
+/// hits than the `maxTotalHits` of the index, which [`RFDSearchIndex::settings`] raises to this.
+const MAX_SECTIONS_PER_RFD: usize = 10_000;
+
+/// RFD states whose content is only visible to the authors of the RFD.
+const AUTHOR_ONLY_STATES: &[&str] = &["prediscussion"];
+
+/// The number of words of section content returned around a match.
+const SEARCH_CROP_LENGTH: usize = 30;
 
 pub struct RFDSearchIndex {}
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// The result of rebuilding the search index.
+#[derive(Debug)]
+pub struct ReindexSummary {
+    /// The index that now holds the previous contents of the live index.
+    pub backup_index: String,
+    pub rfds: usize,
+    pub skipped_rfds: Vec<i32>,
+    pub documents: usize,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct RfdId {
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+}
+
+/// A search of the RFD index.
+#[derive(Debug, Clone, Default)]
+pub struct RFDSearchRequest {
+    pub query: String,
+    /// Only return RFDs in one of these states. All states are searched when empty.
+    pub states: Vec<String>,
+    /// The verified email of the person searching. RFDs in an author only state are returned only
+    /// to their authors, and never when this is not set.
+    pub viewer: Option<String>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+impl RFDSearchRequest {
+    /// Returns if an RFD in the given state, written by `authors`, can be returned.
+    pub fn can_view(&self, state: &str, authors: &str) -> bool {
+        if AUTHOR_ONLY_STATES.contains(&state) {
+            let is_author = match &self.viewer {
+                Some(viewer) => author_emails(authors).any(|email| email.eq_ignore_ascii_case(viewer.trim())),
+                None => false,
+            };
+
+            if !is_author {
+                return false;
+            }
+        }
+
+        self.states.is_empty() || self.states.iter().any(|s| s == state)
+    }
+}
+
+/// The emails in the authors header of an RFD, e.g. `Jane Doe <jane@example.com>, John Doe
+/// <john@example.com>`.
+fn author_emails(authors: &str) -> impl Iterator<Item = &str> {
+    authors.split(',').filter_map(|author| {
+        let (_, email) = author.split_once('<')?;
+        let (email, _) = email.split_once('>')?;
+        Some(email.trim())
+    })
+}
+
+/// A matching section of an RFD.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RFDSearchHit {
+    #[serde(flatten)]
+    pub id: RfdId,
+    pub rfd_number: i32,
+    pub rfd_title: String,
+    pub state: String,
+    pub section: String,
+    pub anchor: String,
+    pub url: String,
+    /// The section name with matches wrapped in `<em>` tags.
+    pub highlighted_section: String,
+    /// The part of the section content around the matches, with matches wrapped in `<em>` tags.
+    pub highlighted_content: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+pub struct RFDSearchResults {
+    pub hits: Vec<RFDSearchHit>,
+    pub offset: usize,
+    pub limit: usize,
+    pub estimated_total_hits: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SectionHit {
     #[serde(rename = "objectID")]
     object_id: String,
+    name: String,
+    anchor: String,
+    url: String,
+    rfd_number: i32,
+    #[serde(default)]
+    hierarchy_lvl0: String,
+    #[serde(rename = "_formatted", default)]
+    formatted: Option<FormattedSection>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FormattedSection {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    content: String,
 }
 
 type HmacMd5 = Hmac<Md5>;
@@ -86,6 +205,9 @@ impl IndexDocument {
 }
 
 impl RFDSearchIndex {
+    /// The index that the RFD site searches against.
+    pub const LIVE_INDEX: &'static str = "rfd";
+
     pub fn default_client() -> Result<MeiliClient> {
         Ok(MeiliClient::new(
             std::env::var("MEILI_URL")?,
@@ -108,14 +230,201 @@ impl RFDSearchIndex {
     }
 
     pub async fn find_rfd_ids(index: &IndexClient, rfd_number: &RFDNumber) -> Result<Vec<String>> {
-        let mut query = SearchQuery::default();
-        query.filter = Some(vec![format!("rfd_number = {}", rfd_number.0)]);
+        let query = SearchQuery {
+            filter: Some(vec![format!("rfd_number = {}", rfd_number.0)]),
+            attributes_to_retrieve: Some(vec!["objectID".to_string()]),
+            limit: Some(MAX_SECTIONS_PER_RFD),
+            ..Default::default()
+        };
 
         let results = index.search::<RfdId>(query).await?;
 
         Ok(results.hits.into_iter().map(|hit| hit.object_id).collect::<Vec<_>>())
     }
 
+    /// The settings every RFD index is created with.
+    pub fn settings() -> IndexSettings {
+        IndexSettings {
+            filterable_attributes: Some(vec!["rfd_number".to_string()]),
+            // Finding the sections of an RFD to replace them needs all of them in one search.
+            pagination: Some(Pagination {
+                max_total_hits: Some(MAX_SECTIONS_PER_RFD),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Rebuild the search index from every RFD in a fresh index and swap it with the live index.
+    /// The previous contents of the live index are kept in the returned backup index so that the
+    /// swap can be reverted, and older backups are deleted.
+    pub async fn reindex(client: &MeiliClient, live_index: &str, rfds: &[RFD]) -> Result<ReindexSummary> {
+        let mut documents = vec![];
+        let mut skipped_rfds = vec![];
+
+        for rfd in rfds {
+            match Self::parse_document(&rfd.number.into(), &rfd.content) {
+                Ok(parsed) => documents.extend(parsed),
+                Err(err) => {
+                    log::warn!("Failed to parse RFD {} for the search index: {}", rfd.number, err);
+                    skipped_rfds.push(rfd.number);
+                }
+            }
+        }
+
+        // Sections with the same id overwrite each other, so only unique ids end up in the index.
+        let expected = documents
+            .iter()
+            .map(|document| document.object_id.as_str())
+            .collect::<HashSet<_>>()
+            .len();
+
+        let new_index = Self::backup_index_name(live_index);
+        let task = client.create_index(&new_index, Some("objectID")).await?;
+        client.wait_for_task(task.task_uid, None, None).await?;
+
+        let index = client.index(new_index.clone());
+        let task = index.settings(Self::settings()).await?;
+        client.wait_for_task(task.task_uid, None, None).await?;
+
+        for batch in documents.chunks(REINDEX_BATCH_SIZE) {
+            let task = index.index_documents(batch, "objectID").await?;
+            client.wait_for_task(task.task_uid, None, None).await?;
+        }
+
+        let stats = index.stats().await?;
+        if stats.number_of_documents as usize != expected {
+            let task = index.delete().await?;
+            client.wait_for_task(task.task_uid, None, None).await?;
+
+            bail!(
+                "index {} has {} documents, expected {}; leaving {} in place",
+                new_index,
+                stats.number_of_documents,
+                expected,
+                live_index
+            );
+        }
+
+        // Swapping requires both indexes to exist.
+        if client.get_index(live_index).await?.is_none() {
+            let task = client.create_index(live_index, Some("objectID")).await?;
+            client.wait_for_task(task.task_uid, None, None).await?;
+        }
+
+        let task = client.swap_indexes(&[(live_index, &new_index)]).await?;
+        client.wait_for_task(task.task_uid, None, None).await?;
+
+        log::info!(
+            "Swapped {} documents into {}, the previous index is kept as {}",
+            expected,
+            live_index,
+            new_index
+        );
+
+        for stale in Self::stale_backups(live_index, &new_index, &client.list_indexes().await?) {
+            log::info!("Deleting old search index backup {}", stale);
+            let task = client.index(stale).delete().await?;
+            client.wait_for_task(task.task_uid, None, None).await?;
+        }
+
+        Ok(ReindexSummary {
+            backup_index: new_index,
+            rfds: rfds.len(),
+            skipped_rfds,
+            documents: expected,
+        })
+    }
+
+    /// Search the sections of the RFDs with the given numbers. The caller is responsible for
+    /// checking that every RFD in `visible` may be returned.
+    pub async fn search(
+        client: &MeiliClient,
+        index: &str,
+        request: &RFDSearchRequest,
+        visible: &HashMap<i32, String>,
+    ) -> Result<RFDSearchResults> {
+        let mut results = RFDSearchResults {
+            hits: vec![],
+            offset: request.offset,
+            limit: request.limit,
+            estimated_total_hits: Some(0),
+        };
+
+        if visible.is_empty() {
+            return Ok(results);
+        }
+
+        let query = SearchQuery {
+            query: Some(request.query.to_string()),
+            filter: Some(vec![Self::visibility_filter(visible.keys())]),
+            attributes_to_highlight: Some(vec!["name".to_string(), "content".to_string()]),
+            attributes_to_crop: Some(vec!["content".to_string()]),
+            crop_length: Some(SEARCH_CROP_LENGTH),
+            offset: Some(request.offset),
+            limit: Some(request.limit),
+            ..Default::default()
+        };
+
+        let response = client.index(index.to_string()).search::<SectionHit>(query).await?;
+
+        results.estimated_total_hits = response.estimated_total_hits;
+        results.hits = response
+            .hits
+            .into_iter()
+            .map(|hit| {
+                let formatted = hit.formatted.unwrap_or_default();
+
+                RFDSearchHit {
+                    id: RfdId {
+                        object_id: hit.object_id,
+                    },
+                    rfd_number: hit.rfd_number,
+                    rfd_title: hit.hierarchy_lvl0,
+                    state: visible.get(&hit.rfd_number).cloned().unwrap_or_default(),
+                    section: hit.name,
+                    anchor: hit.anchor,
+                    url: hit.url,
+                    highlighted_section: formatted.name,
+                    highlighted_content: formatted.content,
+                }
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    fn visibility_filter<'a>(numbers: impl Iterator<Item = &'a i32>) -> String {
+        let mut numbers = numbers.collect::<Vec<_>>();
+        numbers.sort();
+
+        format!(
+            "rfd_number IN [{}]",
+            numbers.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(", ")
+        )
+    }
+
+    fn backup_index_name(live_index: &str) -> String {
+        format!("{}-{}", live_index, Utc::now().format("%Y%m%d%H%M%S"))
+    }
+
+    /// The backups of the live index other than the one to keep.
+    fn stale_backups(live_index: &str, keep: &str, indexes: &[Index]) -> Vec<String> {
+        let prefix = format!("{}-", live_index);
+
+        indexes
+            .iter()
+            .filter(|index| {
+                index.uid != keep
+                    && index
+                        .uid
+                        .strip_prefix(&prefix)
+                        .map(|suffix| suffix.len() == 14 && suffix.chars().all(|c| c.is_ascii_digit()))
+                        .unwrap_or(false)
+            })
+            .map(|index| index.uid.clone())
+            .collect()
+    }
+
     pub fn parse_document(rfd_number: &RFDNumber, content: &str) -> Result<Vec<IndexDocument>> {
         let ParsedDoc { title, sections } = parse(content)?;
         Ok(sections
@@ -125,10 +434,129 @@ impl RFDSearchIndex {
     }
 }
 
+/// Rebuild the RFD search index for a company from the RFDs in the database.
+pub async fn reindex_rfds(db: &Database, company: &Company) -> Result<()> {
+    let rfds = RFDs::get_from_db(db, company.id).await?;
+
+    if rfds.0.is_empty() {
+        // Return early, swapping in an empty index would take search down.
+        return Ok(());
+    }
+
+    let client = RFDSearchIndex::default_client()?;
+    let summary = RFDSearchIndex::reindex(&client, RFDSearchIndex::LIVE_INDEX, &rfds.0).await?;
+
+    log::info!(
+        "Reindexed {} RFDs into {} documents, skipped RFDs {:?}, backup index {}",
+        summary.rfds,
+        summary.documents,
+        summary.skipped_rfds,
+        summary.backup_index
+    );
+
+    Ok(())
+}
+
+/// Search the RFDs of a company, limited to the RFDs the request can see.
+pub async fn search_rfds(db: &Database, company: &Company, request: &RFDSearchRequest) -> Result<RFDSearchResults> {
+    let rfds = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(company.id))
+        .select((rfds::dsl::number, rfds::dsl::state, rfds::dsl::authors))
+        .load_async::<(i32, String, String)>(db.pool())
+        .await?;
+
+    let visible = rfds
+        .into_iter()
+        .filter(|(_, state, authors)| request.can_view(state, authors))
+        .map(|(number, state, _)| (number, state))
+        .collect::<HashMap<_, _>>();
+
+    let client = RFDSearchIndex::default_client()?;
+    RFDSearchIndex::search(&client, RFDSearchIndex::LIVE_INDEX, request, &visible).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const AUTHORS: &str = "Jane Doe <jane@example.com>, John Doe <John@Example.com>";
+
+    #[test]
+    fn hides_prediscussion_rfds() {
+        let all = RFDSearchRequest::default();
+        assert!(all.can_view("published", AUTHORS));
+        assert!(!all.can_view("prediscussion", AUTHORS));
+
+        let committed_only = RFDSearchRequest {
+            states: vec!["committed".to_string()],
+            ..Default::default()
+        };
+        assert!(committed_only.can_view("committed", AUTHORS));
+        assert!(!committed_only.can_view("published", AUTHORS));
+
+        let prediscussion_only = RFDSearchRequest {
+            states: vec!["prediscussion".to_string()],
+            ..Default::default()
+        };
+        assert!(!prediscussion_only.can_view("prediscussion", AUTHORS));
+    }
+
+    #[test]
+    fn shows_prediscussion_rfds_to_authors() {
+        let author = RFDSearchRequest {
+            viewer: Some("john@example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(author.can_view("prediscussion", AUTHORS));
+        assert!(author.can_view("published", AUTHORS));
+
+        let non_author = RFDSearchRequest {
+            viewer: Some("ane@example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(!non_author.can_view("prediscussion", AUTHORS));
+        assert!(non_author.can_view("published", AUTHORS));
+
+        // Authors only see their own RFDs through the state filter too.
+        let author_filtered = RFDSearchRequest {
+            states: vec!["discussion".to_string()],
+            viewer: Some("jane@example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(!author_filtered.can_view("prediscussion", AUTHORS));
+    }
+
+    #[test]
+    fn builds_visibility_filter() {
+        assert_eq!(
+            RFDSearchIndex::visibility_filter([12, 3, 7].iter()),
+            "rfd_number IN [3, 7, 12]"
+        );
+    }
+
+    #[test]
+    fn finds_stale_backups() {
+        let index = |uid: &str| Index {
+            uid: uid.to_string(),
+            primary_key: Some("objectID".to_string()),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let indexes = vec![
+            index("rfd"),
+            index("rfd-20240101000000"),
+            index("rfd-20240102000000"),
+            index("rfd-drafts"),
+            index("other-20240101000000"),
+        ];
+
+        assert_eq!(
+            RFDSearchIndex::stale_backups("rfd", "rfd-20240102000000", &indexes),
+            vec!["rfd-20240101000000".to_string()]
+        );
+    }
+
     #[test]
     fn creates_indexable_documents() {
         let documents = RFDSearchIndex::parse_document(
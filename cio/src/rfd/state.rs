@@ -0,0 +1,268 @@
+//! The states an RFD moves through and the history of those moves.
+//!
+//! An RFD starts out in `prediscussion` or `ideation`, is discussed in a pull request, and ends up
+//! `published`, `committed` or `abandoned`. [`RFDState::can_transition_to`] describes which moves
+//! are allowed; every accepted move is recorded as an [`RFDStateChange`].
+use std::{fmt, str::from_utf8, str::FromStr};
+
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use chrono::{DateTime, Utc};
+use diesel::{
+    deserialize::{self, FromSql},
+    pg::{Pg, PgValue},
+    serialize::{self, Output, ToSql},
+    sql_types::VarChar,
+    ExpressionMethods, FromSqlRow, Insertable, QueryDsl, Queryable,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{db::Database, schema::rfd_state_changes};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, JsonSchema, Deserialize, Serialize, FromSqlRow, AsExpression)]
+#[serde(rename_all = "lowercase")]
+#[diesel(sql_type = VarChar)]
+pub enum RFDState {
+    Prediscussion,
+    Ideation,
+    Discussion,
+    Published,
+    Committed,
+    Abandoned,
+}
+
+impl RFDState {
+    pub const ALL: [RFDState; 6] = [
+        RFDState::Prediscussion,
+        RFDState::Ideation,
+        RFDState::Discussion,
+        RFDState::Published,
+        RFDState::Committed,
+        RFDState::Abandoned,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RFDState::Prediscussion => "prediscussion",
+            RFDState::Ideation => "ideation",
+            RFDState::Discussion => "discussion",
+            RFDState::Published => "published",
+            RFDState::Committed => "committed",
+            RFDState::Abandoned => "abandoned",
+        }
+    }
+
+    /// The states an RFD in this state may move to. Staying in the same state is always allowed.
+    pub fn next_states(&self) -> &'static [RFDState] {
+        match self {
+            RFDState::Prediscussion => &[RFDState::Ideation, RFDState::Discussion, RFDState::Abandoned],
+            RFDState::Ideation => &[RFDState::Prediscussion, RFDState::Discussion, RFDState::Abandoned],
+            // A discussion may be paused by moving back to ideation.
+            RFDState::Discussion => &[
+                RFDState::Ideation,
+                RFDState::Published,
+                RFDState::Committed,
+                RFDState::Abandoned,
+            ],
+            // Published and committed RFDs are reopened for discussion to be revised.
+            RFDState::Published => &[RFDState::Discussion, RFDState::Committed, RFDState::Abandoned],
+            RFDState::Committed => &[RFDState::Discussion, RFDState::Abandoned],
+            // An abandoned RFD may be picked up again, but has to go through discussion again.
+            RFDState::Abandoned => &[RFDState::Prediscussion, RFDState::Ideation, RFDState::Discussion],
+        }
+    }
+
+    pub fn can_transition_to(&self, to: RFDState) -> bool {
+        *self == to || self.next_states().contains(&to)
+    }
+
+    /// The states an RFD may be in while its branch has an open pull request.
+    pub fn allowed_with_pull_request(&self) -> bool {
+        matches!(
+            self,
+            RFDState::Ideation | RFDState::Discussion | RFDState::Published | RFDState::Committed | RFDState::Abandoned
+        )
+    }
+
+    /// The states an RFD may be in on the default branch.
+    pub fn allowed_on_default_branch(&self) -> bool {
+        matches!(self, RFDState::Published | RFDState::Committed | RFDState::Abandoned)
+    }
+}
+
+impl fmt::Display for RFDState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRFDState(pub String);
+
+impl fmt::Display for InvalidRFDState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` is not a valid RFD state", self.0)
+    }
+}
+
+impl std::error::Error for InvalidRFDState {}
+
+impl FromStr for RFDState {
+    type Err = InvalidRFDState;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RFDState::ALL
+            .into_iter()
+            .find(|state| state.as_str() == s.trim())
+            .ok_or_else(|| InvalidRFDState(s.to_string()))
+    }
+}
+
+impl ToSql<VarChar, Pg> for RFDState {
+    fn to_sql(&self, out: &mut Output<Pg>) -> serialize::Result {
+        <str as ToSql<VarChar, Pg>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<VarChar, Pg> for RFDState {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = from_utf8(bytes.as_bytes())?;
+        value.parse::<RFDState>().map_err(|err| {
+            format!(
+                "Encountered unknown RFD state {:?} in database. Unable to deserialize: {}",
+                value, err
+            )
+            .into()
+        })
+    }
+}
+
+/// A move from one state to another that is not allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidRFDTransition {
+    pub from: RFDState,
+    pub to: RFDState,
+}
+
+impl fmt::Display for InvalidRFDTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let allowed = self
+            .from
+            .next_states()
+            .iter()
+            .map(|state| state.as_str())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        write!(
+            f,
+            "an RFD can not move from {} to {}, it can only move to one of: {}",
+            self.from, self.to, allowed
+        )
+    }
+}
+
+impl std::error::Error for InvalidRFDTransition {}
+
+/// Check a move between the state stored for an RFD and the state it is being updated to. RFDs
+/// that we have not seen before may start out in any state, as they may predate this check.
+pub fn check_transition(from: Option<RFDState>, to: RFDState) -> Result<(), InvalidRFDTransition> {
+    match from {
+        Some(from) if !from.can_transition_to(to) => Err(InvalidRFDTransition { from, to }),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Queryable, PartialEq, Clone, JsonSchema, Deserialize, Serialize)]
+#[diesel(table_name = rfd_state_changes)]
+pub struct RFDStateChange {
+    pub id: i32,
+    pub cio_company_id: i32,
+    pub rfd_number: i32,
+    pub from_state: Option<RFDState>,
+    pub to_state: RFDState,
+    pub branch: String,
+    pub commit_sha: String,
+    pub actor: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Insertable, PartialEq, Clone)]
+#[diesel(table_name = rfd_state_changes)]
+pub struct NewRFDStateChange {
+    pub cio_company_id: i32,
+    pub rfd_number: i32,
+    pub from_state: Option<RFDState>,
+    pub to_state: RFDState,
+    pub branch: String,
+    pub commit_sha: String,
+    pub actor: String,
+}
+
+impl NewRFDStateChange {
+    pub async fn create(&self, db: &Database) -> Result<RFDStateChange> {
+        Ok(diesel::insert_into(rfd_state_changes::table)
+            .values(self.clone())
+            .get_result_async(db.pool())
+            .await?)
+    }
+}
+
+impl RFDStateChange {
+    /// The state changes of an RFD, oldest first.
+    pub async fn history(db: &Database, cio_company_id: i32, rfd_number: i32) -> Result<Vec<RFDStateChange>> {
+        Ok(rfd_state_changes::dsl::rfd_state_changes
+            .filter(rfd_state_changes::dsl::cio_company_id.eq(cio_company_id))
+            .filter(rfd_state_changes::dsl::rfd_number.eq(rfd_number))
+            .order_by(rfd_state_changes::dsl::changed_at.asc())
+            .load_async::<RFDStateChange>(db.pool())
+            .await?)
+    }
+
+    /// The state changes of all RFDs of a company since the given time, oldest first.
+    pub async fn since(db: &Database, cio_company_id: i32, since: DateTime<Utc>) -> Result<Vec<RFDStateChange>> {
+        Ok(rfd_state_changes::dsl::rfd_state_changes
+            .filter(rfd_state_changes::dsl::cio_company_id.eq(cio_company_id))
+            .filter(rfd_state_changes::dsl::changed_at.ge(since))
+            .order_by(rfd_state_changes::dsl::changed_at.asc())
+            .load_async::<RFDStateChange>(db.pool())
+            .await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_states() {
+        for state in RFDState::ALL {
+            assert_eq!(state.as_str().parse::<RFDState>(), Ok(state));
+        }
+
+        assert_eq!(" discussion ".parse::<RFDState>(), Ok(RFDState::Discussion));
+        assert!("draft".parse::<RFDState>().is_err());
+        assert!("".parse::<RFDState>().is_err());
+    }
+
+    #[test]
+    fn checks_transitions() {
+        assert!(check_transition(None, RFDState::Published).is_ok());
+        assert!(check_transition(Some(RFDState::Published), RFDState::Published).is_ok());
+        assert!(check_transition(Some(RFDState::Prediscussion), RFDState::Discussion).is_ok());
+        assert!(check_transition(Some(RFDState::Discussion), RFDState::Published).is_ok());
+        assert!(check_transition(Some(RFDState::Published), RFDState::Committed).is_ok());
+        assert!(check_transition(Some(RFDState::Abandoned), RFDState::Discussion).is_ok());
+
+        assert_eq!(
+            check_transition(Some(RFDState::Prediscussion), RFDState::Published),
+            Err(InvalidRFDTransition {
+                from: RFDState::Prediscussion,
+                to: RFDState::Published
+            })
+        );
+        assert!(check_transition(Some(RFDState::Committed), RFDState::Published).is_err());
+        assert!(check_transition(Some(RFDState::Abandoned), RFDState::Committed).is_err());
+    }
+}
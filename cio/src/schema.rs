@@ -40,6 +40,27 @@ table! {
     }
 }
 
+table! {
+    applicant_duplicates (id) {
+        id -> Int4,
+        primary_applicant_id -> Int4,
+        duplicate_applicant_id -> Int4,
+        primary_name -> Varchar,
+        primary_email -> Varchar,
+        primary_role -> Varchar,
+        duplicate_name -> Varchar,
+        duplicate_email -> Varchar,
+        duplicate_role -> Varchar,
+        score -> Float4,
+        signals -> Array<Text>,
+        status -> Varchar,
+        resolved_by -> Varchar,
+        resolved_at -> Nullable<Timestamptz>,
+        cio_company_id -> Int4,
+        airtable_record_id -> Varchar,
+    }
+}
+
 table! {
     applicant_interviews (id) {
         id -> Int4,
@@ -90,6 +111,19 @@ table! {
     }
 }
 
+table! {
+    applicant_stage_changes (id) {
+        id -> Int4,
+        cio_company_id -> Int4,
+        applicant_id -> Int4,
+        role -> Varchar,
+        from_stage -> Nullable<Varchar>,
+        to_stage -> Varchar,
+        actor -> Varchar,
+        changed_at -> Timestamptz,
+    }
+}
+
 table! {
     applicants (id) {
         id -> Int4,
@@ -208,6 +242,23 @@ table! {
     }
 }
 
+table! {
+    audit_events (id) {
+        id -> Int4,
+        cio_company_id -> Int4,
+        actor -> Varchar,
+        service -> Varchar,
+        action -> Varchar,
+        object_id -> Varchar,
+        request_id -> Varchar,
+        saga_id -> Varchar,
+        outcome -> Varchar,
+        error -> Varchar,
+        created_at -> Timestamptz,
+        claimed_by -> Varchar,
+    }
+}
+
 table! {
     auth_user_logins (id) {
         id -> Int4,
@@ -563,6 +614,7 @@ table! {
         members -> Array<Text>,
         restricted_to -> Array<Text>,
         repos -> Array<Text>,
+        slack_channels -> Array<Text>,
         allow_external_members -> Bool,
         allow_web_posting -> Bool,
         is_archived -> Bool,
@@ -793,6 +845,30 @@ table! {
     }
 }
 
+table! {
+    rfd_references (id) {
+        id -> Int4,
+        cio_company_id -> Int4,
+        source_rfd_number -> Int4,
+        target_rfd_number -> Int4,
+        updated_at -> Timestamptz,
+    }
+}
+
+table! {
+    rfd_state_changes (id) {
+        id -> Int4,
+        cio_company_id -> Int4,
+        rfd_number -> Int4,
+        from_state -> Nullable<Varchar>,
+        to_state -> Varchar,
+        branch -> Varchar,
+        commit_sha -> Varchar,
+        actor -> Varchar,
+        changed_at -> Timestamptz,
+    }
+}
+
 table! {
     rfds (id) {
         id -> Int4,
@@ -814,6 +890,7 @@ table! {
         relevant_components -> Array<Text>,
         pdf_link_github -> Varchar,
         pdf_link_google_drive -> Varchar,
+        pdf_links -> Jsonb,
         labels -> Array<Text>,
         cio_company_id -> Int4,
         airtable_record_id -> Varchar,
@@ -943,11 +1020,14 @@ table! {
 
 joinable!(accounts_payables -> companys (cio_company_id));
 joinable!(api_tokens -> companys (auth_company_id));
+joinable!(applicant_duplicates -> companys (cio_company_id));
 joinable!(applicant_interviews -> companys (cio_company_id));
 joinable!(applicant_reviewers -> companys (cio_company_id));
 joinable!(applicant_reviews -> companys (cio_company_id));
+joinable!(applicant_stage_changes -> companys (cio_company_id));
 joinable!(applicants -> companys (cio_company_id));
 joinable!(asset_items -> companys (cio_company_id));
+joinable!(audit_events -> companys (cio_company_id));
 joinable!(auth_user_logins -> companys (cio_company_id));
 joinable!(auth_users -> companys (cio_company_id));
 joinable!(barcode_scans -> companys (cio_company_id));
@@ -970,6 +1050,8 @@ joinable!(page_views -> companys (cio_company_id));
 joinable!(rack_line_subscribers -> companys (cio_company_id));
 joinable!(recorded_meetings -> companys (cio_company_id));
 joinable!(resources -> companys (cio_company_id));
+joinable!(rfd_references -> companys (cio_company_id));
+joinable!(rfd_state_changes -> companys (cio_company_id));
 joinable!(rfds -> companys (cio_company_id));
 joinable!(software_vendors -> companys (cio_company_id));
 joinable!(swag_inventory_items -> companys (cio_company_id));
@@ -979,11 +1061,14 @@ joinable!(users -> companys (cio_company_id));
 allow_tables_to_appear_in_same_query!(
     accounts_payables,
     api_tokens,
+    applicant_duplicates,
     applicant_interviews,
     applicant_reviewers,
     applicant_reviews,
+    applicant_stage_changes,
     applicants,
     asset_items,
+    audit_events,
     auth_user_logins,
     auth_users,
     barcode_scans,
@@ -1007,6 +1092,8 @@ allow_tables_to_appear_in_same_query!(
     rack_line_subscribers,
     recorded_meetings,
     resources,
+    rfd_references,
+    rfd_state_changes,
     rfds,
     software_vendors,
     swag_inventory_items,
@@ -15,11 +15,13 @@ use sendgrid_api::{traits::MailOps, Client as SendGrid};
 use serde::{Deserialize, Serialize};
 use shippo::{Address, CustomsDeclaration, CustomsItem, NewShipment, NewTransaction, Parcel, Shippo};
 use slack_chat_api::{
-    FormattedMessage, MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageType,
+    blocks::{ContextBlock, HeaderBlock, SectionBlock},
+    FormattedMessage, MessageAttachment,
 };
 
 use crate::{
     airtable::{AIRTABLE_INBOUND_TABLE, AIRTABLE_OUTBOUND_TABLE, AIRTABLE_PACKAGE_PICKUPS_TABLE},
+    audit::audit,
     companies::Company,
     configs::User,
     core::UpdateAirtableRecord,
@@ -258,65 +260,14 @@ impl From<NewInboundShipment> for FormattedMessage {
             notes = item.notes.to_string();
         }
 
-        let mut blocks = vec![MessageBlock {
-            block_type: MessageBlockType::Header,
-            text: Some(MessageBlockText {
-                text_type: MessageType::PlainText,
-                text: item.name,
-            }),
-            elements: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        }];
+        let mut attachment = MessageAttachment::colored(get_color_based_on_tracking_status(&item.tracking_status))
+            .block(HeaderBlock::new(&item.name));
 
         if !notes.is_empty() {
-            blocks.push(MessageBlock {
-                block_type: MessageBlockType::Section,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: notes,
-                }),
-                elements: Default::default(),
-                accessory: Default::default(),
-                block_id: Default::default(),
-                fields: Default::default(),
-            });
-        }
-        blocks.push(MessageBlock {
-            block_type: MessageBlockType::Context,
-            elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                text_type: MessageType::Markdown,
-                text: status_msg,
-            })],
-            text: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        });
-
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: get_color_based_on_tracking_status(&item.tracking_status),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks,
-            }],
+            attachment = attachment.block(SectionBlock::markdown(notes));
         }
+
+        FormattedMessage::new().attachment(attachment.block(ContextBlock::new().markdown(status_msg)))
     }
 }
 
@@ -637,62 +588,12 @@ impl From<NewOutboundShipment> for FormattedMessage {
             }
         }
 
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: get_color_based_on_tracking_status(&item.tracking_status),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks: vec![
-                    MessageBlock {
-                        block_type: MessageBlockType::Header,
-                        text: Some(MessageBlockText {
-                            text_type: MessageType::PlainText,
-                            text: item.name.to_string(),
-                        }),
-                        elements: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Section,
-                        text: Some(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: item.contents,
-                        }),
-                        elements: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Context,
-                        elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: status_msg,
-                        })],
-                        text: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                ],
-            }],
-        }
+        FormattedMessage::new().attachment(
+            MessageAttachment::colored(get_color_based_on_tracking_status(&item.tracking_status))
+                .block(HeaderBlock::new(&item.name))
+                .block(SectionBlock::markdown(item.contents))
+                .block(ContextBlock::new().markdown(status_msg)),
+        )
     }
 }
 
@@ -1096,7 +997,7 @@ impl OutboundShipment {
         // Initialize the SendGrid client.
         let sendgrid_client = SendGrid::new_from_env();
         // Send the message.
-        sendgrid_client
+        let result = sendgrid_client
             .mail_send()
             .send_plain_text(
                 &format!("{}, your order from {} has been received!", self.name, company.name),
@@ -1126,7 +1027,10 @@ xoxo,
                 &[],
                 &format!("packages@{}", &company.gsuite_domain),
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(db, &company, "sendgrid", "send_email", &self.email, result).await?;
 
         Ok(())
     }
@@ -1141,7 +1045,7 @@ xoxo,
         // Initialize the SendGrid client.
         let sendgrid_client = SendGrid::new_from_env();
         // Send the message.
-        sendgrid_client
+        let result = sendgrid_client
             .mail_send()
             .send_plain_text(
                 &format!("{}, your package from {} is on the way!", self.name, company.name),
@@ -1173,7 +1077,10 @@ xoxo,
                 &[],
                 &format!("packages@{}", &company.gsuite_domain),
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(db, &company, "sendgrid", "send_email", &self.email, result).await?;
 
         Ok(())
     }
@@ -1184,7 +1091,7 @@ xoxo,
         // Initialize the SendGrid client.
         let sendgrid_client = SendGrid::new_from_env();
         // Send the message.
-        sendgrid_client
+        let result = sendgrid_client
             .mail_send()
             .send_plain_text(
                 &format!("Shipment to {} is ready to be packaged", self.name),
@@ -1226,7 +1133,18 @@ The Shipping Bot",
                 &[],
                 &format!("packages@{}", &company.gsuite_domain),
             )
-            .await?;
+            .await
+            .map_err(anyhow::Error::from);
+
+        audit(
+            db,
+            &company,
+            "sendgrid",
+            "send_email",
+            &format!("packages@{}", &company.gsuite_domain),
+            result,
+        )
+        .await?;
 
         Ok(())
     }
@@ -1542,7 +1460,9 @@ The Shipping Bot",
                         label_file_type: "".to_string(),
                         metadata: "".to_string(),
                     })
-                    .await?;
+                    .await
+                    .map_err(anyhow::Error::from);
+                let label = audit(db, &company, "shippo", "purchase_label", &self.id.to_string(), label).await?;
 
                 // Set the additional fields.
                 self.carrier = clean_carrier_name(&rate.provider);
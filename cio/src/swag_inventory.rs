@@ -19,8 +19,8 @@ use reqwest::StatusCode;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use slack_chat_api::{
-    FormattedMessage, MessageAttachment, MessageBlock, MessageBlockAccessory, MessageBlockText, MessageBlockType,
-    MessageType,
+    blocks::{ContextBlock, ImageElement, SectionBlock, Text},
+    FormattedMessage, MessageAttachment,
 };
 
 use crate::{
@@ -343,51 +343,11 @@ impl From<NewSwagInventoryItem> for FormattedMessage {
     fn from(item: NewSwagInventoryItem) -> Self {
         let text = format!("*{}*\n | current stock: {}", item.name, item.current_stock);
 
-        FormattedMessage {
-            channel: Default::default(),
-            blocks: Default::default(),
-            attachments: vec![MessageAttachment {
-                color: "".to_string(),
-                author_icon: Default::default(),
-                author_link: Default::default(),
-                author_name: Default::default(),
-                fallback: Default::default(),
-                fields: Default::default(),
-                footer: Default::default(),
-                footer_icon: Default::default(),
-                image_url: Default::default(),
-                pretext: Default::default(),
-                text: Default::default(),
-                thumb_url: Default::default(),
-                title: Default::default(),
-                title_link: Default::default(),
-                ts: Default::default(),
-                blocks: vec![
-                    MessageBlock {
-                        block_type: MessageBlockType::Section,
-                        text: Some(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text,
-                        }),
-                        elements: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                    MessageBlock {
-                        block_type: MessageBlockType::Context,
-                        elements: vec![slack_chat_api::BlockOption::MessageBlockText(MessageBlockText {
-                            text_type: MessageType::Markdown,
-                            text: format!("Swag inventory item | {} | {}", item.item, item.size),
-                        })],
-                        text: Default::default(),
-                        accessory: Default::default(),
-                        block_id: Default::default(),
-                        fields: Default::default(),
-                    },
-                ],
-            }],
-        }
+        FormattedMessage::new().attachment(
+            MessageAttachment::default()
+                .block(SectionBlock::markdown(text))
+                .block(ContextBlock::new().markdown(format!("Swag inventory item | {} | {}", item.item, item.size))),
+        )
     }
 }
 
@@ -572,25 +532,14 @@ impl SwagInventoryItem {
 
             let item = self.get_item(db).await.unwrap();
 
-            // Add our image as an accessory.
-            let accessory = MessageBlockAccessory {
-                accessory_type: MessageType::Image,
-                image_url: item.image.to_string(),
-                alt_text: self.item.to_string(),
-                text: None,
-                action_id: Default::default(),
-                value: Default::default(),
-            };
-
-            // Set our accessory.
-            msg.attachments[0].blocks[0].accessory = Some(accessory);
-            // Set our text.
-            let mut t = msg.attachments[0].blocks[0].text.as_ref().unwrap().clone();
-            t.text = format!(
-                "*{}*\nstock changed from `{}` to `{}`",
-                self.name, self.current_stock, new
-            );
-            msg.attachments[0].blocks[0].text = Some(t);
+            // Set our text and add our image as an accessory.
+            if let Some(section) = msg.attachments[0].blocks[0].as_section_mut() {
+                section.text = Some(Text::markdown(format!(
+                    "*{}*\nstock changed from `{}` to `{}`",
+                    self.name, self.current_stock, new
+                )));
+                section.accessory = Some(ImageElement::new(&item.image, &self.item).into());
+            }
 
             if self.current_stock > new {
                 msg.attachments[0].color = crate::colors::Colors::Yellow.to_string();
@@ -0,0 +1,688 @@
+//! Extracting the text of the documents applicants send us.
+//!
+//! Resumes and materials come in as PDF, Word (DOCX) or OpenDocument (ODT) files, and sometimes as
+//! plain text or HTML. All of them are read in process, without shelling out to a converter, so the
+//! result only depends on the bytes of the document. Documents can be large or hostile, so the
+//! [`ExtractLimits`] cap how many bytes we read, including what is inside a compressed document,
+//! how many pages of a PDF we look at, and how much text we return. For PDFs, every Flate
+//! compressed stream is checked before the text is extracted; only the object streams that the PDF
+//! parser unpacks while loading the document are bounded by nothing but the size of the document.
+use std::{
+    fmt,
+    io::{Cursor, Read},
+};
+
+use flate2::read::ZlibDecoder;
+
+use crate::utils::truncate;
+
+/// How much of a document we are willing to read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractLimits {
+    /// The largest document we read, and the largest file or stream we decompress out of one.
+    pub max_bytes: usize,
+    /// How many pages of a PDF we extract the text of.
+    pub max_pages: usize,
+    /// How many characters of text we return.
+    pub max_chars: usize,
+}
+
+impl Default for ExtractLimits {
+    fn default() -> Self {
+        ExtractLimits {
+            max_bytes: 20 * 1024 * 1024,
+            max_pages: 50,
+            // This is as much as we store in Airtable.
+            max_chars: 90_000,
+        }
+    }
+}
+
+/// The kinds of documents we can extract the text of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Odt,
+    Html,
+    Text,
+}
+
+const DOCX_MIME_TYPE: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+const ODT_MIME_TYPE: &str = "application/vnd.oasis.opendocument.text";
+
+impl DocumentKind {
+    /// Figure out what kind of document a file is from its mime type, its extension and finally
+    /// its contents.
+    pub fn detect(name: &str, mime_type: &str, contents: &[u8]) -> Option<DocumentKind> {
+        let mime_type = mime_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+        match mime_type.as_str() {
+            "application/pdf" => return Some(DocumentKind::Pdf),
+            DOCX_MIME_TYPE => return Some(DocumentKind::Docx),
+            ODT_MIME_TYPE => return Some(DocumentKind::Odt),
+            "text/html" | "application/xhtml+xml" => return Some(DocumentKind::Html),
+            _ if mime_type.starts_with("text/") => return Some(DocumentKind::Text),
+            _ => {}
+        }
+
+        let extension = name
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+        match extension.as_str() {
+            "pdf" => return Some(DocumentKind::Pdf),
+            "docx" => return Some(DocumentKind::Docx),
+            "odt" => return Some(DocumentKind::Odt),
+            "html" | "htm" | "xhtml" => return Some(DocumentKind::Html),
+            "txt" | "md" | "markdown" | "adoc" | "asciidoc" | "rst" | "org" => return Some(DocumentKind::Text),
+            _ => {}
+        }
+
+        if contents.starts_with(b"%PDF-") {
+            Some(DocumentKind::Pdf)
+        } else if contents.starts_with(b"PK\x03\x04") {
+            // Both DOCX and ODT are zip archives. ODT stores its mime type uncompressed as the
+            // first file of the archive, so it shows up in the first few bytes.
+            if contents
+                .windows(ODT_MIME_TYPE.len())
+                .take(128)
+                .any(|w| w == ODT_MIME_TYPE.as_bytes())
+            {
+                Some(DocumentKind::Odt)
+            } else {
+                Some(DocumentKind::Docx)
+            }
+        } else if looks_like_html(contents) {
+            Some(DocumentKind::Html)
+        } else if looks_like_text(contents) {
+            Some(DocumentKind::Text)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the contents start like an HTML document.
+fn looks_like_html(contents: &[u8]) -> bool {
+    let start = String::from_utf8_lossy(&contents[..contents.len().min(64)]).to_ascii_lowercase();
+    let start = start.trim_start_matches('\u{feff}').trim_start();
+
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// Whether the contents are text we can show as is: valid UTF-8 without control characters.
+fn looks_like_text(contents: &[u8]) -> bool {
+    match std::str::from_utf8(contents) {
+        Ok(text) => !text
+            .chars()
+            .any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t' | '\u{c}')),
+        Err(_) => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtractError {
+    /// The document, or a file inside of it, is larger than the limits allow.
+    TooLarge { size: usize, limit: usize },
+    /// We do not know how to read this kind of document.
+    Unsupported(String),
+    /// The document is encrypted.
+    Encrypted,
+    /// The document is not what it claims to be, or is broken.
+    Malformed(String),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::TooLarge { size, limit } => write!(
+                f,
+                "the document is {} bytes, which is more than the limit of {} bytes",
+                size, limit
+            ),
+            ExtractError::Unsupported(kind) => write!(f, "extracting text from `{}` is not supported", kind),
+            ExtractError::Encrypted => write!(f, "the document is encrypted"),
+            ExtractError::Malformed(err) => write!(f, "the document is malformed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {}
+
+/// Extract the text of a document. This reads the whole document so should not be called from an
+/// async context directly.
+pub fn extract_text(
+    name: &str,
+    mime_type: &str,
+    contents: &[u8],
+    limits: &ExtractLimits,
+) -> Result<String, ExtractError> {
+    if contents.len() > limits.max_bytes {
+        return Err(ExtractError::TooLarge {
+            size: contents.len(),
+            limit: limits.max_bytes,
+        });
+    }
+
+    let text = match DocumentKind::detect(name, mime_type, contents) {
+        Some(DocumentKind::Pdf) => pdf_text(contents, limits)?,
+        Some(DocumentKind::Docx) => xml_text(&zip_file(contents, "word/document.xml", limits)?, &DOCX),
+        Some(DocumentKind::Odt) => xml_text(&zip_file(contents, "content.xml", limits)?, &ODT),
+        Some(DocumentKind::Html) => html_text(&String::from_utf8_lossy(contents)),
+        Some(DocumentKind::Text) => String::from_utf8_lossy(contents).to_string(),
+        None => {
+            return Err(ExtractError::Unsupported(if mime_type.is_empty() {
+                name.to_string()
+            } else {
+                mime_type.to_string()
+            }))
+        }
+    };
+
+    Ok(truncate(&normalize(&text), limits.max_chars))
+}
+
+fn pdf_text(contents: &[u8], limits: &ExtractLimits) -> Result<String, ExtractError> {
+    let document = lopdf::Document::load_mem(contents).map_err(|err| ExtractError::Malformed(err.to_string()))?;
+    if document.is_encrypted() {
+        return Err(ExtractError::Encrypted);
+    }
+    check_pdf_streams(&document, limits)?;
+
+    let pages = document
+        .get_pages()
+        .into_keys()
+        .take(limits.max_pages)
+        .collect::<Vec<_>>();
+
+    document
+        .extract_text(&pages)
+        .map_err(|err| ExtractError::Malformed(err.to_string()))
+}
+
+/// Check that none of the Flate compressed streams of a PDF decompress to more than the limits
+/// allow, as the PDF library decompresses the streams it needs without any limit. Images are
+/// skipped, since extracting the text never reads them.
+fn check_pdf_streams(document: &lopdf::Document, limits: &ExtractLimits) -> Result<(), ExtractError> {
+    for object in document.objects.values() {
+        let stream = match object {
+            lopdf::Object::Stream(stream) => stream,
+            _ => continue,
+        };
+
+        let is_image = matches!(stream.dict.get(b"Subtype"), Ok(lopdf::Object::Name(name)) if name == b"Image");
+        let is_flate = match stream.dict.get(b"Filter") {
+            Ok(lopdf::Object::Name(name)) => name == b"FlateDecode",
+            Ok(lopdf::Object::Array(filters)) => {
+                matches!(filters.first(), Some(lopdf::Object::Name(name)) if name == b"FlateDecode")
+            }
+            _ => false,
+        };
+        if is_image || !is_flate {
+            continue;
+        }
+
+        // Streams that fail to decompress are left for the PDF library to complain about.
+        let mut decoder = ZlibDecoder::new(stream.content.as_slice()).take(limits.max_bytes as u64 + 1);
+        if let Ok(size) = std::io::copy(&mut decoder, &mut std::io::sink()) {
+            if size > limits.max_bytes as u64 {
+                return Err(ExtractError::TooLarge {
+                    size: size as usize,
+                    limit: limits.max_bytes,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a file out of a zip archive, without decompressing more than the limits allow.
+fn zip_file(contents: &[u8], name: &str, limits: &ExtractLimits) -> Result<String, ExtractError> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(contents)).map_err(|err| ExtractError::Malformed(err.to_string()))?;
+    let file = archive
+        .by_name(name)
+        .map_err(|err| ExtractError::Malformed(format!("{}: {}", name, err)))?;
+
+    // The size in the archive is whatever the archive claims, so we limit what we read instead.
+    let mut buf = vec![];
+    file.take(limits.max_bytes as u64 + 1)
+        .read_to_end(&mut buf)
+        .map_err(|err| ExtractError::Malformed(format!("{}: {}", name, err)))?;
+    if buf.len() > limits.max_bytes {
+        return Err(ExtractError::TooLarge {
+            size: buf.len(),
+            limit: limits.max_bytes,
+        });
+    }
+
+    String::from_utf8(buf).map_err(|err| ExtractError::Malformed(format!("{}: {}", name, err)))
+}
+
+/// Which elements of a document's XML make up its text.
+struct XmlText {
+    /// The elements whose text is part of the document.
+    text: &'static [&'static str],
+    /// The elements that end a paragraph.
+    paragraphs: &'static [&'static str],
+    /// The elements that are a line break.
+    breaks: &'static [&'static str],
+    /// The elements that are a tab.
+    tabs: &'static [&'static str],
+    /// The element that is a run of spaces, with the attribute holding how many.
+    spaces: Option<(&'static str, &'static str)>,
+}
+
+const DOCX: XmlText = XmlText {
+    text: &["w:t"],
+    paragraphs: &["w:p"],
+    breaks: &["w:br", "w:cr"],
+    tabs: &["w:tab"],
+    spaces: None,
+};
+
+const ODT: XmlText = XmlText {
+    text: &["text:p", "text:h"],
+    paragraphs: &["text:p", "text:h"],
+    breaks: &["text:line-break"],
+    tabs: &["text:tab"],
+    spaces: Some(("text:s", "text:c")),
+};
+
+/// Extract the text of a document from its XML. This is not a full XML parser, it only knows
+/// enough about tags, comments and entities to find the text of the elements we care about.
+fn xml_text(xml: &str, rules: &XmlText) -> String {
+    let mut text = String::new();
+    let mut depth = 0usize;
+    let mut rest = xml;
+
+    while !rest.is_empty() {
+        let start = match rest.find('<') {
+            Some(start) => start,
+            None => {
+                if depth > 0 {
+                    text.push_str(&decode_entities(rest));
+                }
+                break;
+            }
+        };
+        if depth > 0 {
+            text.push_str(&decode_entities(&rest[..start]));
+        }
+        rest = &rest[start..];
+
+        // Skip over what is not an element.
+        if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+            let end = cdata.find("]]>").unwrap_or(cdata.len());
+            if depth > 0 {
+                text.push_str(&cdata[..end]);
+            }
+            rest = cdata.get(end + 3..).unwrap_or_default();
+            continue;
+        }
+        let terminator = if rest.starts_with("<!--") {
+            "-->"
+        } else if rest.starts_with("<?") {
+            "?>"
+        } else {
+            ">"
+        };
+        let end = match rest.find(terminator) {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + terminator.len()..];
+        if terminator != ">" || tag.starts_with('!') {
+            continue;
+        }
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let (empty, tag) = match tag.strip_suffix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let (name, attributes) = tag.trim().split_once(char::is_whitespace).unwrap_or((tag.trim(), ""));
+
+        if rules.text.contains(&name) && !empty {
+            if closing {
+                depth = depth.saturating_sub(1);
+            } else {
+                depth += 1;
+            }
+        }
+
+        if closing || empty {
+            if rules.paragraphs.contains(&name) {
+                text.push('\n');
+            }
+            if rules.breaks.contains(&name) {
+                text.push('\n');
+            }
+            if rules.tabs.contains(&name) {
+                text.push('\t');
+            }
+            if let Some((element, count)) = rules.spaces {
+                if name == element {
+                    let count = attribute(attributes, count).and_then(|c| c.parse().ok()).unwrap_or(1);
+                    text.push_str(&" ".repeat(count.min(1024)));
+                }
+            }
+        }
+    }
+
+    text
+}
+
+/// The elements of an HTML document that start a new line.
+const HTML_BLOCKS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "br",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "li",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// The elements of an HTML document whose contents are not part of its text.
+const HTML_HIDDEN: &[&str] = &["head", "script", "style", "template"];
+
+/// Extract the text of an HTML document. Like [`xml_text`] this is not a full parser: tags are
+/// dropped, block elements are on lines of their own, and the contents of [`HTML_HIDDEN`] elements are
+/// skipped. Line breaks in the source are only whitespace, as they are when the page is shown.
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    loop {
+        let start = rest.find('<').unwrap_or(rest.len());
+        text.push_str(&decode_entities(&rest[..start]).replace(['\n', '\r'], " "));
+        rest = &rest[start..];
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map(|end| &comment[end + 3..]).unwrap_or_default();
+            continue;
+        }
+        let end = match rest.find('>') {
+            Some(end) => end,
+            None => break,
+        };
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+
+        let (closing, tag) = match tag.strip_prefix('/') {
+            Some(tag) => (true, tag),
+            None => (false, tag),
+        };
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        if !closing && HTML_HIDDEN.contains(&name.as_str()) {
+            // Hidden elements do not nest, so skip to where the element is closed.
+            let close = format!("</{}", name);
+            rest = rest
+                .to_ascii_lowercase()
+                .find(&close)
+                .map(|end| &rest[end..])
+                .unwrap_or_default();
+            continue;
+        }
+
+        // Both the start and the end of a block end the line, so collapse them into one break.
+        if HTML_BLOCKS.contains(&name.as_str()) {
+            text.truncate(text.trim_end().len());
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// The value of an attribute of an element.
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    while let Some(index) = rest.find(name) {
+        let after = rest[index + name.len()..].trim_start();
+        let preceded = rest[..index].chars().last().map(char::is_whitespace).unwrap_or(true);
+        if preceded {
+            if let Some(value) = after.strip_prefix('=') {
+                let value = value.trim_start();
+                let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+                let value = &value[1..];
+                return value.find(quote).map(|end| &value[..end]);
+            }
+        }
+        rest = &rest[index + name.len()..];
+    }
+
+    None
+}
+
+/// Replace the XML entities in text with what they stand for.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]).unwrap_or_default();
+        let c = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            // Not an XML entity, but the one HTML documents use the most.
+            "nbsp" => Some('\u{a0}'),
+            _ => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|code| code.ok())
+                .and_then(char::from_u32),
+        };
+
+        match c {
+            Some(c) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+/// Tidy up extracted text: collapse runs of whitespace within lines, trim the lines, and keep at
+/// most one empty line in between paragraphs.
+fn normalize(text: &str) -> String {
+    let mut lines: Vec<String> = vec![];
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() && lines.last().map(|last| last.is_empty()).unwrap_or(true) {
+            continue;
+        }
+
+        lines.push(line);
+    }
+    while lines.last().map(|last| last.is_empty()).unwrap_or(false) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        decode_entities, extract_text, html_text, normalize, xml_text, DocumentKind, ExtractError, ExtractLimits, DOCX,
+        ODT,
+    };
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            DocumentKind::detect("resume", "application/pdf", b""),
+            Some(DocumentKind::Pdf)
+        );
+        assert_eq!(
+            DocumentKind::detect("resume.DOCX", "application/octet-stream", b""),
+            Some(DocumentKind::Docx)
+        );
+        assert_eq!(
+            DocumentKind::detect("resume", "text/markdown; charset=utf-8", b""),
+            Some(DocumentKind::Text)
+        );
+        assert_eq!(
+            DocumentKind::detect("resume", "", b"%PDF-1.4\n"),
+            Some(DocumentKind::Pdf)
+        );
+        assert_eq!(
+            DocumentKind::detect(
+                "materials",
+                "",
+                b"PK\x03\x04\x14\0\0\0\0\0mimetypeapplication/vnd.oasis.opendocument.text"
+            ),
+            Some(DocumentKind::Odt)
+        );
+        assert_eq!(
+            DocumentKind::detect("resume", "text/html; charset=utf-8", b""),
+            Some(DocumentKind::Html)
+        );
+        assert_eq!(
+            DocumentKind::detect("materials", "", b"\xef\xbb\xbf\n<!DOCTYPE html>\n<html>"),
+            Some(DocumentKind::Html)
+        );
+        assert_eq!(
+            DocumentKind::detect("materials", "", b"Hello,\nworld"),
+            Some(DocumentKind::Text)
+        );
+        assert_eq!(DocumentKind::detect("materials", "", b"\0\x01\x02"), None);
+    }
+
+    #[test]
+    fn test_docx_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:pPr><w:tabs><w:tab w:val="left" w:pos="720"/></w:tabs></w:pPr><w:r><w:t>Jane</w:t></w:r><w:r><w:t xml:space="preserve"> Doe &amp; Co</w:t></w:r></w:p>
+<w:p><w:r><w:t>Rust</w:t><w:tab/><w:t>C</w:t><w:br/><w:t>&#233;l&#xE8;ve</w:t></w:r><w:r><w:instrText>PAGE</w:instrText></w:r></w:p>
+<w:p/>
+</w:body></w:document>"#;
+
+        assert_eq!(normalize(&xml_text(xml, &DOCX)), "Jane Doe & Co\nRust C\nélève");
+    }
+
+    #[test]
+    fn test_odt_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content><office:automatic-styles><style:style style:name="P1"/></office:automatic-styles>
+<office:body><office:text><text:h text:outline-level="1">Materials</text:h>
+<text:p text:style-name="P1">Why <text:span>Oxide</text:span>?<text:s text:c="3"/>Because.</text:p>
+<!-- a comment <text:p>hidden</text:p> -->
+<text:p>First<text:line-break/>Second</text:p></office:text></office:body></office:document-content>"#;
+
+        assert_eq!(
+            normalize(&xml_text(xml, &ODT)),
+            "Materials\nWhy Oxide? Because.\nFirst\nSecond"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Materials</title><style>p { color: red; }</style></head>
+<body><h1>Why
+Oxide?</h1><p>Because <b>the</b>&nbsp;work&#8230; &amp; the <a href="/">people</a>.<br/>Thanks</p>
+<!-- <p>hidden</p> --><SCRIPT>document.write("<p>hidden</p>")</SCRIPT>
+<ul><li>Rust</li><li>C</li></ul></body></html>"#;
+
+        assert_eq!(
+            normalize(&html_text(html)),
+            "Why Oxide?\nBecause the work… & the people.\nThanks\nRust\nC"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(
+            decode_entities("a &lt;b&gt; &quot;c&quot; &#65;&#x42;"),
+            "a <b> \"c\" AB"
+        );
+        assert_eq!(decode_entities("AT&T & &bogus; &#xZZ;"), "AT&T & &bogus; &#xZZ;");
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("\n\n  Hello   world \t\n\n\n\nBye\r\n\n"),
+            "Hello world\n\nBye"
+        );
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = ExtractLimits {
+            max_bytes: 8,
+            max_pages: 1,
+            max_chars: 5,
+        };
+
+        assert_eq!(
+            extract_text("resume.txt", "text/plain", b"Hello, world", &limits),
+            Err(ExtractError::TooLarge { size: 12, limit: 8 })
+        );
+        assert_eq!(
+            extract_text("resume.txt", "text/plain", b"Hi there", &limits),
+            Ok("Hi th".to_string())
+        );
+        assert!(matches!(
+            extract_text("resume.pdf", "application/pdf", b"%PDF-1", &ExtractLimits::default()),
+            Err(ExtractError::Malformed(_))
+        ));
+        assert_eq!(
+            extract_text("resume.bin", "application/octet-stream", b"\0\x01", &limits),
+            Err(ExtractError::Unsupported("application/octet-stream".to_string()))
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use cio_api::rfd::{IndexDocument, RFDSearchIndex};
-use meilisearch_minimal_api::{IndexClient, IndexSettings, MeiliClient, SearchQuery};
+use meilisearch_minimal_api::{IndexClient, MeiliClient, SearchQuery};
 use uuid::Uuid;
 
 pub struct TestDB {
@@ -67,9 +67,7 @@ Third in the list"#;
 
         let index = db.index_client();
 
-        let mut settings = IndexSettings::default();
-        settings.filterable_attributes = Some(vec!["rfd_number".to_string()]);
-        let _ = index.settings(settings).await.unwrap();
+        let _ = index.settings(RFDSearchIndex::settings()).await.unwrap();
 
         RFDSearchIndex::index_rfd(&db.client, db.index_name.to_string(), &123.into(), content)
             .await
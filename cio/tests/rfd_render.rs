@@ -0,0 +1,50 @@
+use cio_api::rfd::{NativeRenderer, RFDAsciidoc, RFDNumber};
+use std::{borrow::Cow, fs, path::PathBuf, sync::Arc};
+
+// Golden files for the native HTML renderer. Every `rfd_<number>.adoc` file in the corpus is
+// rendered and compared against the `rfd_<number>.html` file next to it. Run with
+// `UPDATE_GOLDEN=1` to regenerate the expected output after an intentional change to the renderer.
+fn corpus() -> Vec<(RFDNumber, PathBuf)> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/ref/rfd_render");
+
+    let mut documents = fs::read_dir(&dir)
+        .unwrap()
+        .filter_map(|entry| {
+            let path = entry.unwrap().path();
+            let number = path.file_stem()?.to_str()?.strip_prefix("rfd_")?.parse::<i32>().ok()?;
+
+            (path.extension()? == "adoc").then_some((RFDNumber::from(number), path))
+        })
+        .collect::<Vec<_>>();
+    documents.sort_by_key(|(_, path)| path.clone());
+
+    documents
+}
+
+#[tokio::test]
+async fn test_native_renderer_golden_files() {
+    let documents = corpus();
+    assert!(!documents.is_empty(), "The RFD render corpus is empty");
+
+    let update = std::env::var("UPDATE_GOLDEN").is_ok();
+
+    for (number, path) in documents {
+        let content = fs::read_to_string(&path).unwrap();
+        let html = RFDAsciidoc::new(Cow::Owned(content))
+            .with_renderer(Arc::new(NativeRenderer))
+            .render_html(&number)
+            .await
+            .unwrap();
+
+        let expected_path = path.with_extension("html");
+
+        if update {
+            fs::write(&expected_path, &html.0).unwrap();
+        } else {
+            let expected = fs::read_to_string(&expected_path)
+                .unwrap_or_else(|err| panic!("Failed to read {:?}: {}", expected_path, err));
+
+            assert_eq!(expected, html.0, "Rendered HTML for {:?} does not match", path);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use cio_api::text_extraction::{extract_text, ExtractError, ExtractLimits};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+// Golden files for text extraction. Every document in the corpus is extracted and compared against
+// the `.txt` file named after it, e.g. `resume.pdf.txt` for `resume.pdf`. Run with
+// `UPDATE_GOLDEN=1` to regenerate the expected output after an intentional change to extraction.
+fn corpus() -> Vec<PathBuf> {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/ref/materials");
+
+    let mut documents = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map(|ext| ext != "txt").unwrap_or(false))
+        .collect::<Vec<_>>();
+    documents.sort();
+
+    documents
+}
+
+fn expected_path(path: &Path) -> PathBuf {
+    let mut expected = path.as_os_str().to_owned();
+    expected.push(".txt");
+
+    expected.into()
+}
+
+#[test]
+fn test_extract_text_golden_files() {
+    let documents = corpus();
+    assert!(!documents.is_empty(), "The materials corpus is empty");
+
+    let update = std::env::var("UPDATE_GOLDEN").is_ok();
+
+    for path in documents {
+        let name = path.file_name().unwrap().to_str().unwrap();
+        let contents = fs::read(&path).unwrap();
+
+        // Leave the mime type out so the kind of document has to be detected.
+        let text = extract_text(name, "", &contents, &ExtractLimits::default()).unwrap();
+
+        let expected_path = expected_path(&path);
+        if update {
+            fs::write(&expected_path, format!("{}\n", text)).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(text, expected.trim_end(), "Extracted text of {} does not match", name);
+    }
+}
+
+#[test]
+fn test_extract_text_limits() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/ref/materials");
+    let pdf = fs::read(dir.join("resume.pdf")).unwrap();
+
+    // Only the first page is read.
+    let limits = ExtractLimits {
+        max_pages: 1,
+        ..Default::default()
+    };
+    let text = extract_text("resume.pdf", "application/pdf", &pdf, &limits).unwrap();
+    assert!(text.starts_with("Jane Doe"));
+    assert!(!text.contains("References"));
+
+    // Documents are limited before they are decompressed, and while they are.
+    let docx = fs::read(dir.join("resume.docx")).unwrap();
+    let limits = ExtractLimits {
+        max_bytes: docx.len() - 1,
+        ..Default::default()
+    };
+    assert!(matches!(
+        extract_text("resume.docx", "", &docx, &limits),
+        Err(ExtractError::TooLarge { .. })
+    ));
+    let large = fs::read(dir.join("../large_document.docx")).unwrap();
+    let limits = ExtractLimits {
+        max_bytes: 64 * 1024,
+        ..Default::default()
+    };
+    assert!(large.len() < limits.max_bytes);
+    assert!(matches!(
+        extract_text("large_document.docx", "", &large, &limits),
+        Err(ExtractError::TooLarge { .. })
+    ));
+    let large = fs::read(dir.join("../large_document.pdf")).unwrap();
+    assert!(large.len() < limits.max_bytes);
+    assert!(matches!(
+        extract_text("large_document.pdf", "", &large, &limits),
+        Err(ExtractError::TooLarge { .. })
+    ));
+    // The same PDF is fine when its streams fit.
+    let text = extract_text("large_document.pdf", "", &large, &ExtractLimits::default()).unwrap();
+    assert!(text.starts_with("Jane Doe"));
+
+    // Documents that claim to be something they are not are rejected.
+    assert!(matches!(
+        extract_text("resume.docx", "", b"not a zip archive", &ExtractLimits::default()),
+        Err(ExtractError::Malformed(_))
+    ));
+}
@@ -23,11 +23,13 @@
 #![allow(clippy::field_reassign_with_default)]
 use std::{env, error, fmt, sync::Arc};
 
+use async_trait::async_trait;
 use bytes::Bytes;
 use chrono::{offset::Utc, DateTime};
 use reqwest::{header, Client, Method, Request, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use token_refresh::{RefreshError, RefreshedToken, TokenRefresh};
 
 /// Entrypoint for interacting with the DocuSign API.
 #[derive(Debug, Clone)]
@@ -209,6 +211,16 @@ impl DocuSign {
             .await
             .unwrap();
 
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
         // Unwrap the response.
         let t: AccessToken = resp.json().await.unwrap();
 
@@ -515,6 +527,23 @@ impl DocuSign {
     }
 }
 
+#[async_trait]
+impl TokenRefresh for DocuSign {
+    async fn refresh(&mut self) -> Result<RefreshedToken, RefreshError> {
+        let t = self
+            .refresh_access_token()
+            .await
+            .map_err(|e| RefreshError::from_response(e.status_code.as_u16(), &e.body))?;
+
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in,
+            refresh_token: t.refresh_token,
+            refresh_token_expires_in: t.x_refresh_token_expires_in,
+        })
+    }
+}
+
 /// Error type returned by our library.
 pub struct APIError {
     pub status_code: StatusCode,
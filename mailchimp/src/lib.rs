@@ -22,10 +22,12 @@
  */
 use std::{collections::HashMap, env, error, fmt, fmt::Debug, sync::Arc};
 
+use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use reqwest::{header, Client, Method, RequestBuilder, StatusCode, Url};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use token_refresh::{RefreshError, RefreshedToken, TokenRefresh};
 
 /// Entrypoint for interacting with the MailChimp API.
 pub struct MailChimp {
@@ -151,7 +153,7 @@ impl MailChimp {
 
         let body = format!(
             "grant_type=refresh_token&client_id={}&client_secret={}&\
-             redirect_uri={}refresh_token={}",
+             redirect_uri={}&refresh_token={}",
             self.client_id,
             self.client_secret,
             urlencoding::encode(&self.redirect_uri),
@@ -165,6 +167,16 @@ impl MailChimp {
             .body(bytes::Bytes::from(body));
         let resp = req.send().await.unwrap();
 
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                return Err(APIError {
+                    status_code: s,
+                    body: resp.text().await.unwrap(),
+                })
+            }
+        };
+
         // Unwrap the response.
         let t: AccessToken = resp.json().await.unwrap();
 
@@ -278,6 +290,23 @@ impl MailChimp {
     }
 }
 
+#[async_trait]
+impl TokenRefresh for MailChimp {
+    async fn refresh(&mut self) -> Result<RefreshedToken, RefreshError> {
+        let t = self
+            .refresh_access_token()
+            .await
+            .map_err(|e| RefreshError::from_response(e.status_code.as_u16(), &e.body))?;
+
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in,
+            refresh_token: t.refresh_token,
+            refresh_token_expires_in: t.x_refresh_token_expires_in,
+        })
+    }
+}
+
 /// Error type returned by our library.
 pub struct APIError {
     pub status_code: StatusCode,
@@ -1,8 +1,8 @@
 use chrono::{DateTime, Utc};
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, Response, StatusCode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 pub struct MeiliClient {
     inner: Arc<InnerClient>,
@@ -12,6 +12,9 @@ pub struct MeiliClient {
 pub enum MeiliError {
     Client(reqwest::Error),
     FailedToParseResponse(serde_json::Error),
+    Api { status: StatusCode, error: ApiError },
+    TaskFailed(Box<Task>),
+    TaskTimeout(u32),
 }
 
 impl std::fmt::Display for MeiliError {
@@ -19,6 +22,12 @@ impl std::fmt::Display for MeiliError {
         match self {
             MeiliError::Client(inner) => write!(f, "Client error: {inner}"),
             MeiliError::FailedToParseResponse(inner) => write!(f, "Failed to parse response: {inner}"),
+            MeiliError::Api { status, error } => write!(f, "Request failed with {status}: {error}"),
+            MeiliError::TaskFailed(task) => match &task.error {
+                Some(error) => write!(f, "Task {} {}: {error}", task.uid, task.status),
+                None => write!(f, "Task {} {}", task.uid, task.status),
+            },
+            MeiliError::TaskTimeout(uid) => write!(f, "Timed out waiting for task {uid}"),
         }
     }
 }
@@ -28,6 +37,7 @@ impl std::error::Error for MeiliError {
         match self {
             MeiliError::Client(inner) => Some(inner),
             MeiliError::FailedToParseResponse(inner) => Some(inner),
+            _ => None,
         }
     }
 }
@@ -44,6 +54,29 @@ impl From<serde_json::Error> for MeiliError {
     }
 }
 
+/// The error body Meilisearch returns for failed requests and failed tasks.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiError {
+    pub message: String,
+    pub code: String,
+    #[serde(rename = "type")]
+    pub error_type: String,
+    #[serde(default)]
+    pub link: String,
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.message, self.code)
+    }
+}
+
+/// How often `wait_for_task` checks the status of a task by default.
+pub const DEFAULT_TASK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long `wait_for_task` waits for a task to finish by default.
+pub const DEFAULT_TASK_TIMEOUT: Duration = Duration::from_secs(60);
+
 impl MeiliClient {
     pub fn new(url: String, key: String) -> Self {
         Self {
@@ -57,6 +90,134 @@ impl MeiliClient {
             id,
         }
     }
+
+    /// Create a new index. Creating an index that already exists results in a failed task.
+    pub async fn create_index(&self, uid: &str, primary_key: Option<&str>) -> Result<TaskResponse, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .post(format!("{}/indexes", self.inner.url))
+            .bearer_auth(&self.inner.key)
+            .json(&CreateIndex { uid, primary_key })
+            .send()
+            .await?;
+
+        parse_response(response).await
+    }
+
+    /// Get an index, returning `None` if it does not exist.
+    pub async fn get_index(&self, uid: &str) -> Result<Option<Index>, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/indexes/{}", self.inner.url, uid))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            Ok(None)
+        } else {
+            parse_response(response).await.map(Some)
+        }
+    }
+
+    /// List all of the indexes on the instance.
+    pub async fn list_indexes(&self) -> Result<Vec<Index>, MeiliError> {
+        let mut indexes = vec![];
+        let limit = 100;
+
+        loop {
+            let response = self
+                .inner
+                .client
+                .get(format!("{}/indexes", self.inner.url))
+                .query(&[("offset", indexes.len()), ("limit", limit)])
+                .bearer_auth(&self.inner.key)
+                .send()
+                .await?;
+
+            let page: Page<Index> = parse_response(response).await?;
+            let done = page.results.len() < limit || indexes.len() + page.results.len() >= page.total;
+            indexes.extend(page.results);
+
+            if done {
+                return Ok(indexes);
+            }
+        }
+    }
+
+    /// Atomically swap the documents, settings and task history of each pair of indexes.
+    pub async fn swap_indexes(&self, pairs: &[(&str, &str)]) -> Result<TaskResponse, MeiliError> {
+        let swaps = pairs
+            .iter()
+            .map(|(a, b)| SwapIndexes { indexes: [*a, *b] })
+            .collect::<Vec<_>>();
+
+        let response = self
+            .inner
+            .client
+            .post(format!("{}/swap-indexes", self.inner.url))
+            .bearer_auth(&self.inner.key)
+            .json(&swaps)
+            .send()
+            .await?;
+
+        parse_response(response).await
+    }
+
+    /// Get the stats of the instance and all of its indexes.
+    pub async fn stats(&self) -> Result<Stats, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/stats", self.inner.url))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        parse_response(response).await
+    }
+
+    pub async fn get_task(&self, uid: u32) -> Result<Task, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/tasks/{}", self.inner.url, uid))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        parse_response(response).await
+    }
+
+    /// Poll a task until it has finished. A task that fails or is canceled is returned as an error.
+    pub async fn wait_for_task(
+        &self,
+        uid: u32,
+        interval: Option<Duration>,
+        timeout: Option<Duration>,
+    ) -> Result<Task, MeiliError> {
+        let interval = interval.unwrap_or(DEFAULT_TASK_INTERVAL);
+        let timeout = timeout.unwrap_or(DEFAULT_TASK_TIMEOUT);
+        let start = std::time::Instant::now();
+
+        loop {
+            let task = self.get_task(uid).await?;
+
+            match task.status {
+                TaskStatus::Succeeded => return Ok(task),
+                TaskStatus::Failed | TaskStatus::Canceled => return Err(MeiliError::TaskFailed(Box::new(task))),
+                TaskStatus::Enqueued | TaskStatus::Processing => {
+                    if start.elapsed() >= timeout {
+                        return Err(MeiliError::TaskTimeout(uid));
+                    }
+
+                    tokio::time::sleep(interval).await;
+                }
+            }
+        }
+    }
 }
 
 pub struct IndexClient {
@@ -65,27 +226,78 @@ pub struct IndexClient {
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SearchQuery {
     #[serde(rename = "q", skip_serializing_if = "Option::is_none")]
     pub query: Option<String>,
+    /// Filter expressions that all have to match, e.g. `rfd_number = 123`. The attributes have to
+    /// be listed in the `filterable_attributes` of the index.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub filter: Option<Vec<String>>,
+    /// Attributes to return the distribution of values for in `facet_distribution`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub facets: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attributes_to_retrieve: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes_to_highlight: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_pre_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight_post_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes_to_crop: Option<Vec<String>>,
+    /// The number of words to crop the attributes in `attributes_to_crop` to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crop_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
 }
 
 impl IndexClient {
-    pub async fn settings(&self, settings: IndexSettings) -> Result<serde_json::Value, MeiliError> {
-        Ok(self
+    pub fn uid(&self) -> &str {
+        &self.id
+    }
+
+    pub async fn get_settings(&self) -> Result<IndexSettings, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/indexes/{}/settings", self.inner.url, self.id))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        parse_response(response).await
+    }
+
+    pub async fn settings(&self, settings: IndexSettings) -> Result<TaskResponse, MeiliError> {
+        let response = self
             .inner
             .client
             .patch(format!("{}/indexes/{}/settings", self.inner.url, self.id))
             .bearer_auth(&self.inner.key)
             .json(&settings)
             .send()
-            .await?
-            .json::<serde_json::Value>()
-            .await?)
+            .await?;
+
+        parse_response(response).await
+    }
+
+    pub async fn stats(&self) -> Result<IndexStats, MeiliError> {
+        let response = self
+            .inner
+            .client
+            .get(format!("{}/indexes/{}/stats", self.inner.url, self.id))
+            .bearer_auth(&self.inner.key)
+            .send()
+            .await?;
+
+        parse_response(response).await
     }
 
     pub async fn search<T>(&self, search: SearchQuery) -> Result<SearchResponse<T>, MeiliError>
@@ -102,10 +314,13 @@ impl IndexClient {
             .await?;
 
         if response.status() == StatusCode::NOT_FOUND {
-            Ok(SearchResponse { hits: vec![] })
+            Ok(SearchResponse {
+                hits: vec![],
+                facet_distribution: None,
+                estimated_total_hits: None,
+            })
         } else {
-            let content = response.text().await?;
-            Ok(serde_json::from_str::<SearchResponse<T>>(&content)?)
+            parse_response(response).await
         }
     }
 
@@ -125,24 +340,23 @@ impl IndexClient {
             .send()
             .await?;
 
-        let content = response.text().await?;
-        Ok(serde_json::from_str::<TaskResponse>(&content)?)
+        parse_response(response).await
     }
 
     pub async fn delete_documents<T>(&self, ids: &[T]) -> Result<TaskResponse, MeiliError>
     where
         T: Serialize,
     {
-        Ok(self
+        let response = self
             .inner
             .client
             .post(format!("{}/indexes/{}/documents/delete-batch", self.inner.url, self.id))
             .bearer_auth(&self.inner.key)
             .json(ids)
             .send()
-            .await?
-            .json::<TaskResponse>()
-            .await?)
+            .await?;
+
+        parse_response(response).await
     }
 
     pub async fn delete(&self) -> Result<TaskResponse, MeiliError> {
@@ -154,33 +368,202 @@ impl IndexClient {
             .send()
             .await?;
 
-        let content = response.text().await?;
-        Ok(serde_json::from_str::<TaskResponse>(&content)?)
+        parse_response(response).await
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct IndexSettings {
-    #[serde(rename = "filterableAttributes", skip_serializing_if = "Option::is_none")]
+    /// The ranking rules in order of importance, e.g. `words`, `typo` or `rfd_number:asc`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranking_rules: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub searchable_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub displayed_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub filterable_attributes: Option<Vec<String>>,
-    #[serde(rename = "sortableAttributes", skip_serializing_if = "Option::is_none")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sortable_attributes: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distinct_attribute: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_words: Option<Vec<String>>,
+    /// Words that are considered equivalent, keyed by the word being searched for.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub synonyms: Option<HashMap<String, Vec<String>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub typo_tolerance: Option<TypoTolerance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub faceting: Option<Faceting>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypoTolerance {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_word_size_for_typos: Option<MinWordSizeForTypos>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_on_words: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_on_attributes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinWordSizeForTypos {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_typo: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub two_typos: Option<u8>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Faceting {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_values_per_facet: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    /// The most hits a search can return, however large its `limit` is. Defaults to 1000.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_total_hits: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SearchResponse<T> {
     pub hits: Vec<T>,
+    /// The number of documents per value of each of the requested facets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub facet_distribution: Option<HashMap<String, HashMap<String, u64>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_total_hits: Option<u64>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TaskResponse {
     #[serde(rename = "taskUid")]
-    task_uid: u32,
+    pub task_uid: u32,
     #[serde(rename = "indexUid")]
-    index_uid: String,
-    status: String,
+    pub index_uid: Option<String>,
+    pub status: TaskStatus,
     #[serde(rename = "enqueuedAt")]
-    enqueued_at: DateTime<Utc>,
+    pub enqueued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TaskStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    Canceled,
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status = match self {
+            TaskStatus::Enqueued => "enqueued",
+            TaskStatus::Processing => "processing",
+            TaskStatus::Succeeded => "succeeded",
+            TaskStatus::Failed => "failed",
+            TaskStatus::Canceled => "canceled",
+        };
+
+        write!(f, "{status}")
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    pub uid: u32,
+    pub index_uid: Option<String>,
+    pub status: TaskStatus,
+    #[serde(rename = "type")]
+    pub task_type: String,
+    #[serde(default)]
+    pub details: Option<serde_json::Value>,
+    #[serde(default)]
+    pub error: Option<ApiError>,
+    pub enqueued_at: DateTime<Utc>,
+    #[serde(default)]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub uid: String,
+    pub primary_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexStats {
+    pub number_of_documents: u64,
+    pub is_indexing: bool,
+    #[serde(default)]
+    pub field_distribution: HashMap<String, u64>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Stats {
+    pub database_size: u64,
+    pub last_update: Option<DateTime<Utc>>,
+    pub indexes: HashMap<String, IndexStats>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CreateIndex<'a> {
+    uid: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    primary_key: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct SwapIndexes<'a> {
+    indexes: [&'a str; 2],
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    results: Vec<T>,
+    total: usize,
+}
+
+/// Parse a successful response, or the error Meilisearch returned in place of it.
+async fn parse_response<T>(response: Response) -> Result<T, MeiliError>
+where
+    T: DeserializeOwned,
+{
+    let status = response.status();
+    let content = response.text().await?;
+
+    if status.is_success() {
+        Ok(serde_json::from_str::<T>(&content)?)
+    } else {
+        Err(MeiliError::Api {
+            status,
+            error: serde_json::from_str::<ApiError>(&content)?,
+        })
+    }
 }
 
 struct InnerClient {
@@ -1,4 +1,6 @@
-use meilisearch_minimal_api::{IndexClient, MeiliClient, SearchQuery};
+use meilisearch_minimal_api::{
+    Faceting, IndexClient, IndexSettings, MeiliClient, MeiliError, Pagination, SearchQuery, TaskStatus, TypoTolerance,
+};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -86,3 +88,98 @@ async fn test_search() {
         assert!(delete_index_result.is_ok());
     }
 }
+
+#[test]
+fn test_serialize_query_and_settings() {
+    let query = SearchQuery {
+        query: Some("doc".to_string()),
+        filter: Some(vec!["rfd_number = 1".to_string()]),
+        facets: Some(vec!["state".to_string()]),
+        attributes_to_retrieve: Some(vec!["objectID".to_string()]),
+        limit: Some(5),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        serde_json::to_value(&query).unwrap(),
+        serde_json::json!({
+            "q": "doc",
+            "filter": ["rfd_number = 1"],
+            "facets": ["state"],
+            "attributesToRetrieve": ["objectID"],
+            "limit": 5,
+        })
+    );
+
+    let settings = IndexSettings {
+        ranking_rules: Some(vec!["words".to_string(), "rfd_number:asc".to_string()]),
+        synonyms: Some([("db".to_string(), vec!["database".to_string()])].into_iter().collect()),
+        typo_tolerance: Some(TypoTolerance {
+            enabled: Some(false),
+            ..Default::default()
+        }),
+        faceting: Some(Faceting {
+            max_values_per_facet: Some(10),
+        }),
+        pagination: Some(Pagination {
+            max_total_hits: Some(5000),
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        serde_json::to_value(&settings).unwrap(),
+        serde_json::json!({
+            "rankingRules": ["words", "rfd_number:asc"],
+            "synonyms": { "db": ["database"] },
+            "typoTolerance": { "enabled": false },
+            "faceting": { "maxValuesPerFacet": 10 },
+            "pagination": { "maxTotalHits": 5000 },
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_create_and_swap_indexes() {
+    if let Some(client) = TestDB::new("test_create_and_swap_indexes") {
+        let blue = client.index_name.clone() + "-blue";
+        let green = client.index_name.clone() + "-green";
+
+        for uid in [&blue, &green] {
+            let task = client.client.create_index(uid, Some("objectID")).await.unwrap();
+            let task = client.client.wait_for_task(task.task_uid, None, None).await.unwrap();
+            assert_eq!(task.status, TaskStatus::Succeeded);
+        }
+
+        // Creating an index twice fails asynchronously
+        let task = client.client.create_index(&blue, None).await.unwrap();
+        let result = client.client.wait_for_task(task.task_uid, None, None).await;
+        assert!(matches!(result, Err(MeiliError::TaskFailed(_))));
+
+        let indexes = client.client.list_indexes().await.unwrap();
+        assert!(indexes.iter().any(|index| index.uid == blue));
+        assert!(indexes.iter().any(|index| index.uid == green));
+
+        let document = Document {
+            object_id: "doc1".to_string(),
+        };
+        let task = client
+            .client
+            .index(green.clone())
+            .index_documents(&[document], "objectID")
+            .await
+            .unwrap();
+        client.client.wait_for_task(task.task_uid, None, None).await.unwrap();
+
+        let task = client.client.swap_indexes(&[(&blue, &green)]).await.unwrap();
+        client.client.wait_for_task(task.task_uid, None, None).await.unwrap();
+
+        let stats = client.client.index(blue.clone()).stats().await.unwrap();
+        assert_eq!(stats.number_of_documents, 1);
+
+        for uid in [blue, green] {
+            let task = client.client.index(uid).delete().await.unwrap();
+            client.client.wait_for_task(task.task_uid, None, None).await.unwrap();
+        }
+    }
+}
@@ -0,0 +1,1215 @@
+//! A typed builder for Slack's Block Kit.
+//!
+//! Each kind of block and element has its own type, so a block can only be given the things
+//! Slack accepts for it: a header only takes plain text, an actions block only takes interactive
+//! elements, an input block only takes input elements. The builders consume and return `self` so
+//! a message reads top to bottom:
+//!
+//! ```
+//! use slack_chat_api::{
+//!     blocks::{ButtonElement, ContextBlock, SectionBlock},
+//!     FormattedMessage,
+//! };
+//!
+//! let msg = FormattedMessage::new()
+//!     .block(SectionBlock::markdown("*Deploy finished*").accessory(ButtonElement::new("Roll back", "rollback")))
+//!     .block(ContextBlock::new().markdown("_5 minutes ago_"));
+//!
+//! assert_eq!(
+//!     serde_json::to_value(&msg.blocks).unwrap(),
+//!     serde_json::json!([
+//!         {
+//!             "type": "section",
+//!             "text": {"type": "mrkdwn", "text": "*Deploy finished*"},
+//!             "accessory": {
+//!                 "type": "button",
+//!                 "text": {"type": "plain_text", "text": "Roll back"},
+//!                 "action_id": "rollback"
+//!             }
+//!         },
+//!         {"type": "context", "elements": [{"type": "mrkdwn", "text": "_5 minutes ago_"}]}
+//!     ])
+//! );
+//! ```
+//!
+//! Slack adds kinds of blocks and elements over time. Anything we do not model deserializes to the
+//! `Unknown` variant of the enum it appears in, so a message or a view submission we did not build
+//! ourselves still parses.
+//!
+//! Docs: https://api.slack.com/reference/block-kit
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A text object.
+///
+/// Docs: https://api.slack.com/reference/block-kit/composition-objects#text
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum Text {
+    #[serde(rename = "plain_text")]
+    Plain {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        emoji: Option<bool>,
+    },
+    #[serde(rename = "mrkdwn")]
+    Markdown {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        verbatim: Option<bool>,
+    },
+    /// A type of text we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Text::plain("")
+    }
+}
+
+impl Text {
+    pub fn plain<T: ToString>(text: T) -> Self {
+        Text::Plain {
+            text: text.to_string(),
+            emoji: None,
+        }
+    }
+
+    pub fn markdown<T: ToString>(text: T) -> Self {
+        Text::Markdown {
+            text: text.to_string(),
+            verbatim: None,
+        }
+    }
+
+    /// The raw text, whatever its type.
+    pub fn text(&self) -> &str {
+        match self {
+            Text::Plain { text, .. } | Text::Markdown { text, .. } => text,
+            Text::Unknown => "",
+        }
+    }
+
+    /// Replace the text, keeping its type.
+    pub fn set_text<T: ToString>(&mut self, new: T) {
+        match self {
+            Text::Plain { text, .. } | Text::Markdown { text, .. } => *text = new.to_string(),
+            Text::Unknown => {}
+        }
+    }
+}
+
+/// A block in a message or a view.
+///
+/// Docs: https://api.slack.com/reference/block-kit/blocks
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Block {
+    Section(SectionBlock),
+    Context(ContextBlock),
+    Divider(DividerBlock),
+    Header(HeaderBlock),
+    Actions(ActionsBlock),
+    Image(ImageBlock),
+    RichText(RichTextBlock),
+    Input(InputBlock),
+    /// A block we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+impl Block {
+    pub fn divider() -> Self {
+        Block::Divider(DividerBlock::default())
+    }
+
+    /// The section in this block, if it is one.
+    pub fn as_section(&self) -> Option<&SectionBlock> {
+        match self {
+            Block::Section(section) => Some(section),
+            _ => None,
+        }
+    }
+
+    /// The section in this block, if it is one, for editing.
+    pub fn as_section_mut(&mut self) -> Option<&mut SectionBlock> {
+        match self {
+            Block::Section(section) => Some(section),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_into_block {
+    ($($block:ident => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$block> for Block {
+                fn from(block: $block) -> Self {
+                    Block::$variant(block)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_block!(
+    SectionBlock => Section,
+    ContextBlock => Context,
+    DividerBlock => Divider,
+    HeaderBlock => Header,
+    ActionsBlock => Actions,
+    ImageBlock => Image,
+    RichTextBlock => RichText,
+    InputBlock => Input,
+);
+
+/// A section block: some text, optional fields laid out in two columns and an optional
+/// accessory on the right.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SectionBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<Text>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<Text>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accessory: Option<Accessory>,
+}
+
+impl SectionBlock {
+    pub fn new(text: Text) -> Self {
+        SectionBlock {
+            text: Some(text),
+            ..Default::default()
+        }
+    }
+
+    pub fn markdown<T: ToString>(text: T) -> Self {
+        SectionBlock::new(Text::markdown(text))
+    }
+
+    pub fn plain<T: ToString>(text: T) -> Self {
+        SectionBlock::new(Text::plain(text))
+    }
+
+    pub fn block_id<T: ToString>(mut self, block_id: T) -> Self {
+        self.block_id = block_id.to_string();
+        self
+    }
+
+    pub fn field(mut self, field: Text) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn accessory<A: Into<Accessory>>(mut self, accessory: A) -> Self {
+        self.accessory = Some(accessory.into());
+        self
+    }
+}
+
+/// A context block: a line of small text and images.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ContextBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    #[serde(default)]
+    pub elements: Vec<ContextElement>,
+}
+
+impl ContextBlock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn block_id<T: ToString>(mut self, block_id: T) -> Self {
+        self.block_id = block_id.to_string();
+        self
+    }
+
+    pub fn element<E: Into<ContextElement>>(mut self, element: E) -> Self {
+        self.elements.push(element.into());
+        self
+    }
+
+    pub fn markdown<T: ToString>(self, text: T) -> Self {
+        self.element(Text::markdown(text))
+    }
+
+    pub fn plain<T: ToString>(self, text: T) -> Self {
+        self.element(Text::plain(text))
+    }
+
+    pub fn image<U: ToString, A: ToString>(self, image_url: U, alt_text: A) -> Self {
+        self.element(ImageElement::new(image_url, alt_text))
+    }
+}
+
+/// An element of a context block: text or a small image.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type")]
+pub enum ContextElement {
+    #[serde(rename = "plain_text")]
+    Plain {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        emoji: Option<bool>,
+    },
+    #[serde(rename = "mrkdwn")]
+    Markdown {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        verbatim: Option<bool>,
+    },
+    #[serde(rename = "image")]
+    Image(ImageElement),
+    /// An element we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+impl From<Text> for ContextElement {
+    fn from(text: Text) -> Self {
+        match text {
+            Text::Plain { text, emoji } => ContextElement::Plain { text, emoji },
+            Text::Markdown { text, verbatim } => ContextElement::Markdown { text, verbatim },
+            Text::Unknown => ContextElement::Unknown,
+        }
+    }
+}
+
+impl From<ImageElement> for ContextElement {
+    fn from(image: ImageElement) -> Self {
+        ContextElement::Image(image)
+    }
+}
+
+/// A divider block.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct DividerBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+}
+
+/// A header block. Headers only take plain text, of at most 150 characters.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct HeaderBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    pub text: Text,
+}
+
+impl HeaderBlock {
+    pub fn new<T: ToString>(text: T) -> Self {
+        HeaderBlock {
+            block_id: Default::default(),
+            text: Text::plain(text),
+        }
+    }
+}
+
+/// An actions block: a row of interactive elements.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ActionsBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    #[serde(default)]
+    pub elements: Vec<ActionElement>,
+}
+
+impl ActionsBlock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn block_id<T: ToString>(mut self, block_id: T) -> Self {
+        self.block_id = block_id.to_string();
+        self
+    }
+
+    pub fn element<E: Into<ActionElement>>(mut self, element: E) -> Self {
+        self.elements.push(element.into());
+        self
+    }
+}
+
+/// An image block.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ImageBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    pub image_url: String,
+    pub alt_text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<Text>,
+}
+
+impl ImageBlock {
+    pub fn new<U: ToString, A: ToString>(image_url: U, alt_text: A) -> Self {
+        ImageBlock {
+            image_url: image_url.to_string(),
+            alt_text: alt_text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn title<T: ToString>(mut self, title: T) -> Self {
+        self.title = Some(Text::plain(title));
+        self
+    }
+}
+
+/// A rich text block, the format Slack's own composer sends messages in.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RichTextBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    #[serde(default)]
+    pub elements: Vec<RichTextElement>,
+}
+
+impl RichTextBlock {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn section(mut self, section: RichTextSection) -> Self {
+        self.elements.push(RichTextElement::RichTextSection(section));
+        self
+    }
+
+    pub fn list(mut self, style: RichTextListStyle, items: Vec<RichTextSection>) -> Self {
+        self.elements.push(RichTextElement::RichTextList(RichTextList {
+            style,
+            elements: items.into_iter().map(RichTextElement::RichTextSection).collect(),
+            indent: None,
+        }));
+        self
+    }
+
+    pub fn preformatted(mut self, section: RichTextSection) -> Self {
+        self.elements.push(RichTextElement::RichTextPreformatted(section));
+        self
+    }
+
+    pub fn quote(mut self, section: RichTextSection) -> Self {
+        self.elements.push(RichTextElement::RichTextQuote(section));
+        self
+    }
+}
+
+/// A top level element of a rich text block.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RichTextElement {
+    RichTextSection(RichTextSection),
+    RichTextList(RichTextList),
+    RichTextPreformatted(RichTextSection),
+    RichTextQuote(RichTextSection),
+    /// An element we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+/// A run of inline rich text.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RichTextSection {
+    #[serde(default)]
+    pub elements: Vec<RichTextSpan>,
+}
+
+impl RichTextSection {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn span(mut self, span: RichTextSpan) -> Self {
+        self.elements.push(span);
+        self
+    }
+
+    pub fn text<T: ToString>(self, text: T) -> Self {
+        self.styled(text, None)
+    }
+
+    pub fn bold<T: ToString>(self, text: T) -> Self {
+        self.styled(
+            text,
+            Some(RichTextStyle {
+                bold: true,
+                ..Default::default()
+            }),
+        )
+    }
+
+    pub fn italic<T: ToString>(self, text: T) -> Self {
+        self.styled(
+            text,
+            Some(RichTextStyle {
+                italic: true,
+                ..Default::default()
+            }),
+        )
+    }
+
+    pub fn code<T: ToString>(self, text: T) -> Self {
+        self.styled(
+            text,
+            Some(RichTextStyle {
+                code: true,
+                ..Default::default()
+            }),
+        )
+    }
+
+    pub fn styled<T: ToString>(self, text: T, style: Option<RichTextStyle>) -> Self {
+        self.span(RichTextSpan::Text {
+            text: text.to_string(),
+            style,
+        })
+    }
+
+    pub fn link<U: ToString, T: ToString>(self, url: U, text: T) -> Self {
+        self.span(RichTextSpan::Link {
+            url: url.to_string(),
+            text: text.to_string(),
+        })
+    }
+
+    pub fn user<T: ToString>(self, user_id: T) -> Self {
+        self.span(RichTextSpan::User {
+            user_id: user_id.to_string(),
+        })
+    }
+
+    pub fn channel<T: ToString>(self, channel_id: T) -> Self {
+        self.span(RichTextSpan::Channel {
+            channel_id: channel_id.to_string(),
+        })
+    }
+
+    pub fn emoji<T: ToString>(self, name: T) -> Self {
+        self.span(RichTextSpan::Emoji { name: name.to_string() })
+    }
+}
+
+/// A bulleted or ordered list. Each item is a rich text section.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct RichTextList {
+    pub style: RichTextListStyle,
+    #[serde(default)]
+    pub elements: Vec<RichTextElement>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub indent: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RichTextListStyle {
+    Bullet,
+    Ordered,
+}
+
+/// An inline element of rich text.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RichTextSpan {
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        style: Option<RichTextStyle>,
+    },
+    Link {
+        url: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        text: String,
+    },
+    User {
+        user_id: String,
+    },
+    Channel {
+        channel_id: String,
+    },
+    Emoji {
+        name: String,
+    },
+    /// An element we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+pub struct RichTextStyle {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bold: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub italic: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strike: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub code: bool,
+}
+
+/// An input block, only valid in modals.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct InputBlock {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub block_id: String,
+    pub label: Text,
+    pub element: InputElement,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<Text>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+}
+
+impl InputBlock {
+    pub fn new<L: ToString, E: Into<InputElement>>(label: L, element: E) -> Self {
+        InputBlock {
+            block_id: Default::default(),
+            label: Text::plain(label),
+            element: element.into(),
+            hint: None,
+            optional: false,
+        }
+    }
+
+    pub fn block_id<T: ToString>(mut self, block_id: T) -> Self {
+        self.block_id = block_id.to_string();
+        self
+    }
+
+    pub fn hint<T: ToString>(mut self, hint: T) -> Self {
+        self.hint = Some(Text::plain(hint));
+        self
+    }
+
+    pub fn optional(mut self) -> Self {
+        self.optional = true;
+        self
+    }
+}
+
+/// An element that can be the accessory of a section block.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Accessory {
+    Button(ButtonElement),
+    StaticSelect(StaticSelectElement),
+    UsersSelect(UsersSelectElement),
+    ChannelsSelect(ChannelsSelectElement),
+    Image(ImageElement),
+    /// An element we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An element that can be placed in an actions block.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ActionElement {
+    Button(ButtonElement),
+    StaticSelect(StaticSelectElement),
+    UsersSelect(UsersSelectElement),
+    ChannelsSelect(ChannelsSelectElement),
+    /// An element we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An element that can be placed in an input block.
+#[derive(Debug, Clone, PartialEq, JsonSchema, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InputElement {
+    PlainTextInput(PlainTextInputElement),
+    StaticSelect(StaticSelectElement),
+    UsersSelect(UsersSelectElement),
+    ChannelsSelect(ChannelsSelectElement),
+    /// An element we do not model, this only happens when deserializing what Slack sends us.
+    #[serde(other)]
+    Unknown,
+}
+
+macro_rules! impl_into_element {
+    ($element:ident => $variant:ident for $($target:ident),*) => {
+        $(
+            impl From<$element> for $target {
+                fn from(element: $element) -> Self {
+                    $target::$variant(element)
+                }
+            }
+        )*
+    };
+}
+
+impl_into_element!(ButtonElement => Button for Accessory, ActionElement);
+impl_into_element!(StaticSelectElement => StaticSelect for Accessory, ActionElement, InputElement);
+impl_into_element!(UsersSelectElement => UsersSelect for Accessory, ActionElement, InputElement);
+impl_into_element!(ChannelsSelectElement => ChannelsSelect for Accessory, ActionElement, InputElement);
+impl_into_element!(ImageElement => Image for Accessory);
+impl_into_element!(PlainTextInputElement => PlainTextInput for InputElement);
+
+/// A button. Clicking it sends its `action_id` and `value` to the interactivity endpoint.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ButtonElement {
+    pub text: Text,
+    pub action_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub value: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<ButtonStyle>,
+}
+
+impl ButtonElement {
+    pub fn new<T: ToString, A: ToString>(text: T, action_id: A) -> Self {
+        ButtonElement {
+            text: Text::plain(text),
+            action_id: action_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn value<T: ToString>(mut self, value: T) -> Self {
+        self.value = value.to_string();
+        self
+    }
+
+    pub fn url<T: ToString>(mut self, url: T) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    pub fn style(mut self, style: ButtonStyle) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, JsonSchema, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonStyle {
+    Primary,
+    Danger,
+}
+
+/// A select menu with a fixed list of options.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct StaticSelectElement {
+    pub action_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+    #[serde(default)]
+    pub options: Vec<SelectOption>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_option: Option<SelectOption>,
+}
+
+impl StaticSelectElement {
+    pub fn new<A: ToString>(action_id: A) -> Self {
+        StaticSelectElement {
+            action_id: action_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn placeholder<T: ToString>(mut self, placeholder: T) -> Self {
+        self.placeholder = Some(Text::plain(placeholder));
+        self
+    }
+
+    pub fn option<T: ToString, V: ToString>(mut self, text: T, value: V) -> Self {
+        self.options.push(SelectOption::new(text, value));
+        self
+    }
+
+    /// Select one of the options up front, by value.
+    pub fn initial_value(mut self, value: &str) -> Self {
+        self.initial_option = self.options.iter().find(|o| o.value == value).cloned();
+        self
+    }
+}
+
+/// An option of a select menu.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct SelectOption {
+    pub text: Text,
+    pub value: String,
+}
+
+impl SelectOption {
+    pub fn new<T: ToString, V: ToString>(text: T, value: V) -> Self {
+        SelectOption {
+            text: Text::plain(text),
+            value: value.to_string(),
+        }
+    }
+}
+
+/// A select menu of the users in the workspace.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct UsersSelectElement {
+    pub action_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub initial_user: String,
+}
+
+impl UsersSelectElement {
+    pub fn new<A: ToString>(action_id: A) -> Self {
+        UsersSelectElement {
+            action_id: action_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn placeholder<T: ToString>(mut self, placeholder: T) -> Self {
+        self.placeholder = Some(Text::plain(placeholder));
+        self
+    }
+
+    pub fn initial_user<T: ToString>(mut self, user_id: T) -> Self {
+        self.initial_user = user_id.to_string();
+        self
+    }
+}
+
+/// A select menu of the public channels in the workspace.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ChannelsSelectElement {
+    pub action_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub initial_channel: String,
+}
+
+impl ChannelsSelectElement {
+    pub fn new<A: ToString>(action_id: A) -> Self {
+        ChannelsSelectElement {
+            action_id: action_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn placeholder<T: ToString>(mut self, placeholder: T) -> Self {
+        self.placeholder = Some(Text::plain(placeholder));
+        self
+    }
+
+    pub fn initial_channel<T: ToString>(mut self, channel_id: T) -> Self {
+        self.initial_channel = channel_id.to_string();
+        self
+    }
+}
+
+/// A small image, as a section accessory or in a context block.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct ImageElement {
+    pub image_url: String,
+    pub alt_text: String,
+}
+
+impl ImageElement {
+    pub fn new<U: ToString, A: ToString>(image_url: U, alt_text: A) -> Self {
+        ImageElement {
+            image_url: image_url.to_string(),
+            alt_text: alt_text.to_string(),
+        }
+    }
+}
+
+/// A text input.
+#[derive(Debug, Clone, Default, PartialEq, JsonSchema, Deserialize, Serialize)]
+pub struct PlainTextInputElement {
+    pub action_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Text>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiline: bool,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub initial_value: String,
+}
+
+impl PlainTextInputElement {
+    pub fn new<A: ToString>(action_id: A) -> Self {
+        PlainTextInputElement {
+            action_id: action_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn placeholder<T: ToString>(mut self, placeholder: T) -> Self {
+        self.placeholder = Some(Text::plain(placeholder));
+        self
+    }
+
+    pub fn multiline(mut self) -> Self {
+        self.multiline = true;
+        self
+    }
+
+    pub fn initial_value<T: ToString>(mut self, value: T) -> Self {
+        self.initial_value = value.to_string();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // Checks that what we build serializes to what Slack documents, and that what Slack documents
+    // parses back to what we build.
+    fn assert_json<T>(value: T, expected: serde_json::Value)
+    where
+        T: std::fmt::Debug + PartialEq + Serialize + serde::de::DeserializeOwned,
+    {
+        assert_eq!(serde_json::to_value(&value).unwrap(), expected);
+        assert_eq!(serde_json::from_value::<T>(expected).unwrap(), value);
+    }
+
+    #[test]
+    fn test_section_block() {
+        assert_json(
+            Block::from(
+                SectionBlock::markdown("A message *with some bold text* and _some italicized text_.")
+                    .block_id("section567")
+                    .field(Text::markdown("*Priority*"))
+                    .field(Text::plain("High"))
+                    .accessory(ImageElement::new(
+                        "https://pbs.twimg.com/profile_images/625633822235693056/lNGUneLX_400x400.jpg",
+                        "cute cat",
+                    )),
+            ),
+            json!({
+                "type": "section",
+                "block_id": "section567",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": "A message *with some bold text* and _some italicized text_."
+                },
+                "fields": [
+                    {"type": "mrkdwn", "text": "*Priority*"},
+                    {"type": "plain_text", "text": "High"}
+                ],
+                "accessory": {
+                    "type": "image",
+                    "image_url": "https://pbs.twimg.com/profile_images/625633822235693056/lNGUneLX_400x400.jpg",
+                    "alt_text": "cute cat"
+                }
+            }),
+        );
+    }
+
+    #[test]
+    fn test_context_block() {
+        assert_json(
+            Block::from(
+                ContextBlock::new()
+                    .image(
+                        "https://image.freepik.com/free-photo/red-drawing-pin_1156-445.jpg",
+                        "images",
+                    )
+                    .markdown("Location: **Dogpatch**"),
+            ),
+            json!({
+                "type": "context",
+                "elements": [
+                    {
+                        "type": "image",
+                        "image_url": "https://image.freepik.com/free-photo/red-drawing-pin_1156-445.jpg",
+                        "alt_text": "images"
+                    },
+                    {"type": "mrkdwn", "text": "Location: **Dogpatch**"}
+                ]
+            }),
+        );
+    }
+
+    #[test]
+    fn test_divider_block() {
+        assert_json(Block::divider(), json!({"type": "divider"}));
+    }
+
+    #[test]
+    fn test_header_block() {
+        assert_json(
+            Block::from(HeaderBlock::new("Budget Performance")),
+            json!({
+                "type": "header",
+                "text": {"type": "plain_text", "text": "Budget Performance"}
+            }),
+        );
+    }
+
+    #[test]
+    fn test_image_block() {
+        let mut image = ImageBlock::new("http://placekitten.com/500/500", "An incredibly cute kitten.")
+            .title("Please enjoy this photo of a kitten");
+        image.block_id = "image4".to_string();
+
+        assert_json(
+            Block::from(image),
+            json!({
+                "type": "image",
+                "title": {"type": "plain_text", "text": "Please enjoy this photo of a kitten"},
+                "block_id": "image4",
+                "image_url": "http://placekitten.com/500/500",
+                "alt_text": "An incredibly cute kitten."
+            }),
+        );
+    }
+
+    #[test]
+    fn test_actions_block() {
+        assert_json(
+            Block::from(
+                ActionsBlock::new()
+                    .block_id("actionblock789")
+                    .element(
+                        ButtonElement::new("Approve", "approve")
+                            .value("click_me_123")
+                            .style(ButtonStyle::Primary),
+                    )
+                    .element(ButtonElement::new("Docs", "docs").url("https://api.slack.com/block-kit"))
+                    .element(
+                        StaticSelectElement::new("text1234")
+                            .placeholder("Select an item")
+                            .option("*this is plain_text text*", "value-0")
+                            .option("*this is plain_text text*", "value-1")
+                            .initial_value("value-1"),
+                    )
+                    .element(
+                        UsersSelectElement::new("text1234")
+                            .placeholder("Select a user")
+                            .initial_user("U064B2C3D4E"),
+                    )
+                    .element(
+                        ChannelsSelectElement::new("text1234")
+                            .placeholder("Select a channel")
+                            .initial_channel("C123ABC456"),
+                    ),
+            ),
+            json!({
+                "type": "actions",
+                "block_id": "actionblock789",
+                "elements": [
+                    {
+                        "type": "button",
+                        "text": {"type": "plain_text", "text": "Approve"},
+                        "action_id": "approve",
+                        "value": "click_me_123",
+                        "style": "primary"
+                    },
+                    {
+                        "type": "button",
+                        "text": {"type": "plain_text", "text": "Docs"},
+                        "action_id": "docs",
+                        "url": "https://api.slack.com/block-kit"
+                    },
+                    {
+                        "type": "static_select",
+                        "action_id": "text1234",
+                        "placeholder": {"type": "plain_text", "text": "Select an item"},
+                        "options": [
+                            {"text": {"type": "plain_text", "text": "*this is plain_text text*"}, "value": "value-0"},
+                            {"text": {"type": "plain_text", "text": "*this is plain_text text*"}, "value": "value-1"}
+                        ],
+                        "initial_option": {
+                            "text": {"type": "plain_text", "text": "*this is plain_text text*"},
+                            "value": "value-1"
+                        }
+                    },
+                    {
+                        "type": "users_select",
+                        "action_id": "text1234",
+                        "placeholder": {"type": "plain_text", "text": "Select a user"},
+                        "initial_user": "U064B2C3D4E"
+                    },
+                    {
+                        "type": "channels_select",
+                        "action_id": "text1234",
+                        "placeholder": {"type": "plain_text", "text": "Select a channel"},
+                        "initial_channel": "C123ABC456"
+                    }
+                ]
+            }),
+        );
+    }
+
+    #[test]
+    fn test_rich_text_block() {
+        assert_json(
+            Block::from(
+                RichTextBlock::new()
+                    .section(
+                        RichTextSection::new()
+                            .text("Hello there, ")
+                            .bold("I am a basic rich text block!")
+                            .link("https://api.slack.com", "docs")
+                            .user("U123ABC456")
+                            .channel("C123ABC456")
+                            .emoji("wave"),
+                    )
+                    .list(
+                        RichTextListStyle::Bullet,
+                        vec![
+                            RichTextSection::new().text("huddles"),
+                            RichTextSection::new().italic("canvas"),
+                        ],
+                    )
+                    .preformatted(RichTextSection::new().text("cargo build"))
+                    .quote(RichTextSection::new().code("Everything is fine")),
+            ),
+            json!({
+                "type": "rich_text",
+                "elements": [
+                    {
+                        "type": "rich_text_section",
+                        "elements": [
+                            {"type": "text", "text": "Hello there, "},
+                            {"type": "text", "text": "I am a basic rich text block!", "style": {"bold": true}},
+                            {"type": "link", "url": "https://api.slack.com", "text": "docs"},
+                            {"type": "user", "user_id": "U123ABC456"},
+                            {"type": "channel", "channel_id": "C123ABC456"},
+                            {"type": "emoji", "name": "wave"}
+                        ]
+                    },
+                    {
+                        "type": "rich_text_list",
+                        "style": "bullet",
+                        "elements": [
+                            {"type": "rich_text_section", "elements": [{"type": "text", "text": "huddles"}]},
+                            {
+                                "type": "rich_text_section",
+                                "elements": [{"type": "text", "text": "canvas", "style": {"italic": true}}]
+                            }
+                        ]
+                    },
+                    {"type": "rich_text_preformatted", "elements": [{"type": "text", "text": "cargo build"}]},
+                    {
+                        "type": "rich_text_quote",
+                        "elements": [{"type": "text", "text": "Everything is fine", "style": {"code": true}}]
+                    }
+                ]
+            }),
+        );
+    }
+
+    #[test]
+    fn test_input_block() {
+        assert_json(
+            Block::from(
+                InputBlock::new(
+                    "Label",
+                    PlainTextInputElement::new("plain_text_input-action")
+                        .placeholder("Write something")
+                        .multiline()
+                        .initial_value("Hello"),
+                )
+                .block_id("notes")
+                .hint("Be kind")
+                .optional(),
+            ),
+            json!({
+                "type": "input",
+                "block_id": "notes",
+                "label": {"type": "plain_text", "text": "Label"},
+                "element": {
+                    "type": "plain_text_input",
+                    "action_id": "plain_text_input-action",
+                    "placeholder": {"type": "plain_text", "text": "Write something"},
+                    "multiline": true,
+                    "initial_value": "Hello"
+                },
+                "hint": {"type": "plain_text", "text": "Be kind"},
+                "optional": true
+            }),
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unknown() {
+        // Blocks and elements we do not model, as Slack documents them.
+        let blocks: Vec<Block> = serde_json::from_value(json!([
+            {
+                "type": "video",
+                "title": {"type": "plain_text", "text": "How to use Slack."},
+                "video_url": "https://www.youtube.com/embed/RRxQQxiM7AA?feature=oembed&autoplay=1",
+                "thumbnail_url": "https://i.ytimg.com/vi/RRxQQxiM7AA/hqdefault.jpg",
+                "alt_text": "How to use Slack?"
+            },
+            {
+                "type": "input",
+                "label": {"type": "plain_text", "text": "Label", "emoji": true},
+                "element": {
+                    "type": "datepicker",
+                    "initial_date": "1990-04-28",
+                    "placeholder": {"type": "plain_text", "text": "Select a date", "emoji": true},
+                    "action_id": "datepicker-action"
+                }
+            },
+            {
+                "type": "section",
+                "text": {"type": "mrkdwn", "text": "Pick a date"},
+                "accessory": {
+                    "type": "overflow",
+                    "action_id": "overflow-action",
+                    "options": [{"text": {"type": "plain_text", "text": "Edit"}, "value": "value-0"}]
+                }
+            },
+            {
+                "type": "actions",
+                "elements": [{"type": "timepicker", "initial_time": "13:37", "action_id": "timepicker-action"}]
+            },
+            {
+                "type": "rich_text",
+                "elements": [
+                    {"type": "rich_text_section", "elements": [{"type": "broadcast", "range": "here"}]},
+                    {"type": "rich_text_table", "rows": []}
+                ]
+            }
+        ]))
+        .unwrap();
+
+        assert_eq!(blocks[0], Block::Unknown);
+        match &blocks[1] {
+            Block::Input(input) => {
+                assert_eq!(input.element, InputElement::Unknown);
+                assert_eq!(input.label.text(), "Label");
+            }
+            block => panic!("expected an input block, got {:?}", block),
+        }
+        assert_eq!(blocks[2].as_section().unwrap().accessory, Some(Accessory::Unknown));
+        assert_eq!(
+            blocks[3],
+            Block::Actions(ActionsBlock {
+                block_id: Default::default(),
+                elements: vec![ActionElement::Unknown],
+            })
+        );
+        assert_eq!(
+            blocks[4],
+            Block::RichText(RichTextBlock {
+                block_id: Default::default(),
+                elements: vec![
+                    RichTextElement::RichTextSection(RichTextSection {
+                        elements: vec![RichTextSpan::Unknown],
+                    }),
+                    RichTextElement::Unknown,
+                ],
+            })
+        );
+
+        let element: ContextElement = serde_json::from_value(json!({"type": "emoji", "name": "wave"})).unwrap();
+        assert_eq!(element, ContextElement::Unknown);
+    }
+}
@@ -25,13 +25,22 @@
  */
 #![allow(clippy::field_reassign_with_default)]
 #![allow(clippy::nonstandard_macro_braces)]
-use std::{collections::HashMap, env, sync::Arc};
+use std::{collections::HashMap, env, sync::Arc, time::Instant};
 
-use anyhow::{bail, Result};
-use reqwest::{header, Body, Client, Method, Request, StatusCode, Url};
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use reqwest::{header, Body, Client, Method, Request, Response, StatusCode, Url};
 use schemars::JsonSchema;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
+use token_refresh::{RefreshError, RefreshedToken, TokenRefresh};
+
+pub mod blocks;
+mod rate_limit;
+
+use blocks::{Block, Text};
+pub use rate_limit::RateLimitTier;
+use rate_limit::RateLimiter;
 
 /// Endpoint for the Slack API.
 const ENDPOINT: &str = "https://slack.com/api/";
@@ -42,12 +51,15 @@ pub struct Slack {
     // This expires in 101 days. It is hardcoded in the GitHub Actions secrets,
     // We might want something a bit better like storing it in the database.
     user_token: String,
+    /// Only set for apps that have token rotation enabled.
+    refresh_token: String,
     client_id: String,
     client_secret: String,
     redirect_uri: String,
     workspace_id: String,
 
     client: Arc<Client>,
+    rate_limiter: Arc<RateLimiter>,
 }
 
 impl Slack {
@@ -73,13 +85,16 @@ impl Slack {
         let client = Client::builder().build();
         match client {
             Ok(c) => {
+                let workspace_id = workspace_id.to_string();
                 let s = Slack {
                     client_id: client_id.to_string(),
                     client_secret: client_secret.to_string(),
-                    workspace_id: workspace_id.to_string(),
+                    rate_limiter: RateLimiter::for_workspace(&workspace_id),
+                    workspace_id,
                     redirect_uri: redirect_uri.to_string(),
                     token: token.to_string(),
                     user_token: user_token.to_string(),
+                    refresh_token: String::new(),
 
                     client: Arc::new(c),
                 };
@@ -115,6 +130,16 @@ impl Slack {
         Slack::new(client_id, client_secret, workspace_id, redirect_uri, token, user_token)
     }
 
+    /// Set the refresh token for the bot token. This is only needed for apps that have token
+    /// rotation enabled, otherwise Slack tokens do not expire.
+    pub fn with_refresh_token<T>(mut self, refresh_token: T) -> Self
+    where
+        T: ToString,
+    {
+        self.refresh_token = refresh_token.to_string();
+        self
+    }
+
     fn request<B>(
         &self,
         token: &str,
@@ -158,11 +183,112 @@ impl Slack {
         Ok(rb.build()?)
     }
 
+    /// Execute a request, keeping to the rate limit tier of its method. Requests that are rate
+    /// limited anyway are retried after the `Retry-After` Slack asks for.
+    async fn execute(&self, request: Request) -> Result<Response> {
+        let method = request.url().path().trim_start_matches("/api/").to_string();
+
+        let mut retries = 0;
+        loop {
+            let wait = self.rate_limiter.reserve(&method, Instant::now());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+
+            let attempt = request
+                .try_clone()
+                .ok_or_else(|| anyhow!("request to `{}` can not be retried", method))?;
+            let resp = self.client.execute(attempt).await?;
+
+            if resp.status() != StatusCode::TOO_MANY_REQUESTS || retries >= rate_limit::MAX_RETRIES {
+                return Ok(resp);
+            }
+
+            retries += 1;
+            let wait = rate_limit::retry_after(resp.headers()).unwrap_or(rate_limit::DEFAULT_RETRY_AFTER);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Call a Web API method and deserialize the response, failing if Slack reports an error.
+    async fn call<B, R>(
+        &self,
+        token: &str,
+        method: Method,
+        path: &str,
+        body: B,
+        query: Option<Vec<(&str, String)>>,
+    ) -> Result<R>
+    where
+        B: Serialize,
+        R: DeserializeOwned,
+    {
+        let request = self.request(token, method, path, body, query)?;
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let value: Value = resp.json().await?;
+        if value["ok"] == Value::Bool(false) {
+            bail!("status code: {}, body: {}", StatusCode::OK, value);
+        }
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Get every page of a paginated Web API method, following `response_metadata.next_cursor`
+    /// until it is empty.
+    async fn pages(&self, token: &str, path: &str, query: Vec<(&str, String)>) -> Result<Vec<Value>> {
+        let mut pages = vec![];
+        let mut cursor = String::new();
+
+        loop {
+            let mut page_query = query.clone();
+            page_query.push(("limit", "200".to_string()));
+            if !cursor.is_empty() {
+                page_query.push(("cursor", cursor));
+            }
+
+            let page: Value = self.call(token, Method::GET, path, (), Some(page_query)).await?;
+            cursor = page["response_metadata"]["next_cursor"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            pages.push(page);
+
+            if cursor.is_empty() {
+                return Ok(pages);
+            }
+        }
+    }
+
+    /// Get the items in the `field` of every page of a paginated Web API method.
+    async fn paginate<T>(&self, token: &str, path: &str, query: Vec<(&str, String)>, field: &str) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let mut items = vec![];
+        for mut page in self.pages(token, path, query).await? {
+            if let Some(value) = page.get_mut(field) {
+                items.append(&mut serde_json::from_value(value.take())?);
+            }
+        }
+
+        Ok(items)
+    }
+
     pub fn user_consent_url(&self) -> String {
         let state = uuid::Uuid::new_v4();
         format!(
             "https://slack.com/oauth/v2/authorize?scope={}&client_id={}&user_scope={}&redirect_uri={}&state={}",
-            "commands,team:read,users:read,users:read.email,users.profile:read,channels:read,chat:write,channels:join",
+            "commands,team:read,users:read,users:read.email,users.profile:read,channels:read,chat:write,channels:join,\
+             app_mentions:read,channels:history,reactions:read,channels:manage,usergroups:read,usergroups:write,\
+             files:write,groups:read,groups:write",
             self.client_id,
             "admin,identify",
             self.redirect_uri,
@@ -200,20 +326,57 @@ impl Slack {
         Ok(t)
     }
 
+    /// Exchange the refresh token for a new bot token.
+    /// FROM: https://api.slack.com/authentication/rotation
+    pub async fn refresh_access_token(&mut self) -> Result<AccessToken> {
+        let mut headers = header::HeaderMap::new();
+        headers.append(header::ACCEPT, header::HeaderValue::from_static("application/json"));
+
+        let params = [
+            ("client_id", self.client_id.to_string()),
+            ("client_secret", self.client_secret.to_string()),
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", self.refresh_token.to_string()),
+        ];
+        let client = reqwest::Client::new();
+        let resp = client
+            .post("https://slack.com/api/oauth.v2.access")
+            .headers(headers)
+            .form(&params)
+            .send()
+            .await?;
+
+        // Unwrap the response.
+        let t: AccessToken = resp.json().await?;
+
+        if t.ok {
+            self.token = t.access_token.to_string();
+            if !t.refresh_token.is_empty() {
+                self.refresh_token = t.refresh_token.to_string();
+            }
+        }
+
+        Ok(t)
+    }
+
     /// List users on a workspace.
     /// FROM: https://api.slack.com/methods/users.list
     pub async fn list_users(&self) -> Result<Vec<User>> {
-        // Build the request.
-        // TODO: paginate.
+        self.paginate(&self.token, "users.list", vec![], "members").await
+    }
+
+    /// Find a user by their email address, returning `None` if there is no such user.
+    /// FROM: https://api.slack.com/methods/users.lookupByEmail
+    pub async fn lookup_user_by_email(&self, email: &str) -> Result<Option<User>> {
         let request = self.request(
             &self.token,
             Method::GET,
-            "users.list",
+            "users.lookupByEmail",
             (),
-            Some(vec![("limit", "100".to_string())]),
+            Some(vec![("email", email.to_string())]),
         )?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -221,9 +384,43 @@ impl Slack {
             }
         };
 
-        let r: APIResponse = resp.json().await?;
+        let mut value: Value = resp.json().await?;
+        if value["ok"] == Value::Bool(false) {
+            if value["error"] == "users_not_found" {
+                return Ok(None);
+            }
+
+            bail!("status code: {}, body: {}", StatusCode::OK, value);
+        }
 
-        Ok(r.users)
+        Ok(Some(serde_json::from_value(value["user"].take())?))
+    }
+
+    /// Get a user by their ID.
+    /// FROM: https://api.slack.com/methods/users.info
+    pub async fn get_user(&self, id: &str) -> Result<User> {
+        let request = self.request(
+            &self.token,
+            Method::GET,
+            "users.info",
+            (),
+            Some(vec![("user", id.to_string())]),
+        )?;
+
+        let resp = self.execute(request).await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let mut value: Value = resp.json().await?;
+        if value["ok"] == Value::Bool(false) {
+            bail!("status code: {}, body: {}", StatusCode::OK, value);
+        }
+
+        Ok(serde_json::from_value(value["user"].take())?)
     }
 
     /// Get the current user's identity.
@@ -232,7 +429,7 @@ impl Slack {
         // Build the request.
         let request = self.request(&self.user_token, Method::GET, "users.identity", (), None)?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -247,20 +444,13 @@ impl Slack {
     /// Get billable info.
     /// FROM: https://api.slack.com/methods/team.billableInfo
     pub async fn billable_info(&self) -> Result<HashMap<String, BillableInfo>> {
-        // Build the request.
-        // TODO: paginate.
-        let request = self.request(&self.user_token, Method::GET, "team.billableInfo", (), None)?;
-
-        let resp = self.client.execute(request).await?;
-        match resp.status() {
-            StatusCode::OK => (),
-            s => {
-                bail!("status code: {}, body: {}", s, resp.text().await?);
-            }
-        };
+        let mut billable_info = HashMap::new();
+        for page in self.pages(&self.user_token, "team.billableInfo", vec![]).await? {
+            let r: BillableInfoResponse = serde_json::from_value(page)?;
+            billable_info.extend(r.billable_info);
+        }
 
-        let r: BillableInfoResponse = resp.json().await?;
-        Ok(r.billable_info)
+        Ok(billable_info)
     }
 
     /// Open a view.
@@ -269,7 +459,7 @@ impl Slack {
         // Build the request.
         let request = self.request(&self.token, Method::POST, "views.open", body, None)?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -307,62 +497,236 @@ impl Slack {
     /// List channels, defaults to public channels.
     /// FROM: https://api.slack.com/methods/conversations.list
     pub async fn list_channels(&self) -> Result<Vec<Channel>> {
-        // Build the request.
-        let mut request = self.request(&self.token, Method::GET, "conversations.list", (), None)?;
+        self.paginate(&self.token, "conversations.list", vec![], "channels")
+            .await
+    }
 
-        let mut resp = self.client.execute(request).await?;
-        match resp.status() {
-            StatusCode::OK => (),
-            s => {
-                bail!("status code: {}, body: {}", s, resp.text().await?);
-            }
-        };
+    /// List the public and private channels that are not archived. Only the private channels
+    /// the bot is a member of are returned.
+    /// FROM: https://api.slack.com/methods/conversations.list
+    pub async fn list_all_channels(&self) -> Result<Vec<Channel>> {
+        self.paginate(
+            &self.token,
+            "conversations.list",
+            vec![
+                ("types", "public_channel,private_channel".to_string()),
+                ("exclude_archived", "true".to_string()),
+            ],
+            "channels",
+        )
+        .await
+    }
 
-        let mut r: ListChannelsResponse = resp.json().await?;
+    /// List the ids of the members of a channel.
+    /// FROM: https://api.slack.com/methods/conversations.members
+    pub async fn list_channel_members(&self, channel: &str) -> Result<Vec<String>> {
+        self.paginate(
+            &self.token,
+            "conversations.members",
+            vec![("channel", channel.to_string())],
+            "members",
+        )
+        .await
+    }
 
-        if !r.ok {
-            bail!(
-                "status code: {}, body: {}",
-                StatusCode::OK,
-                serde_json::json!(r).to_string()
-            );
-        }
+    /// Create a channel.
+    /// FROM: https://api.slack.com/methods/conversations.create
+    pub async fn create_channel(&self, name: &str, is_private: bool) -> Result<Channel> {
+        let r: ChannelResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "conversations.create",
+                serde_json::json!({
+                    "name": name.trim_start_matches('#'),
+                    "is_private": is_private,
+                }),
+                None,
+            )
+            .await?;
+
+        Ok(r.channel)
+    }
+
+    /// Archive a channel.
+    /// FROM: https://api.slack.com/methods/conversations.archive
+    pub async fn archive_channel(&self, channel: &str) -> Result<()> {
+        let _: Value = self
+            .call(
+                &self.token,
+                Method::POST,
+                "conversations.archive",
+                serde_json::json!({ "channel": channel }),
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Invite users to a channel.
+    /// FROM: https://api.slack.com/methods/conversations.invite
+    pub async fn invite_to_channel(&self, channel: &str, users: &[String]) -> Result<Channel> {
+        let r: ChannelResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "conversations.invite",
+                serde_json::json!({
+                    "channel": channel,
+                    "users": users.join(","),
+                }),
+                None,
+            )
+            .await?;
+
+        Ok(r.channel)
+    }
+
+    /// Remove a user from a channel.
+    /// FROM: https://api.slack.com/methods/conversations.kick
+    pub async fn kick_from_channel(&self, channel: &str, user: &str) -> Result<()> {
+        let _: Value = self
+            .call(
+                &self.token,
+                Method::POST,
+                "conversations.kick",
+                serde_json::json!({
+                    "channel": channel,
+                    "user": user,
+                }),
+                None,
+            )
+            .await?;
 
-        let mut channels = r.channels;
+        Ok(())
+    }
 
-        // Paginate.
-        while !r.response_metadata.next_cursor.is_empty() {
-            request = self.request(
+    /// List the user groups of the workspace, including disabled ones.
+    /// FROM: https://api.slack.com/methods/usergroups.list
+    pub async fn list_usergroups(&self, include_users: bool) -> Result<Vec<UserGroup>> {
+        let r: UserGroupsResponse = self
+            .call(
                 &self.token,
                 Method::GET,
-                "conversations.list",
+                "usergroups.list",
                 (),
-                Some(vec![("cursor", r.response_metadata.next_cursor.to_string())]),
-            )?;
-
-            resp = self.client.execute(request).await?;
-            match resp.status() {
-                StatusCode::OK => (),
-                s => {
-                    bail!("status code: {}, body: {}", s, resp.text().await?);
-                }
-            };
+                Some(vec![
+                    ("include_disabled", "true".to_string()),
+                    ("include_users", include_users.to_string()),
+                ]),
+            )
+            .await?;
 
-            // Try to deserialize the response.
-            r = resp.json().await?;
+        Ok(r.usergroups)
+    }
 
-            if !r.ok {
-                bail!(
-                    "status code: {}, body: {}",
-                    StatusCode::OK,
-                    serde_json::json!(r).to_string()
-                );
-            }
+    /// Create a user group.
+    /// FROM: https://api.slack.com/methods/usergroups.create
+    pub async fn create_usergroup(&self, name: &str, handle: &str, description: &str) -> Result<UserGroup> {
+        let r: UserGroupResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "usergroups.create",
+                serde_json::json!({
+                    "name": name,
+                    "handle": handle,
+                    "description": description,
+                }),
+                None,
+            )
+            .await?;
 
-            channels.append(&mut r.channels);
-        }
+        Ok(r.usergroup)
+    }
 
-        Ok(channels)
+    /// Update the name, handle and description of a user group.
+    /// FROM: https://api.slack.com/methods/usergroups.update
+    pub async fn update_usergroup(&self, id: &str, name: &str, handle: &str, description: &str) -> Result<UserGroup> {
+        let r: UserGroupResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "usergroups.update",
+                serde_json::json!({
+                    "usergroup": id,
+                    "name": name,
+                    "handle": handle,
+                    "description": description,
+                }),
+                None,
+            )
+            .await?;
+
+        Ok(r.usergroup)
+    }
+
+    /// Disable a user group.
+    /// FROM: https://api.slack.com/methods/usergroups.disable
+    pub async fn disable_usergroup(&self, id: &str) -> Result<UserGroup> {
+        let r: UserGroupResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "usergroups.disable",
+                serde_json::json!({ "usergroup": id }),
+                None,
+            )
+            .await?;
+
+        Ok(r.usergroup)
+    }
+
+    /// Enable a disabled user group.
+    /// FROM: https://api.slack.com/methods/usergroups.enable
+    pub async fn enable_usergroup(&self, id: &str) -> Result<UserGroup> {
+        let r: UserGroupResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "usergroups.enable",
+                serde_json::json!({ "usergroup": id }),
+                None,
+            )
+            .await?;
+
+        Ok(r.usergroup)
+    }
+
+    /// List the ids of the users in a user group.
+    /// FROM: https://api.slack.com/methods/usergroups.users.list
+    pub async fn list_usergroup_users(&self, id: &str) -> Result<Vec<String>> {
+        let r: UserGroupUsersResponse = self
+            .call(
+                &self.token,
+                Method::GET,
+                "usergroups.users.list",
+                (),
+                Some(vec![("usergroup", id.to_string())]),
+            )
+            .await?;
+
+        Ok(r.users)
+    }
+
+    /// Replace the users in a user group.
+    /// FROM: https://api.slack.com/methods/usergroups.users.update
+    pub async fn update_usergroup_users(&self, id: &str, users: &[String]) -> Result<UserGroup> {
+        let r: UserGroupResponse = self
+            .call(
+                &self.token,
+                Method::POST,
+                "usergroups.users.update",
+                serde_json::json!({
+                    "usergroup": id,
+                    "users": users.join(","),
+                }),
+                None,
+            )
+            .await?;
+
+        Ok(r.usergroup)
     }
 
     /// Invite a user to a workspace.
@@ -371,7 +735,7 @@ impl Slack {
         // Build the request.
         let request = self.request(&self.user_token, Method::POST, "admin.users.invite", invite, None)?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -398,7 +762,7 @@ impl Slack {
 
         let request = self.request(&self.token, Method::POST, "conversations.join", body, None)?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -444,7 +808,7 @@ impl Slack {
     pub async fn post_message_raw(&self, body: &FormattedMessage) -> Result<FormattedMessageResponse> {
         let request = self.request(&self.token, Method::POST, "chat.postMessage", body, None)?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -457,6 +821,98 @@ impl Slack {
         Ok(f)
     }
 
+    /// Post a message as a reply in the thread of another message.
+    /// FROM: https://api.slack.com/methods/chat.postMessage
+    pub async fn post_reply(&self, body: &FormattedMessage, thread_ts: &str) -> Result<FormattedMessageResponse> {
+        let mut body = serde_json::to_value(body)?;
+        body["thread_ts"] = Value::String(thread_ts.to_string());
+
+        self.call(&self.token, Method::POST, "chat.postMessage", body, None)
+            .await
+    }
+
+    /// Replace the contents of a message that was posted earlier.
+    /// FROM: https://api.slack.com/methods/chat.update
+    pub async fn update_message(&self, body: &FormattedMessage, ts: &str) -> Result<FormattedMessageResponse> {
+        let mut body = serde_json::to_value(body)?;
+        body["ts"] = Value::String(ts.to_string());
+
+        self.call(&self.token, Method::POST, "chat.update", body, None).await
+    }
+
+    /// Post a message to a channel that only the given user can see.
+    /// FROM: https://api.slack.com/methods/chat.postEphemeral
+    pub async fn post_ephemeral(&self, body: &FormattedMessage, user: &str) -> Result<()> {
+        let mut body = serde_json::to_value(body)?;
+        body["user"] = Value::String(user.to_string());
+
+        let _: Value = self
+            .call(&self.token, Method::POST, "chat.postEphemeral", body, None)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Upload a file, optionally sharing it to a channel. This follows the same steps as the
+    /// `files.uploadV2` helper of the official SDKs: get an upload URL, send the contents to it
+    /// and then complete the upload.
+    /// FROM: https://api.slack.com/messaging/files#uploading_files
+    pub async fn upload_file(&self, upload: FileUpload) -> Result<Vec<UploadedFile>> {
+        let target: UploadUrlResponse = self
+            .call(
+                &self.token,
+                Method::GET,
+                "files.getUploadURLExternal",
+                (),
+                Some(vec![
+                    ("filename", upload.filename.to_string()),
+                    ("length", upload.content.len().to_string()),
+                ]),
+            )
+            .await?;
+
+        let resp = self
+            .client
+            .post(&target.upload_url)
+            .body(Body::from(upload.content))
+            .send()
+            .await?;
+        match resp.status() {
+            StatusCode::OK => (),
+            s => {
+                bail!("status code: {}, body: {}", s, resp.text().await?);
+            }
+        };
+
+        let title = if upload.title.is_empty() {
+            upload.filename
+        } else {
+            upload.title
+        };
+
+        let mut body = serde_json::json!({
+            "files": [{
+                "id": target.file_id,
+                "title": title,
+            }],
+        });
+        if !upload.channel.is_empty() {
+            body["channel_id"] = Value::String(upload.channel);
+        }
+        if !upload.initial_comment.is_empty() {
+            body["initial_comment"] = Value::String(upload.initial_comment);
+        }
+        if !upload.thread_ts.is_empty() {
+            body["thread_ts"] = Value::String(upload.thread_ts);
+        }
+
+        let r: CompleteUploadResponse = self
+            .call(&self.token, Method::POST, "files.completeUploadExternal", body, None)
+            .await?;
+
+        Ok(r.files)
+    }
+
     /// Remove users from a workspace.
     /// FROM: https://api.slack.com/methods/admin.users.remove
     pub async fn remove_user(&self, user_id: &str) -> Result<()> {
@@ -466,7 +922,7 @@ impl Slack {
         body.insert("user_id", user_id);
         let request = self.request(&self.user_token, Method::POST, "admin.users.remove", body, None)?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -492,7 +948,7 @@ impl Slack {
             None,
         )?;
 
-        let resp = self.client.execute(request).await?;
+        let resp = self.execute(request).await?;
         match resp.status() {
             StatusCode::OK => (),
             s => {
@@ -519,6 +975,29 @@ impl Slack {
     }
 }
 
+#[async_trait]
+impl TokenRefresh for Slack {
+    async fn refresh(&mut self) -> std::result::Result<RefreshedToken, RefreshError> {
+        if self.refresh_token.is_empty() {
+            return Err(RefreshError::NotConfigured(
+                "token rotation is not enabled for this app".to_string(),
+            ));
+        }
+
+        let t = self.refresh_access_token().await.map_err(RefreshError::other)?;
+        if !t.ok {
+            return Err(RefreshError::from_error_code(&t.error, ""));
+        }
+
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in,
+            refresh_token: t.refresh_token,
+            refresh_token_expires_in: 0,
+        })
+    }
+}
+
 /// A message to be sent in Slack.
 ///
 /// Docs: https://api.slack.com/interactivity/slash-commands#responding_to_commands
@@ -601,18 +1080,43 @@ pub struct Modal {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub callback_id: String,
     #[serde(default)]
-    pub title: MessageBlockText,
-    #[serde(default)]
-    pub submit: MessageBlockText,
-    #[serde(default)]
-    pub close: MessageBlockText,
+    pub title: Text,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub submit: Option<Text>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close: Option<Text>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub blocks: Vec<InputBlock>,
+    pub blocks: Vec<Block>,
     // We only need this on deserializing.
     #[serde(default, skip_serializing)]
     pub state: State,
 }
 
+impl Modal {
+    pub fn new<C: ToString, T: ToString>(callback_id: C, title: T) -> Self {
+        Modal {
+            callback_id: callback_id.to_string(),
+            title: Text::plain(title),
+            ..Default::default()
+        }
+    }
+
+    pub fn submit<T: ToString>(mut self, submit: T) -> Self {
+        self.submit = Some(Text::plain(submit));
+        self
+    }
+
+    pub fn close<T: ToString>(mut self, close: T) -> Self {
+        self.close = Some(Text::plain(close));
+        self
+    }
+
+    pub fn block<B: Into<Block>>(mut self, block: B) -> Self {
+        self.blocks.push(block.into());
+        self
+    }
+}
+
 #[derive(Deserialize, Serialize, Default, Clone, Debug, JsonSchema)]
 pub struct InteractiveResponse {
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -624,16 +1128,32 @@ pub struct InteractiveResponse {
 /// A formatted message to send to Slack.
 ///
 /// Docs: https://api.slack.com/messaging/composing/layouts
-#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
 pub struct FormattedMessage {
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub channel: String,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub blocks: Vec<MessageBlock>,
+    pub blocks: Vec<Block>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub attachments: Vec<MessageAttachment>,
 }
 
+impl FormattedMessage {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn block<B: Into<Block>>(mut self, block: B) -> Self {
+        self.blocks.push(block.into());
+        self
+    }
+
+    pub fn attachment(mut self, attachment: MessageAttachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+}
+
 /// A formatted message response from Slack.
 ///
 /// Docs: https://api.slack.com/methods/chat.postMessage
@@ -643,12 +1163,108 @@ pub struct FormattedMessageResponse {
     pub ok: bool,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub channel: String,
+    /// The timestamp that identifies the message, used to update it or reply to it.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ts: String,
     #[serde(default)]
     pub message: serde_json::Value,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub error: String,
 }
 
+/// A file to upload with [`Slack::upload_file`].
+#[derive(Debug, Clone, Default)]
+pub struct FileUpload {
+    pub filename: String,
+    /// Defaults to the filename.
+    pub title: String,
+    pub content: Vec<u8>,
+    /// The id of the channel to share the file to, the file is private to the app when empty.
+    pub channel: String,
+    /// A message to post along with the file.
+    pub initial_comment: String,
+    /// Share the file as a reply in this thread.
+    pub thread_ts: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct UploadUrlResponse {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub upload_url: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub file_id: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct CompleteUploadResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<UploadedFile>,
+}
+
+/// A file that was uploaded.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct UploadedFile {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+}
+
+/// A response that carries a single channel.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct ChannelResponse {
+    #[serde(default)]
+    pub channel: Channel,
+}
+
+/// A user group, which can be mentioned to notify all of its members.
+/// FROM: https://api.slack.com/types/usergroup
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct UserGroup {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub team_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub handle: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// Non zero when the user group has been disabled.
+    #[serde(default)]
+    pub date_delete: i64,
+    /// Only set when the users were asked for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub user_count: i64,
+}
+
+impl UserGroup {
+    pub fn is_disabled(&self) -> bool {
+        self.date_delete != 0
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct UserGroupResponse {
+    #[serde(default)]
+    pub usergroup: UserGroup,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct UserGroupsResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usergroups: Vec<UserGroup>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
+pub struct UserGroupUsersResponse {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub users: Vec<String>,
+}
+
 /// A channel join response.
 #[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
 pub struct JoinChannelResponse {
@@ -759,25 +1375,6 @@ pub struct ChannelValue {
     pub value: String,
 }
 
-/// A Slack message block.
-///
-/// Docs: https://api.slack.com/messaging/composing/layouts#adding-blocks
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct MessageBlock {
-    #[serde(default, rename = "type")]
-    pub block_type: MessageBlockType,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub text: Option<MessageBlockText>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub elements: Vec<BlockOption>,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub block_id: String,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub accessory: Option<MessageBlockAccessory>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub fields: Vec<MessageBlockText>,
-}
-
 /// A Slack message block.
 ///
 /// Docs: https://api.slack.com/messaging/composing/layouts#adding-blocks
@@ -825,14 +1422,6 @@ pub enum ModalType {
     Modal,
 }
 
-/// Block options in Slack.
-#[derive(Debug, Clone, JsonSchema, Deserialize, Serialize)]
-#[serde(untagged)]
-pub enum BlockOption {
-    MessageBlockText(MessageBlockText),
-    ActionBlock(ActionBlock),
-}
-
 /// Message block text in Slack.
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
 pub struct MessageBlockText {
@@ -842,52 +1431,6 @@ pub struct MessageBlockText {
     pub text: String,
 }
 
-/// Input block in Slack.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct InputBlock {
-    #[serde(rename = "type")]
-    pub type_: MessageBlockType,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub element: Option<InputBlockElement>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub label: Option<MessageBlockText>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub text: Option<MessageBlockText>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub hint: Option<MessageBlockText>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub optional: Option<bool>,
-}
-
-/// Input block element in Slack.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct InputBlockElement {
-    #[serde(rename = "type")]
-    pub type_: InputType,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub action_id: String,
-
-    // These two only apply to static select.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub placeholder: Option<MessageBlockText>,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub options: Vec<SelectInputOption>,
-}
-
-/// Select input option in Slack.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct SelectInputOption {
-    #[serde(default)]
-    pub text: MessageBlockText,
-
-    #[serde(
-        default,
-        skip_serializing_if = "String::is_empty",
-        deserialize_with = "deserialize_null_string::deserialize"
-    )]
-    pub value: String,
-}
-
 /// Action block in Slack.
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
 pub struct ActionBlock {
@@ -920,48 +1463,13 @@ pub enum MessageType {
     Button,
 }
 
-/// Input type in Slack.
-#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize, Default)]
-pub enum InputType {
-    #[serde(rename = "static_select")]
-    StaticSelect,
-    #[serde(rename = "plain_text_input")]
-    #[default]
-    PlainText,
-}
-
-/// Message block accessory in Slack.
-#[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
-pub struct MessageBlockAccessory {
-    #[serde(rename = "type")]
-    pub accessory_type: MessageType,
-
-    /// These are for an image.
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub image_url: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub alt_text: String,
-
-    /// These are for a button.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub text: Option<MessageBlockText>,
-    #[serde(
-        default,
-        skip_serializing_if = "String::is_empty",
-        deserialize_with = "deserialize_null_string::deserialize"
-    )]
-    pub value: String,
-    #[serde(default, skip_serializing_if = "String::is_empty")]
-    pub action_id: String,
-}
-
 /// A message attachment in Slack.
 ///
 /// Docs: https://api.slack.com/messaging/composing/layouts#building-attachments
-#[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema, Serialize)]
 pub struct MessageAttachment {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub blocks: Vec<MessageBlock>,
+    pub blocks: Vec<Block>,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub author_icon: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -994,6 +1502,21 @@ pub struct MessageAttachment {
     pub ts: String,
 }
 
+impl MessageAttachment {
+    /// An attachment whose blocks are marked with a colored bar.
+    pub fn colored<C: ToString>(color: C) -> Self {
+        MessageAttachment {
+            color: color.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn block<B: Into<Block>>(mut self, block: B) -> Self {
+        self.blocks.push(block.into());
+        self
+    }
+}
+
 /// A message attachment field in Slack.
 #[derive(Debug, Clone, Deserialize, JsonSchema, Serialize)]
 pub struct MessageAttachmentField {
@@ -1178,7 +1701,15 @@ pub struct AccessToken {
     #[serde(default)]
     pub ok: bool,
     #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub error: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub access_token: String,
+    /// Only returned for apps that have token rotation enabled.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub refresh_token: String,
+    /// Seconds until the token expires, only returned for apps that have token rotation enabled.
+    #[serde(default)]
+    pub expires_in: i64,
     #[serde(default, skip_serializing_if = "String::is_empty")]
     pub token_type: String,
     #[serde(default, skip_serializing_if = "String::is_empty")]
@@ -1321,6 +1852,141 @@ pub struct Container {
     pub is_app_unfurl: bool,
 }
 
+/// A request sent by the Events API.
+///
+/// Docs: https://api.slack.com/apis/connections/events-api#receiving-events
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EventPayload {
+    /// Sent once when the request URL is configured, the challenge must be echoed back.
+    UrlVerification {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        token: String,
+        challenge: String,
+    },
+    EventCallback(Box<EventCallback>),
+    /// Any other kind of request, such as `app_rate_limited`.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The envelope around an event a Slack app is subscribed to.
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+pub struct EventCallback {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub token: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub team_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub api_app_id: String,
+    pub event: Event,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_id: String,
+    #[serde(default)]
+    pub event_time: i64,
+    /// The installations the event is visible to. The user of an authorization is the bot user of
+    /// the app.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub authorizations: Vec<EventAuthorization>,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct EventAuthorization {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub team_id: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_id: String,
+    #[serde(default)]
+    pub is_bot: bool,
+}
+
+/// An event a Slack app is subscribed to.
+///
+/// Docs: https://api.slack.com/events
+#[derive(Debug, JsonSchema, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// The app was mentioned in a message.
+    AppMention(MessageEvent),
+    /// A message was posted in a channel the app is in.
+    Message(MessageEvent),
+    MemberJoinedChannel(MemberJoinedChannelEvent),
+    /// A new member joined the workspace.
+    TeamJoin(Box<TeamJoinEvent>),
+    ReactionAdded(ReactionAddedEvent),
+    /// Any event that is not modelled here.
+    #[serde(other)]
+    Unknown,
+}
+
+/// The fields shared by `message` and `app_mention` events.
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct MessageEvent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ts: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_ts: String,
+    /// Set when the message is a reply in a thread.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub thread_ts: String,
+    /// Set for edits, deletions, joins and other messages that were not simply posted by a user.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub subtype: String,
+    /// Set when the message was posted by a bot.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub bot_id: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct MemberJoinedChannelEvent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub team: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub inviter: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct TeamJoinEvent {
+    pub user: User,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReactionAddedEvent {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub reaction: String,
+    /// The user that posted the item that was reacted to.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub item_user: String,
+    #[serde(default)]
+    pub item: ReactionItem,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub event_ts: String,
+}
+
+#[derive(Debug, JsonSchema, Clone, Default, Serialize, Deserialize)]
+pub struct ReactionItem {
+    #[serde(rename = "type", default, skip_serializing_if = "String::is_empty")]
+    pub item_type: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub channel: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub ts: String,
+}
+
 pub mod deserialize_null_string {
     use serde::{self, Deserialize, Deserializer};
 
@@ -1340,3 +2006,264 @@ pub mod deserialize_null_string {
         Ok(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // Wrap an event in the envelope the Events API sends it in.
+    fn event_callback(event: serde_json::Value) -> EventCallback {
+        let payload: EventPayload = serde_json::from_value(json!({
+            "token": "XXYYZZ",
+            "team_id": "T123ABC456",
+            "api_app_id": "A123ABC456",
+            "event": event,
+            "type": "event_callback",
+            "event_id": "Ev123ABC456",
+            "event_time": 1234567890,
+            "authorizations": [
+                {
+                    "enterprise_id": "E123ABC456",
+                    "team_id": "T123ABC456",
+                    "user_id": "U123ABC456",
+                    "is_bot": true,
+                    "is_enterprise_install": false
+                }
+            ],
+            "is_ext_shared_channel": false,
+            "event_context": "4-eyJldCI6Im1lc3NhZ2UiLCJ0aWQiOiJUMTIzQUJDNDU2In0"
+        }))
+        .unwrap();
+
+        match payload {
+            EventPayload::EventCallback(callback) => *callback,
+            payload => panic!("expected an event callback, got {:?}", payload),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_url_verification() {
+        let payload: EventPayload = serde_json::from_value(json!({
+            "token": "Jhj5dZrVaK7ZwHHjRyZWjbDl",
+            "challenge": "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P",
+            "type": "url_verification"
+        }))
+        .unwrap();
+
+        match payload {
+            EventPayload::UrlVerification { token, challenge } => {
+                assert_eq!(token, "Jhj5dZrVaK7ZwHHjRyZWjbDl");
+                assert_eq!(challenge, "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P");
+            }
+            payload => panic!("expected a url verification, got {:?}", payload),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_unknown_payload() {
+        let payload: EventPayload = serde_json::from_value(json!({
+            "token": "Jhj5dZrVaK7ZwHHjRyZWjbDl",
+            "type": "app_rate_limited",
+            "team_id": "T123456",
+            "minute_rate_limited": 1518467820,
+            "api_app_id": "A123456"
+        }))
+        .unwrap();
+
+        assert!(matches!(payload, EventPayload::Unknown));
+    }
+
+    #[test]
+    fn test_deserialize_event_callback() {
+        let callback = event_callback(json!({
+            "type": "app_mention",
+            "user": "U061F7AUR",
+            "text": "<@U0LAN0Z89> is it everything a river should be?",
+            "ts": "1515449522.000016",
+            "channel": "C123ABC456",
+            "event_ts": "1515449522000016"
+        }));
+
+        assert_eq!(callback.team_id, "T123ABC456");
+        assert_eq!(callback.api_app_id, "A123ABC456");
+        assert_eq!(callback.event_id, "Ev123ABC456");
+        assert_eq!(callback.event_time, 1234567890);
+        assert_eq!(callback.authorizations.len(), 1);
+        assert_eq!(callback.authorizations[0].user_id, "U123ABC456");
+        assert!(callback.authorizations[0].is_bot);
+
+        match callback.event {
+            Event::AppMention(message) => {
+                assert_eq!(message.user, "U061F7AUR");
+                assert_eq!(message.text, "<@U0LAN0Z89> is it everything a river should be?");
+                assert_eq!(message.channel, "C123ABC456");
+                assert_eq!(message.ts, "1515449522.000016");
+                assert_eq!(message.event_ts, "1515449522000016");
+            }
+            event => panic!("expected an app mention, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_message() {
+        let callback = event_callback(json!({
+            "type": "message",
+            "channel": "C123ABC456",
+            "user": "U123ABC456",
+            "text": "Live long and prospect.",
+            "ts": "1355517523.000005",
+            "thread_ts": "1355517500.000003",
+            "event_ts": "1355517523.000005",
+            "channel_type": "channel"
+        }));
+        match callback.event {
+            Event::Message(message) => {
+                assert_eq!(message.text, "Live long and prospect.");
+                assert_eq!(message.thread_ts, "1355517500.000003");
+                assert!(message.subtype.is_empty());
+                assert!(message.bot_id.is_empty());
+            }
+            event => panic!("expected a message, got {:?}", event),
+        }
+
+        let callback = event_callback(json!({
+            "type": "message",
+            "subtype": "bot_message",
+            "ts": "1358877455.000010",
+            "text": "Pushing is the answer",
+            "bot_id": "BB12033",
+            "username": "github",
+            "icons": {}
+        }));
+        match callback.event {
+            Event::Message(message) => {
+                assert_eq!(message.subtype, "bot_message");
+                assert_eq!(message.bot_id, "BB12033");
+                assert!(message.user.is_empty());
+            }
+            event => panic!("expected a message, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_member_joined_channel() {
+        let callback = event_callback(json!({
+            "type": "member_joined_channel",
+            "user": "W123ABC456",
+            "channel": "C123ABC456",
+            "channel_type": "C",
+            "team": "T123ABC456",
+            "inviter": "U123456789"
+        }));
+
+        match callback.event {
+            Event::MemberJoinedChannel(join) => {
+                assert_eq!(join.user, "W123ABC456");
+                assert_eq!(join.channel, "C123ABC456");
+                assert_eq!(join.channel_type, "C");
+                assert_eq!(join.team, "T123ABC456");
+                assert_eq!(join.inviter, "U123456789");
+            }
+            event => panic!("expected a member joined channel event, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_team_join() {
+        let callback = event_callback(json!({
+            "type": "team_join",
+            "user": {
+                "id": "W012A3CDE",
+                "team_id": "T012AB3C4",
+                "name": "spengler",
+                "deleted": false,
+                "color": "9f69e7",
+                "real_name": "Egon Spengler",
+                "tz": "America/Los_Angeles",
+                "tz_label": "Pacific Daylight Time",
+                "tz_offset": -25200,
+                "profile": {
+                    "avatar_hash": "ge3b51ca72de",
+                    "status_text": "Print is dead",
+                    "status_emoji": ":books:",
+                    "real_name": "Egon Spengler",
+                    "display_name": "spengler",
+                    "real_name_normalized": "Egon Spengler",
+                    "display_name_normalized": "spengler",
+                    "email": "spengler@ghostbusters.example.com",
+                    "image_24": "https://.../...-24.jpg",
+                    "image_512": "https://.../...-512.jpg",
+                    "team": "T012AB3C4"
+                },
+                "is_admin": true,
+                "is_owner": false,
+                "is_primary_owner": false,
+                "is_restricted": false,
+                "is_ultra_restricted": false,
+                "is_bot": false,
+                "updated": 1502138686,
+                "is_app_user": false,
+                "has_2fa": false
+            },
+            "cache_ts": 1502138686,
+            "event_ts": "1502138686.000100"
+        }));
+
+        match callback.event {
+            Event::TeamJoin(join) => {
+                assert_eq!(join.user.id, "W012A3CDE");
+                assert_eq!(join.user.real_name, "Egon Spengler");
+                assert_eq!(join.user.profile.email, "spengler@ghostbusters.example.com");
+                assert!(join.user.is_admin);
+                assert!(!join.user.is_bot);
+            }
+            event => panic!("expected a team join event, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_reaction_added() {
+        let callback = event_callback(json!({
+            "type": "reaction_added",
+            "user": "U123ABC456",
+            "reaction": "thumbsup",
+            "item_user": "U222222222",
+            "item": {
+                "type": "message",
+                "channel": "C123ABC456",
+                "ts": "1360782400.498405"
+            },
+            "event_ts": "1360782804.083113"
+        }));
+
+        match callback.event {
+            Event::ReactionAdded(reaction) => {
+                assert_eq!(reaction.user, "U123ABC456");
+                assert_eq!(reaction.reaction, "thumbsup");
+                assert_eq!(reaction.item_user, "U222222222");
+                assert_eq!(reaction.item.item_type, "message");
+                assert_eq!(reaction.item.channel, "C123ABC456");
+                assert_eq!(reaction.item.ts, "1360782400.498405");
+                assert_eq!(reaction.event_ts, "1360782804.083113");
+            }
+            event => panic!("expected a reaction added event, got {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_unknown_event() {
+        let callback = event_callback(json!({
+            "type": "channel_created",
+            "channel": {
+                "id": "C024BE91L",
+                "name": "fun",
+                "created": 1360782804,
+                "creator": "U024BE7LH"
+            }
+        }));
+
+        assert!(matches!(callback.event, Event::Unknown));
+    }
+}
@@ -0,0 +1,205 @@
+//! Client side handling of Slack's rate limits.
+//!
+//! Every Web API method belongs to a tier that allows a number of calls per minute. Calls are
+//! spaced out so that no method goes over the limit of its tier, and when Slack still answers with
+//! a 429 the request is retried after the `Retry-After` it asks for.
+//!
+//! Slack counts calls per app and workspace, so every client for the same workspace in this process
+//! shares one limiter. Other processes calling Slack with the same app are not accounted for, their
+//! calls only show up as 429s.
+//!
+//! FROM: https://api.slack.com/docs/rate-limits
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+
+/// The window rate limits are counted over.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// How long to wait after a 429 that does not say how long to wait.
+pub(crate) const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// How many times a rate limited request is retried before giving up.
+pub(crate) const MAX_RETRIES: u32 = 5;
+
+/// The rate limit tiers of the Web API.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RateLimitTier {
+    /// 1+ calls per minute.
+    Tier1,
+    /// 20+ calls per minute.
+    Tier2,
+    /// 50+ calls per minute.
+    Tier3,
+    /// 100+ calls per minute.
+    Tier4,
+    /// Methods with their own limits, like posting roughly one message per second.
+    Special,
+}
+
+impl RateLimitTier {
+    /// The tier of a Web API method, e.g. `users.list`. Methods we do not know are assumed to be in
+    /// tier 3, which is where most methods are.
+    pub fn for_method(method: &str) -> Self {
+        match method {
+            "team.billableInfo" => RateLimitTier::Tier1,
+            "users.list"
+            | "conversations.list"
+            | "conversations.create"
+            | "conversations.archive"
+            | "usergroups.list"
+            | "usergroups.create"
+            | "usergroups.update"
+            | "usergroups.disable"
+            | "usergroups.enable"
+            | "usergroups.users.update"
+            | "admin.users.invite"
+            | "admin.users.remove" => RateLimitTier::Tier2,
+            "conversations.members"
+            | "usergroups.users.list"
+            | "views.open"
+            | "users.identity"
+            | "files.getUploadURLExternal"
+            | "files.completeUploadExternal" => RateLimitTier::Tier4,
+            "chat.postMessage" | "chat.postEphemeral" => RateLimitTier::Special,
+            _ => RateLimitTier::Tier3,
+        }
+    }
+
+    /// The number of calls allowed per minute.
+    pub fn calls_per_minute(&self) -> usize {
+        match self {
+            RateLimitTier::Tier1 => 1,
+            RateLimitTier::Tier2 => 20,
+            RateLimitTier::Tier3 => 50,
+            RateLimitTier::Tier4 => 100,
+            RateLimitTier::Special => 60,
+        }
+    }
+}
+
+/// Tracks the recent calls to each method to keep them under the limit of their tier.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    calls: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    /// The limiter shared by every client for a workspace.
+    pub(crate) fn for_workspace(workspace_id: &str) -> Arc<RateLimiter> {
+        static LIMITERS: OnceLock<Mutex<HashMap<String, Arc<RateLimiter>>>> = OnceLock::new();
+
+        LIMITERS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap()
+            .entry(workspace_id.to_string())
+            .or_default()
+            .clone()
+    }
+
+    /// Reserve a call to a method at or after `now`, returning how long to wait before making it.
+    pub(crate) fn reserve(&self, method: &str, now: Instant) -> Duration {
+        let limit = RateLimitTier::for_method(method).calls_per_minute();
+
+        let mut calls = self.calls.lock().unwrap();
+        let calls = calls.entry(method.to_string()).or_default();
+
+        while calls.front().map(|call| *call + WINDOW <= now).unwrap_or(false) {
+            calls.pop_front();
+        }
+
+        // If the window is full, the next call can happen once the oldest call in the window
+        // expires.
+        let at = if calls.len() >= limit {
+            calls[calls.len() - limit] + WINDOW
+        } else {
+            now
+        };
+        calls.push_back(at);
+
+        at.saturating_duration_since(now)
+    }
+}
+
+/// Read how long Slack asked us to wait from the headers of a 429.
+pub(crate) fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_reserve() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+
+        // Tier 2 allows 20 calls a minute, the first 20 go out right away.
+        for _ in 0..20 {
+            assert_eq!(limiter.reserve("users.list", start), Duration::ZERO);
+        }
+
+        // The next ones wait for the oldest calls in the window to expire, one window per 20 calls.
+        assert_eq!(limiter.reserve("users.list", start), WINDOW);
+        assert_eq!(
+            limiter.reserve("users.list", start + Duration::from_secs(10)),
+            WINDOW - Duration::from_secs(10)
+        );
+        for _ in 0..18 {
+            limiter.reserve("users.list", start);
+        }
+        assert_eq!(limiter.reserve("users.list", start), WINDOW * 2);
+
+        // Each method is counted on its own.
+        assert_eq!(limiter.reserve("usergroups.list", start), Duration::ZERO);
+
+        // Once the window has passed, calls are allowed again as the reserved ones expire.
+        let later = start + WINDOW * 3;
+        assert_eq!(limiter.reserve("users.list", later), Duration::ZERO);
+
+        // Tier 1 only allows a single call a minute.
+        assert_eq!(limiter.reserve("team.billableInfo", start), Duration::ZERO);
+        assert_eq!(limiter.reserve("team.billableInfo", start), WINDOW);
+    }
+
+    #[test]
+    fn test_for_workspace() {
+        let limiter = RateLimiter::for_workspace("T_RATE_LIMIT_TEST");
+        assert!(Arc::ptr_eq(&limiter, &RateLimiter::for_workspace("T_RATE_LIMIT_TEST")));
+        assert!(!Arc::ptr_eq(
+            &limiter,
+            &RateLimiter::for_workspace("T_RATE_LIMIT_OTHER")
+        ));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(30)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static(" 5 "));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(5)));
+
+        // Slack only sends seconds, anything else is ignored.
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"));
+        assert_eq!(retry_after(&headers), None);
+    }
+}
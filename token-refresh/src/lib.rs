@@ -0,0 +1,165 @@
+//! A common interface for API clients whose OAuth access tokens have to be refreshed.
+//!
+//! Each client implements [`TokenRefresh`] so that callers can refresh stored tokens ahead of
+//! their expiry without knowing how a particular provider shapes its token responses.
+use std::{error, fmt};
+
+use async_trait::async_trait;
+
+/// Error codes that providers use to say that a refresh token is no longer valid, and that the
+/// integration has to be authorized again.
+const REVOKED_ERROR_CODES: &[&str] = &[
+    // RFC 6749 section 5.2.
+    "invalid_grant",
+    // Zoho.
+    "invalid_code",
+    // Slack.
+    "invalid_refresh_token",
+    "token_revoked",
+    "token_expired",
+];
+
+/// The tokens returned by a successful refresh.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefreshedToken {
+    pub access_token: String,
+    /// Seconds until the access token expires, or 0 if the provider did not say.
+    pub expires_in: i64,
+    /// The new refresh token if the provider rotated it, otherwise empty.
+    pub refresh_token: String,
+    /// Seconds until the refresh token expires, or 0 if the provider did not say.
+    pub refresh_token_expires_in: i64,
+}
+
+#[derive(Debug)]
+pub enum RefreshError {
+    /// The provider rejected the refresh token. Someone has to authorize the integration again.
+    Revoked(String),
+    /// The client does not have what it needs to refresh, e.g. no refresh token.
+    NotConfigured(String),
+    Other(Box<dyn error::Error + Send + Sync>),
+}
+
+impl RefreshError {
+    pub fn other<E: Into<Box<dyn error::Error + Send + Sync>>>(err: E) -> Self {
+        RefreshError::Other(err.into())
+    }
+
+    /// Classify an error code returned by a token endpoint.
+    pub fn from_error_code(code: &str, description: &str) -> Self {
+        let message = if description.is_empty() {
+            code.to_string()
+        } else {
+            format!("{}: {}", code, description)
+        };
+
+        if REVOKED_ERROR_CODES.contains(&code) {
+            RefreshError::Revoked(message)
+        } else {
+            RefreshError::other(message)
+        }
+    }
+
+    /// Classify a failed response from a token endpoint. Most providers follow RFC 6749 and
+    /// return a JSON body with an `error` code.
+    pub fn from_response(status: u16, body: &str) -> Self {
+        let parsed = serde_json::from_str::<serde_json::Value>(body).ok();
+        let field = |name: &str| {
+            parsed
+                .as_ref()
+                .and_then(|v| v.get(name))
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let code = field("error");
+        if code.is_empty() {
+            return RefreshError::other(format!("status code: {}, body: {}", status, body));
+        }
+
+        RefreshError::from_error_code(&code, &field("error_description"))
+    }
+
+    /// Classify an error from a client that only reports the response of the token endpoint in
+    /// its error message.
+    pub fn from_client_error<E: Into<Box<dyn error::Error + Send + Sync>>>(err: E) -> Self {
+        let err = err.into();
+        let message = err.to_string();
+
+        if REVOKED_ERROR_CODES.iter().any(|code| message.contains(code)) {
+            RefreshError::Revoked(message)
+        } else {
+            RefreshError::Other(err)
+        }
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        matches!(self, RefreshError::Revoked(_))
+    }
+}
+
+impl fmt::Display for RefreshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RefreshError::Revoked(message) => write!(f, "refresh token was revoked: {}", message),
+            RefreshError::NotConfigured(message) => write!(f, "unable to refresh token: {}", message),
+            RefreshError::Other(err) => write!(f, "refreshing token failed: {}", err),
+        }
+    }
+}
+
+impl error::Error for RefreshError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            RefreshError::Other(err) => Some(err.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A client that can exchange its refresh token for a new access token. Implementations update
+/// the client in place so that it can keep being used after the refresh.
+#[async_trait]
+pub trait TokenRefresh: Send {
+    async fn refresh(&mut self) -> Result<RefreshedToken, RefreshError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_response() {
+        let err = RefreshError::from_response(400, r#"{"error":"invalid_grant","error_description":"expired"}"#);
+        assert!(err.is_revoked());
+        assert_eq!(err.to_string(), "refresh token was revoked: invalid_grant: expired");
+
+        let err = RefreshError::from_response(400, r#"{"error":"invalid_client"}"#);
+        assert!(!err.is_revoked());
+
+        let err = RefreshError::from_response(502, "Bad Gateway");
+        assert!(!err.is_revoked());
+        assert_eq!(
+            err.to_string(),
+            "refreshing token failed: status code: 502, body: Bad Gateway"
+        );
+    }
+
+    #[test]
+    fn test_from_error_code() {
+        assert!(RefreshError::from_error_code("token_revoked", "").is_revoked());
+        assert!(RefreshError::from_error_code("invalid_code", "").is_revoked());
+        assert!(!RefreshError::from_error_code("ratelimited", "").is_revoked());
+    }
+
+    #[test]
+    fn test_from_client_error() {
+        let err = RefreshError::from_client_error(r#"code: 400, error: {"error": "invalid_grant"}"#);
+        assert!(err.is_revoked());
+
+        let err = RefreshError::from_client_error("connection reset by peer");
+        assert!(!err.is_revoked());
+        assert_eq!(err.to_string(), "refreshing token failed: connection reset by peer");
+    }
+}
@@ -1,6 +1,12 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_trait::async_trait;
 use dropshot_verify_request::{bearer::BearerProvider, query::QueryTokenProvider};
+use http::header::HeaderMap;
+use serde::Deserialize;
+
+/// The actor recorded for changes made through endpoints authenticated with the [`InternalToken`].
+/// The token is shared, so it does not say who is making the change.
+pub const INTERNAL_ACTOR: &str = "internal";
 
 pub struct InternalToken;
 
@@ -18,6 +24,10 @@ impl QueryTokenProvider for InternalToken {
     }
 }
 
+/// The actor recorded for changes made through endpoints authenticated with the [`HiringToken`],
+/// which is held by the application site.
+pub const HIRING_ACTOR: &str = "hiring";
+
 pub struct HiringToken;
 
 #[async_trait]
@@ -27,6 +37,10 @@ impl BearerProvider for HiringToken {
     }
 }
 
+/// The actor recorded for changes made through endpoints authenticated with the
+/// [`AirtableToken`], which is held by the scripts of our Airtable bases.
+pub const AIRTABLE_ACTOR: &str = "airtable:script";
+
 pub struct AirtableToken;
 
 #[async_trait]
@@ -36,6 +50,10 @@ impl BearerProvider for AirtableToken {
     }
 }
 
+/// The actor recorded for changes made through endpoints authenticated with the [`RFDToken`], which
+/// is held by the RFD site.
+pub const RFD_ACTOR: &str = "rfd";
+
 pub struct RFDToken;
 
 #[async_trait]
@@ -62,3 +80,115 @@ impl QueryTokenProvider for MailChimpToken {
         Ok(std::env::var("MAILCHIMP_WH_KEY")?)
     }
 }
+
+/// The header that tools authenticated with a shared token use to pass the Google access token of
+/// the person they are making a request for.
+pub const VIEWER_TOKEN_HEADER: &str = "x-viewer-token";
+
+/// What Google's tokeninfo endpoint says about an access token.
+#[derive(Debug, Deserialize)]
+struct TokenInfo {
+    #[serde(default)]
+    aud: String,
+    #[serde(default)]
+    azp: String,
+    #[serde(default)]
+    email: String,
+    /// Given as the string `"true"` for access tokens, but as a boolean elsewhere.
+    #[serde(default)]
+    email_verified: serde_json::Value,
+}
+
+/// The client id of our Google OAuth application, taken from the client secret in
+/// `GOOGLE_KEY_ENCODED` that the Google API clients are configured with.
+fn google_client_id() -> Result<String> {
+    let secret: serde_json::Value = serde_json::from_slice(&base64::decode(std::env::var("GOOGLE_KEY_ENCODED")?)?)?;
+
+    match secret["web"]["client_id"]
+        .as_str()
+        .or_else(|| secret["installed"]["client_id"].as_str())
+    {
+        Some(client_id) if !client_id.is_empty() => Ok(client_id.to_string()),
+        _ => bail!("the Google client secret does not contain a client id"),
+    }
+}
+
+/// The email of a viewer token, if it was issued to our application for a verified email on the
+/// company domain. A token issued to any other application could otherwise be replayed here.
+fn verified_viewer(info: &TokenInfo, client_id: &str, domain: &str) -> Result<String> {
+    if info.aud != client_id && info.azp != client_id {
+        bail!("the viewer token was not issued to this application");
+    }
+
+    let verified = info.email_verified == serde_json::Value::Bool(true) || info.email_verified == "true";
+    if info.email.is_empty() || !verified {
+        bail!("the viewer token does not belong to a verified email");
+    }
+
+    match info.email.rsplit_once('@') {
+        Some((_, email_domain)) if !domain.is_empty() && email_domain.eq_ignore_ascii_case(domain) => {
+            Ok(info.email.to_string())
+        }
+        _ => bail!("the viewer token does not belong to an email on `{}`", domain),
+    }
+}
+
+/// The email of the person a request is made for, as verified by Google. Only tokens issued to our
+/// application for a verified email on `domain` (the company's `gsuite_domain`) are accepted.
+/// Returns `None` when the request does not carry a viewer token.
+pub async fn viewer_email(headers: &HeaderMap, domain: &str) -> Result<Option<String>> {
+    let token = match headers.get(VIEWER_TOKEN_HEADER) {
+        Some(token) => token.to_str()?,
+        None => return Ok(None),
+    };
+
+    let info: TokenInfo = reqwest::Client::new()
+        .get("https://oauth2.googleapis.com/tokeninfo")
+        .query(&[("access_token", token)])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    verified_viewer(&info, &google_client_id()?, domain).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use http::header::HeaderMap;
+
+    use super::{verified_viewer, viewer_email, TokenInfo};
+
+    #[tokio::test]
+    async fn test_viewer_email_without_token() {
+        assert_eq!(viewer_email(&HeaderMap::new(), "example.com").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_verified_viewer() {
+        let info = TokenInfo {
+            aud: "client".to_string(),
+            azp: "client".to_string(),
+            email: "jane@example.com".to_string(),
+            email_verified: serde_json::Value::String("true".to_string()),
+        };
+        assert_eq!(
+            verified_viewer(&info, "client", "example.com").unwrap(),
+            "jane@example.com"
+        );
+
+        // Tokens issued to another application are rejected.
+        assert!(verified_viewer(&info, "other-client", "example.com").is_err());
+
+        // So are emails outside of the company domain.
+        assert!(verified_viewer(&info, "client", "example.org").is_err());
+        assert!(verified_viewer(&info, "client", "").is_err());
+
+        let unverified = TokenInfo {
+            email_verified: serde_json::Value::String("false".to_string()),
+            ..info
+        };
+        assert!(verified_viewer(&unverified, "client", "example.com").is_err());
+    }
+}
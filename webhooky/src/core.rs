@@ -22,6 +22,13 @@ pub enum SubCommand {
     Server(Server),
 
     CreateServerSpec(SpecOut),
+    EnforceApplicantRetention(EnforceApplicantRetention),
+    #[clap(name = "reencrypt-api-tokens")]
+    ReencryptAPITokens(ReencryptAPITokens),
+    #[clap(name = "refresh-api-tokens")]
+    RefreshAPITokens(RefreshAPITokens),
+    #[clap(name = "reindex-rfds")]
+    ReindexRFDs(ReindexRFDs),
     SendRFDChangelog(SendRFDChangelog),
     SyncAnalytics(SyncAnalytics),
     #[clap(name = "sync-api-tokens")]
@@ -69,6 +76,19 @@ pub struct SpecOut {
     pub spec_file: std::path::PathBuf,
 }
 
+/// A subcommand for anonymizing and deleting applicants that are past their retention.
+#[derive(Parser, Debug, Clone)]
+pub struct EnforceApplicantRetention {}
+
+/// A subcommand for encrypting the API tokens that are still stored in plaintext, and re-encrypting
+/// the ones encrypted with a key other than the current one. Run it once after rotating the key.
+#[derive(Parser, Debug, Clone)]
+pub struct ReencryptAPITokens {}
+
+/// A subcommand for rebuilding the RFD search index from scratch.
+#[derive(Parser, Debug, Clone)]
+pub struct ReindexRFDs {}
+
 /// A subcommand for sending the RFD changelog.
 #[derive(Parser, Clone, Debug)]
 pub struct SendRFDChangelog {}
@@ -77,6 +97,10 @@ pub struct SendRFDChangelog {}
 #[derive(Parser, Debug, Clone)]
 pub struct SyncAnalytics {}
 
+/// A subcommand for running the background job of refreshing API tokens that are about to expire.
+#[derive(Parser, Debug, Clone)]
+pub struct RefreshAPITokens {}
+
 /// A subcommand for running the background job of syncing API tokens.
 #[derive(Parser, Debug, Clone)]
 pub struct SyncAPITokens {}
@@ -163,6 +187,10 @@ pub struct SyncZoho {}
 
 pub fn into_job_command(cmd: &str) -> Option<SubCommand> {
     match cmd {
+        "enforce-applicant-retention" => Some(SubCommand::EnforceApplicantRetention(EnforceApplicantRetention {})),
+        "reencrypt-api-tokens" => Some(SubCommand::ReencryptAPITokens(ReencryptAPITokens {})),
+        "refresh-api-tokens" => Some(SubCommand::RefreshAPITokens(RefreshAPITokens {})),
+        "reindex-rfds" => Some(SubCommand::ReindexRFDs(ReindexRFDs {})),
         "send-rfd-changelog" => Some(SubCommand::SendRFDChangelog(SendRFDChangelog {})),
         "sync-analytics" => Some(SubCommand::SyncAnalytics(SyncAnalytics {})),
         "sync-api-tokens" => Some(SubCommand::SyncAPITokens(SyncAPITokens {})),
@@ -4,30 +4,31 @@ use chrono::{TimeZone, Utc};
 use chrono_humanize::HumanTime;
 use cio_api::{
     analytics::NewPageView,
+    applicant_dedupe::{ApplicantDuplicate, DISMISS_DUPLICATE_ACTION, MERGE_DUPLICATE_ACTION},
+    applicant_pipeline::{status_modified_by_in_airtable, MovedBy, NewApplicantStageChange},
+    applicant_rubric::refresh_reviewer_leaderboard_entry,
     applicants::Applicant,
     asset_inventory::AssetItem,
+    audit::{AuditEvent, AuditQuery, AuditScope},
     certs::Certificate,
     companies::Company,
     configs::User,
-    journal_clubs::JournalClubMeeting,
     rfd::RFD,
-    schema::{applicants, inbound_shipments, journal_club_meetings, outbound_shipments},
+    schema::applicants,
     shipments::{InboundShipment, NewInboundShipment, OutboundShipment, OutboundShipments},
     swag_inventory::SwagInventoryItem,
     swag_store::Order,
-    utils::{decode_base64, merge_json},
+    utils::decode_base64,
 };
-use diesel::{BoolExpressionMethods, ExpressionMethods, PgTextExpressionMethods, QueryDsl};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl};
 use dropshot::{Path, RequestContext};
 use google_drive::traits::{DriveOps, FileOps};
 use log::{error, info, warn};
-use rand::{distributions::Alphanumeric, thread_rng, Rng};
 use slack_chat_api::{
-    BotCommand, FormattedMessage, InputBlock, InputBlockElement, InputType, InteractivePayload, InteractiveResponse,
-    MessageAttachment, MessageBlock, MessageBlockText, MessageBlockType, MessageResponse, MessageResponseType,
-    MessageType, SelectInputOption, View,
+    blocks::{InputBlock, PlainTextInputElement, SectionBlock, StaticSelectElement},
+    BotCommand, InteractivePayload, InteractiveResponse, Slack, View,
 };
-use std::{collections::HashMap, ffi::OsStr, str::FromStr};
+use std::{collections::HashMap, ffi::OsStr};
 
 use crate::{
     context::ServerContext,
@@ -36,7 +37,7 @@ use crate::{
         AirtableRowEvent, ApplicationFileUploadData, CounterResponse, GitHubRateLimit, RFDPathParams,
         ShippoTrackingUpdateEvent,
     },
-    slack_commands::SlackCommand,
+    slack_commands::{SlackCommandContext, SlackCommandRegistry},
 };
 
 pub async fn handle_products_sold_count(rqctx: &RequestContext<ServerContext>) -> Result<CounterResponse> {
@@ -83,7 +84,7 @@ pub async fn handle_rfd_update_by_number(
         .ok_or_else(|| anyhow::anyhow!("no RFD was found with number `{}`", num))?;
 
     let update = rfd.create_sync(&oxide).await?;
-    let updater = RFDUpdater::default();
+    let updater = RFDUpdater::for_context(&api_context.app);
     updater.handle(&api_context.app, &[update]).await?;
 
     Ok(())
@@ -112,6 +113,19 @@ pub async fn handle_github_rate_limit(rqctx: &RequestContext<ServerContext>) ->
     })
 }
 
+pub async fn handle_list_audit_events(
+    rqctx: &RequestContext<ServerContext>,
+    query: AuditQuery,
+) -> Result<Vec<AuditEvent>> {
+    let api_context = rqctx.context();
+
+    let db = &api_context.app.db;
+
+    let company = &api_context.app.company;
+
+    query.run(db, company.id).await
+}
+
 pub async fn handle_slack_commands(
     rqctx: &RequestContext<ServerContext>,
     bot_command: BotCommand,
@@ -122,316 +136,14 @@ pub async fn handle_slack_commands(
     // Get the company from the Slack team id.
     let company = Company::get_from_slack_team_id(db, &bot_command.team_id).await?;
 
-    // Get the command type.
-    let command = SlackCommand::from_str(&bot_command.command).unwrap();
-    let text = bot_command.text.trim();
-
-    // Create a basic divider we can use as a reference.
-    let divider = MessageAttachment {
-        color: Default::default(),
-        author_icon: Default::default(),
-        author_link: Default::default(),
-        author_name: Default::default(),
-        fallback: Default::default(),
-        fields: Default::default(),
-        footer: Default::default(),
-        footer_icon: Default::default(),
-        image_url: Default::default(),
-        pretext: Default::default(),
-        text: Default::default(),
-        thumb_url: Default::default(),
-        title: Default::default(),
-        title_link: Default::default(),
-        ts: Default::default(),
-        blocks: vec![MessageBlock {
-            block_type: MessageBlockType::Divider,
-            text: None,
-            elements: Default::default(),
-            accessory: Default::default(),
-            block_id: Default::default(),
-            fields: Default::default(),
-        }],
-    };
-
-    // Filter by command type and do the command.
-    let response = match command {
-        SlackCommand::Meet => {
-            let mut name = text.replace(' ', "-");
-            if name.is_empty() {
-                // Generate a new random string.
-                name = thread_rng()
-                    .sample_iter(&Alphanumeric)
-                    .take(6)
-                    .map(char::from)
-                    .collect();
-            }
-
-            json!(MessageResponse {
-                response_type: MessageResponseType::InChannel,
-                text: format!("https://g.co/meet/oxide-{}", name.to_lowercase()),
-            })
-        }
-        SlackCommand::Applicants => {
-            let mut status = cio_api::applicant_status::Status::NeedsToBeTriaged;
-
-            if text.to_lowercase() == "onboarding" {
-                status = cio_api::applicant_status::Status::Onboarding;
-            } else if text.to_lowercase() == "interviewing" {
-                status = cio_api::applicant_status::Status::Interviewing;
-            } else if text.to_lowercase() == "giving offer" {
-                status = cio_api::applicant_status::Status::GivingOffer;
-            } else if text.to_lowercase() == "next steps" {
-                status = cio_api::applicant_status::Status::NextSteps;
-            } else if text.to_lowercase() == "hired" {
-                status = cio_api::applicant_status::Status::Hired;
-            } else if text.to_lowercase() == "deferred" {
-                status = cio_api::applicant_status::Status::Deferred;
-            } else if text.to_lowercase() == "declined" {
-                status = cio_api::applicant_status::Status::Declined;
-            } else if text.to_lowercase() == "withdrawn" {
-                status = cio_api::applicant_status::Status::Withdrawn;
-            }
-
-            // Get the applicants that need to be triaged.
-            let applicants = applicants::dsl::applicants
-                .filter(
-                    applicants::dsl::cio_company_id
-                        .eq(company.id)
-                        .and(applicants::dsl::status.eq(status.to_string())),
-                )
-                .load_async::<Applicant>(db.pool())
-                .await?;
-
-            if applicants.len() > 10 {
-                json!(MessageResponse {
-                    response_type: MessageResponseType::InChannel,
-                    text: format!(
-                        "Found `{}` applicants with status `{}`. Sorry, that's too many to return at once.",
-                        applicants.len(),
-                        status.to_string()
-                    ),
-                })
-            } else if applicants.is_empty() {
-                json!(MessageResponse {
-                    response_type: MessageResponseType::InChannel,
-                    text: format!(
-                        "Sorry <@{}> :scream: I could not find any applicants with status `{}`",
-                        bot_command.user_id,
-                        status.to_string()
-                    ),
-                })
-            } else {
-                // We know we have at least one item, lets add it.
-                let mut msg: FormattedMessage = applicants.get(0).unwrap().clone().into();
-                for (i, a) in applicants.into_iter().enumerate() {
-                    if i == 0 {
-                        continue;
-                    }
-
-                    if i > 0 {
-                        // Add our divider.
-                        msg.attachments.push(divider.clone());
-                    }
-
-                    // Add the rest of the blocks.
-                    let mut m: FormattedMessage = a.into();
-                    msg.attachments.append(&mut m.attachments);
-                }
-
-                json!(msg)
-            }
-        }
-        SlackCommand::Applicant => {
-            if let Ok(applicant) = applicants::dsl::applicants
-                .filter(
-                    applicants::dsl::cio_company_id
-                        .eq(company.id)
-                        .and(applicants::dsl::name.ilike(format!("%{}%", text))),
-                )
-                .first_async::<Applicant>(db.pool())
-                .await
-            {
-                let r: FormattedMessage = applicant.into();
-                json!(r)
-            } else {
-                json!(MessageResponse {
-                    response_type: MessageResponseType::InChannel,
-                    text: format!(
-                        "Sorry <@{}> :scream: I could not find an applicant matching `{}`",
-                        bot_command.user_id, text
-                    ),
-                })
-            }
-        }
-        SlackCommand::Shipments => {
-            let msg = if !text.is_empty() && text != "outbound" && text != "inbound" {
-                json!(MessageResponse {
-                    response_type: MessageResponseType::InChannel,
-                    text: format!(
-                        "Sorry <@{}> :scream: `{}` is valid, try `outbound` or `inbound` or leave blank for both",
-                        bot_command.user_id, text
-                    ),
-                })
-            } else {
-                let outbound = if text.is_empty() || text == "outbound" {
-                    outbound_shipments::dsl::outbound_shipments
-                        .filter(
-                            outbound_shipments::dsl::cio_company_id
-                                .eq(company.id)
-                                .and(outbound_shipments::dsl::tracking_status.ne("DELIVERED".to_string()))
-                                .and(
-                                    outbound_shipments::dsl::status
-                                        .ne(cio_api::shipment_status::Status::PickedUp.to_string()),
-                                ),
-                        )
-                        .load_async::<OutboundShipment>(db.pool())
-                        .await?
-                } else {
-                    Default::default()
-                };
-
-                let inbound = if text.is_empty() || text == "inbound" {
-                    inbound_shipments::dsl::inbound_shipments
-                        .filter(
-                            inbound_shipments::dsl::cio_company_id
-                                .eq(company.id)
-                                .and(inbound_shipments::dsl::tracking_status.ne("DELIVERED".to_string()))
-                                .and(inbound_shipments::dsl::delivered_time.is_null()),
-                        )
-                        .load_async::<InboundShipment>(db.pool())
-                        .await?
-                } else {
-                    Default::default()
-                };
-
-                if outbound.is_empty() && text == "outbound" {
-                    json!(MessageResponse {
-                        response_type: MessageResponseType::InChannel,
-                        text: format!(
-                            "Sorry <@{}> :scream: I could not find any `outbound` shipments pending delivery",
-                            bot_command.user_id,
-                        ),
-                    })
-                } else if inbound.is_empty() && text == "inbound" {
-                    json!(MessageResponse {
-                        response_type: MessageResponseType::InChannel,
-                        text: format!(
-                            "Sorry <@{}> :scream: I could not find any `inbound` shipments pending delivery",
-                            bot_command.user_id,
-                        ),
-                    })
-                } else if inbound.is_empty() && outbound.is_empty() {
-                    json!(MessageResponse {
-                        response_type: MessageResponseType::InChannel,
-                        text: format!(
-                            "Sorry <@{}> :scream: I could not find any shipments that had not been delivered",
-                            bot_command.user_id,
-                        ),
-                    })
-                } else {
-                    let mut fm: FormattedMessage = if (text.is_empty() || text == "outbound") && !outbound.is_empty() {
-                        outbound.get(0).unwrap().clone().into()
-                    } else {
-                        inbound.get(0).unwrap().clone().into()
-                    };
-
-                    for (i, a) in outbound.clone().into_iter().enumerate() {
-                        if i == 0 {
-                            continue;
-                        }
-
-                        if i > 0 {
-                            // Add our divider.
-                            fm.attachments.push(divider.clone());
-                        }
-
-                        // Add the rest of the blocks.
-                        let mut m: FormattedMessage = a.into();
-                        fm.attachments.append(&mut m.attachments);
-                    }
-
-                    for (i, a) in inbound.into_iter().enumerate() {
-                        if i == 0 && ((text == "inbound" || text.is_empty()) && outbound.is_empty()) {
-                            continue;
-                        }
-
-                        if i > 0 {
-                            // Add our divider.
-                            fm.attachments.push(divider.clone());
-                        }
-
-                        // Add the rest of the blocks.
-                        let mut m: FormattedMessage = a.into();
-                        fm.attachments.append(&mut m.attachments);
-                    }
-
-                    json!(fm)
-                }
-            };
-
-            msg
-        }
-        SlackCommand::Papers => {
-            // If we asked for the closed meetings then only show those, otherwise
-            // default to the open meetings.
-            let mut state = "open";
-            if text == "closed" {
-                state = "closed";
-            }
-            let meetings = journal_club_meetings::dsl::journal_club_meetings
-                .filter(
-                    journal_club_meetings::dsl::cio_company_id
-                        .eq(company.id)
-                        .and(journal_club_meetings::dsl::state.eq(state.to_string())),
-                )
-                .load_async::<JournalClubMeeting>(db.pool())
-                .await?;
-
-            let mut msg: serde_json::Value = Default::default();
-            for (i, m) in meetings.into_iter().enumerate() {
-                if i > 0 {
-                    // Merge a divider onto the stack.
-                    let object = json!({
-                        "blocks": [{
-                            "type": "divider"
-                        }]
-                    });
-
-                    merge_json(&mut msg, object);
-                }
-
-                let obj: FormattedMessage = m.into();
-                merge_json(&mut msg, json!(obj));
-            }
-
-            msg
-        }
-        SlackCommand::Paper => {
-            if let Ok(meeting) = journal_club_meetings::dsl::journal_club_meetings
-                .filter(
-                    journal_club_meetings::dsl::cio_company_id
-                        .eq(company.id)
-                        .and(journal_club_meetings::dsl::title.ilike(format!("%{}%", text))),
-                )
-                .first_async::<JournalClubMeeting>(db.pool())
-                .await
-            {
-                let r: FormattedMessage = meeting.into();
-                json!(r)
-            } else {
-                json!(MessageResponse {
-                    response_type: MessageResponseType::InChannel,
-                    text: format!(
-                        "Sorry <@{}> :scream: I could not find a journal club meeting matching \
-                         `{}`",
-                        bot_command.user_id, text
-                    ),
-                })
-            }
-        }
+    let ctx = SlackCommandContext {
+        db: db.clone(),
+        company,
+        app_config: api_context.app.app_config.clone(),
+        command: bot_command,
     };
 
-    Ok(response)
+    Ok(SlackCommandRegistry::default().dispatch(ctx).await)
 }
 
 pub async fn handle_slack_interactive(
@@ -577,20 +289,80 @@ pub async fn handle_slack_interactive(
         return Ok(interactive_response);
     }
 
+    // The person that clicked the button, for the audit log.
+    let actor = if payload.user.name.is_empty() {
+        format!("slack:{}", payload.user.id)
+    } else {
+        format!("slack:{}", payload.user.name)
+    };
+
     // Handle the actions for re-running functions.
     for action in payload.actions {
         // Trigger the action if it's a function.
         if action.action_id == "function" {
             // Run the command in the background so we don't have to wait for it.
-            if let Err(e) = crate::handlers_cron::run_subcmd_job(ctx, &action.value).await {
+            if let Err(e) = crate::handlers_cron::run_subcmd_job(ctx, &action.value, &actor).await {
                 error!("Subcommand execution failed {:?}", e);
             }
+        } else if action.action_id == MERGE_DUPLICATE_ACTION || action.action_id == DISMISS_DUPLICATE_ACTION {
+            let text = match AuditScope::for_request(&actor, &rqctx.request_id)
+                .run(resolve_applicant_duplicate(
+                    db,
+                    &company,
+                    &action.action_id,
+                    &action.value,
+                ))
+                .await
+            {
+                Ok(text) => text,
+                Err(e) => {
+                    error!("Resolving duplicate applicant `{}` failed: {:?}", action.value, e);
+                    format!("Resolving the duplicate applicant failed: {}", e)
+                }
+            };
+
+            // Replace the buttons with what was done.
+            if !payload.response_url.is_empty() {
+                Slack::post_to_channel(
+                    &payload.response_url,
+                    &json!({
+                        "replace_original": true,
+                        "text": text,
+                    }),
+                )
+                .await?;
+            }
         }
     }
 
     Ok(interactive_response)
 }
 
+/// Merge or dismiss the pair of duplicate applicants a Slack button was clicked for.
+async fn resolve_applicant_duplicate(
+    db: &cio_api::db::Database,
+    company: &Company,
+    action_id: &str,
+    value: &str,
+) -> Result<String> {
+    // The value of the buttons is the id of the pair.
+    let mut duplicate = ApplicantDuplicate::pending(db, company.id, value.parse()?).await?;
+
+    if action_id == MERGE_DUPLICATE_ACTION {
+        let primary = duplicate.merge(db).await?;
+        Ok(format!(
+            "Merged {} <{}> into {} <{}>.",
+            duplicate.duplicate_name, duplicate.duplicate_email, primary.name, primary.email
+        ))
+    } else {
+        duplicate.dismiss(db).await?;
+        Ok(format!(
+            "{} <{}> and {} <{}> are not the same person.",
+            duplicate.primary_name, duplicate.primary_email, duplicate.duplicate_name, duplicate.duplicate_email
+        ))
+    }
+}
+
 pub async fn handle_airtable_employees_print_home_address_label(
     rqctx: &RequestContext<ServerContext>,
     event: AirtableRowEvent,
@@ -700,6 +472,54 @@ pub async fn handle_airtable_applicants_request_background_check(
     Ok(())
 }
 
+pub async fn handle_airtable_applicant_duplicates_merge(
+    rqctx: &RequestContext<ServerContext>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable, the database has the current status.
+    let record =
+        ApplicantDuplicate::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    let mut duplicate = ApplicantDuplicate::get_by_id(&api_context.app.db, record.id).await?;
+
+    let primary = duplicate.merge(&api_context.app.db).await?;
+    info!(
+        "merged duplicate applicant {} into {}",
+        duplicate.duplicate_email, primary.email
+    );
+
+    Ok(())
+}
+
+pub async fn handle_airtable_applicant_duplicates_dismiss(
+    rqctx: &RequestContext<ServerContext>,
+    event: AirtableRowEvent,
+) -> Result<()> {
+    let api_context = rqctx.context();
+
+    if event.record_id.is_empty() {
+        bail!("record id is empty");
+    }
+
+    // Get the row from airtable, the database has the current status.
+    let record =
+        ApplicantDuplicate::get_from_airtable(&event.record_id, &api_context.app.db, event.cio_company_id).await?;
+    let mut duplicate = ApplicantDuplicate::get_by_id(&api_context.app.db, record.id).await?;
+
+    duplicate.dismiss(&api_context.app.db).await?;
+    info!(
+        "dismissed duplicate applicant {} of {}",
+        duplicate.duplicate_email, duplicate.primary_email
+    );
+
+    Ok(())
+}
+
 pub async fn handle_airtable_applicants_update(
     rqctx: &RequestContext<ServerContext>,
     event: AirtableRowEvent,
@@ -720,10 +540,51 @@ pub async fn handle_airtable_applicants_update(
     // Grab our old applicant from the database.
     let mut db_applicant = Applicant::get_by_id(&api_context.app.db, applicant.id).await?;
 
-    // Grab the status and the status raw.
-    let status = cio_api::applicant_status::Status::from_str(&applicant.status).unwrap();
+    // Check the move against the pipeline for the applicant's role.
+    let pipeline = api_context
+        .app
+        .app_config
+        .read()
+        .unwrap()
+        .hiring
+        .pipeline(&db_applicant.role);
+    let to = pipeline
+        .stage(&applicant.status)
+        .map(|stage| stage.name.to_string())
+        .unwrap_or_else(|| applicant.status.to_string());
+    // Check who may advance applicants against the collaborator that changed the status.
+    let company = Company::get_by_id(&api_context.app.db, event.cio_company_id).await?;
+    let modified_by = match status_modified_by_in_airtable(&company, &event.record_id).await {
+        Ok(modified_by) => modified_by,
+        Err(err) => {
+            warn!(
+                "Failed to get who changed the status of applicant {}: {}",
+                applicant.email, err
+            );
+            None
+        }
+    };
+    let moved_by = match &modified_by {
+        Some(email) => MovedBy::Person(email),
+        None => MovedBy::Unknown,
+    };
+    let status = match pipeline.check_transition(&applicant, &db_applicant.status, &to, moved_by) {
+        Ok(stage) => stage.name.to_string(),
+        Err(err) => {
+            warn!(
+                "Rejecting stage change of applicant {} from {} to {}: {}",
+                applicant.email, db_applicant.status, applicant.status, err
+            );
 
-    let status_changed = db_applicant.status != status.to_string();
+            // Put the last accepted stage back in Airtable.
+            db_applicant.update(&api_context.app.db).await?;
+
+            return Ok(());
+        }
+    };
+
+    let previous_status = db_applicant.status.to_string();
+    let status_changed = previous_status != status;
 
     db_applicant.status = status.to_string();
     if !applicant.raw_status.is_empty() {
@@ -737,40 +598,53 @@ pub async fn handle_airtable_applicants_update(
 
     // If the status is now Giving Offer we should and it's changed from whatever it was before,
     // let do the docusign stuff.
-    if status_changed && status == cio_api::applicant_status::Status::GivingOffer {
+    if status_changed && status == cio_api::applicant_status::Status::GivingOffer.to_string() {
         // Update the row in our database, first just in case..
         db_applicant.update(&api_context.app.db).await?;
 
-        // Create our docusign client.
-        let company = db_applicant.company(&api_context.app.db).await?;
-        let dsa = company.authenticate_docusign(&api_context.app.db).await;
-        if let Ok(ds) = dsa {
-            let offer_letter = api_context
-                .app
-                .app_config
-                .read()
-                .unwrap()
-                .envelopes
-                .create_offer_letter(&db_applicant);
-            db_applicant
-                .do_docusign_offer(&api_context.app.db, &ds, offer_letter)
-                .await?;
-
-            let piia_letter = api_context
-                .app
-                .app_config
-                .read()
-                .unwrap()
-                .envelopes
-                .create_piia_letter(&db_applicant);
-            db_applicant
-                .do_docusign_piia(&api_context.app.db, &ds, piia_letter)
-                .await?;
+        // The collaborator Airtable says changed the status is the one sending the offer.
+        let mut scope = AuditScope::current();
+        if let Some(email) = &modified_by {
+            scope.actor = format!("airtable:{}", email);
         }
+
+        scope
+            .run(async {
+                // Create our docusign client.
+                let company = db_applicant.company(&api_context.app.db).await?;
+                let dsa = company.authenticate_docusign(&api_context.app.db).await;
+                if let Ok(ds) = dsa {
+                    let offer_letter = api_context
+                        .app
+                        .app_config
+                        .read()
+                        .unwrap()
+                        .envelopes
+                        .create_offer_letter(&db_applicant);
+                    db_applicant
+                        .do_docusign_offer(&api_context.app.db, &ds, offer_letter)
+                        .await?;
+
+                    let piia_letter = api_context
+                        .app
+                        .app_config
+                        .read()
+                        .unwrap()
+                        .envelopes
+                        .create_piia_letter(&db_applicant);
+                    db_applicant
+                        .do_docusign_piia(&api_context.app.db, &ds, piia_letter)
+                        .await?;
+                }
+
+                Ok::<(), anyhow::Error>(())
+            })
+            .await?;
     }
 
     // Update the row in our database.
     db_applicant.update(&api_context.app.db).await?;
+    NewApplicantStageChange::record(&api_context.app.db, &db_applicant, &previous_status).await?;
 
     info!("applicant {} updated successfully", applicant.email);
     Ok(())
@@ -948,11 +822,13 @@ pub async fn handle_applicant_review(
 ) -> Result<()> {
     let api_context = rqctx.context().clone();
 
-    if event.name.is_empty() || event.applicant.is_empty() || event.reviewer.is_empty() || event.evaluation.is_empty() {
-        bail!("review is empty");
-    }
+    // Reject reviews that do not follow the rubric before we store anything.
+    let rubric = api_context.app.app_config.read().unwrap().hiring.rubric.clone();
+    rubric.validate(&event)?;
 
-    tokio::spawn(async move {
+    // Keep the audit scope of the request for the work done in the background.
+    let scope = AuditScope::current();
+    tokio::spawn(scope.run(async move {
         // Add them to the database.
         let mut review = event.upsert(&api_context.app.db).await?;
 
@@ -980,8 +856,12 @@ pub async fn handle_applicant_review(
             applicant.email, review.reviewer
         );
 
+        // Count the review towards the reviewer's entry on the leaderboard.
+        let company = review.company(&api_context.app.db).await?;
+        refresh_reviewer_leaderboard_entry(&api_context.app.db, &company, &review.reviewer).await?;
+
         Ok::<(), anyhow::Error>(())
-    });
+    }));
 
     Ok(())
 }
@@ -1454,155 +1334,33 @@ pub async fn handle_shipbob(rqctx: &RequestContext<ServerContext>, event: serde_
 const SLACK_TRACK_SHIPMENT_MODAL_DESCRIPTION:  &str = "After submitting the carrer and tracking number, your shipment will be tracked in the `Shipments` Airtable and notifications for status updates will post to the #shipments channel.";
 
 fn create_slack_shipment_tracking_modal() -> Result<slack_chat_api::Modal> {
-    Ok(slack_chat_api::Modal {
-        type_: slack_chat_api::ModalType::Modal,
-        title: MessageBlockText {
-            text_type: MessageType::PlainText,
-            text: "Track a shipment".to_string(),
-        },
-        callback_id: "track_shipment_modal".to_string(),
-        submit: MessageBlockText {
-            text_type: MessageType::PlainText,
-            text: "Track shipment".to_string(),
-        },
-        close: MessageBlockText {
-            text_type: MessageType::PlainText,
-            text: "Cancel".to_string(),
-        },
-
-        blocks: vec![
-            InputBlock {
-                type_: MessageBlockType::Section,
-                text: Some(MessageBlockText {
-                    text_type: MessageType::Markdown,
-                    text: SLACK_TRACK_SHIPMENT_MODAL_DESCRIPTION.to_string(),
-                }),
-                element: None,
-                label: None,
-                optional: None,
-                hint: Default::default(),
-            },
-            InputBlock {
-                type_: MessageBlockType::Input,
-                text: None,
-                element: Some(InputBlockElement {
-                    type_: InputType::PlainText,
-                    action_id: "name".to_string(),
-                    options: vec![],
-                    placeholder: None,
-                }),
-                label: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "Name".to_string(),
-                }),
-                optional: None,
-                hint: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "A short description of the package so that we can easily know what is inside.".to_string(),
-                }),
-            },
-            InputBlock {
-                type_: MessageBlockType::Input,
-                text: None,
-                element: Some(InputBlockElement {
-                    type_: InputType::StaticSelect,
-                    action_id: "carrier".to_string(),
-                    placeholder: Some(MessageBlockText {
-                        text_type: MessageType::PlainText,
-                        text: "Select a shipping carrier".to_string(),
-                    }),
-                    options: vec![
-                        SelectInputOption {
-                            text: MessageBlockText {
-                                text_type: MessageType::PlainText,
-                                text: "DHL".to_string(),
-                            },
-                            value: "DHL".to_string(),
-                        },
-                        SelectInputOption {
-                            text: MessageBlockText {
-                                text_type: MessageType::PlainText,
-                                text: "FedEx".to_string(),
-                            },
-                            value: "FedEx".to_string(),
-                        },
-                        SelectInputOption {
-                            text: MessageBlockText {
-                                text_type: MessageType::PlainText,
-                                text: "UPS".to_string(),
-                            },
-                            value: "UPS".to_string(),
-                        },
-                        SelectInputOption {
-                            text: MessageBlockText {
-                                text_type: MessageType::PlainText,
-                                text: "USPS".to_string(),
-                            },
-                            value: "USPS".to_string(),
-                        },
-                    ],
-                }),
-                label: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "Carrier".to_string(),
-                }),
-                optional: None,
-                hint: Default::default(),
-            },
-            InputBlock {
-                type_: MessageBlockType::Input,
-                text: None,
-                element: Some(InputBlockElement {
-                    type_: InputType::PlainText,
-                    action_id: "tracking_number".to_string(),
-                    options: vec![],
-                    placeholder: None,
-                }),
-                label: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "Tracking number".to_string(),
-                }),
-                optional: None,
-                hint: Default::default(),
-            },
-            InputBlock {
-                type_: MessageBlockType::Input,
-                text: None,
-                element: Some(InputBlockElement {
-                    type_: InputType::PlainText,
-                    action_id: "order_number".to_string(),
-                    options: vec![],
-                    placeholder: None,
-                }),
-                label: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "Order number".to_string(),
-                }),
-                optional: Some(true),
-                hint: Default::default(),
-            },
-            InputBlock {
-                type_: MessageBlockType::Input,
-                text: None,
-                element: Some(InputBlockElement {
-                    type_: InputType::PlainText,
-                    action_id: "notes".to_string(),
-                    options: vec![],
-                    placeholder: None,
-                }),
-                label: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "Notes".to_string(),
-                }),
-                optional: Some(true),
-                hint: Some(MessageBlockText {
-                    text_type: MessageType::PlainText,
-                    text: "Any other additional information.".to_string(),
-                }),
-            },
-        ],
-        state: Default::default(),
-    })
+    Ok(slack_chat_api::Modal::new("track_shipment_modal", "Track a shipment")
+        .submit("Track shipment")
+        .close("Cancel")
+        .block(SectionBlock::markdown(SLACK_TRACK_SHIPMENT_MODAL_DESCRIPTION))
+        .block(
+            InputBlock::new("Name", PlainTextInputElement::new("name"))
+                .hint("A short description of the package so that we can easily know what is inside."),
+        )
+        .block(InputBlock::new(
+            "Carrier",
+            StaticSelectElement::new("carrier")
+                .placeholder("Select a shipping carrier")
+                .option("DHL", "DHL")
+                .option("FedEx", "FedEx")
+                .option("UPS", "UPS")
+                .option("USPS", "USPS"),
+        ))
+        .block(InputBlock::new(
+            "Tracking number",
+            PlainTextInputElement::new("tracking_number"),
+        ))
+        .block(InputBlock::new("Order number", PlainTextInputElement::new("order_number")).optional())
+        .block(
+            InputBlock::new("Notes", PlainTextInputElement::new("notes"))
+                .optional()
+                .hint("Any other additional information."),
+        ))
 }
 
 fn from_json_value_to_string(t: &serde_json::Map<String, serde_json::Value>) -> String {
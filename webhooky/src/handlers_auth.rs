@@ -101,7 +101,7 @@ pub async fn handle_auth_google_callback(
     token.expand();
 
     // Update it in the database.
-    token.upsert(&api_context.app.db).await?;
+    token.upsert_encrypted(&api_context.app.db, &company).await?;
 
     Ok(())
 }
@@ -160,7 +160,7 @@ pub async fn handle_auth_gusto_callback(
     };
     token.expand();
     // Update it in the database.
-    token.upsert(&api_context.app.db).await?;
+    token.upsert_encrypted(&api_context.app.db, &company).await?;
 
     Ok(())
 }
@@ -222,7 +222,7 @@ pub async fn handle_auth_zoom_callback(
     };
     token.expand();
     // Update it in the database.
-    token.upsert(&api_context.app.db).await?;
+    token.upsert_encrypted(&api_context.app.db, &company).await?;
 
     Ok(())
 }
@@ -267,8 +267,9 @@ pub async fn handle_auth_slack_callback(
         product: "slack".to_string(),
         token_type: t.token_type.to_string(),
         access_token: t.access_token.to_string(),
-        expires_in: 0,
-        refresh_token: "".to_string(),
+        // These are only set when token rotation is enabled for the app.
+        expires_in: t.expires_in as i32,
+        refresh_token: t.refresh_token.to_string(),
         refresh_token_expires_in: 0,
         company_id: t.team.id.to_string(),
         item_id: t.team.name.to_string(),
@@ -283,6 +284,7 @@ pub async fn handle_auth_slack_callback(
         cio_company_id: 1,
     };
     token.expand();
+    let token = token.encrypted(&company).await?;
 
     // Update it in the database.
     let mut new_token = if let Ok(existing) = api_tokens::dsl::api_tokens
@@ -328,6 +330,7 @@ pub async fn handle_auth_slack_callback(
             cio_company_id: 1,
         };
         user_token.expand();
+        let user_token = user_token.encrypted(&company).await?;
 
         // Update it in the database.
         let mut new_user_token = if let Ok(existing) = api_tokens::dsl::api_tokens
@@ -406,7 +409,7 @@ pub async fn handle_auth_quickbooks_callback(
     token.expand();
 
     // Update it in the database.
-    token.upsert(&api_context.app.db).await?;
+    token.upsert_encrypted(&api_context.app.db, &company).await?;
 
     Ok(())
 }
@@ -479,7 +482,7 @@ pub async fn handle_auth_docusign_callback(
     token.expand();
 
     // Update it in the database.
-    token.upsert(&api_context.app.db).await?;
+    token.upsert_encrypted(&api_context.app.db, &company).await?;
 
     Ok(())
 }
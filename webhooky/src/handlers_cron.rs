@@ -8,7 +8,9 @@ use log::info;
 
 use crate::context::ServerContext;
 
-pub async fn run_subcmd_job(server_context: &ServerContext, cmd_name: &str) -> Result<uuid::Uuid> {
+/// Start the job as a saga, or return the id of the run already in progress. The audit events of
+/// the job are attributed to `actor`.
+pub async fn run_subcmd_job(server_context: &ServerContext, cmd_name: &str, actor: &str) -> Result<uuid::Uuid> {
     let db = &server_context.app.db;
 
     // Check if we already have an in-progress run for this job.
@@ -47,6 +49,7 @@ pub async fn run_subcmd_job(server_context: &ServerContext, cmd_name: &str) -> R
         server_context.exec_registry.clone(),
         &id,
         cmd_name,
+        actor,
     )
     .await?;
 
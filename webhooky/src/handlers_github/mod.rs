@@ -8,7 +8,7 @@ use cio_api::{
     },
     core::GitHubCommit,
     repos::NewRepo,
-    rfd::{GitHubRFDBranch, GitHubRFDRepo, GitHubRFDUpdate},
+    rfd::{GitHubRFDBranch, GitHubRFDCommit, GitHubRFDRepo, GitHubRFDUpdate},
     shorturls::{generate_shorturls_for_configs_links, generate_shorturls_for_repos},
 };
 use dropshot::{RequestContext, ServerContext as DropshotServerContext, SharedExtractor};
@@ -267,14 +267,25 @@ async fn handle_rfd_push(github: Arc<octorust::Client>, api_context: &Context, e
     // We are always creating updates based on the branch that is defined by the event, independent
     // of it if corresponds with RFD number of the file(s) updated. We are only responsible for
     // generating updates, not determining if they make sense to process.
-    let updates = get_rfd_updates(&branch, &commit);
+    let updates = get_rfd_updates(&branch, &commit, &event.sender.login);
+
+    // Lint the header of each updated RFD. Problems are reported on a check run for the commit and
+    // do not block the update.
+    for update in &updates {
+        if let Err(err) = rfd::run_rfd_lint_check(api_context, update).await {
+            warn!(
+                "Failed to lint RFD {} on the {} branch: {:?}",
+                update.number, update.branch.branch, err
+            );
+        }
+    }
 
-    let handler = RFDUpdater::default();
+    let handler = RFDUpdater::for_context(api_context);
 
     handler.handle(api_context, &updates).await
 }
 
-fn get_rfd_updates(branch: &GitHubRFDBranch, commit: &GitHubCommit) -> Vec<GitHubRFDUpdate> {
+fn get_rfd_updates(branch: &GitHubRFDBranch, commit: &GitHubCommit, actor: &str) -> Vec<GitHubRFDUpdate> {
     let mut updates = vec![];
 
     // Iterate through all of the updated files and for anything that looks like and RFD document
@@ -300,6 +311,10 @@ fn get_rfd_updates(branch: &GitHubRFDBranch, commit: &GitHubCommit) -> Vec<GitHu
                 updates.push(GitHubRFDUpdate {
                     number: number.into(),
                     branch: branch.clone(),
+                    commit: Some(GitHubRFDCommit {
+                        sha: commit.id.to_string(),
+                        actor: actor.to_string(),
+                    }),
                 });
             } else {
                 log::warn!(
@@ -362,7 +377,7 @@ pub async fn handle_rfd_pull_request(
         let repo = GitHubRFDRepo::new(company).await?;
         let branch = repo.branch(branch_name.to_string());
 
-        let handler = RFDUpdater::default();
+        let handler = RFDUpdater::for_context(api_context);
 
         handler
             .handle(
@@ -370,6 +385,7 @@ pub async fn handle_rfd_pull_request(
                 &[GitHubRFDUpdate {
                     number: number.into(),
                     branch,
+                    commit: None,
                 }],
             )
             .await?;
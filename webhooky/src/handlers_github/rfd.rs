@@ -1,9 +1,17 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
+use chrono::Utc;
 use cio_api::{
+    app_config::{RFDActionConfig, RFDActionKind},
+    audit::SYSTEM_ACTOR,
+    configs::Users,
     core::GitHubPullRequest,
     features::Features,
-    rfd::{GitHubRFDReadmeLocation, GitHubRFDUpdate, NewRFD, RFDOutputError, RFDSearchIndex, RemoteRFD, RFD},
+    rfd::{
+        check_transition, extract_references, GitHubRFDReadmeLocation, GitHubRFDUpdate, NewRFD, NewRFDStateChange,
+        PDFStorageTarget, RFDKnownAuthor, RFDLintLevel, RFDLintRules, RFDOutputError, RFDReference, RFDReferences,
+        RFDSearchIndex, RFDState, RemoteRFD, RFD,
+    },
     shorturls::generate_shorturls_for_rfds,
     utils::{create_or_update_file_in_github_repo, decode_base64, get_file_content_from_repo},
 };
@@ -13,6 +21,7 @@ use google_storage1::{
     hyper, hyper_rustls,
 };
 use log::{info, warn};
+use slack_chat_api::{blocks::SectionBlock, FormattedMessage};
 use std::cmp::Ordering;
 
 use crate::context::Context;
@@ -37,20 +46,33 @@ pub struct RFDUpdater {
     actions: Vec<Box<dyn RFDUpdateAction + Send + Sync>>,
 }
 
+/// The actions that run when a company does not configure any, in order.
+fn default_actions() -> Vec<RFDActionConfig> {
+    vec![
+        RFDActionKind::EnforceRFDStateTransition,
+        RFDActionKind::CopyImagesToGCP,
+        RFDActionKind::UpdateSearch,
+        RFDActionKind::UpdatePDFs,
+        RFDActionKind::GenerateShortUrls,
+        RFDActionKind::CreatePullRequest,
+        RFDActionKind::UpdatePullRequest,
+        RFDActionKind::ParseRFDLabels,
+        RFDActionKind::UpdateReferences,
+        RFDActionKind::UpdateDiscussionUrl,                    // Stops on error
+        RFDActionKind::EnsureRFDWithPullRequestIsInValidState, // Stops on error
+        RFDActionKind::EnsureRFDOnDefaultIsInValidState,       // Stops on error
+    ]
+    .into_iter()
+    .map(|action| RFDActionConfig {
+        action,
+        stop_on_error: None,
+    })
+    .collect()
+}
+
 impl Default for RFDUpdater {
     fn default() -> Self {
-        Self::new(vec![
-            Box::new(CopyImagesToGCP),
-            Box::new(UpdateSearch),
-            Box::new(UpdatePDFs),
-            Box::new(GenerateShortUrls),
-            Box::new(CreatePullRequest),
-            Box::new(UpdatePullRequest),
-            Box::new(ParseRFDLabels),
-            Box::new(UpdateDiscussionUrl),                    // Stops on error
-            Box::new(EnsureRFDWithPullRequestIsInValidState), // Stops on error
-            Box::new(EnsureRFDOnDefaultIsInValidState),       // Stops on error
-        ])
+        Self::from_config(&default_actions())
     }
 }
 
@@ -59,6 +81,46 @@ impl RFDUpdater {
         Self { actions }
     }
 
+    /// Build an updater from a configured list of actions. Falls back to the default actions when
+    /// the list is empty.
+    pub fn from_config(config: &[RFDActionConfig]) -> Self {
+        Self::from_config_without(config, &[])
+    }
+
+    /// Like `from_config`, but leaves out the actions in `skip`.
+    pub fn from_config_without(config: &[RFDActionConfig], skip: &[RFDActionKind]) -> Self {
+        let config = if config.is_empty() {
+            default_actions()
+        } else {
+            config.to_vec()
+        };
+
+        Self::new(
+            config
+                .iter()
+                .filter(|config| !skip.contains(&config.action))
+                .map(|config| -> Box<dyn RFDUpdateAction + Send + Sync> {
+                    let action = action_from_kind(&config.action);
+
+                    match config.stop_on_error {
+                        Some(stop_on_error) => Box::new(WithErrorHandling { action, stop_on_error }),
+                        None => action,
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// The updater configured for the company of the context.
+    pub fn for_context(api_context: &Context) -> Self {
+        Self::for_context_without(api_context, &[])
+    }
+
+    /// The updater configured for the company of the context, without the actions in `skip`.
+    pub fn for_context_without(api_context: &Context, skip: &[RFDActionKind]) -> Self {
+        Self::from_config_without(&api_context.app_config.read().unwrap().rfd.actions, skip)
+    }
+
     /// Handle a `push` event for the rfd repo.
     pub async fn handle(&self, api_context: &Context, updates: &[GitHubRFDUpdate]) -> Result<()> {
         // Loop through the updates that were provided and process them individually. We also throw
@@ -237,6 +299,54 @@ pub enum RFDUpdateActionErr {
     Stop(anyhow::Error),
 }
 
+fn action_from_kind(kind: &RFDActionKind) -> Box<dyn RFDUpdateAction + Send + Sync> {
+    match kind {
+        RFDActionKind::EnforceRFDStateTransition => Box::new(EnforceRFDStateTransition),
+        RFDActionKind::CopyImagesToGCP => Box::new(CopyImagesToGCP),
+        RFDActionKind::UpdateSearch => Box::new(UpdateSearch),
+        RFDActionKind::UpdatePDFs => Box::new(UpdatePDFs),
+        RFDActionKind::GenerateShortUrls => Box::new(GenerateShortUrls),
+        RFDActionKind::CreatePullRequest => Box::new(CreatePullRequest),
+        RFDActionKind::UpdatePullRequest => Box::new(UpdatePullRequest),
+        RFDActionKind::ParseRFDLabels => Box::new(ParseRFDLabels),
+        RFDActionKind::ApplyRFDLabels => Box::new(ApplyRFDLabels),
+        RFDActionKind::UpdateReferences => Box::new(UpdateReferences),
+        RFDActionKind::UpdateDiscussionUrl => Box::new(UpdateDiscussionUrl),
+        RFDActionKind::EnsureRFDWithPullRequestIsInValidState => Box::new(EnsureRFDWithPullRequestIsInValidState),
+        RFDActionKind::EnsureRFDOnDefaultIsInValidState => Box::new(EnsureRFDOnDefaultIsInValidState),
+        RFDActionKind::NotifySlack { channel } => Box::new(NotifySlack {
+            channel: channel.to_string(),
+        }),
+    }
+}
+
+/// Overrides whether the errors of the wrapped action stop the update.
+struct WithErrorHandling {
+    action: Box<dyn RFDUpdateAction + Send + Sync>,
+    stop_on_error: bool,
+}
+
+#[async_trait]
+impl RFDUpdateAction for WithErrorHandling {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        self.action.run(ctx, rfd).await.map_err(|err| {
+            let err = match err {
+                RFDUpdateActionErr::Continue(err) | RFDUpdateActionErr::Stop(err) => err,
+            };
+
+            if self.stop_on_error {
+                RFDUpdateActionErr::Stop(err)
+            } else {
+                RFDUpdateActionErr::Continue(err)
+            }
+        })
+    }
+}
+
 pub struct CopyImagesToGCP;
 
 #[async_trait]
@@ -307,9 +417,14 @@ impl RFDUpdateAction for UpdateSearch {
     ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
         let RFDUpdateActionContext { update, .. } = ctx;
         let client = RFDSearchIndex::default_client().map_err(into_continue)?;
-        RFDSearchIndex::index_rfd(&client, "rfd".to_string(), &rfd.number.into(), &rfd.content)
-            .await
-            .map_err(into_continue)?;
+        RFDSearchIndex::index_rfd(
+            &client,
+            RFDSearchIndex::LIVE_INDEX.to_string(),
+            &rfd.number.into(),
+            &rfd.content,
+        )
+        .await
+        .map_err(into_continue)?;
         info!("Updated search index with RFD {}", update.number);
 
         Ok(RFDUpdateActionResponse::default())
@@ -340,18 +455,21 @@ impl UpdatePDFs {
             }
         };
 
-        // Upload the generate PDF
-        let upload = pdf.upload(&api_context.db, &api_context.company).await?;
+        // Upload the generate PDF to each of the company's storage locations
+        let targets = api_context.app_config.read().unwrap().rfd.pdf_storage_targets();
+        let upload = pdf.upload(&api_context.db, &api_context.company, &targets).await?;
 
         // Store the PDF urls as needed to the RFD record
-        if let Some(github_url) = upload.github_url {
-            rfd.pdf_link_github.replace_range(.., &github_url);
+        if let Some(github_url) = upload.urls.get(PDFStorageTarget::GitHub.name()) {
+            rfd.pdf_link_github.replace_range(.., github_url);
         }
 
-        if let Some(google_drive_url) = upload.google_drive_url {
-            rfd.pdf_link_google_drive.replace_range(.., &google_drive_url);
+        if let Some(google_drive_url) = upload.urls.get(PDFStorageTarget::GoogleDrive.name()) {
+            rfd.pdf_link_google_drive.replace_range(.., google_drive_url);
         }
 
+        rfd.pdf_links.extend(upload);
+
         Ok(())
     }
 
@@ -527,7 +645,9 @@ impl RFDUpdateAction for CreatePullRequest {
         // handling an update on the default branch, and there are no previous pull requests for
         // for this branch. This includes Closed pull requests, therefore this action will not
         // re-open or create a new pull request for a branch that previously had an open PR
-        if update.branch.branch != update.branch.default_branch && rfd.state == "discussion" && pull_requests.is_empty()
+        if update.branch.branch != update.branch.default_branch
+            && rfd.parsed_state() == Some(RFDState::Discussion)
+            && pull_requests.is_empty()
         {
             let pull = github
                 .pulls()
@@ -733,6 +853,277 @@ impl RFDUpdateAction for UpdateDiscussionUrl {
     }
 }
 
+/// Reject state changes that are not allowed by [`RFDState::can_transition_to`] and record the
+/// ones that are. A rejected change keeps the previous state in the database, both in the state and
+/// in the stored content, and fails a check run on the pushed commit.
+pub struct EnforceRFDStateTransition;
+
+#[async_trait]
+impl RFDUpdateAction for EnforceRFDStateTransition {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext {
+            api_context,
+            github,
+            update,
+            old_rfd,
+            ..
+        } = ctx;
+
+        let from = old_rfd.and_then(|old| old.parsed_state());
+
+        let result = match rfd.parsed_state() {
+            Some(to) if from == Some(to) => return Ok(RFDUpdateActionResponse::default()),
+            Some(to) => check_transition(from, to).map(|_| to).map_err(|err| err.to_string()),
+            None => Err(format!("`{}` is not a valid RFD state", rfd.state)),
+        };
+
+        match result {
+            Ok(to) => {
+                let actor = update
+                    .commit
+                    .as_ref()
+                    .map(|commit| commit.actor.to_string())
+                    .unwrap_or_default();
+
+                record_state_change(api_context, update, rfd, from, to, actor)
+                    .await
+                    .map_err(into_continue)?;
+            }
+            Err(message) => {
+                warn!(
+                    "Rejecting state change of RFD {} on branch {}: {}",
+                    rfd.number, update.branch.branch, message
+                );
+
+                // Keep the last accepted state until the document is fixed
+                if let Some(old_rfd) = old_rfd {
+                    rfd.restore_state(old_rfd).map_err(into_continue)?;
+                }
+
+                if let Some(commit) = &update.commit {
+                    fail_state_check_run(github, update, &commit.sha, rfd, &message)
+                        .await
+                        .map_err(into_continue)?;
+                }
+            }
+        }
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
+/// Record a change of the state of an RFD in `rfd_state_changes`, attributed to `actor`.
+async fn record_state_change(
+    api_context: &Context,
+    update: &GitHubRFDUpdate,
+    rfd: &RFD,
+    from: Option<RFDState>,
+    to: RFDState,
+    actor: String,
+) -> Result<()> {
+    let commit_sha = match &update.commit {
+        Some(commit) => commit.sha.to_string(),
+        None => rfd.sha.to_string(),
+    };
+
+    NewRFDStateChange {
+        cio_company_id: api_context.company.id,
+        rfd_number: rfd.number,
+        from_state: from,
+        to_state: to,
+        branch: update.branch.branch.to_string(),
+        commit_sha,
+        actor,
+    }
+    .create(&api_context.db)
+    .await?;
+
+    info!(
+        "RFD {} moved from state {:?} to {} on branch {}",
+        rfd.number, from, to, update.branch.branch
+    );
+
+    Ok(())
+}
+
+async fn fail_state_check_run(
+    github: &octorust::Client,
+    update: &GitHubRFDUpdate,
+    sha: &str,
+    rfd: &RFD,
+    message: &str,
+) -> Result<()> {
+    let name = "CIO bot: RFD state".to_string();
+
+    let check = github
+        .checks()
+        .create(
+            &update.branch.owner,
+            &update.branch.repo,
+            &octorust::types::ChecksCreateRequest {
+                actions: vec![],
+                completed_at: None,
+                conclusion: None,
+                details_url: "".to_string(),
+                external_id: "".to_string(),
+                head_sha: sha.to_string(),
+                name: name.to_string(),
+                output: None,
+                started_at: Some(Utc::now()),
+                status: Some(octorust::types::JobStatus::InProgress),
+            },
+        )
+        .await?
+        .body;
+
+    github
+        .checks()
+        .update(
+            &update.branch.owner,
+            &update.branch.repo,
+            check.id,
+            &octorust::types::ChecksUpdateRequest {
+                actions: vec![],
+                completed_at: Some(Utc::now()),
+                conclusion: Some(octorust::types::ChecksCreateRequestConclusion::Failure),
+                details_url: "".to_string(),
+                external_id: "".to_string(),
+                name: name.to_string(),
+                output: Some(octorust::types::ChecksUpdateRequestOutput {
+                    annotations: vec![],
+                    images: vec![],
+                    summary: format!(
+                        "The state change of RFD {} was rejected: {}. The RFD stays in the `{}` state until \
+                         the document is updated.",
+                        rfd.number, message, rfd.state
+                    ),
+                    text: String::new(),
+                    title: format!("RFD {} has an invalid state change", rfd.number),
+                }),
+                started_at: None,
+                status: Some(octorust::types::JobStatus::Completed),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Lint the header block of the RFD of an update and report the violations as annotations on a
+/// check run for the pushed commit. Updates without a pushed commit are not linted.
+pub async fn run_rfd_lint_check(api_context: &Context, update: &GitHubRFDUpdate) -> Result<()> {
+    let commit = match &update.commit {
+        Some(commit) => commit,
+        None => return Ok(()),
+    };
+
+    let github = update.client();
+    let readme = update.branch.get_readme_contents(&update.number).await?;
+
+    let users = Users::get_from_db(&api_context.db, api_context.company.id).await?;
+    let labels = match github
+        .issues()
+        .list_all_labels_for_repo(&update.branch.owner, &update.branch.repo)
+        .await
+    {
+        Ok(response) => response.body.into_iter().map(|label| label.name).collect(),
+        Err(err) => {
+            warn!(
+                "Failed to list the labels of the RFD repo, labels will not be linted: {}",
+                err
+            );
+            vec![]
+        }
+    };
+
+    let rules = RFDLintRules {
+        authors: users.0.iter().map(RFDKnownAuthor::from).collect(),
+        labels,
+    };
+
+    let violations = readme.content.lint(&update.number, &rules);
+
+    let failures = violations
+        .iter()
+        .filter(|violation| violation.level == RFDLintLevel::Failure)
+        .count();
+    let warnings = violations.len() - failures;
+
+    let conclusion = if failures > 0 {
+        octorust::types::ChecksCreateRequestConclusion::Failure
+    } else if warnings > 0 {
+        octorust::types::ChecksCreateRequestConclusion::Neutral
+    } else {
+        octorust::types::ChecksCreateRequestConclusion::Success
+    };
+
+    let path = readme.location.file.trim_start_matches('/').to_string();
+
+    // GitHub accepts at most 50 annotations per request
+    let annotations = violations
+        .iter()
+        .take(50)
+        .map(|violation| octorust::types::ChecksCreateRequestOutputAnnotations {
+            annotation_level: match violation.level {
+                RFDLintLevel::Warning => octorust::types::ChecksCreateRequestOutputAnnotationsLevel::Warning,
+                RFDLintLevel::Failure => octorust::types::ChecksCreateRequestOutputAnnotationsLevel::Failure,
+            },
+            end_column: 0,
+            end_line: violation.line as i64,
+            message: violation.message.to_string(),
+            path: path.to_string(),
+            raw_details: String::new(),
+            start_column: 0,
+            start_line: violation.line as i64,
+            title: violation.title.to_string(),
+        })
+        .collect();
+
+    github
+        .checks()
+        .create(
+            &update.branch.owner,
+            &update.branch.repo,
+            &octorust::types::ChecksCreateRequest {
+                actions: vec![],
+                completed_at: Some(Utc::now()),
+                conclusion: Some(conclusion),
+                details_url: "".to_string(),
+                external_id: "".to_string(),
+                head_sha: commit.sha.to_string(),
+                name: "CIO bot: RFD metadata".to_string(),
+                output: Some(octorust::types::ChecksCreateRequestOutput {
+                    annotations,
+                    images: vec![],
+                    summary: format!(
+                        "Found {} errors and {} warnings in the header of RFD {}",
+                        failures, warnings, update.number
+                    ),
+                    text: String::new(),
+                    title: if violations.is_empty() {
+                        format!("RFD {} metadata is valid", update.number)
+                    } else {
+                        format!("RFD {} metadata has problems", update.number)
+                    },
+                }),
+                started_at: None,
+                status: Some(octorust::types::JobStatus::Completed),
+            },
+        )
+        .await?;
+
+    info!(
+        "Linted RFD {} on branch {}: {} errors, {} warnings",
+        update.number, update.branch.branch, failures, warnings
+    );
+
+    Ok(())
+}
+
 pub struct EnsureRFDWithPullRequestIsInValidState;
 
 #[async_trait]
@@ -742,7 +1133,12 @@ impl RFDUpdateAction for EnsureRFDWithPullRequestIsInValidState {
         ctx: &mut RFDUpdateActionContext,
         rfd: &mut RFD,
     ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
-        let RFDUpdateActionContext { pull_requests, .. } = ctx;
+        let RFDUpdateActionContext {
+            api_context,
+            pull_requests,
+            update,
+            ..
+        } = ctx;
 
         let mut requires_source_commit = false;
 
@@ -769,14 +1165,28 @@ impl RFDUpdateAction for EnsureRFDWithPullRequestIsInValidState {
                 //                  initial thoughts on an idea
                 //   * abandoned  - A RFD may be in this state if it had previously been abandoned or is in
                 //                  the process of being abandoned
-                if rfd.state != "discussion"
-                    && rfd.state != "published"
-                    && rfd.state != "committed"
-                    && rfd.state != "ideation"
-                    && rfd.state != "abandoned"
+                if !rfd
+                    .parsed_state()
+                    .map(|state| state.allowed_with_pull_request())
+                    .unwrap_or(false)
                 {
-                    rfd.update_state("discussion").map_err(RFDUpdateActionErr::Stop)?;
+                    let from = rfd.parsed_state();
+                    rfd.update_state(RFDState::Discussion)
+                        .map_err(RFDUpdateActionErr::Stop)?;
                     requires_source_commit = true;
+
+                    // The state is forced rather than requested, so it is recorded as ours without
+                    // checking the transition.
+                    record_state_change(
+                        api_context,
+                        update,
+                        rfd,
+                        from,
+                        RFDState::Discussion,
+                        SYSTEM_ACTOR.to_string(),
+                    )
+                    .await
+                    .map_err(into_continue)?;
                 }
             }
             Ordering::Greater => {
@@ -808,9 +1218,10 @@ impl RFDUpdateAction for EnsureRFDOnDefaultIsInValidState {
         // If an RFD exists on the default branch then it should be in either the published or
         // abandoned state
         if update.branch.branch == update.branch.default_branch
-            && rfd.state != "published"
-            && rfd.state != "committed"
-            && rfd.state != "abandoned"
+            && !rfd
+                .parsed_state()
+                .map(|state| state.allowed_on_default_branch())
+                .unwrap_or(false)
         {
             log::warn!("RFD {} on the default branch is in an invalid state. It needs to be updated to either publisehd or abandoned", rfd.number);
         }
@@ -836,3 +1247,318 @@ impl RFDUpdateAction for ParseRFDLabels {
         Ok(RFDUpdateActionResponse::default())
     }
 }
+
+/// Stores the RFDs referenced by the content of the RFD and warns about references to RFDs that
+/// have been abandoned.
+pub struct UpdateReferences;
+
+#[async_trait]
+impl RFDUpdateAction for UpdateReferences {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext { api_context, .. } = ctx;
+
+        let targets = extract_references(&rfd.content().map_err(into_continue)?, rfd.number);
+
+        RFDReference::replace_for(&api_context.db, api_context.company.id, rfd.number, &targets)
+            .await
+            .map_err(into_continue)?;
+
+        let references = RFDReferences::for_rfd(&api_context.db, api_context.company.id, rfd.number)
+            .await
+            .map_err(into_continue)?;
+
+        for warning in references.warnings {
+            warn!("RFD {} has a problematic reference: {}", rfd.number, warning);
+        }
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
+/// Syncs the labels parsed from the RFD document by [`ParseRFDLabels`] to the open pull request of
+/// the RFD. Labels that were removed from the document are removed from the pull request, any other
+/// labels on the pull request are left alone.
+pub struct ApplyRFDLabels;
+
+impl ApplyRFDLabels {
+    /// The labels to add to the pull request, and the labels to remove from it.
+    fn changes(pull_request: &GitHubPullRequest, old_rfd: Option<&RFD>, rfd: &RFD) -> (Vec<String>, Vec<String>) {
+        let on_pull_request = |label: &String| pull_request.labels.iter().any(|existing| &existing.name == label);
+
+        let missing = rfd
+            .labels
+            .iter()
+            .filter(|label| !on_pull_request(label))
+            .cloned()
+            .collect::<Vec<String>>();
+
+        let removed = old_rfd
+            .map(|old_rfd| {
+                old_rfd
+                    .labels
+                    .iter()
+                    .filter(|label| !rfd.labels.contains(*label))
+                    .filter(|label| on_pull_request(label))
+                    .cloned()
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+
+        (missing, removed)
+    }
+}
+
+#[async_trait]
+impl RFDUpdateAction for ApplyRFDLabels {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext {
+            update,
+            pull_requests,
+            github,
+            old_rfd,
+            ..
+        } = ctx;
+
+        let open_prs = pull_requests
+            .iter()
+            .filter(|pr| pr.state == "open")
+            .collect::<Vec<&GitHubPullRequest>>();
+
+        // As with updating the pull request, labels are only applied when there is a single open
+        // pull request for the branch
+        if open_prs.len() != 1 {
+            return Ok(RFDUpdateActionResponse::default());
+        }
+
+        let pull_request = open_prs[0];
+        let (missing, removed) = Self::changes(pull_request, *old_rfd, rfd);
+
+        if !missing.is_empty() {
+            github
+                .issues()
+                .add_labels(
+                    &update.branch.owner,
+                    &update.branch.repo,
+                    pull_request.number,
+                    &octorust::types::IssuesAddLabelsRequestOneOf::StringVector(missing),
+                )
+                .await
+                .map_err(into_continue)?;
+        }
+
+        for label in removed {
+            github
+                .issues()
+                .remove_label(&update.branch.owner, &update.branch.repo, pull_request.number, &label)
+                .await
+                .map_err(into_continue)?;
+        }
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
+/// Posts to a Slack channel when an RFD changes state or a pull request is opened to discuss it.
+pub struct NotifySlack {
+    pub channel: String,
+}
+
+impl NotifySlack {
+    fn messages(&self, pull_requests: &[GitHubPullRequest], old_rfd: Option<&RFD>, rfd: &RFD) -> Vec<String> {
+        let mut messages = vec![];
+        let old_state = old_rfd.map(|old_rfd| old_rfd.state.as_str()).unwrap_or_default();
+
+        if old_state != rfd.state {
+            messages.push(if old_state.is_empty() {
+                format!(
+                    "RFD {} <{}|{}> was created in `{}`",
+                    rfd.number, rfd.link, rfd.title, rfd.state
+                )
+            } else {
+                format!(
+                    "RFD {} <{}|{}> moved from `{}` to `{}`",
+                    rfd.number, rfd.link, rfd.title, old_state, rfd.state
+                )
+            });
+        }
+
+        // A discussion pull request is new if it is not yet stored as the discussion link of the RFD
+        let open_prs = pull_requests.iter().filter(|pr| pr.state == "open").collect::<Vec<_>>();
+        if let [pull_request] = open_prs.as_slice() {
+            let known = old_rfd
+                .map(|old_rfd| old_rfd.discussion == pull_request.html_url)
+                .unwrap_or(false);
+
+            if rfd.parsed_state() == Some(RFDState::Discussion) && !known {
+                messages.push(format!(
+                    "RFD {} <{}|{}> is open for discussion in <{}|#{}>",
+                    rfd.number, rfd.link, rfd.title, pull_request.html_url, pull_request.number
+                ));
+            }
+        }
+
+        messages
+    }
+}
+
+#[async_trait]
+impl RFDUpdateAction for NotifySlack {
+    async fn run(
+        &self,
+        ctx: &mut RFDUpdateActionContext,
+        rfd: &mut RFD,
+    ) -> Result<RFDUpdateActionResponse, RFDUpdateActionErr> {
+        let RFDUpdateActionContext {
+            api_context,
+            pull_requests,
+            old_rfd,
+            ..
+        } = ctx;
+
+        for text in self.messages(pull_requests, *old_rfd, rfd) {
+            let mut msg = FormattedMessage::new().block(SectionBlock::markdown(text));
+            msg.channel = self.channel.to_string();
+
+            api_context
+                .company
+                .post_to_slack_channel(&api_context.db, &msg)
+                .await
+                .map_err(into_continue)?;
+        }
+
+        Ok(RFDUpdateActionResponse::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn mock_rfd(state: &str, labels: &[&str]) -> RFD {
+        serde_json::from_value(json!({
+            "number": 123,
+            "name": "RFD 123 Widgets",
+            "title": "Widgets",
+            "state": state,
+            "link": "https://github.com/company/rfd/tree/0123/rfd/0123",
+            "labels": labels,
+        }))
+        .unwrap()
+    }
+
+    fn mock_pull_request(labels: &[&str]) -> GitHubPullRequest {
+        serde_json::from_value(json!({
+            "number": 7,
+            "state": "open",
+            "html_url": "https://github.com/company/rfd/pull/7",
+            "labels": labels.iter().map(|label| json!({ "name": label })).collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_updater_from_config() {
+        assert_eq!(
+            RFDUpdater::from_config(&[]).actions.len(),
+            RFDUpdater::default().actions.len()
+        );
+
+        let updater = RFDUpdater::from_config(&[
+            RFDActionConfig {
+                action: RFDActionKind::ParseRFDLabels,
+                stop_on_error: None,
+            },
+            RFDActionConfig {
+                action: RFDActionKind::NotifySlack {
+                    channel: "#rfd".to_string(),
+                },
+                stop_on_error: Some(true),
+            },
+        ]);
+        assert_eq!(updater.actions.len(), 2);
+    }
+
+    #[test]
+    fn test_updater_from_config_without() {
+        assert_eq!(
+            RFDUpdater::from_config_without(&[], &[RFDActionKind::GenerateShortUrls])
+                .actions
+                .len(),
+            RFDUpdater::default().actions.len() - 1
+        );
+
+        let config = [
+            RFDActionConfig {
+                action: RFDActionKind::GenerateShortUrls,
+                stop_on_error: None,
+            },
+            RFDActionConfig {
+                action: RFDActionKind::UpdateSearch,
+                stop_on_error: Some(true),
+            },
+        ];
+        let updater = RFDUpdater::from_config_without(&config, &[RFDActionKind::GenerateShortUrls]);
+        assert_eq!(updater.actions.len(), 1);
+    }
+
+    #[test]
+    fn test_apply_rfd_labels_changes() {
+        let pull_request = mock_pull_request(&["storage", "networking", "unrelated"]);
+        let old_rfd = mock_rfd("discussion", &["storage", "networking"]);
+        let rfd = mock_rfd("discussion", &["storage", "control plane"]);
+
+        assert_eq!(
+            ApplyRFDLabels::changes(&pull_request, Some(&old_rfd), &rfd),
+            (vec!["control plane".to_string()], vec!["networking".to_string()])
+        );
+
+        // Without a previous version there is nothing to remove, and labels that were only ever on
+        // the pull request are left alone.
+        assert_eq!(
+            ApplyRFDLabels::changes(&pull_request, None, &rfd),
+            (vec!["control plane".to_string()], vec![])
+        );
+    }
+
+    #[test]
+    fn test_notify_slack_messages() {
+        let notify = NotifySlack {
+            channel: "#rfd".to_string(),
+        };
+        let pull_requests = vec![mock_pull_request(&[])];
+
+        let created = mock_rfd("ideation", &[]);
+        assert_eq!(
+            notify.messages(&[], None, &created),
+            vec!["RFD 123 <https://github.com/company/rfd/tree/0123/rfd/0123|Widgets> was created in `ideation`"]
+        );
+
+        let discussion = mock_rfd("discussion", &[]);
+        assert_eq!(
+            notify.messages(&pull_requests, Some(&created), &discussion),
+            vec![
+                "RFD 123 <https://github.com/company/rfd/tree/0123/rfd/0123|Widgets> moved from `ideation` to \
+                 `discussion`",
+                "RFD 123 <https://github.com/company/rfd/tree/0123/rfd/0123|Widgets> is open for discussion in \
+                 <https://github.com/company/rfd/pull/7|#7>",
+            ]
+        );
+
+        // Nothing changed once the pull request is stored as the discussion link.
+        let mut discussed = discussion.clone();
+        discussed.discussion = "https://github.com/company/rfd/pull/7".to_string();
+        assert!(notify
+            .messages(&pull_requests, Some(&discussed), &discussion)
+            .is_empty());
+    }
+}
@@ -1,10 +1,16 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use async_bb8_diesel::AsyncRunQueryDsl;
 use chrono::{DateTime, Utc};
-use cio_api::{applicants::Applicant, schema::applicants};
+use cio_api::{
+    applicant_pipeline::{stage_metrics, ApplicantStageChange, MovedBy, NewApplicantStageChange, StageMetrics},
+    applicant_retention::{delete_applicant_data, export_applicant_data, ApplicantDataExport},
+    applicant_rubric::{reviewer_calibration, reviewer_leaderboard, ReviewerCalibration},
+    applicants::{Applicant, NewApplicantReviewer},
+    schema::applicants,
+};
 use diesel::{ExpressionMethods, QueryDsl};
 use schemars::JsonSchema;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::context::Context;
 
@@ -55,3 +61,84 @@ pub async fn handle_applicant_upload_token(ctx: &Context, email: String) -> Resu
         token: token.token,
     })
 }
+
+/// The stage changes of an applicant, oldest first.
+pub async fn handle_applicant_stages(ctx: &Context, applicant_id: i32) -> Result<Vec<ApplicantStageChange>> {
+    ApplicantStageChange::history(&ctx.db, ctx.company.id, applicant_id).await
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ApplicantStageRequest {
+    /// The stage to move the applicant to.
+    pub stage: String,
+}
+
+/// Move an applicant to another stage of the pipeline for their role, returning their stage
+/// changes. Fails with a [`cio_api::applicant_pipeline::PipelineError`] if the pipeline does not
+/// allow the move, or if `moved_by` may not advance applicants to the stage. The stage change is
+/// recorded with the actor of the current audit scope, which is expected to be `moved_by`.
+pub async fn handle_applicant_stage_move(
+    ctx: &Context,
+    applicant_id: i32,
+    request: ApplicantStageRequest,
+    moved_by: MovedBy<'_>,
+) -> Result<Vec<ApplicantStageChange>> {
+    let mut applicant = Applicant::get_by_id(&ctx.db, applicant_id).await?;
+    if applicant.cio_company_id != ctx.company.id {
+        bail!("applicant {} does not exist", applicant_id);
+    }
+
+    let pipeline = ctx.app_config.read().unwrap().hiring.pipeline(&applicant.role);
+    let stage = pipeline
+        .check_transition(&applicant, &applicant.status, &request.stage, moved_by)?
+        .name
+        .to_string();
+
+    let previous_status = std::mem::replace(&mut applicant.status, stage);
+    applicant.update(&ctx.db).await?;
+    NewApplicantStageChange::record(&ctx.db, &applicant, &previous_status).await?;
+
+    ApplicantStageChange::history(&ctx.db, ctx.company.id, applicant_id).await
+}
+
+/// How long applicants for a role spend in each stage of its pipeline.
+pub async fn handle_applicant_stage_metrics(ctx: &Context, role: &str) -> Result<Vec<StageMetrics>> {
+    let pipeline = ctx.app_config.read().unwrap().hiring.pipeline(role);
+    let changes = ApplicantStageChange::for_role(&ctx.db, ctx.company.id, role).await?;
+
+    Ok(stage_metrics(&pipeline, &changes, Utc::now()))
+}
+
+/// How every reviewer's evaluations compare to the other reviewers and to the outcomes.
+pub async fn handle_applicant_review_calibration(ctx: &Context) -> Result<Vec<ReviewerCalibration>> {
+    let rubric = ctx.app_config.read().unwrap().hiring.rubric.clone();
+
+    reviewer_calibration(&ctx.db, &ctx.company, &rubric).await
+}
+
+/// The reviewer leaderboard, counted from the reviews in the database.
+pub async fn handle_applicant_review_leaderboard(ctx: &Context) -> Result<Vec<NewApplicantReviewer>> {
+    reviewer_leaderboard(&ctx.db, &ctx.company).await
+}
+
+/// Everything we keep about the applicant with this email.
+pub async fn handle_applicant_data_export(ctx: &Context, email: &str) -> Result<ApplicantDataExport> {
+    export_applicant_data(&ctx.db, &ctx.company, email).await
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ApplicantDataDeletion {
+    email: String,
+    /// How many applications were deleted.
+    applications: usize,
+}
+
+/// Delete everything we keep about the applicant with this email.
+pub async fn handle_applicant_data_delete(ctx: &Context, email: &str) -> Result<ApplicantDataDeletion> {
+    let applications = delete_applicant_data(&ctx.db, &ctx.company, email).await?;
+
+    Ok(ApplicantDataDeletion {
+        email: email.to_string(),
+        applications,
+    })
+}
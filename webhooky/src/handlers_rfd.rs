@@ -1,7 +1,12 @@
 use anyhow::Result;
 use async_bb8_diesel::AsyncRunQueryDsl;
 use cio_api::{
-    rfd::{GitHubRFDRepo, NewRFD, RFDEntry, RFDIndexEntry, RFDs, RFD},
+    app_config::RFDActionKind,
+    audit::AuditScope,
+    rfd::{
+        reserve_next_rfd, GitHubRFDRepo, NewRFD, RFDEntry, RFDIndexEntry, RFDReferences, RFDReservation,
+        RFDSearchRequest, RFDSearchResults, RFDs, RFD,
+    },
     schema::rfds,
 };
 use diesel::{ExpressionMethods, QueryDsl};
@@ -9,14 +14,8 @@ use log::{info, warn};
 
 use crate::{
     context::Context,
-    handlers_github::{
-        rfd::{
-            CopyImagesToGCP, CreatePullRequest, EnsureRFDOnDefaultIsInValidState,
-            EnsureRFDWithPullRequestIsInValidState, GenerateShortUrls, ParseRFDLabels, UpdateDiscussionUrl, UpdatePDFs,
-            UpdatePullRequest, UpdateSearch,
-        },
-        RFDUpdater,
-    },
+    handlers_github::{rfd::GenerateShortUrls, RFDUpdater},
+    server::{RFDReserveRequest, RFDSearchParams},
 };
 
 pub async fn handle_rfd_index(ctx: &Context, offset: i32, limit: u32) -> Result<Vec<RFDIndexEntry>> {
@@ -62,6 +61,50 @@ pub async fn handle_rfd_view(ctx: &Context, num: i32) -> Result<Option<RFDEntry>
     }
 }
 
+pub async fn handle_rfd_references(ctx: &Context, num: i32) -> Result<Option<RFDReferences>> {
+    let exists = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(ctx.company.id))
+        .filter(rfds::dsl::number.eq(num))
+        .select(rfds::dsl::id)
+        .load_async::<i32>(ctx.db.pool())
+        .await?;
+
+    if exists.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(RFDReferences::for_rfd(&ctx.db, ctx.company.id, num).await?))
+}
+
+pub async fn handle_rfd_reserve(ctx: &Context, request: RFDReserveRequest) -> Result<RFDReservation> {
+    reserve_next_rfd(&ctx.db, &ctx.company, &request.title, &request.authors).await
+}
+
+pub async fn handle_rfd_search(
+    ctx: &Context,
+    params: RFDSearchParams,
+    viewer: Option<String>,
+) -> Result<RFDSearchResults> {
+    let request = RFDSearchRequest {
+        query: params.q,
+        states: params
+            .state
+            .map(|states| {
+                states
+                    .split(',')
+                    .map(|state| state.trim().to_lowercase())
+                    .filter(|state| !state.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default(),
+        viewer,
+        offset: params.offset.unwrap_or(0) as usize,
+        limit: params.limit.unwrap_or(20).min(100) as usize,
+    };
+
+    cio_api::rfd::search_rfds(&ctx.db, &ctx.company, &request).await
+}
+
 // Sync the rfds with our database.
 pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
     let repo = GitHubRFDRepo::new(&context.company).await?;
@@ -74,24 +117,15 @@ pub async fn refresh_db_rfds(context: &Context) -> Result<()> {
         let mut tasks: Vec<tokio::task::JoinHandle<Result<()>>> = vec![];
 
         for update in batch.into_iter() {
-            let task = tokio::spawn(enclose! { (context) async move {
-
-                let updater = RFDUpdater::new(vec![
-                    Box::new(CopyImagesToGCP),
-                    Box::new(UpdateSearch),
-                    Box::new(UpdatePDFs),
-                    Box::new(CreatePullRequest),
-                    Box::new(UpdatePullRequest),
-                    Box::new(ParseRFDLabels),
-                    Box::new(UpdateDiscussionUrl),
-                    Box::new(EnsureRFDWithPullRequestIsInValidState),
-                    Box::new(EnsureRFDOnDefaultIsInValidState),
-                ]);
+            let task = tokio::spawn(AuditScope::current().run(enclose! { (context) async move {
+
+                // Short urls are generated once for all RFDs below.
+                let updater = RFDUpdater::for_context_without(&context, &[RFDActionKind::GenerateShortUrls]);
 
                 updater.handle(&context, &[update]).await?;
 
                 Ok(())
-            }});
+            }}));
 
             tasks.push(task);
         }
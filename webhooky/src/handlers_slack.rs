@@ -1,5 +1,15 @@
 use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
 use async_trait::async_trait;
+use cio_api::{
+    app_config::RFDConfig,
+    audit::AuditScope,
+    companies::Company,
+    configs::User,
+    rfd::{extract_references, RFDContent, RFDState},
+    schema::{rfds, users},
+};
+use diesel::{ExpressionMethods, QueryDsl};
 use dropshot::{RequestContext, ServerContext, SharedExtractor, UntypedBody};
 use dropshot_verify_request::sig::HmacSignatureVerifier;
 use hmac::Hmac;
@@ -7,9 +17,10 @@ use log::{info, warn};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use sha2::Sha256;
+use slack_chat_api::{blocks::SectionBlock, Event, EventCallback, EventPayload, FormattedMessage, MessageEvent};
 use std::borrow::Cow;
 
-use crate::http::Headers;
+use crate::{context::Context, http::Headers};
 
 #[derive(Debug)]
 pub struct SlackWebhookVerification;
@@ -72,3 +83,383 @@ impl HmacSignatureVerifier for SlackWebhookVerification {
 pub struct InteractiveEvent {
     pub payload: String,
 }
+
+/// The header Slack sets when it delivers an event again because an earlier delivery was not
+/// acknowledged in time.
+const SLACK_RETRY_HEADER: &str = "x-slack-retry-num";
+
+/// Handle a request from the Slack Events API. Slack expects a response within three seconds, so
+/// events are acknowledged right away and handled in the background.
+pub async fn handle_slack_event(
+    rqctx: &RequestContext<crate::context::ServerContext>,
+    payload: EventPayload,
+) -> Result<serde_json::Value> {
+    let retry = rqctx.request.headers().get(SLACK_RETRY_HEADER).is_some();
+    let (response, callback) = respond(payload, retry);
+    let callback = match callback {
+        Some(callback) => callback,
+        None => return Ok(response),
+    };
+
+    let ctx = rqctx.context().app.clone();
+    let company = Company::get_from_slack_team_id(&ctx.db, &callback.team_id).await?;
+
+    let scope = AuditScope::current();
+    tokio::spawn(scope.run(async move {
+        let event_id = callback.event_id.to_string();
+        if let Err(err) = handle_event_callback(&ctx, &company, *callback).await {
+            warn!("failed to handle slack event {}: {}", event_id, err);
+        }
+    }));
+
+    Ok(response)
+}
+
+/// Split a request from the Events API into the response Slack expects and the event to handle, if
+/// there is one. Retried events are acknowledged but not handled again, as the first delivery was
+/// already handled in the background and handling it twice would post duplicate replies.
+fn respond(payload: EventPayload, retry: bool) -> (serde_json::Value, Option<Box<EventCallback>>) {
+    match payload {
+        EventPayload::UrlVerification { challenge, .. } => (json!({ "challenge": challenge }), None),
+        EventPayload::EventCallback(callback) if retry => {
+            info!("ignoring retried slack event {}", callback.event_id);
+            (json!({}), None)
+        }
+        EventPayload::EventCallback(callback) => (json!({}), Some(callback)),
+        EventPayload::Unknown => {
+            info!("ignoring unknown slack event payload");
+            (json!({}), None)
+        }
+    }
+}
+
+async fn handle_event_callback(ctx: &Context, company: &Company, callback: EventCallback) -> Result<()> {
+    // The bot user of the app, used to avoid replying to ourselves.
+    let bot_user = callback
+        .authorizations
+        .iter()
+        .find(|authorization| authorization.is_bot)
+        .map(|authorization| authorization.user_id.to_string())
+        .unwrap_or_default();
+
+    match callback.event {
+        Event::AppMention(message) => link_rfds(ctx, company, &message).await,
+        Event::Message(message) => {
+            if !is_posted_by_person(&message, &bot_user) {
+                return Ok(());
+            }
+
+            link_rfds(ctx, company, &message).await
+        }
+        Event::TeamJoin(join) => greet_new_member(ctx, company, &join.user).await,
+        Event::MemberJoinedChannel(join) => {
+            info!("slack user {} joined channel {}", join.user, join.channel);
+            Ok(())
+        }
+        Event::ReactionAdded(reaction) => {
+            info!(
+                "slack user {} reacted with :{}: in channel {}",
+                reaction.user, reaction.reaction, reaction.item.channel
+            );
+            Ok(())
+        }
+        Event::Unknown => Ok(()),
+    }
+}
+
+/// Whether a message was simply posted by a person, as opposed to edited, posted by a bot or
+/// mentioning the app. Mentions of the app arrive as both a message and an app mention, they are
+/// only handled as the latter.
+fn is_posted_by_person(message: &MessageEvent, bot_user: &str) -> bool {
+    message.subtype.is_empty()
+        && message.bot_id.is_empty()
+        && message.user != bot_user
+        && (bot_user.is_empty() || !message.text.contains(&format!("<@{}>", bot_user)))
+}
+
+/// Reply in a thread with links to the RFDs mentioned in a message. RFDs still in prediscussion
+/// are left out as they are not meant to be shared widely yet.
+async fn link_rfds(ctx: &Context, company: &Company, message: &MessageEvent) -> Result<()> {
+    let numbers = extract_references(&RFDContent::new_markdown(message.text.as_str()), 0);
+    if numbers.is_empty() {
+        return Ok(());
+    }
+
+    let rfds = rfds::dsl::rfds
+        .filter(rfds::dsl::cio_company_id.eq(company.id))
+        .filter(rfds::dsl::number.eq_any(numbers.into_iter().collect::<Vec<_>>()))
+        .filter(rfds::dsl::state.ne(RFDState::Prediscussion.as_str()))
+        .order_by(rfds::dsl::number)
+        .select((
+            rfds::dsl::number_string,
+            rfds::dsl::name,
+            rfds::dsl::state,
+            rfds::dsl::rendered_link,
+        ))
+        .load_async::<(String, String, String, String)>(ctx.db.pool())
+        .await?;
+
+    if rfds.is_empty() {
+        return Ok(());
+    }
+
+    let config = ctx.app_config.read().unwrap().rfd.clone();
+    let text = rfd_links(&config, rfds);
+
+    let slack = company.authenticate_slack(&ctx.db).await?;
+    slack
+        .post_reply(&section_message(&message.channel, text), reply_thread_ts(message))
+        .await?;
+
+    Ok(())
+}
+
+/// A list of links to RFDs, from their number string, name, state and stored rendered link.
+fn rfd_links(config: &RFDConfig, rfds: Vec<(String, String, String, String)>) -> String {
+    rfds.into_iter()
+        .map(|(number_string, name, state, rendered_link)| {
+            let link = config.rendered_url(&number_string).unwrap_or(rendered_link);
+            format!("• <{}|{}> ({})", link, name, state)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The thread to reply to a message in: the thread it is in, or a new one started from it.
+fn reply_thread_ts(message: &MessageEvent) -> &str {
+    if message.thread_ts.is_empty() {
+        &message.ts
+    } else {
+        &message.thread_ts
+    }
+}
+
+/// Send a direct message to a new member of the workspace with what we know about them.
+async fn greet_new_member(ctx: &Context, company: &Company, member: &slack_chat_api::User) -> Result<()> {
+    if member.is_bot || member.profile.email.is_empty() {
+        return Ok(());
+    }
+
+    let user = match users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(company.id))
+        .filter(users::dsl::email.eq(member.profile.email.to_lowercase()))
+        .first_async::<User>(ctx.db.pool())
+        .await
+    {
+        Ok(user) => user,
+        Err(_) => {
+            info!("no user record for new slack member {}, not greeting them", member.id);
+            return Ok(());
+        }
+    };
+
+    let manager = if user.manager.is_empty() {
+        None
+    } else {
+        User::get_from_db(&ctx.db, company.id, user.manager.to_string()).await
+    };
+    let text = welcome_text(company, &user, manager.as_ref());
+
+    let slack = company.authenticate_slack(&ctx.db).await?;
+    slack.post_message(&section_message(&member.id, text)).await?;
+
+    Ok(())
+}
+
+/// The greeting for a new member of the workspace.
+fn welcome_text(company: &Company, user: &User, manager: Option<&User>) -> String {
+    let mut text = format!("Welcome to {}, {}! :wave:", company.name, user.first_name);
+
+    if let Some(manager) = manager {
+        text.push_str(&format!(
+            "\n• Your manager is {} ({}), reach out to them with any questions.",
+            manager.full_name(),
+            manager.email
+        ));
+    }
+
+    if !user.groups.is_empty() {
+        text.push_str(&format!(
+            "\n• You are a member of the groups: {}.",
+            user.groups.join(", ")
+        ));
+    }
+
+    if user.github.is_empty() {
+        text.push_str(&format!(
+            "\n• We do not know your GitHub handle yet, please let your manager know it so you can be added to \
+             https://github.com/{}.",
+            company.github_org
+        ));
+    } else {
+        text.push_str(&format!(
+            "\n• Your GitHub account @{} has been invited to https://github.com/{}.",
+            user.github, company.github_org
+        ));
+    }
+
+    if !user.building.is_empty() {
+        text.push_str(&format!("\n• Your building is {}.", user.building));
+    }
+
+    text
+}
+
+fn section_message(channel: &str, text: String) -> FormattedMessage {
+    let mut msg = FormattedMessage::new().block(SectionBlock::markdown(text));
+    msg.channel = channel.to_string();
+    msg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mock_message(text: &str) -> MessageEvent {
+        MessageEvent {
+            user: "U061F7AUR".to_string(),
+            text: text.to_string(),
+            channel: "C123ABC456".to_string(),
+            ts: "1515449522.000016".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn mock_user(first_name: &str, last_name: &str) -> User {
+        serde_json::from_value(json!({
+            "first_name": first_name,
+            "last_name": last_name,
+            "username": first_name.to_lowercase(),
+            "email": format!("{}@example.com", first_name.to_lowercase()),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_respond() {
+        let payload = serde_json::from_value(json!({
+            "token": "Jhj5dZrVaK7ZwHHjRyZWjbDl",
+            "challenge": "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P",
+            "type": "url_verification"
+        }))
+        .unwrap();
+        let (response, callback) = respond(payload, false);
+        assert_eq!(
+            response,
+            json!({ "challenge": "3eZbrw1aBm2rZgRNFdxV2595E9CY3gmdALWMmHkvFXO7tYXAYM8P" })
+        );
+        assert!(callback.is_none());
+
+        let payload = json!({
+            "team_id": "T123ABC456",
+            "event": {"type": "app_mention", "user": "U061F7AUR", "text": "<@U0LAN0Z89> RFD 123", "channel": "C123ABC456"},
+            "type": "event_callback",
+            "event_id": "Ev123ABC456"
+        });
+        let (response, callback) = respond(serde_json::from_value(payload.clone()).unwrap(), false);
+        assert_eq!(response, json!({}));
+        assert_eq!(callback.unwrap().event_id, "Ev123ABC456");
+
+        // Retries of an event are acknowledged without handling it again.
+        let (response, callback) = respond(serde_json::from_value(payload).unwrap(), true);
+        assert_eq!(response, json!({}));
+        assert!(callback.is_none());
+
+        let payload = serde_json::from_value(json!({"type": "app_rate_limited", "team_id": "T123ABC456"})).unwrap();
+        let (response, callback) = respond(payload, false);
+        assert_eq!(response, json!({}));
+        assert!(callback.is_none());
+    }
+
+    #[test]
+    fn test_is_posted_by_person() {
+        assert!(is_posted_by_person(&mock_message("See RFD 123"), "U0LAN0Z89"));
+        assert!(is_posted_by_person(&mock_message("See RFD 123"), ""));
+
+        // Mentions of the app are handled as app mentions.
+        assert!(!is_posted_by_person(&mock_message("<@U0LAN0Z89> RFD 123"), "U0LAN0Z89"));
+
+        // Edits, bots and the app itself are ignored.
+        let mut edit = mock_message("See RFD 123");
+        edit.subtype = "message_changed".to_string();
+        assert!(!is_posted_by_person(&edit, "U0LAN0Z89"));
+
+        let mut bot = mock_message("See RFD 123");
+        bot.bot_id = "BB12033".to_string();
+        assert!(!is_posted_by_person(&bot, "U0LAN0Z89"));
+
+        let mut reply = mock_message("See RFD 123");
+        reply.user = "U0LAN0Z89".to_string();
+        assert!(!is_posted_by_person(&reply, "U0LAN0Z89"));
+    }
+
+    #[test]
+    fn test_rfd_links() {
+        let rfds = vec![
+            (
+                "0001".to_string(),
+                "Requests for Discussion".to_string(),
+                "published".to_string(),
+                "https://example.github.io/rfd/0001".to_string(),
+            ),
+            (
+                "0123".to_string(),
+                "Some Design".to_string(),
+                "discussion".to_string(),
+                "https://example.github.io/rfd/0123".to_string(),
+            ),
+        ];
+
+        assert_eq!(
+            rfd_links(&RFDConfig::default(), rfds.clone()),
+            "• <https://example.github.io/rfd/0001|Requests for Discussion> (published)\n• \
+             <https://example.github.io/rfd/0123|Some Design> (discussion)"
+        );
+
+        let config = RFDConfig {
+            site_url: Some("https://rfd.example.com/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            rfd_links(&config, rfds),
+            "• <https://rfd.example.com/rfd/0001|Requests for Discussion> (published)\n• \
+             <https://rfd.example.com/rfd/0123|Some Design> (discussion)"
+        );
+    }
+
+    #[test]
+    fn test_reply_thread_ts() {
+        let mut message = mock_message("See RFD 123");
+        assert_eq!(reply_thread_ts(&message), "1515449522.000016");
+
+        message.thread_ts = "1515449500.000001".to_string();
+        assert_eq!(reply_thread_ts(&message), "1515449500.000001");
+    }
+
+    #[test]
+    fn test_welcome_text() {
+        let company: Company = serde_json::from_value(json!({
+            "name": "Example Company",
+            "github_org": "example",
+        }))
+        .unwrap();
+
+        let mut user = mock_user("Jane", "Doe");
+        assert_eq!(
+            welcome_text(&company, &user, None),
+            "Welcome to Example Company, Jane! :wave:\n• We do not know your GitHub handle yet, please let your \
+             manager know it so you can be added to https://github.com/example."
+        );
+
+        user.groups = vec!["eng".to_string(), "all".to_string()];
+        user.github = "janedoe".to_string();
+        user.building = "HQ".to_string();
+        let manager = mock_user("John", "Smith");
+        assert_eq!(
+            welcome_text(&company, &user, Some(&manager)),
+            "Welcome to Example Company, Jane! :wave:\n• Your manager is John Smith (john@example.com), reach out to \
+             them with any questions.\n• You are a member of the groups: eng, all.\n• Your GitHub account @janedoe \
+             has been invited to https://github.com/example.\n• Your building is HQ."
+        );
+    }
+}
@@ -3,9 +3,40 @@ use anyhow::Result;
 
 pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Result<()> {
     match cmd {
-        crate::core::SubCommand::SendRFDChangelog(_) => {
+        crate::core::SubCommand::EnforceApplicantRetention(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+
+            let policy = app_config.read().unwrap().hiring.retention.clone();
+            let erased = cio_api::applicant_retention::enforce_applicant_retention(&db, &company, &policy).await?;
+            log::info!("enforced applicant retention on {} applicants", erased);
+        }
+        crate::core::SubCommand::ReencryptAPITokens(_) => {
             let Context { db, company, .. } = context;
-            cio_api::rfd::send_rfd_changelog(&db, &company).await?;
+            cio_api::api_tokens::reencrypt_api_tokens(&db, &company).await?;
+        }
+        crate::core::SubCommand::RefreshAPITokens(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::api_tokens::refresh_expiring_api_tokens(&db, &company).await?;
+        }
+        crate::core::SubCommand::ReindexRFDs(_) => {
+            let Context { db, company, .. } = context;
+            cio_api::rfd::reindex_rfds(&db, &company).await?;
+        }
+        crate::core::SubCommand::SendRFDChangelog(_) => {
+            let Context {
+                app_config,
+                db,
+                company,
+                ..
+            } = context;
+
+            let config = app_config.read().unwrap().rfd.clone();
+            cio_api::rfd::send_rfd_changelog(&db, &company, &config).await?;
         }
         crate::core::SubCommand::SyncAnalytics(_) => {
             let Context { db, company, .. } = context;
@@ -27,6 +58,10 @@ pub async fn run_job_cmd(cmd: crate::core::SubCommand, context: Context) -> Resu
             let app_config = app_config.read().unwrap().clone();
             cio_api::applicants::refresh_new_applicants_and_reviews(&db, &company, &app_config).await?;
             cio_api::applicant_reviews::refresh_reviews(&db, &company).await?;
+            cio_api::applicant_rubric::refresh_reviewer_leaderboard(&db, &company).await?;
+
+            // Look for applicants that applied more than once.
+            cio_api::applicant_dedupe::refresh_applicant_duplicates(&db, &company).await?;
 
             // Refresh DocuSign for the applicants.
             cio_api::applicants::refresh_docusign_for_applicants(&db, &company, &app_config).await?;
@@ -4,6 +4,7 @@ use std::sync::Mutex;
 
 use anyhow::Result;
 use cio_api::{
+    audit::AuditScope,
     db::Database,
     functions::{FnOutput, Function},
 };
@@ -75,6 +76,8 @@ pub struct Saga;
 pub struct Params {
     cmd_name: String,
     saga_id: uuid::Uuid,
+    /// Who triggered the saga, recorded as the actor of its audit events.
+    actor: String,
 }
 
 #[derive(Debug)]
@@ -103,12 +106,14 @@ pub async fn run_cmd(
     registry: Arc<steno::ActionRegistry<Saga>>,
     id: &uuid::Uuid,
     cmd_name: &str,
+    actor: &str,
 ) -> Result<()> {
     report_health(&format!("Run cmd [{}]", cmd_name));
 
     let params = Params {
         cmd_name: cmd_name.to_string(),
         saga_id: *id,
+        actor: actor.to_string(),
     };
 
     let mut builder = steno::DagBuilder::new(steno::SagaName::new(cmd_name));
@@ -150,6 +155,7 @@ async fn action_run_cmd(action_context: steno::ActionContext<Saga>) -> Result<Fn
     let db = &action_context.user_data().db;
     let cmd_name = &action_context.saga_params::<Params>()?.cmd_name;
     let saga_id = &action_context.saga_params::<Params>()?.saga_id;
+    let actor = &action_context.saga_params::<Params>()?.actor;
 
     report_health(&format!("Create job command [{}]", cmd_name));
 
@@ -165,7 +171,9 @@ async fn action_run_cmd(action_context: steno::ActionContext<Saga>) -> Result<Fn
 
         report_health(&format!("Await job [{}]", cmd_name));
 
-        let result = crate::job::run_job_cmd(sub_cmd, context).with_logger(logger).await;
+        let result = AuditScope::for_saga(&saga_id.to_string(), actor)
+            .run(crate::job::run_job_cmd(sub_cmd, context).with_logger(logger))
+            .await;
 
         match result {
             Ok(_) => {
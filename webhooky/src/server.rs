@@ -5,8 +5,13 @@ use anyhow::{anyhow, Result};
 use chrono::{DateTime, Utc};
 use cio_api::{
     analytics::NewPageView,
+    applicant_pipeline::{ApplicantStageChange, MovedBy, PipelineError, StageMetrics},
+    applicant_retention::ApplicantDataExport,
+    applicant_rubric::{ReviewerCalibration, RubricError},
+    applicants::NewApplicantReviewer,
+    audit::{AuditEvent, AuditQuery, AuditScope, SYSTEM_ACTOR},
     functions::Function,
-    rfd::{RFDEntry, RFDIndexEntry},
+    rfd::{RFDEntry, RFDIndexEntry, RFDReferences, RFDReservation, RFDSearchResults},
     swag_store::Order,
 };
 use clokwerk::{AsyncScheduler, Job, TimeUnits};
@@ -32,15 +37,18 @@ use signal_hook::{
     consts::{SIGINT, SIGTERM},
     iterator::Signals,
 };
-use slack_chat_api::{BotCommand, Slack};
+use slack_chat_api::{BotCommand, EventPayload, Slack};
 use std::any::Any;
 use zoom_api::Client as Zoom;
 
 use crate::{
-    auth::{AirtableToken, HiringToken, InternalToken, RFDToken, ShippoToken},
+    auth::{
+        AirtableToken, HiringToken, InternalToken, RFDToken, ShippoToken, AIRTABLE_ACTOR, HIRING_ACTOR, INTERNAL_ACTOR,
+        RFD_ACTOR,
+    },
     context::ServerContext,
     github_types::GitHubWebhook,
-    handlers_hiring::{ApplicantInfo, ApplicantUploadToken},
+    handlers_hiring::{ApplicantDataDeletion, ApplicantInfo, ApplicantStageRequest, ApplicantUploadToken},
     handlers_slack::InteractiveEvent,
 };
 
@@ -74,10 +82,15 @@ fn create_api() -> ApiDescription<ServerContext> {
      */
     api.register(ping).unwrap();
     api.register(github_rate_limit).unwrap();
+    api.register(list_audit_events).unwrap();
     api.register(listen_airtable_applicants_request_background_check_webhooks)
         .unwrap();
     api.register(listen_airtable_applicants_update_webhooks).unwrap();
     api.register(listen_airtable_applicants_recreate_piia_webhooks).unwrap();
+    api.register(listen_airtable_applicant_duplicates_merge_webhooks)
+        .unwrap();
+    api.register(listen_airtable_applicant_duplicates_dismiss_webhooks)
+        .unwrap();
     api.register(listen_airtable_assets_items_print_barcode_label_webhooks)
         .unwrap();
     api.register(listen_airtable_employees_print_home_address_label_webhooks)
@@ -108,6 +121,13 @@ fn create_api() -> ApiDescription<ServerContext> {
     api.register(listen_application_files_upload_requests).unwrap();
     api.register(listen_applicant_info).unwrap();
     api.register(listen_applicant_upload_token).unwrap();
+    api.register(listen_applicant_stages).unwrap();
+    api.register(listen_applicant_stage_move).unwrap();
+    api.register(listen_applicant_stage_metrics).unwrap();
+    api.register(listen_applicant_review_calibration).unwrap();
+    api.register(listen_applicant_review_leaderboard).unwrap();
+    api.register(listen_applicant_data_export).unwrap();
+    api.register(listen_applicant_data_delete).unwrap();
 
     api.register(listen_auth_docusign_callback).unwrap();
     api.register(listen_auth_docusign_consent).unwrap();
@@ -132,14 +152,22 @@ fn create_api() -> ApiDescription<ServerContext> {
     api.register(listen_shippo_tracking_update_webhooks).unwrap();
     api.register(listen_easypost_tracking_update_webhooks).unwrap();
     api.register(listen_slack_commands_webhooks).unwrap();
+    api.register(listen_slack_events_webhooks).unwrap();
     api.register(listen_slack_interactive_webhooks).unwrap();
     api.register(listen_shipbob_webhooks).unwrap();
     api.register(listen_store_order_create).unwrap();
     api.register(listen_rfd_index).unwrap();
     api.register(listen_rfd_view).unwrap();
+    api.register(listen_rfd_references).unwrap();
+    api.register(listen_rfd_search).unwrap();
+    api.register(listen_rfd_reserve).unwrap();
     api.register(trigger_rfd_update_by_number).unwrap();
     api.register(trigger_cleanup_create).unwrap();
 
+    api.register(trigger_enforce_applicant_retention_create).unwrap();
+    api.register(trigger_refresh_api_tokens_create).unwrap();
+    api.register(trigger_reindex_rfds_create).unwrap();
+    api.register(trigger_reencrypt_api_tokens_create).unwrap();
     api.register(trigger_sync_analytics_create).unwrap();
     api.register(trigger_sync_api_tokens_create).unwrap();
     api.register(trigger_sync_applications_create).unwrap();
@@ -234,6 +262,12 @@ pub async fn server(
         // scheduler
         //     .every(1.day())
         //     .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-analytics")});
+        scheduler.every(1.day()).run(
+            enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "enforce-applicant-retention")},
+        );
+        scheduler
+            .every(15.minutes())
+            .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "refresh-api-tokens")});
         scheduler
             .every(23.hours())
             .run(enclose! { (server_context) move || create_do_job_fn(server_context.clone(), "sync-api-tokens")});
@@ -354,7 +388,7 @@ pub fn create_do_job_fn(ctx: ServerContext, job: &str) -> Pin<Box<dyn std::futur
 pub async fn do_job(ctx: ServerContext, job: String) {
     info!("triggering cron job `{}`", job);
 
-    if let Err(err) = crate::handlers_cron::run_subcmd_job(&ctx, &job).await {
+    if let Err(err) = crate::handlers_cron::run_subcmd_job(&ctx, &job, SYSTEM_ACTOR).await {
         error!("Failed to spawn job: {:?}", err)
     }
 }
@@ -401,7 +435,10 @@ async fn listen_github_webhooks(
     rqctx: RequestContext<ServerContext>,
     body: HmacVerifiedBody<crate::handlers_github::GitHubWebhookVerification, GitHubWebhook>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_github::handle_github(&rqctx, body.into_inner()?)
+    let event = body.into_inner()?;
+
+    AuditScope::for_request(&format!("github:{}", event.sender.login), &rqctx.request_id)
+        .run(crate::handlers_github::handle_github(&rqctx, event))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -422,7 +459,8 @@ async fn trigger_rfd_update_by_number(
     _auth: Bearer<InternalToken>,
     path_params: Path<RFDPathParams>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_rfd_update_by_number(&rqctx, path_params)
+    AuditScope::for_request(INTERNAL_ACTOR, &rqctx.request_id)
+        .run(crate::handlers::handle_rfd_update_by_number(&rqctx, path_params))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -440,6 +478,22 @@ async fn github_rate_limit(rqctx: RequestContext<ServerContext>) -> Result<HttpR
         .map_err(handle_anyhow_err_as_http_err)
 }
 
+/** List the recorded side effects on external services, most recent first. */
+#[endpoint {
+    method = GET,
+    path = "/audit",
+}]
+async fn list_audit_events(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+    query_args: Query<AuditQuery>,
+) -> Result<HttpResponseOk<Vec<AuditEvent>>, HttpError> {
+    crate::handlers::handle_list_audit_events(&rqctx, query_args.into_inner())
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
 /// A GitHub RateLimit
 #[derive(Debug, Clone, Default, JsonSchema, Deserialize, Serialize)]
 pub struct GitHubRateLimit {
@@ -463,7 +517,12 @@ async fn listen_airtable_employees_print_home_address_label_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_employees_print_home_address_label(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_employees_print_home_address_label(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -481,7 +540,10 @@ async fn listen_airtable_certificates_renew_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_certificates_renew(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_certificates_renew(&rqctx, event))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -499,7 +561,12 @@ async fn listen_airtable_assets_items_print_barcode_label_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_assets_items_print_barcode_label(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_assets_items_print_barcode_label(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -517,7 +584,10 @@ async fn listen_airtable_swag_inventory_items_print_barcode_labels_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_swag_inventory_items_print_barcode_labels(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_swag_inventory_items_print_barcode_labels(&rqctx, event))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -535,7 +605,12 @@ async fn listen_airtable_applicants_request_background_check_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_applicants_request_background_check(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_applicants_request_background_check(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -554,7 +629,10 @@ async fn listen_airtable_applicants_update_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_applicants_update(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_applicants_update(&rqctx, event))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -573,7 +651,60 @@ async fn listen_airtable_applicants_recreate_piia_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::listen_airtable_applicants_recreate_piia_webhooks(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::listen_airtable_applicants_recreate_piia_webhooks(
+            &rqctx, event,
+        ))
+        .await
+        .map(accepted)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/**
+ * Listen for requests to merge a duplicate applicant into the applicant it duplicates.
+ * These are set up with an Airtable script on the workspaces themselves.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/applicants/duplicates/merge",
+}]
+async fn listen_airtable_applicant_duplicates_merge_webhooks(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_applicant_duplicates_merge(
+            &rqctx, event,
+        ))
+        .await
+        .map(accepted)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/**
+ * Listen for requests to dismiss a possible duplicate applicant.
+ * These are set up with an Airtable script on the workspaces themselves.
+ */
+#[endpoint {
+    method = POST,
+    path = "/airtable/applicants/duplicates/dismiss",
+}]
+async fn listen_airtable_applicant_duplicates_dismiss_webhooks(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<AirtableToken>,
+    body_param: TypedBody<AirtableRowEvent>,
+) -> Result<HttpResponseAccepted<String>, HttpError> {
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_applicant_duplicates_dismiss(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -592,7 +723,12 @@ async fn listen_airtable_shipments_outbound_create_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_shipments_outbound_create(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_shipments_outbound_create(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -605,6 +741,16 @@ pub struct AirtableRowEvent {
     pub record_id: String,
     #[serde(default)]
     pub cio_company_id: i32,
+    /// The email of the Airtable collaborator that triggered the event, as given by the Airtable
+    /// script sending it. Anyone with the Airtable token can set it, so it is not verified.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub user_email: String,
+}
+
+/// The audit scope for the side effects of an Airtable event. The actor is the Airtable script
+/// that holds the token, the collaborator it names is only recorded as a claim.
+fn airtable_scope(event: &AirtableRowEvent, request_id: &str) -> AuditScope {
+    AuditScope::for_request(AIRTABLE_ACTOR, request_id).claimed_by(&event.user_email)
 }
 
 /**
@@ -619,7 +765,12 @@ async fn listen_airtable_shipments_outbound_reprint_label_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_shipments_outbound_reprint_label(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_shipments_outbound_reprint_label(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -637,7 +788,12 @@ async fn listen_airtable_shipments_outbound_reprint_receipt_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_shipments_outbound_reprint_receipt(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_shipments_outbound_reprint_receipt(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -655,13 +811,17 @@ async fn listen_airtable_shipments_outbound_resend_shipment_status_email_to_reci
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_shipments_outbound_resend_shipment_status_email_to_recipient(
-        &rqctx,
-        body_param.into_inner(),
-    )
-    .await
-    .map(accepted)
-    .map_err(handle_anyhow_err_as_http_err)
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(
+            crate::handlers::handle_airtable_shipments_outbound_resend_shipment_status_email_to_recipient(
+                &rqctx, event,
+            ),
+        )
+        .await
+        .map(accepted)
+        .map_err(handle_anyhow_err_as_http_err)
 }
 
 /**
@@ -676,7 +836,12 @@ async fn listen_airtable_shipments_outbound_schedule_pickup_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_shipments_outbound_schedule_pickup(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_shipments_outbound_schedule_pickup(
+            &rqctx, event,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -734,10 +899,17 @@ async fn listen_applicant_review_requests(
     _auth: Bearer<InternalToken>,
     body_param: TypedBody<cio_api::applicant_reviews::NewApplicantReview>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_applicant_review(&rqctx, body_param.into_inner())
+    AuditScope::for_request(INTERNAL_ACTOR, &rqctx.request_id)
+        .run(crate::handlers::handle_applicant_review(
+            &rqctx,
+            body_param.into_inner(),
+        ))
         .await
         .map(accepted)
-        .map_err(handle_anyhow_err_as_http_err)
+        .map_err(|err| match err.downcast_ref::<RubricError>() {
+            Some(err) => HttpError::for_bad_request(None, err.to_string()),
+            None => handle_anyhow_err_as_http_err(err),
+        })
 }
 
 #[derive(Deserialize, JsonSchema)]
@@ -776,7 +948,168 @@ async fn listen_applicant_upload_token(
     path_params: Path<ApplicantInfoParams>,
 ) -> Result<HttpResponseOk<ApplicantUploadToken>, HttpError> {
     log::info!("Running applicant upload token handler");
-    crate::handlers_hiring::handle_applicant_upload_token(&rqctx.context().app, path_params.into_inner().email)
+    AuditScope::for_request(HIRING_ACTOR, &rqctx.request_id)
+        .run(crate::handlers_hiring::handle_applicant_upload_token(
+            &rqctx.context().app,
+            path_params.into_inner().email,
+        ))
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ApplicantStagesParams {
+    id: i32,
+}
+
+/// List the stage changes of an applicant, oldest first
+#[endpoint {
+    method = GET,
+    path = "/hiring/applicants/{id}/stages",
+}]
+async fn listen_applicant_stages(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+    path_params: Path<ApplicantStagesParams>,
+) -> Result<HttpResponseOk<Vec<ApplicantStageChange>>, HttpError> {
+    crate::handlers_hiring::handle_applicant_stages(&rqctx.context().app, path_params.into_inner().id)
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/// Move an applicant to another stage of the pipeline for their role
+#[endpoint {
+    method = POST,
+    path = "/hiring/applicants/{id}/stages",
+}]
+async fn listen_applicant_stage_move(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+    path_params: Path<ApplicantStagesParams>,
+    body_param: TypedBody<ApplicantStageRequest>,
+) -> Result<HttpResponseOk<Vec<ApplicantStageChange>>, HttpError> {
+    // The person making the move is only known when their Google token comes with the request,
+    // anything else holding the internal token is automation.
+    let viewer = crate::auth::viewer_email(rqctx.request.headers(), &rqctx.context().app.company.gsuite_domain)
+        .await
+        .map_err(|err| {
+            warn!("Failed to verify the viewer of an applicant stage move: {}", err);
+            HttpError::for_status(None, http::StatusCode::UNAUTHORIZED)
+        })?;
+    let moved_by = match &viewer {
+        Some(email) => MovedBy::Person(email),
+        None => MovedBy::Internal,
+    };
+
+    AuditScope::for_request(viewer.as_deref().unwrap_or(SYSTEM_ACTOR), &rqctx.request_id)
+        .run(crate::handlers_hiring::handle_applicant_stage_move(
+            &rqctx.context().app,
+            path_params.into_inner().id,
+            body_param.into_inner(),
+            moved_by,
+        ))
+        .await
+        .map(HttpResponseOk)
+        .map_err(|err| match err.downcast_ref::<PipelineError>() {
+            Some(err) => HttpError::for_bad_request(None, err.to_string()),
+            None => handle_anyhow_err_as_http_err(err),
+        })
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct StageMetricsParams {
+    /// The role to compute the metrics for.
+    pub role: String,
+}
+
+/// Get how long applicants for a role spend in each stage of its pipeline
+#[endpoint {
+    method = GET,
+    path = "/hiring/stages/metrics",
+}]
+async fn listen_applicant_stage_metrics(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+    query: Query<StageMetricsParams>,
+) -> Result<HttpResponseOk<Vec<StageMetrics>>, HttpError> {
+    crate::handlers_hiring::handle_applicant_stage_metrics(&rqctx.context().app, &query.into_inner().role)
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/// Get how every reviewer's evaluations compare to the other reviewers and to the outcomes
+#[endpoint {
+    method = GET,
+    path = "/hiring/reviews/calibration",
+}]
+async fn listen_applicant_review_calibration(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseOk<Vec<ReviewerCalibration>>, HttpError> {
+    crate::handlers_hiring::handle_applicant_review_calibration(&rqctx.context().app)
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/// Get the reviewer leaderboard, counted from the reviews
+#[endpoint {
+    method = GET,
+    path = "/hiring/reviews/leaderboard",
+}]
+async fn listen_applicant_review_leaderboard(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseOk<Vec<NewApplicantReviewer>>, HttpError> {
+    crate::handlers_hiring::handle_applicant_review_leaderboard(&rqctx.context().app)
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct ApplicantDataParams {
+    email: String,
+}
+
+/// Export everything we keep about the applicant with this email
+#[endpoint {
+    method = GET,
+    path = "/hiring/privacy/{email}",
+}]
+async fn listen_applicant_data_export(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+    path_params: Path<ApplicantDataParams>,
+) -> Result<HttpResponseOk<ApplicantDataExport>, HttpError> {
+    AuditScope::for_request(INTERNAL_ACTOR, &rqctx.request_id)
+        .run(crate::handlers_hiring::handle_applicant_data_export(
+            &rqctx.context().app,
+            &path_params.into_inner().email,
+        ))
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/// Delete everything we keep about the applicant with this email
+#[endpoint {
+    method = DELETE,
+    path = "/hiring/privacy/{email}",
+}]
+async fn listen_applicant_data_delete(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+    path_params: Path<ApplicantDataParams>,
+) -> Result<HttpResponseOk<ApplicantDataDeletion>, HttpError> {
+    AuditScope::for_request(INTERNAL_ACTOR, &rqctx.request_id)
+        .run(crate::handlers_hiring::handle_applicant_data_delete(
+            &rqctx.context().app,
+            &path_params.into_inner().email,
+        ))
         .await
         .map(HttpResponseOk)
         .map_err(handle_anyhow_err_as_http_err)
@@ -793,7 +1126,11 @@ async fn listen_test_application_submit_requests(
     _auth: Bearer<HiringToken>,
     body_param: TypedBody<cio_api::application_form::ApplicationForm>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_test_application_submit(&rqctx, body_param.into_inner())
+    AuditScope::for_request(HIRING_ACTOR, &rqctx.request_id)
+        .run(crate::handlers::handle_test_application_submit(
+            &rqctx,
+            body_param.into_inner(),
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -810,7 +1147,11 @@ async fn listen_application_submit_requests(
     _auth: Bearer<HiringToken>,
     body_param: TypedBody<cio_api::application_form::ApplicationForm>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_application_submit(&rqctx, body_param.into_inner())
+    AuditScope::for_request(HIRING_ACTOR, &rqctx.request_id)
+        .run(crate::handlers::handle_application_submit(
+            &rqctx,
+            body_param.into_inner(),
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1035,7 +1376,10 @@ async fn listen_airtable_shipments_inbound_create_webhooks(
     _auth: Bearer<AirtableToken>,
     body_param: TypedBody<AirtableRowEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_airtable_shipments_inbound_create(&rqctx, body_param.into_inner())
+    let event = body_param.into_inner();
+
+    airtable_scope(&event, &rqctx.request_id)
+        .run(crate::handlers::handle_airtable_shipments_inbound_create(&rqctx, event))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1053,7 +1397,11 @@ async fn listen_store_order_create(
     _auth: Bearer<InternalToken>,
     body_param: TypedBody<Order>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_store_order_create(&rqctx, body_param.into_inner())
+    AuditScope::for_request(INTERNAL_ACTOR, &rqctx.request_id)
+        .run(crate::handlers::handle_store_order_create(
+            &rqctx,
+            body_param.into_inner(),
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1070,7 +1418,11 @@ async fn listen_easypost_tracking_update_webhooks(
     rqctx: RequestContext<ServerContext>,
     body_param: TypedBody<EasyPostTrackingUpdateEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_easypost_tracking_update(&rqctx, body_param.into_inner())
+    AuditScope::for_request("easypost", &rqctx.request_id)
+        .run(crate::handlers::handle_easypost_tracking_update(
+            &rqctx,
+            body_param.into_inner(),
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1131,7 +1483,11 @@ async fn listen_shippo_tracking_update_webhooks(
     _auth: QueryToken<ShippoToken>,
     body_param: TypedBody<serde_json::Value>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_shippo_tracking_update(&rqctx, body_param.into_inner())
+    AuditScope::for_request("shippo", &rqctx.request_id)
+        .run(crate::handlers::handle_shippo_tracking_update(
+            &rqctx,
+            body_param.into_inner(),
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1157,7 +1513,11 @@ async fn listen_checkr_background_update_webhooks(
     rqctx: RequestContext<ServerContext>,
     body: HmacVerifiedBodyAudit<crate::handlers_checkr::CheckrWebhookVerification, checkr::WebhookEvent>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_checkr_background_update(&rqctx, body.into_inner()?)
+    AuditScope::for_request("checkr", &rqctx.request_id)
+        .run(crate::handlers::handle_checkr_background_update(
+            &rqctx,
+            body.into_inner()?,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1207,7 +1567,8 @@ async fn listen_auth_google_callback(
     rqctx: RequestContext<ServerContext>,
     query_args: Query<AuthCallback>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_auth::handle_auth_google_callback(&rqctx, query_args)
+    AuditScope::for_request("google", &rqctx.request_id)
+        .run(crate::handlers_auth::handle_auth_google_callback(&rqctx, query_args))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1268,7 +1629,8 @@ async fn listen_auth_gusto_callback(
     rqctx: RequestContext<ServerContext>,
     query_args: Query<AuthCallback>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_auth::handle_auth_gusto_callback(&rqctx, query_args)
+    AuditScope::for_request("gusto", &rqctx.request_id)
+        .run(crate::handlers_auth::handle_auth_gusto_callback(&rqctx, query_args))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1315,7 +1677,8 @@ async fn listen_auth_zoom_callback(
     rqctx: RequestContext<ServerContext>,
     query_args: Query<AuthCallback>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_auth::handle_auth_zoom_callback(&rqctx, query_args)
+    AuditScope::for_request("zoom", &rqctx.request_id)
+        .run(crate::handlers_auth::handle_auth_zoom_callback(&rqctx, query_args))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1346,7 +1709,8 @@ async fn listen_auth_slack_callback(
     rqctx: RequestContext<ServerContext>,
     query_args: Query<AuthCallback>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_auth::handle_auth_slack_callback(&rqctx, query_args)
+    AuditScope::for_request("slack", &rqctx.request_id)
+        .run(crate::handlers_auth::handle_auth_slack_callback(&rqctx, query_args))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1377,7 +1741,10 @@ async fn listen_auth_quickbooks_callback(
     rqctx: RequestContext<ServerContext>,
     query_args: Query<AuthCallback>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_auth::handle_auth_quickbooks_callback(&rqctx, query_args)
+    AuditScope::for_request("quickbooks", &rqctx.request_id)
+        .run(crate::handlers_auth::handle_auth_quickbooks_callback(
+            &rqctx, query_args,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1424,7 +1791,8 @@ async fn listen_auth_docusign_callback(
     rqctx: RequestContext<ServerContext>,
     query_args: Query<AuthCallback>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers_auth::handle_auth_docusign_callback(&rqctx, query_args)
+    AuditScope::for_request("docusign", &rqctx.request_id)
+        .run(crate::handlers_auth::handle_auth_docusign_callback(&rqctx, query_args))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1439,7 +1807,11 @@ async fn listen_docusign_envelope_update_webhooks(
     rqctx: RequestContext<ServerContext>,
     body: HmacVerifiedBody<crate::handlers_docusign::DocusignWebhookVerification, docusign::Envelope>,
 ) -> Result<HttpResponseAccepted<String>, HttpError> {
-    crate::handlers::handle_docusign_envelope_update(&rqctx, body.into_inner()?)
+    AuditScope::for_request("docusign", &rqctx.request_id)
+        .run(crate::handlers::handle_docusign_envelope_update(
+            &rqctx,
+            body.into_inner()?,
+        ))
         .await
         .map(accepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1470,7 +1842,26 @@ async fn listen_slack_commands_webhooks(
     rqctx: RequestContext<ServerContext>,
     body: HmacVerifiedBodyAudit<crate::handlers_slack::SlackWebhookVerification, BotCommand>,
 ) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
-    crate::handlers::handle_slack_commands(&rqctx, body.into_inner()?)
+    let command = body.into_inner()?;
+
+    AuditScope::for_request(&format!("slack:{}", command.user_name), &rqctx.request_id)
+        .run(crate::handlers::handle_slack_commands(&rqctx, command))
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/** Listen for Slack Events API webhooks. */
+#[endpoint {
+    method = POST,
+    path = "/slack/events",
+}]
+async fn listen_slack_events_webhooks(
+    rqctx: RequestContext<ServerContext>,
+    body: HmacVerifiedBody<crate::handlers_slack::SlackWebhookVerification, EventPayload>,
+) -> Result<HttpResponseOk<serde_json::Value>, HttpError> {
+    AuditScope::for_request("slack", &rqctx.request_id)
+        .run(crate::handlers_slack::handle_slack_event(&rqctx, body.into_inner()?))
         .await
         .map(HttpResponseOk)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1502,7 +1893,8 @@ async fn listen_shipbob_webhooks(
     _auth: QueryTokenAudit<InternalToken>,
     body_param: TypedBody<serde_json::Value>,
 ) -> Result<HttpResponseOk<String>, HttpError> {
-    crate::handlers::handle_shipbob(&rqctx, body_param.into_inner())
+    AuditScope::for_request("shipbob", &rqctx.request_id)
+        .run(crate::handlers::handle_shipbob(&rqctx, body_param.into_inner()))
         .await
         .map(ok)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1571,6 +1963,87 @@ async fn listen_rfd_view(
     }
 }
 
+/// Get the RFDs referenced by an rfd, the RFDs that reference it, and its dependency graph
+#[endpoint {
+    method = GET,
+    path = "/rfd/{num}/references",
+}]
+async fn listen_rfd_references(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<RFDToken>,
+    path_params: Path<RFDPathParams>,
+) -> Result<HttpResponseOk<RFDReferences>, HttpError> {
+    match crate::handlers_rfd::handle_rfd_references(&rqctx.context().app, path_params.into_inner().num).await {
+        Ok(Some(references)) => Ok(HttpResponseOk(references)),
+        Ok(None) => Err(HttpError::for_not_found(None, "".to_string())),
+        Err(err) => Err(handle_anyhow_err_as_http_err(err)),
+    }
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct RFDSearchParams {
+    /// The text to search for.
+    pub q: String,
+    /// A comma separated list of RFD states to limit the results to. RFDs in prediscussion are only
+    /// returned to their authors, who are identified by the Google access token in the
+    /// `X-Viewer-Token` header.
+    pub state: Option<String>,
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+/// Search the sections of all RFDs, RFDs in prediscussion only for their authors
+#[endpoint {
+    method = GET,
+    path = "/rfds/search",
+}]
+async fn listen_rfd_search(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<RFDToken>,
+    query: Query<RFDSearchParams>,
+) -> Result<HttpResponseOk<RFDSearchResults>, HttpError> {
+    let viewer = crate::auth::viewer_email(rqctx.request.headers(), &rqctx.context().app.company.gsuite_domain)
+        .await
+        .map_err(|err| {
+            warn!("Failed to verify the viewer of an RFD search: {}", err);
+            HttpError::for_status(None, http::StatusCode::UNAUTHORIZED)
+        })?;
+
+    crate::handlers_rfd::handle_rfd_search(&rqctx.context().app, query.into_inner(), viewer)
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct RFDReserveRequest {
+    /// The title of the new RFD.
+    pub title: String,
+    /// The authors of the new RFD, in the `Name <email>` form used in RFD headers.
+    #[serde(default)]
+    pub authors: Vec<String>,
+}
+
+/// Reserve the next free RFD number and create its branch with a templated README
+#[endpoint {
+    method = POST,
+    path = "/rfds/reserve",
+}]
+async fn listen_rfd_reserve(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<RFDToken>,
+    body_param: TypedBody<RFDReserveRequest>,
+) -> Result<HttpResponseOk<RFDReservation>, HttpError> {
+    AuditScope::for_request(RFD_ACTOR, &rqctx.request_id)
+        .run(crate::handlers_rfd::handle_rfd_reserve(
+            &rqctx.context().app,
+            body_param.into_inner(),
+        ))
+        .await
+        .map(HttpResponseOk)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
 /** Listen for triggering a function run of sync repos. */
 #[endpoint {
     method = POST,
@@ -1580,7 +2053,7 @@ async fn trigger_sync_repos_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-repos")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-repos", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1595,7 +2068,7 @@ async fn trigger_sync_rfds_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-rfds")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-rfds", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1610,7 +2083,7 @@ async fn trigger_sync_travel_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-travel")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-travel", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1625,7 +2098,7 @@ async fn trigger_sync_zoho_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-zoho")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-zoho", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1640,7 +2113,7 @@ async fn trigger_sync_functions_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-functions")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-functions", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1655,7 +2128,7 @@ async fn trigger_sync_finance_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-finance")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-finance", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1670,7 +2143,7 @@ async fn trigger_sync_salesforce_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-salesforce")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-salesforce", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1685,7 +2158,7 @@ async fn trigger_sync_shipments_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shipments")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shipments", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1700,7 +2173,7 @@ async fn trigger_sync_shorturls_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shorturls")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-shorturls", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1715,7 +2188,7 @@ async fn trigger_sync_configs_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-configs")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-configs", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1730,7 +2203,7 @@ async fn trigger_sync_recorded_meetings_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-recorded-meetings")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-recorded-meetings", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1745,7 +2218,7 @@ async fn trigger_sync_asset_inventory_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-inventory")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-asset-inventory", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1760,7 +2233,7 @@ async fn trigger_sync_swag_inventory_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-swag-inventory")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-swag-inventory", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1775,7 +2248,7 @@ async fn trigger_sync_interviews_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-interviews")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-interviews", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1790,7 +2263,7 @@ async fn trigger_sync_applications_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-applications")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-applications", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1805,7 +2278,7 @@ async fn trigger_sync_analytics_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-analytics")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-analytics", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1820,7 +2293,7 @@ async fn trigger_sync_companies_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-companies")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-companies", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1835,7 +2308,7 @@ async fn trigger_sync_other_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-other")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-other", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1850,7 +2323,7 @@ async fn trigger_sync_huddles_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-huddles")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-huddles", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1865,7 +2338,7 @@ async fn trigger_sync_mailing_lists_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-mailing-lists")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-mailing-lists", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1880,7 +2353,67 @@ async fn trigger_sync_journal_clubs_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-journal-clubs")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-journal-clubs", SYSTEM_ACTOR)
+        .await
+        .map(HttpResponseAccepted)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/** Listen for triggering a function run of enforce applicant retention. */
+#[endpoint {
+    method = POST,
+    path = "/run/enforce-applicant-retention",
+}]
+async fn trigger_enforce_applicant_retention_create(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "enforce-applicant-retention", SYSTEM_ACTOR)
+        .await
+        .map(HttpResponseAccepted)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/** Listen for triggering a function run of refresh api tokens. */
+#[endpoint {
+    method = POST,
+    path = "/run/refresh-api-tokens",
+}]
+async fn trigger_refresh_api_tokens_create(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "refresh-api-tokens", SYSTEM_ACTOR)
+        .await
+        .map(HttpResponseAccepted)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/** Listen for triggering a function run of reindex rfds. */
+#[endpoint {
+    method = POST,
+    path = "/run/reindex-rfds",
+}]
+async fn trigger_reindex_rfds_create(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "reindex-rfds", SYSTEM_ACTOR)
+        .await
+        .map(HttpResponseAccepted)
+        .map_err(handle_anyhow_err_as_http_err)
+}
+
+/** Listen for triggering a function run of reencrypt api tokens. */
+#[endpoint {
+    method = POST,
+    path = "/run/reencrypt-api-tokens",
+}]
+async fn trigger_reencrypt_api_tokens_create(
+    rqctx: RequestContext<ServerContext>,
+    _auth: Bearer<InternalToken>,
+) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "reencrypt-api-tokens", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
@@ -1895,7 +2428,7 @@ async fn trigger_sync_api_tokens_create(
     rqctx: RequestContext<ServerContext>,
     _auth: Bearer<InternalToken>,
 ) -> Result<HttpResponseAccepted<uuid::Uuid>, HttpError> {
-    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-api-tokens")
+    crate::handlers_cron::run_subcmd_job(rqctx.context(), "sync-api-tokens", SYSTEM_ACTOR)
         .await
         .map(HttpResponseAccepted)
         .map_err(handle_anyhow_err_as_http_err)
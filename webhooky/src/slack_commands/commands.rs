@@ -0,0 +1,465 @@
+use anyhow::Result;
+use async_bb8_diesel::AsyncRunQueryDsl;
+use async_trait::async_trait;
+use cio_api::{
+    applicant_status::Status,
+    applicants::Applicant,
+    configs::User,
+    journal_clubs::JournalClubMeeting,
+    rfd::reserve_next_rfd,
+    schema::{applicants, inbound_shipments, journal_club_meetings, outbound_shipments, users},
+    shipments::{InboundShipment, OutboundShipment},
+};
+use diesel::{BoolExpressionMethods, ExpressionMethods, PgTextExpressionMethods, QueryDsl};
+use log::warn;
+use rand::{distributions::Alphanumeric, thread_rng, Rng};
+use slack_chat_api::{blocks::Block, FormattedMessage, MessageAttachment};
+use std::str::FromStr;
+
+use super::{
+    ephemeral, in_channel, SlackArgKind, SlackCommandArg, SlackCommandArgs, SlackCommandContext, SlackCommandHandler,
+    SlackCommandPermission,
+};
+
+/// The group that commands exposing applicant data are restricted to.
+const HIRING_GROUP: &str = "hiring";
+
+/// Create a basic divider we can use between attachments.
+fn divider() -> MessageAttachment {
+    MessageAttachment::default().block(Block::divider())
+}
+
+pub struct Meet;
+
+#[async_trait]
+impl SlackCommandHandler for Meet {
+    fn name(&self) -> &'static str {
+        "meet"
+    }
+
+    fn help(&self) -> &'static str {
+        "Create a Google Meet link, with a random name if none is given."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[SlackCommandArg {
+            name: "name",
+            help: "The name of the meeting.",
+            kind: SlackArgKind::Text,
+            required: false,
+        }]
+    }
+
+    async fn run(&self, _ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let name = match args.get("name") {
+            Some(name) => name.replace(' ', "-"),
+            // Generate a new random string.
+            None => thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(6)
+                .map(char::from)
+                .collect(),
+        };
+
+        Ok(in_channel(format!("https://g.co/meet/oxide-{}", name.to_lowercase())))
+    }
+}
+
+pub struct Applicants;
+
+#[async_trait]
+impl SlackCommandHandler for Applicants {
+    fn name(&self) -> &'static str {
+        "applicants"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the applicants with a status, the ones that need to be triaged by default."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[SlackCommandArg {
+            name: "status",
+            help: "The status of the applicants to list.",
+            kind: SlackArgKind::Choice(&[
+                "needs to be triaged",
+                "next steps",
+                "interviewing",
+                "giving offer",
+                "onboarding",
+                "hired",
+                "contractor",
+                "deferred",
+                "declined",
+                "withdrawn",
+            ]),
+            required: false,
+        }]
+    }
+
+    fn permission(&self) -> SlackCommandPermission {
+        SlackCommandPermission::Group(HIRING_GROUP)
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let status = args
+            .get("status")
+            .map(|status| Status::from_str(status).unwrap())
+            .unwrap_or_default();
+
+        // Get the applicants with the status.
+        let applicants = applicants::dsl::applicants
+            .filter(
+                applicants::dsl::cio_company_id
+                    .eq(ctx.company.id)
+                    .and(applicants::dsl::status.eq(status.to_string())),
+            )
+            .load_async::<Applicant>(ctx.db.pool())
+            .await?;
+
+        if applicants.len() > 10 {
+            Ok(in_channel(format!(
+                "Found `{}` applicants with status `{}`. Sorry, that's too many to return at once.",
+                applicants.len(),
+                status.to_string()
+            )))
+        } else if applicants.is_empty() {
+            Ok(in_channel(format!(
+                "Sorry <@{}> :scream: I could not find any applicants with status `{}`",
+                ctx.command.user_id,
+                status.to_string()
+            )))
+        } else {
+            let mut applicants = applicants.into_iter();
+
+            // We know we have at least one item, lets add it.
+            let mut msg: FormattedMessage = applicants.next().unwrap().into();
+            for a in applicants {
+                // Add our divider.
+                msg.attachments.push(divider());
+
+                // Add the rest of the blocks.
+                let mut m: FormattedMessage = a.into();
+                msg.attachments.append(&mut m.attachments);
+            }
+
+            Ok(json!(msg))
+        }
+    }
+}
+
+pub struct ApplicantSearch;
+
+#[async_trait]
+impl SlackCommandHandler for ApplicantSearch {
+    fn name(&self) -> &'static str {
+        "applicant"
+    }
+
+    fn help(&self) -> &'static str {
+        "Show the first applicant whose name matches."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[SlackCommandArg {
+            name: "name",
+            help: "All or part of the name of the applicant.",
+            kind: SlackArgKind::Text,
+            required: true,
+        }]
+    }
+
+    fn permission(&self) -> SlackCommandPermission {
+        SlackCommandPermission::Group(HIRING_GROUP)
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let name = args.get("name").unwrap_or_default();
+
+        if let Ok(applicant) = applicants::dsl::applicants
+            .filter(
+                applicants::dsl::cio_company_id
+                    .eq(ctx.company.id)
+                    .and(applicants::dsl::name.ilike(format!("%{}%", name))),
+            )
+            .first_async::<Applicant>(ctx.db.pool())
+            .await
+        {
+            let r: FormattedMessage = applicant.into();
+            Ok(json!(r))
+        } else {
+            Ok(in_channel(format!(
+                "Sorry <@{}> :scream: I could not find an applicant matching `{}`",
+                ctx.command.user_id, name
+            )))
+        }
+    }
+}
+
+pub struct Shipments;
+
+#[async_trait]
+impl SlackCommandHandler for Shipments {
+    fn name(&self) -> &'static str {
+        "shipments"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the shipments that have not been delivered yet."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[SlackCommandArg {
+            name: "direction",
+            help: "Only list shipments going this way, both by default.",
+            kind: SlackArgKind::Choice(&["inbound", "outbound"]),
+            required: false,
+        }]
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let direction = args.get("direction");
+        let wants_outbound = direction.is_none() || direction == Some("outbound");
+        let wants_inbound = direction.is_none() || direction == Some("inbound");
+
+        let outbound = if wants_outbound {
+            outbound_shipments::dsl::outbound_shipments
+                .filter(
+                    outbound_shipments::dsl::cio_company_id
+                        .eq(ctx.company.id)
+                        .and(outbound_shipments::dsl::tracking_status.ne("DELIVERED".to_string()))
+                        .and(
+                            outbound_shipments::dsl::status.ne(cio_api::shipment_status::Status::PickedUp.to_string()),
+                        ),
+                )
+                .load_async::<OutboundShipment>(ctx.db.pool())
+                .await?
+        } else {
+            Default::default()
+        };
+
+        let inbound = if wants_inbound {
+            inbound_shipments::dsl::inbound_shipments
+                .filter(
+                    inbound_shipments::dsl::cio_company_id
+                        .eq(ctx.company.id)
+                        .and(inbound_shipments::dsl::tracking_status.ne("DELIVERED".to_string()))
+                        .and(inbound_shipments::dsl::delivered_time.is_null()),
+                )
+                .load_async::<InboundShipment>(ctx.db.pool())
+                .await?
+        } else {
+            Default::default()
+        };
+
+        if outbound.is_empty() && inbound.is_empty() {
+            return Ok(in_channel(format!(
+                "Sorry <@{}> :scream: I could not find any {}shipments pending delivery",
+                ctx.command.user_id,
+                direction
+                    .map(|direction| format!("`{}` ", direction))
+                    .unwrap_or_default()
+            )));
+        }
+
+        let mut messages = outbound
+            .into_iter()
+            .map(FormattedMessage::from)
+            .chain(inbound.into_iter().map(FormattedMessage::from));
+
+        // We know we have at least one item, lets add it.
+        let mut fm = messages.next().unwrap();
+        for mut m in messages {
+            // Add our divider.
+            fm.attachments.push(divider());
+
+            // Add the rest of the blocks.
+            fm.attachments.append(&mut m.attachments);
+        }
+
+        Ok(json!(fm))
+    }
+}
+
+pub struct Papers;
+
+#[async_trait]
+impl SlackCommandHandler for Papers {
+    fn name(&self) -> &'static str {
+        "papers"
+    }
+
+    fn help(&self) -> &'static str {
+        "List the journal club meetings, the open ones by default."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[SlackCommandArg {
+            name: "state",
+            help: "The state of the meetings to list.",
+            kind: SlackArgKind::Choice(&["open", "closed"]),
+            required: false,
+        }]
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let state = args.get("state").unwrap_or("open");
+
+        let meetings = journal_club_meetings::dsl::journal_club_meetings
+            .filter(
+                journal_club_meetings::dsl::cio_company_id
+                    .eq(ctx.company.id)
+                    .and(journal_club_meetings::dsl::state.eq(state.to_string())),
+            )
+            .load_async::<JournalClubMeeting>(ctx.db.pool())
+            .await?;
+
+        let mut msg = FormattedMessage::new();
+        for (i, m) in meetings.into_iter().enumerate() {
+            if i > 0 {
+                msg.blocks.push(Block::divider());
+            }
+
+            let mut obj: FormattedMessage = m.into();
+            msg.blocks.append(&mut obj.blocks);
+        }
+
+        Ok(json!(msg))
+    }
+}
+
+pub struct Paper;
+
+#[async_trait]
+impl SlackCommandHandler for Paper {
+    fn name(&self) -> &'static str {
+        "paper"
+    }
+
+    fn help(&self) -> &'static str {
+        "Show the first journal club meeting whose title matches."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[SlackCommandArg {
+            name: "title",
+            help: "All or part of the title of the meeting.",
+            kind: SlackArgKind::Text,
+            required: true,
+        }]
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let title = args.get("title").unwrap_or_default();
+
+        if let Ok(meeting) = journal_club_meetings::dsl::journal_club_meetings
+            .filter(
+                journal_club_meetings::dsl::cio_company_id
+                    .eq(ctx.company.id)
+                    .and(journal_club_meetings::dsl::title.ilike(format!("%{}%", title))),
+            )
+            .first_async::<JournalClubMeeting>(ctx.db.pool())
+            .await
+        {
+            let r: FormattedMessage = meeting.into();
+            Ok(json!(r))
+        } else {
+            Ok(in_channel(format!(
+                "Sorry <@{}> :scream: I could not find a journal club meeting matching `{}`",
+                ctx.command.user_id, title
+            )))
+        }
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+pub struct RFD;
+
+/// The user running the command, in the `Name <email>` form used in RFD headers.
+async fn rfd_author(ctx: &SlackCommandContext) -> Result<String> {
+    let slack = ctx.company.authenticate_slack(&ctx.db).await?;
+    let member = slack.get_user(&ctx.command.user_id).await?;
+
+    let user = users::dsl::users
+        .filter(users::dsl::cio_company_id.eq(ctx.company.id))
+        .filter(users::dsl::email.eq(member.profile.email.to_lowercase()))
+        .first_async::<User>(ctx.db.pool())
+        .await?;
+
+    Ok(format!("{} <{}>", user.full_name(), user.email))
+}
+
+#[async_trait]
+impl SlackCommandHandler for RFD {
+    fn name(&self) -> &'static str {
+        "rfd"
+    }
+
+    fn help(&self) -> &'static str {
+        "Reserve the next RFD number and create its branch with a templated README."
+    }
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[
+            SlackCommandArg {
+                name: "action",
+                help: "What to do.",
+                kind: SlackArgKind::Choice(&["new"]),
+                required: true,
+            },
+            SlackCommandArg {
+                name: "title",
+                help: "The title of the new RFD.",
+                kind: SlackArgKind::Text,
+                required: true,
+            },
+        ]
+    }
+
+    fn is_slow(&self) -> bool {
+        // Reserving a number takes a handful of round trips to GitHub.
+        true
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value> {
+        let title = args.get("title").unwrap_or_default();
+
+        // The person reserving the RFD is its author, an RFD without authors fails the lints.
+        let author = match rfd_author(ctx).await {
+            Ok(author) => author,
+            Err(err) => {
+                warn!(
+                    "failed to find the user record of slack user {}: {}",
+                    ctx.command.user_id, err
+                );
+
+                return Ok(ephemeral(format!(
+                    "Sorry <@{}> :scream: I could not find your user record to name you as the author of the RFD",
+                    ctx.command.user_id
+                )));
+            }
+        };
+
+        match reserve_next_rfd(&ctx.db, &ctx.company, title, &[author]).await {
+            Ok(reservation) => Ok(in_channel(format!(
+                "<@{}> reserved RFD {} `{}` :tada:\nBranch: <{}|{}>\nREADME: {}\nOpen a pull request when it is \
+                 ready for discussion: {}",
+                ctx.command.user_id,
+                reservation.number,
+                reservation.title,
+                reservation.tree_link,
+                reservation.branch,
+                reservation.readme_link,
+                reservation.pull_request_link
+            ))),
+            Err(err) => {
+                warn!("failed to reserve RFD `{}`: {}", title, err);
+
+                Ok(ephemeral(format!(
+                    "Sorry <@{}> :scream: I could not reserve an RFD for `{}`",
+                    ctx.command.user_id, title
+                )))
+            }
+        }
+    }
+}
@@ -0,0 +1,641 @@
+//! Slack slash commands.
+//!
+//! Every command implements [`SlackCommandHandler`], declaring its name, the arguments it takes,
+//! who may run it and its help text. The [`SlackCommandRegistry`] parses the text of an incoming
+//! command against those declarations, checks permissions and runs the command. `/cio help` lists
+//! every registered command and `/cio <command> ...` runs any of them.
+//!
+//! Slack only waits three seconds for a response to a slash command. Commands that can take longer
+//! than that mark themselves as slow, are acknowledged immediately and post their result to the
+//! `response_url` of the command once they are done.
+use anyhow::Result;
+use async_trait::async_trait;
+use cio_api::{
+    app_config::{AppConfig, SlackConfig},
+    audit::AuditScope,
+    companies::Company,
+    db::Database,
+};
+use log::{info, warn};
+use slack_chat_api::{BotCommand, MessageResponse, MessageResponseType};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, RwLock},
+};
+
+mod commands;
+
+/// The command that exposes help and access to all of the other commands.
+pub const CIO_COMMAND: &str = "/cio";
+
+/// Who is allowed to run a command.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SlackCommandPermission {
+    /// Anyone in the Slack workspace.
+    Anyone,
+
+    /// Only members of the named group in the Slack config of the company.
+    Group(&'static str),
+}
+
+/// The kinds of arguments a command can take.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SlackArgKind {
+    /// One of a fixed set of lowercase values, which may contain spaces.
+    Choice(&'static [&'static str]),
+
+    /// The rest of the text of the command.
+    Text,
+}
+
+/// An argument declared by a command. Arguments are parsed in the order they are declared.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct SlackCommandArg {
+    pub name: &'static str,
+    pub help: &'static str,
+    pub kind: SlackArgKind,
+    pub required: bool,
+}
+
+impl SlackCommandArg {
+    fn usage(&self) -> String {
+        let value = match self.kind {
+            SlackArgKind::Choice(choices) if choices.len() == 1 && self.required => return choices[0].to_string(),
+            SlackArgKind::Choice(choices) if choices.len() <= 3 => choices.join("|"),
+            _ => self.name.to_string(),
+        };
+
+        if self.required {
+            format!("<{}>", value)
+        } else {
+            format!("[{}]", value)
+        }
+    }
+}
+
+/// The arguments of a command after they have been parsed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SlackCommandArgs {
+    values: HashMap<&'static str, String>,
+}
+
+impl SlackCommandArgs {
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(|value| value.as_str())
+    }
+}
+
+/// The reasons a command can not be run, reported back to the user that tried to run it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlackCommandError {
+    UnknownCommand(String),
+    MissingArgument(&'static str),
+    InvalidChoice {
+        arg: &'static str,
+        value: String,
+        choices: &'static [&'static str],
+    },
+    UnexpectedArgument(String),
+    PermissionDenied(&'static str),
+}
+
+impl fmt::Display for SlackCommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlackCommandError::UnknownCommand(name) => write!(f, "`{}` is not a command I know", name),
+            SlackCommandError::MissingArgument(arg) => write!(f, "`{}` is required", arg),
+            SlackCommandError::InvalidChoice { arg, value, choices } => write!(
+                f,
+                "`{}` is not a valid {}, try one of {}",
+                value,
+                arg,
+                choices
+                    .iter()
+                    .map(|choice| format!("`{}`", choice))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            SlackCommandError::UnexpectedArgument(text) => write!(f, "I did not expect `{}`", text),
+            SlackCommandError::PermissionDenied(group) => {
+                write!(f, "this command is restricted to the `{}` group", group)
+            }
+        }
+    }
+}
+
+/// Parse the text of a command against the arguments it declares.
+pub fn parse_args(spec: &[SlackCommandArg], text: &str) -> Result<SlackCommandArgs, SlackCommandError> {
+    let mut args = SlackCommandArgs::default();
+    let mut rest = text.trim();
+
+    for arg in spec {
+        match arg.kind {
+            SlackArgKind::Choice(choices) => {
+                let lower = rest.to_lowercase();
+                let choice = choices
+                    .iter()
+                    .filter(|choice| lower == **choice || lower.starts_with(&format!("{} ", choice)))
+                    .max_by_key(|choice| choice.len());
+
+                match choice {
+                    Some(choice) => {
+                        args.values.insert(arg.name, choice.to_string());
+                        rest = rest[choice.len()..].trim_start();
+                    }
+                    None if rest.is_empty() => {
+                        if arg.required {
+                            return Err(SlackCommandError::MissingArgument(arg.name));
+                        }
+                    }
+                    None => {
+                        return Err(SlackCommandError::InvalidChoice {
+                            arg: arg.name,
+                            value: rest.to_string(),
+                            choices,
+                        });
+                    }
+                }
+            }
+            SlackArgKind::Text => {
+                if !rest.is_empty() {
+                    args.values.insert(arg.name, rest.to_string());
+                    rest = "";
+                } else if arg.required {
+                    return Err(SlackCommandError::MissingArgument(arg.name));
+                }
+            }
+        }
+    }
+
+    if !rest.is_empty() {
+        return Err(SlackCommandError::UnexpectedArgument(rest.to_string()));
+    }
+
+    Ok(args)
+}
+
+/// Everything a command needs to run.
+#[derive(Clone)]
+pub struct SlackCommandContext {
+    pub db: Database,
+    pub company: Company,
+    pub app_config: Arc<RwLock<AppConfig>>,
+    pub command: BotCommand,
+}
+
+#[async_trait]
+pub trait SlackCommandHandler: Send + Sync {
+    /// The name of the command without the leading slash.
+    fn name(&self) -> &'static str;
+
+    /// A one line description of what the command does.
+    fn help(&self) -> &'static str;
+
+    fn args(&self) -> &'static [SlackCommandArg] {
+        &[]
+    }
+
+    fn permission(&self) -> SlackCommandPermission {
+        SlackCommandPermission::Anyone
+    }
+
+    /// Commands that may take longer than Slack is willing to wait are acknowledged right away and
+    /// their response is sent to the `response_url` of the command later.
+    fn is_slow(&self) -> bool {
+        false
+    }
+
+    async fn run(&self, ctx: &SlackCommandContext, args: &SlackCommandArgs) -> Result<serde_json::Value>;
+}
+
+fn usage(command: &dyn SlackCommandHandler) -> String {
+    let mut usage = format!("/{}", command.name());
+    for arg in command.args() {
+        usage.push(' ');
+        usage.push_str(&arg.usage());
+    }
+
+    usage
+}
+
+/// A response only the user that ran the command can see.
+pub fn ephemeral(text: String) -> serde_json::Value {
+    json!(MessageResponse {
+        response_type: MessageResponseType::Ephemeral,
+        text,
+    })
+}
+
+/// A response everyone in the channel can see.
+pub fn in_channel(text: String) -> serde_json::Value {
+    json!(MessageResponse {
+        response_type: MessageResponseType::InChannel,
+        text,
+    })
+}
+
+#[derive(Clone)]
+pub struct SlackCommandRegistry {
+    commands: Vec<Arc<dyn SlackCommandHandler>>,
+}
+
+impl Default for SlackCommandRegistry {
+    fn default() -> Self {
+        Self::new(vec![
+            Arc::new(commands::Meet),
+            Arc::new(commands::Applicants),
+            Arc::new(commands::ApplicantSearch),
+            Arc::new(commands::Papers),
+            Arc::new(commands::Paper),
+            Arc::new(commands::Shipments),
+            Arc::new(commands::RFD),
+        ])
+    }
+}
+
+impl SlackCommandRegistry {
+    pub fn new(commands: Vec<Arc<dyn SlackCommandHandler>>) -> Self {
+        Self { commands }
+    }
+
+    /// Find a command by name, with or without its leading slash.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn SlackCommandHandler>> {
+        let name = name.trim_start_matches('/').to_lowercase();
+        self.commands.iter().find(|command| command.name() == name).cloned()
+    }
+
+    /// The help for a single command, or a summary of all commands when no name is given.
+    pub fn help(&self, name: &str) -> Result<String, SlackCommandError> {
+        if name.is_empty() {
+            let mut help = "Here is everything I can do:\n".to_string();
+            for command in &self.commands {
+                help.push_str(&format!("• `{}` {}\n", usage(command.as_ref()), command.help()));
+            }
+            help.push_str(&format!(
+                "Commands can also be run as `{} <command> ...`, and `{} help <command>` explains a command \
+                 in more detail.",
+                CIO_COMMAND, CIO_COMMAND
+            ));
+
+            return Ok(help);
+        }
+
+        let command = self
+            .get(name)
+            .ok_or_else(|| SlackCommandError::UnknownCommand(name.to_string()))?;
+
+        let mut help = format!("`{}` {}\n", usage(command.as_ref()), command.help());
+        for arg in command.args() {
+            help.push_str(&format!(
+                "• `{}`{} {}",
+                arg.name,
+                if arg.required { "" } else { " (optional)" },
+                arg.help
+            ));
+            if let SlackArgKind::Choice(choices) = arg.kind {
+                help.push_str(&format!(": {}", choices.join(", ")));
+            }
+            help.push('\n');
+        }
+        if let SlackCommandPermission::Group(group) = command.permission() {
+            help.push_str(&format!("Restricted to the `{}` group.\n", group));
+        }
+
+        Ok(help.trim_end().to_string())
+    }
+
+    /// Work out what to do with an incoming command: answer it right away, for help and for
+    /// problems with the command itself, or run one of the registered commands.
+    fn route(&self, bot_command: &BotCommand, slack: &SlackConfig) -> Dispatch {
+        let sorry = |err: &dyn fmt::Display| {
+            Dispatch::Respond(ephemeral(format!("Sorry <@{}> :scream: {}", bot_command.user_id, err)))
+        };
+
+        let text = bot_command.text.trim();
+        let (name, text) = if bot_command.command == CIO_COMMAND {
+            let (name, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            if name.is_empty() || name == "help" {
+                return match self.help(rest.trim()) {
+                    Ok(help) => Dispatch::Respond(ephemeral(help)),
+                    Err(err) => sorry(&err),
+                };
+            }
+
+            (name.to_string(), rest.trim().to_string())
+        } else {
+            (bot_command.command.to_string(), text.to_string())
+        };
+
+        let command = match self.get(&name) {
+            Some(command) => command,
+            None => {
+                return sorry(&format!(
+                    "{}, try `{} help`",
+                    SlackCommandError::UnknownCommand(name),
+                    CIO_COMMAND
+                ))
+            }
+        };
+
+        let args = match parse_args(command.args(), &text) {
+            Ok(args) => args,
+            Err(err) => return sorry(&format!("{}. Usage: `{}`", err, usage(command.as_ref()))),
+        };
+
+        if let SlackCommandPermission::Group(group) = command.permission() {
+            if slack.groups.is_empty() {
+                warn!(
+                    "`/{}` is open to everyone as no Slack groups are configured, set `slack.groups.{}` to restrict it",
+                    command.name(),
+                    group
+                );
+            }
+
+            if !slack.allows(group, &bot_command.user_id) {
+                return sorry(&SlackCommandError::PermissionDenied(group));
+            }
+        }
+
+        Dispatch::Run { command, args, text }
+    }
+
+    /// Run an incoming command. Problems with the command itself are reported back to the user
+    /// rather than returned as errors, as Slack would only show a generic failure for those.
+    pub async fn dispatch(&self, ctx: SlackCommandContext) -> serde_json::Value {
+        let slack = ctx.app_config.read().unwrap().slack.clone();
+
+        let (command, args, text) = match self.route(&ctx.command, &slack) {
+            Dispatch::Respond(response) => return response,
+            Dispatch::Run { command, args, text } => (command, args, text),
+        };
+
+        if command.is_slow() && !ctx.command.response_url.is_empty() {
+            let summary = format!("/{} {}", command.name(), text);
+
+            let scope = AuditScope::current();
+            tokio::spawn(scope.run(async move {
+                let response = run(command.as_ref(), &ctx, &args).await;
+                if let Err(err) = send_follow_up(&ctx.command.response_url, &response).await {
+                    warn!(
+                        "failed to send the response to slack command `{}`: {}",
+                        command.name(),
+                        err
+                    );
+                }
+            }));
+
+            return ephemeral(format!(
+                "Working on `{}`, I'll post the result here when it is done.",
+                summary.trim()
+            ));
+        }
+
+        run(command.as_ref(), &ctx, &args).await
+    }
+}
+
+/// What to do with an incoming command.
+enum Dispatch {
+    /// Respond right away without running a command.
+    Respond(serde_json::Value),
+    /// Run a command with the arguments parsed from the text after its name.
+    Run {
+        command: Arc<dyn SlackCommandHandler>,
+        args: SlackCommandArgs,
+        text: String,
+    },
+}
+
+async fn run(
+    command: &dyn SlackCommandHandler,
+    ctx: &SlackCommandContext,
+    args: &SlackCommandArgs,
+) -> serde_json::Value {
+    info!(
+        "running slack command `{}` for {}",
+        command.name(),
+        ctx.command.user_name
+    );
+
+    match command.run(ctx, args).await {
+        Ok(response) => response,
+        Err(err) => {
+            warn!("slack command `{}` failed: {}", command.name(), err);
+
+            ephemeral(format!(
+                "Sorry <@{}> :scream: something went wrong running `/{}`",
+                ctx.command.user_id,
+                command.name()
+            ))
+        }
+    }
+}
+
+/// Send a delayed response for a command to its `response_url`.
+async fn send_follow_up(response_url: &str, response: &serde_json::Value) -> Result<()> {
+    reqwest::Client::new()
+        .post(response_url)
+        .json(response)
+        .send()
+        .await?
+        .error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STATUS: SlackCommandArg = SlackCommandArg {
+        name: "status",
+        help: "The status to filter by",
+        kind: SlackArgKind::Choice(&["hired", "giving offer", "giving"]),
+        required: false,
+    };
+
+    const TITLE: SlackCommandArg = SlackCommandArg {
+        name: "title",
+        help: "The title",
+        kind: SlackArgKind::Text,
+        required: true,
+    };
+
+    #[test]
+    fn test_parse_optional_choice() {
+        assert_eq!(parse_args(&[STATUS], "").unwrap().get("status"), None);
+        assert_eq!(parse_args(&[STATUS], "Hired").unwrap().get("status"), Some("hired"));
+        assert_eq!(
+            parse_args(&[STATUS], " giving  offer ").unwrap_err(),
+            SlackCommandError::UnexpectedArgument("offer".to_string())
+        );
+        assert_eq!(
+            parse_args(&[STATUS], "Giving offer").unwrap().get("status"),
+            Some("giving offer")
+        );
+        assert_eq!(
+            parse_args(&[STATUS], "fired").unwrap_err(),
+            SlackCommandError::InvalidChoice {
+                arg: "status",
+                value: "fired".to_string(),
+                choices: &["hired", "giving offer", "giving"],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_subcommand_and_text() {
+        let new = SlackCommandArg {
+            name: "action",
+            help: "What to do",
+            kind: SlackArgKind::Choice(&["new"]),
+            required: true,
+        };
+
+        let args = parse_args(&[new, TITLE], "new  Widget sprockets").unwrap();
+        assert_eq!(args.get("action"), Some("new"));
+        assert_eq!(args.get("title"), Some("Widget sprockets"));
+
+        assert_eq!(
+            parse_args(&[new, TITLE], "new").unwrap_err(),
+            SlackCommandError::MissingArgument("title")
+        );
+        assert_eq!(
+            parse_args(&[new, TITLE], "").unwrap_err(),
+            SlackCommandError::MissingArgument("action")
+        );
+        assert!(matches!(
+            parse_args(&[new, TITLE], "newish title"),
+            Err(SlackCommandError::InvalidChoice { .. })
+        ));
+
+        assert_eq!(new.usage(), "new");
+        assert_eq!(TITLE.usage(), "<title>");
+        assert_eq!(STATUS.usage(), "[hired|giving offer|giving]");
+    }
+
+    fn bot_command(command: &str, text: &str, user_name: &str) -> BotCommand {
+        BotCommand {
+            command: command.to_string(),
+            text: text.to_string(),
+            user_id: format!("U{}", user_name.to_uppercase()),
+            user_name: user_name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn response_text(dispatch: Dispatch) -> String {
+        match dispatch {
+            Dispatch::Respond(response) => response["text"].as_str().unwrap().to_string(),
+            Dispatch::Run { command, .. } => panic!("expected a response, got a run of {}", command.name()),
+        }
+    }
+
+    fn hiring() -> SlackConfig {
+        SlackConfig {
+            groups: HashMap::from([("hiring".to_string(), vec!["UJANE".to_string()])]),
+        }
+    }
+
+    #[test]
+    fn test_help() {
+        let registry = SlackCommandRegistry::default();
+
+        let help = registry.help("").unwrap();
+        assert!(help.contains("• `/meet [name]`"));
+        assert!(help.contains("• `/applicants"));
+
+        let help = registry.help("/applicants").unwrap();
+        assert!(help.starts_with("`/applicants"));
+        assert!(help.ends_with("Restricted to the `hiring` group."));
+        assert!(!registry.help("meet").unwrap().contains("Restricted"));
+
+        assert_eq!(
+            registry.help("nope").unwrap_err(),
+            SlackCommandError::UnknownCommand("nope".to_string())
+        );
+    }
+
+    #[test]
+    fn test_route_help() {
+        let registry = SlackCommandRegistry::default();
+
+        let text = response_text(registry.route(&bot_command(CIO_COMMAND, "", "jane"), &hiring()));
+        assert!(text.starts_with("Here is everything I can do"));
+
+        let text = response_text(registry.route(&bot_command(CIO_COMMAND, "help meet", "jane"), &hiring()));
+        assert!(text.starts_with("`/meet [name]`"));
+
+        let text = response_text(registry.route(&bot_command(CIO_COMMAND, "dance", "jane"), &hiring()));
+        assert!(text.contains("`dance` is not a command I know"));
+    }
+
+    #[test]
+    fn test_route_command() {
+        let registry = SlackCommandRegistry::default();
+
+        match registry.route(&bot_command(CIO_COMMAND, "meet  standup", "john"), &hiring()) {
+            Dispatch::Run { command, args, text } => {
+                assert_eq!(command.name(), "meet");
+                assert_eq!(args.get("name"), Some("standup"));
+                assert_eq!(text, "standup");
+            }
+            Dispatch::Respond(response) => panic!("expected a run, got {}", response),
+        }
+
+        // Commands can also be run directly.
+        assert!(matches!(
+            registry.route(&bot_command("/meet", "", "john"), &hiring()),
+            Dispatch::Run { .. }
+        ));
+    }
+
+    #[test]
+    fn test_route_permissions() {
+        let registry = SlackCommandRegistry::default();
+
+        // Members of the group can run the command.
+        assert!(matches!(
+            registry.route(&bot_command("/applicants", "", "jane"), &hiring()),
+            Dispatch::Run { .. }
+        ));
+
+        // Everyone else can not.
+        let text = response_text(registry.route(&bot_command("/applicants", "", "john"), &hiring()));
+        assert!(text.contains("restricted to the `hiring` group"));
+
+        // Including someone that took the user name of a member.
+        let impostor = BotCommand {
+            user_id: "UJOHN".to_string(),
+            ..bot_command("/applicants", "", "jane")
+        };
+        let text = response_text(registry.route(&impostor, &hiring()));
+        assert!(text.contains("restricted to the `hiring` group"));
+
+        // Neither can anyone when other groups are configured, but not this one.
+        let finance = SlackConfig {
+            groups: HashMap::from([("finance".to_string(), vec!["UJANE".to_string()])]),
+        };
+        let text = response_text(registry.route(&bot_command("/applicants", "", "jane"), &finance));
+        assert!(text.contains("restricted to the `hiring` group"));
+
+        // Without any groups configured, anyone can run the command.
+        assert!(matches!(
+            registry.route(&bot_command("/applicants", "", "john"), &SlackConfig::default()),
+            Dispatch::Run { .. }
+        ));
+
+        // Commands are checked for bad arguments before permissions.
+        let text = response_text(registry.route(&bot_command("/applicants", "fired", "john"), &hiring()));
+        assert!(text.contains("Usage: `/applicants"));
+    }
+
+    #[test]
+    fn test_parse_unexpected_text() {
+        assert_eq!(
+            parse_args(&[], "surprise").unwrap_err(),
+            SlackCommandError::UnexpectedArgument("surprise".to_string())
+        );
+    }
+}
@@ -6,8 +6,10 @@ use std::{
 };
 
 use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use reqwest::{header, Client, Method, Request, Response, StatusCode, Url};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use token_refresh::{RefreshError, RefreshedToken, TokenRefresh};
 
 const TOKEN_ENDPOINT: &str = "https://accounts.zoho.com";
 const CRM_ENDPOINT: &str = "https://www.zohoapis.com/crm/v2/";
@@ -106,7 +108,7 @@ impl Zoho {
     /// Attempts to refresh the currently stored access token. This will return an error if
     /// any one of refresh_token, client_id, or client_secret are not set.
     pub async fn refresh_access_token(&self) -> Result<AccessTokenRefreshResponse> {
-        self.inner.refresh_access_token().await
+        Ok(self.inner.refresh_access_token().await?)
     }
 
     /// Fetches a list of available modules and their metadata
@@ -211,7 +213,7 @@ impl ZohoClient {
     /// Refreshes the internal access token stored in this client and returns back the refresh
     /// token response sent by Zoho. This will return an error if any one of refresh_token,
     /// client_id, or client_secret are not set.
-    async fn refresh_access_token(&self) -> Result<AccessTokenRefreshResponse> {
+    async fn refresh_access_token(&self) -> Result<AccessTokenRefreshResponse, RefreshError> {
         if let (Some(refresh_token), Some(client_id), Some(client_secret)) =
             (&self.refresh_token, &self.client_id, &self.client_secret)
         {
@@ -223,8 +225,15 @@ impl ZohoClient {
             ];
 
             let request = self.request(TOKEN_ENDPOINT, &Method::POST, "oauth/v2/token", &(), Some(params));
-            let response = self.client.execute(request).await?;
-            let new_token: AccessTokenRefreshResponse = response.json().await?;
+            let response = self.client.execute(request).await.map_err(RefreshError::other)?;
+            let status = response.status();
+            let body = response.text().await.map_err(RefreshError::other)?;
+
+            // Zoho reports a rejected refresh token with a 200 and an `error` field in the body.
+            let new_token: AccessTokenRefreshResponse = match serde_json::from_str(&body) {
+                Ok(new_token) if status == StatusCode::OK => new_token,
+                _ => return Err(RefreshError::from_response(status.as_u16(), &body)),
+            };
 
             {
                 let mut token = self
@@ -236,8 +245,8 @@ impl ZohoClient {
 
             Ok(new_token)
         } else {
-            Err(anyhow!(
-                "Unable to refresh access token without refresh token, client id, and client secret"
+            Err(RefreshError::NotConfigured(
+                "Unable to refresh access token without refresh token, client id, and client secret".to_string(),
             ))
         }
     }
@@ -253,6 +262,21 @@ impl ZohoClient {
     }
 }
 
+#[async_trait]
+impl TokenRefresh for Zoho {
+    async fn refresh(&mut self) -> Result<RefreshedToken, RefreshError> {
+        let t = self.inner.refresh_access_token().await?;
+
+        // Zoho refresh tokens do not expire and are not rotated.
+        Ok(RefreshedToken {
+            access_token: t.access_token,
+            expires_in: t.expires_in as i64,
+            refresh_token: String::new(),
+            refresh_token_expires_in: 0,
+        })
+    }
+}
+
 /// Refreshed access tokens last for one hour
 /// https://www.zoho.com/crm/developer/docs/api/v2/refresh.html
 #[derive(Debug, Deserialize)]